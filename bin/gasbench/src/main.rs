@@ -25,7 +25,7 @@ use defi_address_book::UniswapV2PoolAddress;
 use defi_entities::{Market, MarketState, PoolWrapper, Swap, SwapAmountType, SwapLine};
 
 use loom_actors::SharedState;
-use loom_multicaller::{MulticallerDeployer, MulticallerEncoder, MulticallerSwapEncoder};
+use loom_multicaller::{MulticallerDecoder, MulticallerDeployer, MulticallerEncoder, MulticallerSwapEncoder};
 use loom_revm_db::LoomInMemoryDB;
 use loom_utils::{BalanceCheater, NWETH};
 
@@ -80,7 +80,7 @@ async fn main() -> Result<()> {
     let encoder = Arc::new(MulticallerSwapEncoder::new(multicaller_address));
 
     //preload state
-    preload_market_state(client.clone(), vec![multicaller_address], vec![], vec![], market_state_instance.clone(), None).await?;
+    preload_market_state(client.clone(), vec![multicaller_address], vec![], vec![], vec![], market_state_instance.clone(), None).await?;
 
     //Preloading market
     preload_pools(client.clone(), market_instance.clone(), market_state_instance.clone()).await?;
@@ -139,6 +139,13 @@ async fn main() -> Result<()> {
         let calls = encoder.make_calls(&swap)?;
         let (to, payload) = encoder.encode_calls(calls)?;
 
+        if cli.decode {
+            match MulticallerDecoder::to_pretty_string(&payload) {
+                Ok(decoded) => println!("{}\n{}", sp_dto, decoded),
+                Err(e) => error!("decode error : {:?}", e),
+            }
+        }
+
         calldata_map.insert(swap_path.into(), payload.clone());
 
         let tx_request = TransactionRequest::default().to(to).from(operator_address).input(TransactionInput::new(payload));
@@ -24,6 +24,7 @@ async fn main() -> Result<()> {
     let (topology, mut worker_task_vec) = Topology::from(topology_config).await?;
 
     let client = topology.get_client(Some("local".to_string()).as_ref())?;
+    let client_config = topology.get_client_config(Some("local".to_string()).as_ref())?;
     let blockchain = topology.get_blockchain(Some("mainnet".to_string()).as_ref())?;
     let tx_signers = topology.get_signers(Some("env_signer".to_string()).as_ref())?;
 
@@ -33,7 +34,10 @@ async fn main() -> Result<()> {
     info!("Creating shared state");
 
     info!("Starting state change arb actor");
-    let mut state_change_arb_actor = StateChangeArbActor::new(client.clone(), true, true);
+    let searcher_config = topology.get_searcher_config(Some("mainnet".to_string()).as_ref());
+    let mut state_change_arb_actor = StateChangeArbActor::new(client.clone(), true, true)
+        .with_simulation(client_config.simulation.into())
+        .with_searcher_config(searcher_config);
     match state_change_arb_actor
         .access(blockchain.mempool())
         .access(blockchain.latest_block())
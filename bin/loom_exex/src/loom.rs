@@ -58,8 +58,9 @@ where
         .broadcaster
         .as_ref()
         .and_then(|b| b.get("flashbots"))
-        .map(|b| match b {
-            BroadcasterConfig::Flashbots(f) => f.relays(),
+        .and_then(|b| match b {
+            BroadcasterConfig::Flashbots(f) => Some(f.relays()),
+            _ => None,
         })
         .unwrap_or_default();
 
@@ -71,6 +72,7 @@ where
         .initialize_signers_with_encrypted_key(private_key_encrypted)? // initialize signer with encrypted key
         .with_block_history()? // collect blocks
         .with_price_station()? // calculate price fo tokens
+        .with_gas_price_oracle()? // collect priority fees and base fees of recent blocks
         .with_health_monitor_pools()? // monitor pools health to disable empty
         .with_health_monitor_state()? // monitor state health
         .with_health_monitor_stuffing_tx()? // collect stuffing tx information
@@ -16,9 +16,9 @@ use defi_address_book::{TokenAddress, UniswapV3PoolAddress};
 use defi_blockchain::Blockchain;
 use defi_entities::required_state::RequiredState;
 use defi_entities::{PoolClass, Swap, SwapAmountType, SwapLine};
-use defi_events::{MessageTxCompose, TxComposeData};
+use defi_events::{MessageTxCompose, TxCompose, TxComposeData, TxState};
 use defi_pools::state_readers::ERC20StateReader;
-use loom_multicaller::EncoderHelper;
+use loom_multicaller::{EncoderHelper, MulticallerDecoder};
 use loom_utils::evm::env_for_block;
 use loom_utils::NWETH;
 use tracing::{debug, error, info};
@@ -87,6 +87,7 @@ async fn main() -> Result<()> {
     let mut block_sub = bc.new_block_with_tx_channel().subscribe().await;
     let mut logs_sub = bc.new_block_logs_channel().subscribe().await;
     let mut state_update_sub = bc.new_block_state_update_channel().subscribe().await;
+    let mut compose_sub = compose_channel.subscribe().await;
 
     //let memepool = bc.mempool();
     let market = bc.market();
@@ -133,6 +134,27 @@ async fn main() -> Result<()> {
                 }
             }
 
+            compose = compose_sub.recv() => {
+                match compose {
+                    Ok(compose)=>{
+                        if let TxCompose::Sign(data) = compose.inner {
+                            for tx in data.tx_bundle.unwrap_or_default() {
+                                if let TxState::SignatureRequired(tx_request) = tx {
+                                    match tx_request.input.input().map(|input| MulticallerDecoder::to_pretty_string(input)) {
+                                        Some(Ok(decoded)) => debug!("Multicaller calls :\n{}", decoded),
+                                        Some(Err(e)) => error!("Multicaller calls decode error : {}", e),
+                                        None => {}
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    Err(e)=>{
+                        error!("Error receiving compose messages: {e}");
+                    }
+                }
+            }
+
             logs = logs_sub.recv() => {
                 match logs{
                     Ok(logs_update)=>{
@@ -0,0 +1,9 @@
+pub use oracle::IAaveOracle;
+pub use pool::IAaveV3Pool;
+pub use pool_data_provider::IAaveV3PoolDataProvider;
+pub use price_source::IChainlinkAggregatorProxy;
+
+mod oracle;
+mod pool;
+mod pool_data_provider;
+mod price_source;
@@ -0,0 +1,11 @@
+use alloy_sol_types::sol;
+
+sol! {
+    #[sol(abi = true, rpc)]
+    #[derive(Debug, PartialEq, Eq)]
+    interface IAaveOracle {
+        function getAssetPrice(address asset) external view returns (uint256);
+
+        function getSourceOfAsset(address asset) external view returns (address);
+    }
+}
@@ -0,0 +1,53 @@
+use alloy_sol_types::sol;
+
+sol! {
+    #[sol(abi = true, rpc)]
+    #[derive(Debug, PartialEq, Eq)]
+    interface IAaveV3Pool {
+        event Supply(address indexed reserve, address user, address indexed onBehalfOf, uint256 amount, uint16 indexed referralCode);
+
+        event Withdraw(address indexed reserve, address indexed user, address indexed to, uint256 amount);
+
+        event Borrow(
+            address indexed reserve,
+            address user,
+            address indexed onBehalfOf,
+            uint256 amount,
+            uint8 interestRateMode,
+            uint256 borrowRate,
+            uint16 indexed referralCode
+        );
+
+        event Repay(address indexed reserve, address indexed user, address indexed repayer, uint256 amount, bool useATokens);
+
+        event LiquidationCall(
+            address indexed collateralAsset,
+            address indexed debtAsset,
+            address indexed user,
+            uint256 debtToCover,
+            uint256 liquidatedCollateralAmount,
+            address liquidator,
+            bool receiveAToken
+        );
+
+        function getUserAccountData(address user)
+            external
+            view
+            returns (
+                uint256 totalCollateralBase,
+                uint256 totalDebtBase,
+                uint256 availableBorrowsBase,
+                uint256 currentLiquidationThreshold,
+                uint256 ltv,
+                uint256 healthFactor
+            );
+
+        function getReservesList() external view returns (address[] memory);
+
+        function liquidationCall(address collateralAsset, address debtAsset, address user, uint256 debtToCover, bool receiveAToken) external;
+
+        function flashLoanSimple(address receiverAddress, address asset, uint256 amount, bytes calldata params, uint16 referralCode) external;
+
+        function FLASHLOAN_PREMIUM_TOTAL() external view returns (uint128);
+    }
+}
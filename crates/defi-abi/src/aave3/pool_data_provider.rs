@@ -0,0 +1,43 @@
+use alloy_sol_types::sol;
+
+sol! {
+    #[sol(abi = true, rpc)]
+    #[derive(Debug, PartialEq, Eq)]
+    interface IAaveV3PoolDataProvider {
+        function getUserReserveData(address asset, address user)
+            external
+            view
+            returns (
+                uint256 currentATokenBalance,
+                uint256 currentStableDebt,
+                uint256 currentVariableDebt,
+                uint256 principalStableDebt,
+                uint256 scaledVariableDebt,
+                uint256 stableBorrowRate,
+                uint256 liquidityRate,
+                uint40 stableRateLastUpdated,
+                bool usageAsCollateralEnabled
+            );
+
+        function getReserveConfigurationData(address asset)
+            external
+            view
+            returns (
+                uint256 decimals,
+                uint256 ltv,
+                uint256 liquidationThreshold,
+                uint256 liquidationBonus,
+                uint256 reserveFactor,
+                bool usageAsCollateralEnabled,
+                bool borrowingEnabled,
+                bool stableBorrowRateEnabled,
+                bool isActive,
+                bool isFrozen
+            );
+
+        function getReserveTokensAddresses(address asset)
+            external
+            view
+            returns (address aTokenAddress, address stableDebtTokenAddress, address variableDebtTokenAddress);
+    }
+}
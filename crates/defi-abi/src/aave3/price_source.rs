@@ -0,0 +1,11 @@
+use alloy_sol_types::sol;
+
+sol! {
+    #[sol(abi = true, rpc)]
+    #[derive(Debug, PartialEq, Eq)]
+    interface IChainlinkAggregatorProxy {
+        function aggregator() external view returns (address);
+
+        function latestAnswer() external view returns (int256);
+    }
+}
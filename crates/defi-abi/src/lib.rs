@@ -2,16 +2,20 @@ pub use erc20::IERC20;
 pub use multicaller::IMultiCaller;
 pub use weth::IWETH;
 
+pub mod aave3;
 pub mod balancer;
 pub mod curve;
 mod erc20;
 pub mod lido;
+pub mod maker;
 pub mod maverick;
 pub mod multicaller;
+pub mod oneinch;
 pub mod uniswap2;
 pub mod uniswap3;
 pub mod uniswap4;
 pub mod uniswap_periphery;
 mod weth;
+pub mod zeroex;
 
 pub mod pancake;
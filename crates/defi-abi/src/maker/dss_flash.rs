@@ -0,0 +1,20 @@
+use alloy_sol_types::sol;
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    interface IERC3156FlashBorrower {
+        function onFlashLoan(address initiator, address token, uint256 amount, uint256 fee, bytes calldata data) external returns (bytes32);
+    }
+}
+
+sol! {
+    #[sol(abi = true, rpc)]
+    #[derive(Debug, PartialEq, Eq)]
+    interface IDssFlash {
+        function max() external view returns (uint256);
+        function toll() external view returns (uint256);
+        function maxFlashLoan(address token) external view returns (uint256);
+        function flashFee(address token, uint256 amount) external view returns (uint256);
+        function flashLoan(address receiver, address token, uint256 amount, bytes calldata data) external returns (bool);
+    }
+}
@@ -0,0 +1,3 @@
+pub use dss_flash::{IDssFlash, IERC3156FlashBorrower};
+
+mod dss_flash;
@@ -0,0 +1,3 @@
+pub use router::*;
+
+mod router;
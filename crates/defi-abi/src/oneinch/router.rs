@@ -0,0 +1,54 @@
+use alloy_sol_types::sol;
+
+sol! {
+    #[sol(abi = true, rpc)]
+    #[derive(Debug, PartialEq, Eq)]
+    interface IAggregationRouterV5 {
+        function unoswap(address srcToken, uint256 amount, uint256 minReturn, uint256[] calldata pools)
+            external
+            payable
+            returns (uint256 returnAmount);
+
+        function unoswapTo(address recipient, address srcToken, uint256 amount, uint256 minReturn, uint256[] calldata pools)
+            external
+            payable
+            returns (uint256 returnAmount);
+
+        function uniswapV3Swap(uint256 amount, uint256 minReturn, uint256[] calldata pools)
+            external
+            payable
+            returns (uint256 returnAmount);
+
+        function uniswapV3SwapTo(address recipient, uint256 amount, uint256 minReturn, uint256[] calldata pools)
+            external
+            payable
+            returns (uint256 returnAmount);
+    }
+}
+
+sol! {
+    #[sol(abi = true, rpc)]
+    #[derive(Debug, PartialEq, Eq)]
+    interface IAggregationRouterV6 {
+        function unoswap(uint256 token, uint256 amount, uint256 minReturn, uint256 dex)
+            external
+            returns (uint256 returnAmount);
+
+        function unoswap2(uint256 token, uint256 amount, uint256 minReturn, uint256 dex, uint256 dex2)
+            external
+            returns (uint256 returnAmount);
+
+        function unoswap3(uint256 token, uint256 amount, uint256 minReturn, uint256 dex, uint256 dex2, uint256 dex3)
+            external
+            returns (uint256 returnAmount);
+
+        function ethUnoswap(uint256 minReturn, uint256 dex) external payable returns (uint256 returnAmount);
+
+        function ethUnoswap2(uint256 minReturn, uint256 dex, uint256 dex2) external payable returns (uint256 returnAmount);
+
+        function ethUnoswap3(uint256 minReturn, uint256 dex, uint256 dex2, uint256 dex3)
+            external
+            payable
+            returns (uint256 returnAmount);
+    }
+}
@@ -18,5 +18,54 @@ sol! {
             virtual
             override
             returns (uint amountIn);
+
+        function swapExactTokensForTokens(uint amountIn, uint amountOutMin, address[] calldata path, address to, uint deadline)
+            external
+            returns (uint[] memory amounts);
+
+        function swapTokensForExactTokens(uint amountOut, uint amountInMax, address[] calldata path, address to, uint deadline)
+            external
+            returns (uint[] memory amounts);
+
+        function swapExactETHForTokens(uint amountOutMin, address[] calldata path, address to, uint deadline)
+            external
+            payable
+            returns (uint[] memory amounts);
+
+        function swapTokensForExactETH(uint amountOut, uint amountInMax, address[] calldata path, address to, uint deadline)
+            external
+            returns (uint[] memory amounts);
+
+        function swapExactTokensForETH(uint amountIn, uint amountOutMin, address[] calldata path, address to, uint deadline)
+            external
+            returns (uint[] memory amounts);
+
+        function swapETHForExactTokens(uint amountOut, address[] calldata path, address to, uint deadline)
+            external
+            payable
+            returns (uint[] memory amounts);
+
+        function swapExactTokensForTokensSupportingFeeOnTransferTokens(
+            uint amountIn,
+            uint amountOutMin,
+            address[] calldata path,
+            address to,
+            uint deadline
+        ) external;
+
+        function swapExactETHForTokensSupportingFeeOnTransferTokens(
+            uint amountOutMin,
+            address[] calldata path,
+            address to,
+            uint deadline
+        ) external payable;
+
+        function swapExactTokensForETHSupportingFeeOnTransferTokens(
+            uint amountIn,
+            uint amountOutMin,
+            address[] calldata path,
+            address to,
+            uint deadline
+        ) external;
     }
 }
@@ -142,6 +142,14 @@ sol! {
         function lockAcquired(address lockCaller, bytes calldata data) external returns (bytes memory);
     }
 
+    #[derive(Debug, PartialEq, Eq)]
+    interface IUniswapV4UnlockCallback {
+        /// @notice Called by the pool manager on `msg.sender` when the manager is unlocked
+        /// @param data The data that was passed to the call to unlock
+        /// @return Any data that you want to be returned from the unlock call
+        function unlockCallback(bytes calldata data) external returns (bytes memory);
+    }
+
 
 
 
@@ -282,6 +290,11 @@ sol! {
         /// @return The data returned by the call to `ILockCallback(msg.sender).lockAcquired(data)`
         function lock(address lockTarget, bytes calldata data) external payable returns (bytes memory);
 
+        /// @notice All interactions on the contract that account deltas require unlocking
+        /// @param data Any data to pass to the callback, via `IUnlockCallback(msg.sender).unlockCallback(data)`
+        /// @return The data returned by the call to `IUnlockCallback(msg.sender).unlockCallback(data)`
+        function unlock(bytes calldata data) external returns (bytes memory);
+
         struct ModifyLiquidityParams {
             // the lower and upper tick of the position
             int24 tickLower;
@@ -326,8 +339,12 @@ sol! {
         /// @notice Called by the user to move value from ERC6909 balance
         function burn(address from, uint256 id, uint256 amount) external;
 
-        /// @notice Called by the user to pay what is owed
-        function settle(Currency token) external payable returns (uint256 paid);
+        /// @notice Writes the current ERC20 balance of the specified currency to transient storage
+        /// @dev Must be called before the currency is transferred to the manager and settled
+        function sync(Currency currency) external;
+
+        /// @notice Called by the user to pay what is owed for the currency synced before
+        function settle() external payable returns (uint256 paid);
 
         /// @notice Sets the protocol's swap fee for the given pool
         /// Protocol fees are always a portion of the LP swap fee that is owed. If that fee is 0, no protocol fees will accrue even if it is set to > 0.
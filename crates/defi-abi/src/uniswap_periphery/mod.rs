@@ -1,7 +1,13 @@
 pub use custorm_quoter::ICustomQuoter;
+pub use periphery_payments::{IPeripheryPayments, ISelfPermit};
 pub use quoter::IQuoterV2;
+pub use swap_router::{ISwapRouter, ISwapRouter02};
 pub use ticklens::ITickLens;
+pub use universal_router::{IUniversalRouter, UniversalRouterCommand};
 
 mod custorm_quoter;
+mod periphery_payments;
 mod quoter;
+mod swap_router;
 mod ticklens;
+mod universal_router;
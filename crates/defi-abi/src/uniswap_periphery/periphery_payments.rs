@@ -0,0 +1,37 @@
+use alloy_sol_types::sol;
+
+sol! {
+    #[sol(abi = true, rpc)]
+    #[derive(Debug, PartialEq, Eq)]
+    interface IPeripheryPayments {
+        function unwrapWETH9(uint256 amountMinimum, address recipient) external payable;
+
+        function unwrapWETH9(uint256 amountMinimum) external payable;
+
+        function refundETH() external payable;
+
+        function sweepToken(address token, uint256 amountMinimum, address recipient) external payable;
+
+        function sweepToken(address token, uint256 amountMinimum) external payable;
+
+        function wrapETH(uint256 value) external payable;
+
+        function pull(address token, uint256 value) external payable;
+    }
+}
+
+sol! {
+    #[sol(abi = true, rpc)]
+    #[derive(Debug, PartialEq, Eq)]
+    interface ISelfPermit {
+        function selfPermit(address token, uint256 value, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external payable;
+
+        function selfPermitIfNecessary(address token, uint256 value, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external payable;
+
+        function selfPermitAllowed(address token, uint256 nonce, uint256 expiry, uint8 v, bytes32 r, bytes32 s) external payable;
+
+        function selfPermitAllowedIfNecessary(address token, uint256 nonce, uint256 expiry, uint8 v, bytes32 r, bytes32 s)
+            external
+            payable;
+    }
+}
@@ -0,0 +1,52 @@
+use alloy_sol_types::sol;
+
+sol! {
+    #[sol(abi = true, rpc)]
+    #[derive(Debug, PartialEq, Eq)]
+    interface IUniversalRouter {
+        function execute(bytes calldata commands, bytes[] calldata inputs, uint256 deadline) external payable;
+
+        function execute(bytes calldata commands, bytes[] calldata inputs) external payable;
+    }
+}
+
+/// Universal router command types. Command type is the command byte masked with COMMAND_TYPE_MASK
+pub struct UniversalRouterCommand;
+
+impl UniversalRouterCommand {
+    pub const FLAG_ALLOW_REVERT: u8 = 0x80;
+    pub const COMMAND_TYPE_MASK: u8 = 0x3f;
+
+    pub const V3_SWAP_EXACT_IN: u8 = 0x00;
+    pub const V3_SWAP_EXACT_OUT: u8 = 0x01;
+    pub const PERMIT2_TRANSFER_FROM: u8 = 0x02;
+    pub const PERMIT2_PERMIT_BATCH: u8 = 0x03;
+    pub const SWEEP: u8 = 0x04;
+    pub const TRANSFER: u8 = 0x05;
+    pub const PAY_PORTION: u8 = 0x06;
+    pub const V2_SWAP_EXACT_IN: u8 = 0x08;
+    pub const V2_SWAP_EXACT_OUT: u8 = 0x09;
+    pub const PERMIT2_PERMIT: u8 = 0x0a;
+    pub const WRAP_ETH: u8 = 0x0b;
+    pub const UNWRAP_WETH: u8 = 0x0c;
+    pub const PERMIT2_TRANSFER_FROM_BATCH: u8 = 0x0d;
+    pub const BALANCE_CHECK_ERC20: u8 = 0x0e;
+    pub const EXECUTE_SUB_PLAN: u8 = 0x21;
+
+    /// Commands that move tokens or check balances without swapping
+    pub fn is_non_swap(command_type: u8) -> bool {
+        matches!(
+            command_type,
+            Self::PERMIT2_TRANSFER_FROM
+                | Self::PERMIT2_PERMIT_BATCH
+                | Self::SWEEP
+                | Self::TRANSFER
+                | Self::PAY_PORTION
+                | Self::PERMIT2_PERMIT
+                | Self::WRAP_ETH
+                | Self::UNWRAP_WETH
+                | Self::PERMIT2_TRANSFER_FROM_BATCH
+                | Self::BALANCE_CHECK_ERC20
+        )
+    }
+}
@@ -0,0 +1,3 @@
+pub use proxy::*;
+
+mod proxy;
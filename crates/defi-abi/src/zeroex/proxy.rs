@@ -0,0 +1,25 @@
+use alloy_sol_types::sol;
+
+sol! {
+    #[sol(abi = true, rpc)]
+    #[derive(Debug, PartialEq, Eq)]
+    interface IZeroExProxy {
+        function sellToUniswap(address[] calldata tokens, uint256 sellAmount, uint256 minBuyAmount, bool isSushi)
+            external
+            payable
+            returns (uint256 buyAmount);
+
+        function sellEthForTokenToUniswapV3(bytes memory encodedPath, uint256 minBuyAmount, address recipient)
+            external
+            payable
+            returns (uint256 buyAmount);
+
+        function sellTokenForEthToUniswapV3(bytes memory encodedPath, uint256 sellAmount, uint256 minBuyAmount, address recipient)
+            external
+            returns (uint256 buyAmount);
+
+        function sellTokenForTokenToUniswapV3(bytes memory encodedPath, uint256 sellAmount, uint256 minBuyAmount, address recipient)
+            external
+            returns (uint256 buyAmount);
+    }
+}
@@ -4,7 +4,7 @@ use std::env;
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use debug_provider::AnvilDebugProviderFactory;
-use defi_actors::SwapCalculator;
+use defi_actors::{StateChangeArbSearcherConfig, SwapCalculator};
 use defi_address_book::TokenAddress;
 use defi_entities::required_state::RequiredStateReader;
 use defi_entities::{Market, PoolClass, PoolWrapper, SwapLine, SwapPath, Token};
@@ -65,10 +65,12 @@ pub fn bench_swap_calculator(c: &mut Criterion) {
 
     let swap_line = SwapLine { path: swap_path, ..Default::default() };
 
+    let searcher_config = StateChangeArbSearcherConfig::default();
+
     println!("SwapLine: {}", swap_line);
     group.bench_function("calculate", |b| {
         b.iter(|| {
-            SwapCalculator::calculate(black_box(&mut swap_line.clone()), black_box(&state_db), black_box(Env::default()))
+            SwapCalculator::calculate(black_box(&mut swap_line.clone()), black_box(&state_db), black_box(Env::default()), &searcher_config)
                 .expect("Failed to calculate swap");
         })
     });
@@ -70,6 +70,7 @@ pub async fn nonce_and_balance_monitor_worker(
                     match market_event_msg {
                         MarketEvents::BlockTxUpdate{ block_hash, .. }=>{
                             if let Some(block_entry) = block_history_state.read().await.get_entry(&block_hash).cloned() {
+                                let block_number = block_entry.number();
                                 if let Some(block) = block_entry.block {
                                     if let BlockTransactions::Full(txs) = block.transactions {
 
@@ -96,6 +97,9 @@ pub async fn nonce_and_balance_monitor_worker(
                                                 }
                                             }
                                         }
+
+                                        // nonces of mined transactions and expired reservations are released
+                                        accounts_lock.reconcile(block_number);
                                     }
                                 }
                             }
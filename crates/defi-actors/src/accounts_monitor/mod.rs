@@ -1,3 +1,5 @@
 mod accounts_actor;
+mod nonce_reservation;
 
 pub use accounts_actor::NonceAndBalanceMonitorActor;
+pub(crate) use nonce_reservation::{confirm_request_nonce, release_nonce_on_error, release_request_nonce};
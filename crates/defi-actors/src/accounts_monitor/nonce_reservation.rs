@@ -0,0 +1,51 @@
+use std::future::Future;
+
+use alloy_primitives::BlockNumber;
+use eyre::Result;
+
+use defi_entities::AccountNonceAndBalanceState;
+use defi_events::TxComposeData;
+use loom_actors::SharedState;
+
+/// Extends the nonce reservation held by the request until the last target block.
+pub(crate) async fn confirm_request_nonce(
+    account_nonce_balance: Option<&SharedState<AccountNonceAndBalanceState>>,
+    request: &TxComposeData,
+    max_block: BlockNumber,
+) {
+    if let (Some(account_nonce_balance), Some(signer), Some(reserved)) =
+        (account_nonce_balance, request.signer.as_ref(), request.reserved_nonce)
+    {
+        account_nonce_balance.write().await.confirm_nonce(&signer.address(), reserved, max_block);
+    }
+}
+
+/// Releases the nonce reservation held by the dropped request.
+pub(crate) async fn release_request_nonce(
+    account_nonce_balance: Option<&SharedState<AccountNonceAndBalanceState>>,
+    request: &TxComposeData,
+) {
+    if let (Some(account_nonce_balance), Some(signer), Some(reserved)) =
+        (account_nonce_balance, request.signer.as_ref(), request.reserved_nonce)
+    {
+        account_nonce_balance.write().await.release_nonce(&signer.address(), reserved);
+    }
+}
+
+/// Runs the compose task for the request and releases the nonce reservation it holds if the task drops the request.
+pub(crate) async fn release_nonce_on_error<F, Fut>(
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
+    request: TxComposeData,
+    task: F,
+) -> Result<()>
+where
+    F: FnOnce(TxComposeData) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let reservation = request.signer.as_ref().map(|signer| signer.address()).zip(request.reserved_nonce);
+    let result = task(request).await;
+    if let (Err(_), Some(account_nonce_balance), Some((account, reserved))) = (&result, account_nonce_balance, reservation) {
+        account_nonce_balance.write().await.release_nonce(&account, reserved);
+    }
+    result
+}
@@ -15,13 +15,15 @@ use loom_actors_macros::{Accessor, Consumer, Producer};
 
 use crate::backrun::block_state_change_processor::BlockStateChangeProcessorActor;
 
-use super::{PendingTxStateChangeProcessorActor, StateChangeArbSearcherActor};
+use super::{PendingTxSimulation, PendingTxStateChangeProcessorActor, StateChangeArbSearcherActor, StateChangeArbSearcherConfig};
 
 #[derive(Accessor, Consumer, Producer)]
 pub struct StateChangeArbActor<P, T, N> {
     client: P,
     use_blocks: bool,
     use_mempool: bool,
+    simulation: PendingTxSimulation,
+    searcher_config: StateChangeArbSearcherConfig,
     #[accessor]
     market: Option<SharedState<Market>>,
     #[accessor]
@@ -55,6 +57,8 @@ where
             client,
             use_blocks,
             use_mempool,
+            simulation: PendingTxSimulation::default(),
+            searcher_config: StateChangeArbSearcherConfig::default(),
             market: None,
             mempool: None,
             latest_block: None,
@@ -68,6 +72,14 @@ where
             _n: PhantomData,
         }
     }
+
+    pub fn with_simulation(self, simulation: PendingTxSimulation) -> Self {
+        Self { simulation, ..self }
+    }
+
+    pub fn with_searcher_config(self, searcher_config: StateChangeArbSearcherConfig) -> Self {
+        Self { searcher_config, ..self }
+    }
 }
 
 impl<P, T, N> Actor for StateChangeArbActor<P, T, N>
@@ -80,7 +92,7 @@ where
         let searcher_pool_update_channel = Broadcaster::new(100);
         let mut tasks: Vec<JoinHandle<WorkerResult>> = Vec::new();
 
-        let mut state_update_searcher = StateChangeArbSearcherActor::new(true);
+        let mut state_update_searcher = StateChangeArbSearcherActor::new(true).with_config(self.searcher_config.clone());
         match state_update_searcher
             .access(self.market.clone().unwrap())
             .consume(searcher_pool_update_channel.clone())
@@ -98,7 +110,8 @@ where
         }
 
         if self.mempool_events_tx.is_some() && self.use_mempool {
-            let mut pending_tx_state_processor = PendingTxStateChangeProcessorActor::new(self.client.clone());
+            let mut pending_tx_state_processor =
+                PendingTxStateChangeProcessorActor::new(self.client.clone()).with_simulation(self.simulation);
             match pending_tx_state_processor
                 .access(self.mempool.clone().unwrap())
                 .access(self.latest_block.clone().unwrap())
@@ -75,7 +75,8 @@ pub async fn block_state_change_worker(
             Vec::new(),
             "block_searcher".to_string(),
             9000,
-        );
+        )
+        .with_parent_block_hash(block_hash);
         run_async!(state_updates_broadcaster.send(request));
     }
 }
@@ -1,12 +1,17 @@
 pub use arb_actor::StateChangeArbActor;
 pub use block_state_change_processor::BlockStateChangeProcessorActor;
+pub use pending_tx_simulator::PendingTxSimulation;
 pub use pending_tx_state_change_processor::PendingTxStateChangeProcessorActor;
+pub use searcher_config::StateChangeArbSearcherConfig;
 pub use state_change_arb_searcher::StateChangeArbSearcherActor;
 pub use swap_calculator::SwapCalculator;
 
 mod block_state_change_processor;
+mod pending_tx_simulator;
 mod pending_tx_state_change_processor;
+mod searcher_config;
 mod state_change_arb_searcher;
+mod swap_calculation_cache;
 
 mod affected_pools;
 mod affected_pools_code;
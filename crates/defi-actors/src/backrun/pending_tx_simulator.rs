@@ -0,0 +1,367 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+
+use alloy_network::Network;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types::state::StateOverride;
+use alloy_rpc_types::TransactionRequest;
+use alloy_rpc_types_trace::geth::AccountState;
+use alloy_transport::Transport;
+use eyre::{eyre, Result};
+use futures::future::join_all;
+use lazy_static::lazy_static;
+use revm::primitives::{AccountInfo, BlockEnv, Bytecode, Env, EvmState, TxEnv, TxKind, CANCUN};
+use revm::{DatabaseRef, Evm};
+use tracing::{debug, trace};
+
+use defi_entities::MarketState;
+use defi_types::GethStateUpdate;
+use loom_actors::SharedState;
+
+lazy_static! {
+    static ref COINBASE: Address = "0x1f9090aaE28b8a3dCeaDf281B0F12828e676c326".parse().unwrap();
+}
+
+const MAX_FETCH_ITERATIONS: usize = 5;
+
+/// How pending transactions are simulated to get their state change
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PendingTxSimulation {
+    /// debug_traceCall on the node
+    #[default]
+    Tracer,
+    /// local revm on the market state. Falls back to the tracer when state is missing
+    Local,
+}
+
+#[derive(Debug, Default)]
+struct FetchedState {
+    accounts: HashMap<Address, AccountInfo>,
+    storage: HashMap<(Address, U256), U256>,
+}
+
+/// Reads market state and fetched state under the node state override, recording everything missing in both
+struct MissingStateTrackingDB<'a> {
+    market_state: &'a MarketState,
+    fetched: &'a FetchedState,
+    state_override: &'a StateOverride,
+    missing_accounts: RefCell<HashSet<Address>>,
+    missing_slots: RefCell<HashSet<(Address, U256)>>,
+}
+
+impl<'a> MissingStateTrackingDB<'a> {
+    fn new(market_state: &'a MarketState, fetched: &'a FetchedState, state_override: &'a StateOverride) -> Self {
+        Self {
+            market_state,
+            fetched,
+            state_override,
+            missing_accounts: RefCell::new(HashSet::new()),
+            missing_slots: RefCell::new(HashSet::new()),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.missing_accounts.borrow().is_empty() && self.missing_slots.borrow().is_empty()
+    }
+}
+
+impl DatabaseRef for MissingStateTrackingDB<'_> {
+    type Error = Infallible;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = if let Some(info) = self.fetched.accounts.get(&address) {
+            Some(info.clone())
+        } else if self.market_state.is_account(&address) {
+            self.market_state.state_db.basic_ref(address)?
+        } else {
+            self.missing_accounts.borrow_mut().insert(address);
+            None
+        };
+
+        Ok(info.map(|mut info| {
+            if let Some(account_override) = self.state_override.get(&address) {
+                if let Some(balance) = account_override.balance {
+                    info.balance = balance;
+                }
+                if let Some(nonce) = account_override.nonce {
+                    info.nonce = nonce;
+                }
+            }
+            info
+        }))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.market_state.state_db.code_by_hash_ref(code_hash)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(account_override) = self.state_override.get(&address) {
+            let slot = B256::from(index);
+            if let Some(state) = account_override.state.as_ref() {
+                return Ok(state.get(&slot).map(|value| (*value).into()).unwrap_or_default());
+            }
+            if let Some(value) = account_override.state_diff.as_ref().and_then(|state_diff| state_diff.get(&slot)) {
+                return Ok((*value).into());
+            }
+        }
+
+        if let Some(value) = self.fetched.storage.get(&(address, index)) {
+            Ok(*value)
+        } else if self.market_state.is_slot(&address, &index) {
+            self.market_state.state_db.storage_ref(address, index)
+        } else {
+            self.missing_slots.borrow_mut().insert((address, index));
+            Ok(U256::ZERO)
+        }
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.market_state.state_db.block_hash_ref(number)
+    }
+}
+
+/// Pending transaction is executed in the next block
+fn env_for_pending_tx(tx: &TransactionRequest, next_block_number: u64, next_block_timestamp: u64, next_base_fee: u64) -> Env {
+    Env {
+        cfg: Default::default(),
+        block: BlockEnv {
+            number: U256::from(next_block_number),
+            coinbase: *COINBASE,
+            timestamp: U256::from(next_block_timestamp),
+            basefee: U256::from(next_base_fee),
+            ..Default::default()
+        },
+        tx: TxEnv {
+            caller: tx.from.unwrap_or_default(),
+            gas_limit: tx.gas.unwrap_or_default(),
+            gas_price: U256::from(tx.max_fee_per_gas.unwrap_or(tx.gas_price.unwrap_or_default())),
+            gas_priority_fee: tx.max_priority_fee_per_gas.map(U256::from),
+            transact_to: tx.to.unwrap_or(TxKind::Create),
+            value: tx.value.unwrap_or_default(),
+            data: tx.input.input().cloned().unwrap_or_default(),
+            // pending tx nonce may be ahead of the latest state, skip nonce and chain id checks
+            nonce: None,
+            chain_id: None,
+            ..Default::default()
+        },
+    }
+}
+
+/// Converts revm state into geth prestate tracer diff mode pair (pre, post)
+fn evm_state_to_diff<DB: DatabaseRef<Error = Infallible>>(db: DB, state: EvmState) -> (GethStateUpdate, GethStateUpdate) {
+    let mut pre = GethStateUpdate::new();
+    let mut post = GethStateUpdate::new();
+
+    for (address, account) in state.into_iter() {
+        if !account.is_touched() {
+            continue;
+        }
+
+        let pre_info = db.basic_ref(address).ok().flatten().unwrap_or_default();
+
+        let mut pre_account = AccountState { balance: Some(pre_info.balance), nonce: Some(pre_info.nonce), ..AccountState::default() };
+        let mut post_account = AccountState::default();
+
+        if account.info.balance != pre_info.balance {
+            post_account.balance = Some(account.info.balance);
+        }
+        if account.info.nonce != pre_info.nonce {
+            post_account.nonce = Some(account.info.nonce);
+        }
+        if account.is_created() {
+            post_account.code = account.info.code.as_ref().map(|x| x.original_bytes());
+        }
+
+        for (slot, value) in account.storage.iter().filter(|(_, value)| value.is_changed()) {
+            pre_account.storage.insert((*slot).into(), value.original_value.into());
+            post_account.storage.insert((*slot).into(), value.present_value.into());
+        }
+
+        if post_account.balance.is_some() || post_account.nonce.is_some() || post_account.code.is_some() || !post_account.storage.is_empty()
+        {
+            pre.insert(address, pre_account);
+            post.insert(address, post_account);
+        }
+    }
+
+    (pre, post)
+}
+
+async fn fetch_missing_state<P, T, N>(
+    client: P,
+    fetched: &mut FetchedState,
+    missing_accounts: HashSet<Address>,
+    missing_slots: HashSet<(Address, U256)>,
+) -> Result<()>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + Send + Sync + Clone + 'static,
+{
+    let accounts = join_all(missing_accounts.into_iter().map(|address| {
+        let client = client.clone();
+        async move {
+            let balance = client.get_balance(address).await?;
+            let nonce = client.get_transaction_count(address).await?;
+            let code: Bytes = client.get_code_at(address).await?;
+            let code = Bytecode::new_raw(code);
+            Ok::<(Address, AccountInfo), eyre::Report>((address, AccountInfo::new(balance, nonce, code.hash_slow(), code)))
+        }
+    }))
+    .await;
+
+    for account in accounts {
+        let (address, info) = account?;
+        trace!(%address, "Fetched account");
+        fetched.accounts.insert(address, info);
+    }
+
+    let slots = join_all(missing_slots.into_iter().map(|(address, slot)| {
+        let client = client.clone();
+        async move {
+            let value = client.get_storage_at(address, slot).await?;
+            Ok::<((Address, U256), U256), eyre::Report>(((address, slot), value))
+        }
+    }))
+    .await;
+
+    for slot in slots {
+        let (key, value) = slot?;
+        fetched.storage.insert(key, value);
+    }
+
+    Ok(())
+}
+
+/// Simulates transaction with revm on top of the market state and returns its (pre, post) diff like debug_traceCall
+/// with prestate tracer in diff mode. Accounts and slots missing in market state are fetched from the node, the state
+/// override is applied on top like in the traced call.
+/// Market state read lock is held only while the transaction is executed, not while missing state is fetched.
+pub async fn local_trace_call_diff<P, T, N>(
+    client: P,
+    market_state: &SharedState<MarketState>,
+    state_override: &StateOverride,
+    tx: &TransactionRequest,
+    next_block_number: u64,
+    next_block_timestamp: u64,
+    next_base_fee: u64,
+) -> Result<(GethStateUpdate, GethStateUpdate)>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + Send + Sync + Clone + 'static,
+{
+    let env = env_for_pending_tx(tx, next_block_number, next_block_timestamp, next_base_fee);
+    let mut fetched = FetchedState::default();
+
+    for iteration in 0..MAX_FETCH_ITERATIONS {
+        let (missing_accounts, missing_slots) = {
+            let market_state_guard = market_state.read().await;
+            let db = MissingStateTrackingDB::new(&market_state_guard, &fetched, state_override);
+
+            let mut evm = Evm::builder().with_spec_id(CANCUN).with_ref_db(&db).with_env(Box::new(env.clone())).build();
+            let result = evm.transact().map_err(|e| eyre!("LOCAL_SIMULATION_ERROR : {}", e))?;
+            drop(evm);
+
+            if db.is_complete() {
+                debug!(iteration, accounts = fetched.accounts.len(), slots = fetched.storage.len(), "Local simulation finished");
+                return Ok(evm_state_to_diff(&db, result.state));
+            }
+            (db.missing_accounts.take(), db.missing_slots.take())
+        };
+
+        trace!(iteration, accounts = missing_accounts.len(), slots = missing_slots.len(), "Fetching missing state");
+        fetch_missing_state(client.clone(), &mut fetched, missing_accounts, missing_slots).await?;
+    }
+
+    Err(eyre!("LOCAL_SIMULATION_STATE_MISSING"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use loom_revm_db::LoomInMemoryDB;
+    use revm::primitives::{Account, AccountStatus, EvmStorageSlot};
+
+    #[test]
+    fn test_missing_state_tracking() {
+        let known = Address::repeat_byte(1);
+        let unknown = Address::repeat_byte(2);
+
+        let mut state_db = LoomInMemoryDB::default();
+        state_db.insert_account_info(known, AccountInfo::from_balance(U256::from(100)));
+        state_db.insert_account_storage(known, U256::from(1), U256::from(10)).unwrap();
+        let market_state = MarketState::new(state_db);
+
+        let mut fetched = FetchedState::default();
+        fetched.storage.insert((known, U256::from(3)), U256::from(30));
+
+        let state_override = StateOverride::default();
+        let db = MissingStateTrackingDB::new(&market_state, &fetched, &state_override);
+
+        assert_eq!(db.basic_ref(known).unwrap().unwrap().balance, U256::from(100));
+        assert_eq!(db.storage_ref(known, U256::from(1)).unwrap(), U256::from(10));
+        assert_eq!(db.storage_ref(known, U256::from(3)).unwrap(), U256::from(30));
+        assert!(db.is_complete());
+
+        assert!(db.basic_ref(unknown).unwrap().is_none());
+        assert_eq!(db.storage_ref(known, U256::from(2)).unwrap(), U256::ZERO);
+        assert!(!db.is_complete());
+        assert!(db.missing_accounts.borrow().contains(&unknown));
+        assert!(db.missing_slots.borrow().contains(&(known, U256::from(2))));
+    }
+
+    #[test]
+    fn test_missing_state_tracking_with_override() {
+        let known = Address::repeat_byte(1);
+
+        let mut state_db = LoomInMemoryDB::default();
+        state_db.insert_account_info(known, AccountInfo::from_balance(U256::from(100)));
+        state_db.insert_account_storage(known, U256::from(1), U256::from(10)).unwrap();
+        let market_state = MarketState::new(state_db);
+        let fetched = FetchedState::default();
+
+        let mut state_override = StateOverride::default();
+        let account_override = state_override.entry(known).or_default();
+        account_override.balance = Some(U256::from(200));
+        account_override.state_diff = Some([(B256::from(U256::from(1)), B256::from(U256::from(20)))].into_iter().collect());
+
+        let db = MissingStateTrackingDB::new(&market_state, &fetched, &state_override);
+
+        assert_eq!(db.basic_ref(known).unwrap().unwrap().balance, U256::from(200));
+        assert_eq!(db.storage_ref(known, U256::from(1)).unwrap(), U256::from(20));
+        assert!(db.is_complete());
+    }
+
+    #[test]
+    fn test_evm_state_to_diff() {
+        let address = Address::repeat_byte(1);
+        let untouched = Address::repeat_byte(2);
+
+        let mut state_db = LoomInMemoryDB::default();
+        state_db.insert_account_info(address, AccountInfo::from_balance(U256::from(100)));
+
+        let mut account = Account::from(AccountInfo::from_balance(U256::from(90)));
+        account.status = AccountStatus::Touched;
+        account.storage.insert(U256::from(1), EvmStorageSlot::new_changed(U256::from(5), U256::from(6)));
+        account.storage.insert(U256::from(2), EvmStorageSlot::new(U256::from(7)));
+
+        let mut state = EvmState::default();
+        state.insert(address, account);
+        state.insert(untouched, Account::from(AccountInfo::default()));
+
+        let (pre, post) = evm_state_to_diff(&state_db, state);
+
+        assert_eq!(pre.len(), 1);
+        assert_eq!(post.len(), 1);
+        assert_eq!(pre[&address].balance, Some(U256::from(100)));
+        assert_eq!(post[&address].balance, Some(U256::from(90)));
+        assert_eq!(post[&address].nonce, None);
+        assert_eq!(post[&address].storage.len(), 1);
+        assert_eq!(post[&address].storage[&B256::from(U256::from(1))], B256::from(U256::from(6)));
+        assert_eq!(pre[&address].storage[&B256::from(U256::from(1))], B256::from(U256::from(5)));
+    }
+}
@@ -11,7 +11,7 @@ use eyre::{eyre, Result};
 use lazy_static::lazy_static;
 use revm::primitives::bitvec::macros::internal::funty::Fundamental;
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::marker::PhantomData;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -20,14 +20,15 @@ use tracing::{debug, error, warn};
 use debug_provider::DebugProviderExt;
 use defi_blockchain::Blockchain;
 use defi_entities::required_state::accounts_vec_len;
-use defi_entities::{LatestBlock, Market, MarketState};
+use defi_entities::{LatestBlock, Market, MarketState, PoolWrapper};
 use defi_events::{MarketEvents, MempoolEvents, StateUpdateEvent};
-use defi_types::{debug_trace_call_diff, GethStateUpdateVec, Mempool, TRACING_CALL_OPTS};
+use defi_types::{debug_trace_call_diff, ChainParameters, GethStateUpdateVec, Mempool, MempoolTx, TRACING_CALL_OPTS};
 use loom_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
 use loom_actors_macros::{Accessor, Consumer, Producer};
 
 use super::affected_pools::get_affected_pools;
 use super::affected_pools_code::{get_affected_pools_from_code, is_pool_code};
+use super::pending_tx_simulator::{local_trace_call_diff, PendingTxSimulation};
 
 lazy_static! {
     static ref COINBASE: Address = "0x1f9090aaE28b8a3dCeaDf281B0F12828e676c326".parse().unwrap();
@@ -37,6 +38,7 @@ lazy_static! {
 #[allow(clippy::too_many_arguments)]
 pub async fn pending_tx_state_change_task<P, T, N>(
     client: P,
+    simulation: PendingTxSimulation,
     tx_hash: TxHash,
     market: SharedState<Market>,
     mempool: SharedState<Mempool>,
@@ -135,8 +137,36 @@ where
         return Err(eyre!("NON_AFFECTING_TX"));
     }
 
-    let diff_trace_result =
-        debug_trace_call_diff(client.clone(), transaction_request, BlockNumberOrTag::Latest.into(), Some(call_opts)).await;
+    if !mempool_tx.impact.is_affecting() {
+        debug!("Router swap does not go through market pools hash={:?}", tx_hash);
+        affecting_tx.write().await.insert(tx_hash, false);
+        return Err(eyre!("NON_AFFECTING_TX"));
+    }
+
+    let diff_trace_result = match simulation {
+        PendingTxSimulation::Local => {
+            match local_trace_call_diff(
+                client.clone(),
+                &market_state,
+                &cur_state_override,
+                &transaction_request,
+                cur_block_number,
+                cur_block_time,
+                cur_next_base_fee,
+            )
+            .await
+            {
+                Ok(diff) => Ok(diff),
+                Err(e) => {
+                    warn!("Local simulation failed, falling back to tracer hash={:?}, err={}", tx.hash, e);
+                    debug_trace_call_diff(client.clone(), transaction_request, BlockNumberOrTag::Latest.into(), Some(call_opts)).await
+                }
+            }
+        }
+        PendingTxSimulation::Tracer => {
+            debug_trace_call_diff(client.clone(), transaction_request, BlockNumberOrTag::Latest.into(), Some(call_opts)).await
+        }
+    };
     match diff_trace_result {
         Ok((pre, post)) => {
             state_required_vec.push(pre.clone());
@@ -179,7 +209,8 @@ where
                         vec![mempool_tx.tx.clone().unwrap()],
                         "pending_tx_searcher".to_string(),
                         9000,
-                    );
+                    )
+                    .with_parent_block_hash(latest_header.hash);
                     if let Err(e) = state_updates_broadcaster.send(request).await {
                         error!("state_updates_broadcaster : {}", e)
                     }
@@ -222,7 +253,8 @@ where
                                     vec![mempool_tx.tx.unwrap()],
                                     "poolcode_searcher".to_string(),
                                     3000,
-                                );
+                                )
+                                .with_parent_block_hash(latest_header.hash);
                                 if let Err(e) = state_updates_broadcaster.send(request).await {
                                     error!("state_updates_broadcaster : {}", e)
                                 }
@@ -246,9 +278,76 @@ where
     }
 }
 
+/// Pools of the market the hinted transaction swaps through, taken from the hint logs and the call target.
+fn hint_affected_pools(market: &Market, mempool_tx: &MempoolTx) -> BTreeMap<PoolWrapper, Vec<(Address, Address)>> {
+    let log_addresses = mempool_tx.logs.iter().flatten().map(|log| log.address());
+    let to_address = mempool_tx.hint.as_ref().and_then(|hint| hint.to);
+
+    let mut affected_pools: BTreeMap<PoolWrapper, Vec<(Address, Address)>> = BTreeMap::new();
+    for address in log_addresses.chain(to_address) {
+        if let Some(pool) = market.get_pool(&address) {
+            if market.is_pool(&address) && !affected_pools.contains_key(pool) {
+                affected_pools.insert(pool.clone(), pool.get_swap_directions());
+            }
+        }
+    }
+    affected_pools
+}
+
+/// Process a transaction known from an order flow hint only. The signed transaction is missing, so it is not simulated :
+/// pools are taken from the hint and the backrun is searched on the current state with the transaction referenced by hash.
+pub async fn hint_state_change_task(
+    tx_hash: TxHash,
+    market: SharedState<Market>,
+    mempool: SharedState<Mempool>,
+    latest_block: SharedState<LatestBlock>,
+    market_state: SharedState<MarketState>,
+    cur_next_base_fee: u64,
+    state_updates_broadcaster: Broadcaster<StateUpdateEvent>,
+) -> Result<()> {
+    let mempool_tx = mempool.read().await.get_tx_by_hash(&tx_hash).cloned().ok_or(eyre!("MEMPOOL_TX_NOT_FOUND"))?;
+    if !mempool_tx.is_partial() {
+        // signed transaction is simulated by the pending tx task
+        return Ok(());
+    }
+
+    let affected_pools = hint_affected_pools(&*market.read().await, &mempool_tx);
+    if affected_pools.is_empty() {
+        debug!("Hinted tx does not swap through market pools hash={:?}", tx_hash);
+        return Ok(());
+    }
+
+    let latest_header = latest_block.read().await.block_header.clone().ok_or(eyre!("LATEST_HEADER_IS_EMPTY"))?;
+    debug!("Hint affected pools {:?} {} pools : {}", tx_hash, mempool_tx.source, affected_pools.len());
+
+    let cur_state_db = market_state.read().await.state_db.clone();
+    let request = StateUpdateEvent::new(
+        latest_header.number.as_u64() + 1,
+        latest_header.timestamp.as_u64() + 12,
+        cur_next_base_fee,
+        cur_state_db,
+        Vec::new(),
+        None,
+        affected_pools,
+        vec![tx_hash],
+        Vec::new(),
+        "hint_searcher".to_string(),
+        9000,
+    )
+    .with_parent_block_hash(latest_header.hash);
+
+    state_updates_broadcaster.send(request).await.map_err(|e| {
+        error!("state_updates_broadcaster : {}", e);
+        eyre!("BROADCASTER_ERROR")
+    })?;
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn pending_tx_state_change_worker<P, T, N>(
     client: P,
+    simulation: PendingTxSimulation,
+    chain_parameters: ChainParameters,
     market: SharedState<Market>,
     mempool: SharedState<Mempool>,
     latest_block: SharedState<LatestBlock>,
@@ -279,7 +378,7 @@ where
                     if let MarketEvents::BlockHeaderUpdate{ block_number, block_hash, timestamp, base_fee, next_base_fee } = market_event_msg {
                         debug!("Block header update {} {} base_fee {} ", block_number, block_hash, base_fee);
                         cur_block_number = Some( block_number.as_u64() + 1);
-                        cur_block_time = Some(timestamp + 12 );
+                        cur_block_time = Some(chain_parameters.next_block_timestamp(timestamp));
                         cur_next_base_fee = next_base_fee;
 
                         for _counter in 0..5  {
@@ -297,10 +396,23 @@ where
             msg = mempool_events_rx.recv() => {
                 if let Ok(msg) = msg {
                     let mempool_event_msg : MempoolEvents = msg;
-                    if let MempoolEvents::MempoolActualTxUpdate{ tx_hash }  = mempool_event_msg {
+                    if let MempoolEvents::MempoolHintUpdate{ tx_hash } = mempool_event_msg {
+                        tokio::task::spawn(
+                            hint_state_change_task(
+                                tx_hash,
+                                market.clone(),
+                                mempool.clone(),
+                                latest_block.clone(),
+                                market_state.clone(),
+                                cur_next_base_fee,
+                                state_updates_broadcaster.clone(),
+                            )
+                        );
+                    } else if let MempoolEvents::MempoolActualTxUpdate{ tx_hash }  = mempool_event_msg {
                         tokio::task::spawn(
                             pending_tx_state_change_task(
                                 client.clone(),
+                                simulation,
                                 tx_hash,
                                 market.clone(),
                                 mempool.clone(),
@@ -324,6 +436,8 @@ where
 #[derive(Accessor, Consumer, Producer)]
 pub struct PendingTxStateChangeProcessorActor<P, T, N> {
     client: P,
+    simulation: PendingTxSimulation,
+    chain_parameters: ChainParameters,
     #[accessor]
     market: Option<SharedState<Market>>,
     #[accessor]
@@ -351,6 +465,8 @@ where
     pub fn new(client: P) -> PendingTxStateChangeProcessorActor<P, T, N> {
         PendingTxStateChangeProcessorActor {
             client,
+            simulation: PendingTxSimulation::default(),
+            chain_parameters: ChainParameters::ethereum(),
             market: None,
             mempool: None,
             market_state: None,
@@ -363,8 +479,13 @@ where
         }
     }
 
+    pub fn with_simulation(self, simulation: PendingTxSimulation) -> Self {
+        Self { simulation, ..self }
+    }
+
     pub fn on_bc(self, bc: &Blockchain) -> Self {
         Self {
+            chain_parameters: bc.chain_parameters(),
             market: Some(bc.market()),
             mempool: Some(bc.mempool()),
             market_state: Some(bc.market_state()),
@@ -386,6 +507,8 @@ where
     fn start(&self) -> ActorResult {
         let task = tokio::task::spawn(pending_tx_state_change_worker(
             self.client.clone(),
+            self.simulation,
+            self.chain_parameters.clone(),
             self.market.clone().unwrap(),
             self.mempool.clone().unwrap(),
             self.latest_block.clone().unwrap(),
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use alloy_primitives::utils::parse_units;
+use alloy_primitives::{Address, U256};
+use lazy_static::lazy_static;
+
+use defi_entities::{SwapLine, Token};
+
+lazy_static! {
+    static ref START_OPTIMIZE_INPUT: U256 = parse_units("0.01", "ether").unwrap().get_absolute();
+}
+
+/// Profitability thresholds and search limits of the state change arb searcher.
+#[derive(Clone, Debug)]
+pub struct StateChangeArbSearcherConfig {
+    /// Minimum absolute profit in ETH for a swap line to be sent to compose
+    pub min_profit_eth: U256,
+    /// Swap line profit must exceed next block base fee multiplied by this number of gas units
+    pub gas_units_multiplier: u64,
+    /// Value in ETH of the starting input amount for swap line optimization
+    pub start_input_eth: U256,
+    /// Starting input value in ETH per basic token, overrides start_input_eth
+    pub token_start_input_eth: HashMap<Address, U256>,
+    /// Maximum number of swap paths calculated for one state update event
+    pub max_paths_per_event: Option<usize>,
+    /// Swap lines with profit above this percentage of the best one are sent to compose, 10000 = 100%
+    pub best_answer_pct: u32,
+}
+
+impl Default for StateChangeArbSearcherConfig {
+    fn default() -> Self {
+        Self {
+            min_profit_eth: U256::ZERO,
+            gas_units_multiplier: 200_000,
+            start_input_eth: *START_OPTIMIZE_INPUT,
+            token_start_input_eth: HashMap::new(),
+            max_paths_per_event: None,
+            best_answer_pct: 9000,
+        }
+    }
+}
+
+impl StateChangeArbSearcherConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_min_profit_eth(self, min_profit_eth: U256) -> Self {
+        Self { min_profit_eth, ..self }
+    }
+
+    pub fn with_gas_units_multiplier(self, gas_units_multiplier: u64) -> Self {
+        Self { gas_units_multiplier, ..self }
+    }
+
+    pub fn with_start_input_eth(self, start_input_eth: U256) -> Self {
+        Self { start_input_eth, ..self }
+    }
+
+    pub fn with_token_start_input_eth(self, token: Address, start_input_eth: U256) -> Self {
+        let mut token_start_input_eth = self.token_start_input_eth;
+        token_start_input_eth.insert(token, start_input_eth);
+        Self { token_start_input_eth, ..self }
+    }
+
+    pub fn with_max_paths_per_event(self, max_paths_per_event: usize) -> Self {
+        Self { max_paths_per_event: Some(max_paths_per_event), ..self }
+    }
+
+    pub fn with_best_answer_pct(self, best_answer_pct: u32) -> Self {
+        Self { best_answer_pct, ..self }
+    }
+
+    /// Starting input amount of the token, None if token price is not set.
+    pub fn start_input(&self, token: &Token) -> Option<U256> {
+        let start_input_eth = self.token_start_input_eth.get(&token.get_address()).unwrap_or(&self.start_input_eth);
+        token.calc_token_value_from_eth(*start_input_eth)
+    }
+
+    /// Minimum absolute profit in ETH for the next block base fee.
+    pub fn min_profit(&self, next_base_fee: u64) -> U256 {
+        let gas_cost = U256::from(next_base_fee) * U256::from(self.gas_units_multiplier);
+        self.min_profit_eth.max(gas_cost)
+    }
+
+    pub fn is_profitable(&self, swap_line: &SwapLine, next_base_fee: u64) -> bool {
+        match swap_line.profit() {
+            Ok(profit) => profit.is_positive() && swap_line.abs_profit_eth() > self.min_profit(next_base_fee),
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_min_profit() {
+        let config = StateChangeArbSearcherConfig::default();
+        assert_eq!(config.min_profit(10), U256::from(2_000_000));
+
+        let config = config.with_gas_units_multiplier(100_000).with_min_profit_eth(U256::from(1_500_000));
+        assert_eq!(config.min_profit(10), U256::from(1_500_000));
+        assert_eq!(config.min_profit(20), U256::from(2_000_000));
+    }
+
+    #[test]
+    fn test_start_input() {
+        let weth = Token::new(Address::repeat_byte(0x01));
+        let usdc = Token::new(Address::repeat_byte(0x02));
+        let config =
+            StateChangeArbSearcherConfig::default().with_token_start_input_eth(usdc.get_address(), *START_OPTIMIZE_INPUT * U256::from(10));
+
+        assert_eq!(config.start_input(&weth), None);
+
+        weth.set_eth_price(Some(parse_units("1", "ether").unwrap().get_absolute()));
+        usdc.set_eth_price(Some(U256::from(2_500_000_000u64)));
+        assert_eq!(config.start_input(&weth), Some(*START_OPTIMIZE_INPUT));
+        assert_eq!(config.start_input(&usdc), Some(U256::from(250_000_000u64)));
+    }
+}
@@ -12,21 +12,22 @@ use tokio::sync::broadcast::error::RecvError;
 use tracing::warn;
 use tracing::{debug, error, info, trace};
 
-use crate::backrun::SwapCalculator;
-use alloy_primitives::utils::parse_units;
+use crate::backrun::swap_calculation_cache::SwapCalculationCache;
+use crate::backrun::{StateChangeArbSearcherConfig, SwapCalculator};
 use defi_blockchain::Blockchain;
 use defi_entities::{Market, PoolWrapper, Swap, SwapLine, SwapPath};
 use defi_events::{BestTxCompose, HealthEvent, Message, MessageHealthEvent, MessageTxCompose, StateUpdateEvent, TxCompose, TxComposeData};
 use defi_types::SwapError;
-use lazy_static::lazy_static;
 use loom_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
 use loom_actors_macros::{Accessor, Consumer, Producer};
 
 async fn state_change_arb_searcher_task(
     thread_pool: Arc<ThreadPool>,
     smart: bool,
+    config: Arc<StateChangeArbSearcherConfig>,
     state_update_event: StateUpdateEvent,
     market: SharedState<Market>,
+    calculation_cache: SharedState<SwapCalculationCache>,
     swap_request_tx: Broadcaster<MessageTxCompose>,
     pool_health_monitor_tx: Broadcaster<MessageHealthEvent>,
 ) -> Result<()> {
@@ -64,61 +65,87 @@ async fn state_change_arb_searcher_task(
         );
         return Err(eyre!("NO_SWAP_PATHS"));
     }
-    info!("Calculation started: swap_path_vec_len={} elapsed={}", swap_path_vec.len(), chrono::Local::now() - start_time);
+    if let Some(max_paths_per_event) = config.max_paths_per_event {
+        swap_path_vec.truncate(max_paths_per_event);
+    }
+    let swap_path_vec_len = swap_path_vec.len();
+
+    let block_hash = state_update_event.parent_block_hash;
+    let mut cached_results: Vec<Result<SwapLine, SwapError>> = Vec::new();
+    let mut calculate_vec: Vec<(SwapPath, u64)> = Vec::new();
+    {
+        let mut calculation_cache_guard = calculation_cache.write().await;
+        calculation_cache_guard.on_block(block_hash);
+        for swap_path in swap_path_vec.into_iter() {
+            let fingerprint = calculation_cache_guard.path_fingerprint(&swap_path, state_update_event.state_update());
+            match calculation_cache_guard.get(&swap_path, fingerprint) {
+                Some(result) => cached_results.push(result),
+                None => calculate_vec.push((swap_path, fingerprint)),
+            }
+        }
+    }
+
+    info!(
+        "Calculation started: swap_path_vec_len={} cached={} elapsed={}",
+        swap_path_vec_len,
+        cached_results.len(),
+        chrono::Local::now() - start_time
+    );
 
     let env = state_update_event.evm_env();
 
-    let channel_len = swap_path_vec.len();
+    let channel_len = swap_path_vec_len;
     let (swap_path_tx, mut swap_line_rx) = tokio::sync::mpsc::channel(channel_len);
 
+    for result in cached_results.into_iter() {
+        if let Err(error) = swap_path_tx.try_send((None, result)) {
+            error!(%error, "swap_path_tx.try_send")
+        }
+    }
+
     let market_state_clone = db.clone();
-    let swap_path_vec_len = swap_path_vec.len();
+    let calculation_config = config.clone();
 
     tokio::task::spawn(async move {
         thread_pool.install(|| {
-            swap_path_vec.into_par_iter().for_each_with((&swap_path_tx, &market_state_clone, &env), |req, item| {
-                let mut mut_item: SwapLine = SwapLine { path: item, ..Default::default() };
-                #[cfg(not(debug_assertions))]
-                let start_time = chrono::Local::now();
-                let calc_result = SwapCalculator::calculate(&mut mut_item, req.1, req.2.clone());
-                #[cfg(not(debug_assertions))]
-                let took_time = chrono::Local::now() - start_time;
-
-                match calc_result {
-                    Ok(_) => {
-                        #[cfg(not(debug_assertions))]
-                        {
-                            if took_time > TimeDelta::new(0, 10 * 1000000).unwrap() {
-                                warn!("Took longer than expected {} {}", took_time, mut_item.clone())
-                            }
-                        }
-                        trace!("Calc result received: {}", mut_item);
-
-                        if let Ok(profit) = mut_item.profit() {
-                            if profit.is_positive() && mut_item.abs_profit_eth() > U256::from(state_update_event.next_base_fee * 200_000) {
-                                if let Err(error) = swap_path_tx.try_send(Ok(mut_item)) {
-                                    error!(%error, "swap_path_tx.try_send")
+            calculate_vec.into_par_iter().for_each_with(
+                (&swap_path_tx, &market_state_clone, &env, &calculation_config),
+                |req, (item, fingerprint)| {
+                    let mut mut_item: SwapLine = SwapLine { path: item.clone(), ..Default::default() };
+                    #[cfg(not(debug_assertions))]
+                    let start_time = chrono::Local::now();
+                    let calc_result = SwapCalculator::calculate(&mut mut_item, req.1, req.2.clone(), req.3);
+                    #[cfg(not(debug_assertions))]
+                    let took_time = chrono::Local::now() - start_time;
+
+                    let calc_result = match calc_result {
+                        Ok(_) => {
+                            #[cfg(not(debug_assertions))]
+                            {
+                                if took_time > TimeDelta::new(0, 10 * 1000000).unwrap() {
+                                    warn!("Took longer than expected {} {}", took_time, mut_item.clone())
                                 }
-                            } else {
-                                trace!("profit is not enough")
                             }
+                            trace!("Calc result received: {}", mut_item);
+                            Ok(mut_item)
                         }
-                    }
-                    Err(e) => {
-                        #[cfg(not(debug_assertions))]
-                        {
-                            if took_time > TimeDelta::new(0, 10 * 1000000).unwrap() {
-                                warn!("Took longer than expected {:?} {}", e, mut_item.clone())
+                        Err(e) => {
+                            #[cfg(not(debug_assertions))]
+                            {
+                                if took_time > TimeDelta::new(0, 10 * 1000000).unwrap() {
+                                    warn!("Took longer than expected {:?} {}", e, mut_item.clone())
+                                }
                             }
+                            trace!("Swap error: {:?}", e);
+                            Err(e)
                         }
-                        trace!("Swap error: {:?}", e);
+                    };
 
-                        if let Err(error) = swap_path_tx.try_send(Err(e)) {
-                            error!(%error, "try_send to swap_path_tx")
-                        }
+                    if let Err(error) = req.0.try_send((Some((item, fingerprint)), calc_result)) {
+                        error!(%error, "swap_path_tx.try_send")
                     }
-                }
-            });
+                },
+            );
         });
         debug!(elapsed = %(chrono::Local::now() - start_time), "Calculation iteration finished");
     });
@@ -132,13 +159,25 @@ async fn state_change_arb_searcher_task(
 
     let mut answers = 0;
 
-    let mut best_answers = BestTxCompose::new_with_pct(U256::from(9000));
+    let mut best_answers = BestTxCompose::new_with_pct(U256::from(config.best_answer_pct));
 
     let mut failed_pools: HashSet<SwapError> = HashSet::new();
 
-    while let Some(swap_line_result) = swap_line_rx.recv().await {
+    let mut calculated_results: Vec<(SwapPath, u64, Result<SwapLine, SwapError>)> = Vec::new();
+
+    while let Some((calculated_path, swap_line_result)) = swap_line_rx.recv().await {
+        if let Some((swap_path, fingerprint)) = calculated_path {
+            calculated_results.push((swap_path, fingerprint, swap_line_result.clone()));
+        }
+
         match swap_line_result {
             Ok(swap_line) => {
+                if !config.is_profitable(&swap_line, state_update_event.next_base_fee) {
+                    trace!("profit is not enough");
+                    answers += 1;
+                    continue;
+                }
+
                 let encode_request = TxCompose::Route(TxComposeData {
                     next_block_number: state_update_event.next_block_number,
                     next_block_timestamp: state_update_event.next_block_timestamp,
@@ -171,10 +210,22 @@ async fn state_change_arb_searcher_task(
 
         answers += 1;
     }
+
+    let mut calculation_cache_guard = calculation_cache.write().await;
+    for (swap_path, fingerprint, result) in calculated_results.into_iter() {
+        calculation_cache_guard.insert(block_hash, swap_path, fingerprint, result);
+    }
+    let (cache_len, cache_hits, cache_misses) =
+        (calculation_cache_guard.len(), calculation_cache_guard.hits(), calculation_cache_guard.misses());
+    drop(calculation_cache_guard);
+
     info!(
         origin = %state_update_event.origin,
         swap_path_vec_len,
         answers,
+        cache_len,
+        cache_hits,
+        cache_misses,
         elapsed = %(chrono::Local::now() - start_time),
         stuffing_hash = %state_update_event.stuffing_tx_hash(),
         "Calculation finished"
@@ -183,8 +234,11 @@ async fn state_change_arb_searcher_task(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn state_change_arb_searcher_worker(
     smart: bool,
+    config: StateChangeArbSearcherConfig,
+    thread_pool: Option<Arc<ThreadPool>>,
     market: SharedState<Market>,
     search_request_rx: Broadcaster<StateUpdateEvent>,
     swap_request_tx: Broadcaster<MessageTxCompose>,
@@ -192,9 +246,16 @@ pub async fn state_change_arb_searcher_worker(
 ) -> WorkerResult {
     subscribe!(search_request_rx);
 
-    let cpus = num_cpus::get();
-    info!("Starting state arb searcher cpus={cpus}, tasks={}", cpus / 2);
-    let thread_pool = Arc::new(ThreadPoolBuilder::new().num_threads(cpus / 2).build()?);
+    let thread_pool = match thread_pool {
+        Some(thread_pool) => thread_pool,
+        None => {
+            let cpus = num_cpus::get();
+            info!("Starting state arb searcher cpus={cpus}, tasks={}", cpus / 2);
+            Arc::new(ThreadPoolBuilder::new().num_threads(cpus / 2).build()?)
+        }
+    };
+    let calculation_cache = SharedState::new(SwapCalculationCache::new());
+    let config = Arc::new(config);
 
     loop {
         tokio::select! {
@@ -205,8 +266,10 @@ pub async fn state_change_arb_searcher_worker(
                         state_change_arb_searcher_task(
                             thread_pool.clone(),
                             smart,
+                            config.clone(),
                             msg,
                             market.clone(),
+                            calculation_cache.clone(),
                             swap_request_tx.clone(),
                             pool_health_monitor_tx.clone()
                         )
@@ -217,13 +280,11 @@ pub async fn state_change_arb_searcher_worker(
     }
 }
 
-lazy_static! {
-    static ref START_OPTIMIZE_INPUT: U256 = parse_units("0.01", "ether").unwrap().get_absolute();
-}
-
 #[derive(Accessor, Consumer, Producer)]
 pub struct StateChangeArbSearcherActor {
     smart: bool,
+    config: StateChangeArbSearcherConfig,
+    thread_pool: Option<Arc<ThreadPool>>,
     #[accessor]
     market: Option<SharedState<Market>>,
     #[consumer]
@@ -236,7 +297,24 @@ pub struct StateChangeArbSearcherActor {
 
 impl StateChangeArbSearcherActor {
     pub fn new(smart: bool) -> StateChangeArbSearcherActor {
-        StateChangeArbSearcherActor { smart, market: None, state_update_rx: None, compose_tx: None, pool_health_monitor_tx: None }
+        StateChangeArbSearcherActor {
+            smart,
+            config: StateChangeArbSearcherConfig::default(),
+            thread_pool: None,
+            market: None,
+            state_update_rx: None,
+            compose_tx: None,
+            pool_health_monitor_tx: None,
+        }
+    }
+
+    pub fn with_config(self, config: StateChangeArbSearcherConfig) -> Self {
+        Self { config, ..self }
+    }
+
+    /// Searches on the given thread pool instead of creating a new one
+    pub fn with_thread_pool(self, thread_pool: Arc<ThreadPool>) -> Self {
+        Self { thread_pool: Some(thread_pool), ..self }
     }
 
     pub fn on_bc(self, bc: &Blockchain) -> Self {
@@ -254,6 +332,8 @@ impl Actor for StateChangeArbSearcherActor {
     fn start(&self) -> ActorResult {
         let task = tokio::task::spawn(state_change_arb_searcher_worker(
             self.smart,
+            self.config.clone(),
+            self.thread_pool.clone(),
             self.market.clone().unwrap(),
             self.state_update_rx.clone().unwrap(),
             self.compose_tx.clone().unwrap(),
@@ -0,0 +1,254 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+
+use alloy_primitives::{Address, BlockHash, U256};
+
+use defi_entities::{PoolWrapper, SwapLine, SwapPath};
+use defi_types::{GethStateUpdateVec, SwapError};
+
+/// Calculated swap lines reused across state update events built on the same parent block.
+/// Entries are keyed by swap path and fingerprint of the state update on accounts the path pools read,
+/// so the result is reused only when none of those slots changed. The fingerprint only covers the update, the parent
+/// block is tracked by hash so a reorged block with the same number invalidates the entries.
+#[derive(Default)]
+pub struct SwapCalculationCache {
+    block_hash: BlockHash,
+    pool_accounts: HashMap<Address, Vec<Address>>,
+    entries: HashMap<(SwapPath, u64), Result<SwapLine, SwapError>>,
+    hits: u64,
+    misses: u64,
+}
+
+impl SwapCalculationCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Invalidates all entries when the parent block changes.
+    pub fn on_block(&mut self, block_hash: BlockHash) {
+        if self.block_hash != block_hash {
+            self.block_hash = block_hash;
+            self.entries.clear();
+            self.hits = 0;
+            self.misses = 0;
+        }
+    }
+
+    fn accounts_for_pool(&mut self, pool: &PoolWrapper) -> &Vec<Address> {
+        self.pool_accounts.entry(pool.get_address()).or_insert_with(|| {
+            let mut accounts = pool.get_state_required().map(|required_state| required_state.accounts()).unwrap_or_default();
+            if !accounts.contains(&pool.get_address()) {
+                accounts.push(pool.get_address());
+            }
+            accounts
+        })
+    }
+
+    /// Fingerprint of the final values of slots in the state update for accounts read by the pool.
+    /// Read only cells of the pool are skipped as they never affect calculation.
+    pub fn pool_fingerprint(&mut self, pool: &PoolWrapper, state_update: &GethStateUpdateVec) -> u64 {
+        let pool_address = pool.get_address();
+        let read_only_cells = pool.get_read_only_cell_vec();
+        let accounts = self.accounts_for_pool(pool).clone();
+
+        let mut cells: BTreeMap<(Address, U256), U256> = BTreeMap::new();
+        let mut balances: BTreeMap<Address, U256> = BTreeMap::new();
+
+        for account_updates in state_update.iter() {
+            for account in accounts.iter() {
+                let Some(account_state) = account_updates.get(account) else {
+                    continue;
+                };
+                if let Some(balance) = account_state.balance {
+                    balances.insert(*account, balance);
+                }
+                for (slot, value) in account_state.storage.iter() {
+                    let slot: U256 = (*slot).into();
+                    if *account == pool_address && read_only_cells.contains(&slot) {
+                        continue;
+                    }
+                    cells.insert((*account, slot), (*value).into());
+                }
+            }
+        }
+
+        let mut hasher = DefaultHasher::new();
+        cells.hash(&mut hasher);
+        balances.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn path_fingerprint(&mut self, swap_path: &SwapPath, state_update: &GethStateUpdateVec) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for pool in swap_path.pools.iter() {
+            self.pool_fingerprint(pool, state_update).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    pub fn get(&mut self, swap_path: &SwapPath, fingerprint: u64) -> Option<Result<SwapLine, SwapError>> {
+        match self.entries.get(&(swap_path.clone(), fingerprint)) {
+            Some(entry) => {
+                self.hits += 1;
+                Some(entry.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn insert(&mut self, block_hash: BlockHash, swap_path: SwapPath, fingerprint: u64, result: Result<SwapLine, SwapError>) {
+        if block_hash == self.block_hash {
+            self.entries.insert((swap_path, fingerprint), result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::B256;
+    use alloy_rpc_types_trace::geth::AccountState;
+    use defi_entities::required_state::RequiredState;
+    use defi_entities::{AbiSwapEncoder, Pool, PoolClass, PoolProtocol, Token};
+    use eyre::{ErrReport, Result};
+    use loom_revm_db::LoomInMemoryDB;
+    use revm::primitives::Env;
+    use std::sync::Arc;
+
+    #[derive(Clone)]
+    struct MockPool {
+        address: Address,
+        token0: Address,
+        token1: Address,
+    }
+
+    impl Pool for MockPool {
+        fn get_class(&self) -> PoolClass {
+            PoolClass::UniswapV2
+        }
+
+        fn get_protocol(&self) -> PoolProtocol {
+            PoolProtocol::UniswapV2
+        }
+
+        fn get_address(&self) -> Address {
+            self.address
+        }
+
+        fn get_tokens(&self) -> Vec<Address> {
+            vec![self.token0, self.token1]
+        }
+
+        fn calculate_out_amount(
+            &self,
+            _state: &LoomInMemoryDB,
+            _env: Env,
+            _token_address_from: &Address,
+            _token_address_to: &Address,
+            _in_amount: U256,
+        ) -> Result<(U256, u64), ErrReport> {
+            panic!("Not implemented")
+        }
+
+        fn calculate_in_amount(
+            &self,
+            _state: &LoomInMemoryDB,
+            _env: Env,
+            _token_address_from: &Address,
+            _token_address_to: &Address,
+            _out_amount: U256,
+        ) -> Result<(U256, u64), ErrReport> {
+            panic!("Not implemented")
+        }
+
+        fn can_flash_swap(&self) -> bool {
+            panic!("Not implemented")
+        }
+
+        fn get_encoder(&self) -> &dyn AbiSwapEncoder {
+            panic!("Not implemented")
+        }
+
+        fn get_read_only_cell_vec(&self) -> Vec<U256> {
+            vec![U256::from(7)]
+        }
+
+        fn get_state_required(&self) -> Result<RequiredState> {
+            let mut state_required = RequiredState::new();
+            state_required.add_slot(self.token0, U256::from(1));
+            Ok(state_required)
+        }
+    }
+
+    fn slot_update(address: Address, slot: u64, value: u64) -> GethStateUpdateVec {
+        let mut account_state = AccountState::default();
+        account_state.storage.insert(B256::from(U256::from(slot)), B256::from(U256::from(value)));
+        vec![BTreeMap::from([(address, account_state)])]
+    }
+
+    fn swap_error(pool: Address) -> SwapError {
+        SwapError {
+            msg: "TEST".to_string(),
+            pool,
+            token_from: Address::ZERO,
+            token_to: Address::ZERO,
+            is_in_amount: true,
+            amount: U256::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_path_fingerprint() {
+        let pool = MockPool { address: Address::repeat_byte(0x01), token0: Address::repeat_byte(0x02), token1: Address::repeat_byte(0x03) };
+        let token0 = Arc::new(Token::new(pool.token0));
+        let token1 = Arc::new(Token::new(pool.token1));
+        let swap_path = SwapPath::new(vec![token0, token1], vec![PoolWrapper::from(pool.clone())]);
+
+        let (block_hash, reorged_block_hash) = (BlockHash::repeat_byte(0x01), BlockHash::repeat_byte(0x02));
+        let mut cache = SwapCalculationCache::new();
+        cache.on_block(block_hash);
+
+        let empty_fingerprint = cache.path_fingerprint(&swap_path, &GethStateUpdateVec::new());
+        // unrelated account
+        assert_eq!(cache.path_fingerprint(&swap_path, &slot_update(Address::repeat_byte(0x04), 0, 1)), empty_fingerprint);
+        // read only cell of the pool
+        assert_eq!(cache.path_fingerprint(&swap_path, &slot_update(pool.address, 7, 1)), empty_fingerprint);
+        // pool slot and required state slot
+        let pool_fingerprint = cache.path_fingerprint(&swap_path, &slot_update(pool.address, 8, 1));
+        assert_ne!(pool_fingerprint, empty_fingerprint);
+        assert_ne!(cache.path_fingerprint(&swap_path, &slot_update(pool.token0, 1, 1)), empty_fingerprint);
+        assert_ne!(cache.path_fingerprint(&swap_path, &slot_update(pool.address, 8, 2)), pool_fingerprint);
+
+        assert!(cache.get(&swap_path, pool_fingerprint).is_none());
+        cache.insert(block_hash, swap_path.clone(), pool_fingerprint, Err(swap_error(pool.address)));
+        assert!(cache.get(&swap_path, pool_fingerprint).is_some());
+        assert!(cache.get(&swap_path, empty_fingerprint).is_none());
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+
+        // results of the reorged block with the same number are dropped
+        cache.on_block(reorged_block_hash);
+        assert!(cache.is_empty());
+        cache.insert(block_hash, swap_path.clone(), pool_fingerprint, Err(swap_error(pool.address)));
+        assert!(cache.is_empty());
+    }
+}
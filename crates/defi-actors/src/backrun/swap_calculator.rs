@@ -1,22 +1,22 @@
-use alloy_primitives::utils::parse_units;
-use alloy_primitives::U256;
 use defi_entities::SwapLine;
 use defi_types::SwapError;
-use lazy_static::lazy_static;
 use loom_revm_db::LoomInMemoryDB;
 use reth_primitives::revm_primitives::Env;
 
-lazy_static! {
-    static ref START_OPTIMIZE_INPUT: U256 = parse_units("0.01", "ether").unwrap().get_absolute();
-}
+use crate::backrun::StateChangeArbSearcherConfig;
 
 pub struct SwapCalculator {}
 
 impl SwapCalculator {
     #[inline]
-    pub fn calculate<'a>(path: &'a mut SwapLine, state: &LoomInMemoryDB, env: Env) -> eyre::Result<&'a mut SwapLine, SwapError> {
+    pub fn calculate<'a>(
+        path: &'a mut SwapLine,
+        state: &LoomInMemoryDB,
+        env: Env,
+        config: &StateChangeArbSearcherConfig,
+    ) -> eyre::Result<&'a mut SwapLine, SwapError> {
         let first_token = path.get_first_token().unwrap();
-        if let Some(amount_in) = first_token.calc_token_value_from_eth(*START_OPTIMIZE_INPUT) {
+        if let Some(amount_in) = config.start_input(first_token) {
             //trace!("calculate : {} amount in : {}",first_token.get_symbol(), first_token.to_float(amount_in) );
             path.optimize_with_in_amount(state, env, amount_in)
         } else {
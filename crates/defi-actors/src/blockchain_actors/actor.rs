@@ -4,11 +4,14 @@ use std::sync::Arc;
 
 use crate::backrun::BlockStateChangeProcessorActor;
 use crate::{
-    ArbSwapPathMergerActor, BlockHistoryActor, CurvePoolLoaderOneShotActor, DiffPathMergerActor, EvmEstimatorActor,
-    FlashbotsBroadcastActor, GethEstimatorActor, HistoryPoolLoaderOneShotActor, InitializeSignersOneShotBlockingActor,
-    MarketStatePreloadedOneShotActor, MempoolActor, NewPoolLoaderActor, NodeBlockActor, NodeBlockActorConfig, NodeExExGrpcActor,
-    NodeMempoolActor, NonceAndBalanceMonitorActor, PendingTxStateChangeProcessorActor, PoolHealthMonitorActor, PoolLoaderActor, PriceActor,
-    RequiredPoolLoaderActor, SamePathMergerActor, StateChangeArbSearcherActor, StateHealthMonitorActor, SwapRouterActor, TxSignersActor,
+    ArbSwapPathMergerActor, BidFeedbackMonitorActor, BlockHistoryActor, CurvePoolLoaderOneShotActor, DiffPathMergerActor,
+    EvmEstimatorActor, FlashbotsBroadcastActor, GasPriceOracleActor, GethEstimatorActor, HistoryPoolLoaderOneShotActor,
+    InitializeSignersOneShotBlockingActor, LiquidationSearcherActor, LiquidationSearcherConfig, MarketStatePreloadedOneShotActor,
+    MempoolActor, MevShareHintActor, MulticallerAllowanceActor, MulticallerAllowanceConfig, NewPoolLoaderActor, NodeBlockActor,
+    NodeBlockActorConfig, NodeExExGrpcActor, NodeMempoolActor, NonceAndBalanceMonitorActor, PaperTradingBroadcastActor,
+    PendingTxSimulation, PendingTxStateChangeProcessorActor, PoolHealthMonitorActor, PoolLoaderActor, PriceActor, ProfitSweeperActor,
+    ProfitSweeperConfig, RequiredPoolLoaderActor, SamePathMergerActor, StateChangeArbSearcherActor, StateChangeArbSearcherConfig,
+    StateHealthMonitorActor, Strategy, StrategyActor, SwapRouterActor, TxSignersActor,
 };
 use alloy_network::Ethereum;
 use alloy_primitives::{Address, B256, U256};
@@ -17,16 +20,17 @@ use alloy_transport::Transport;
 use debug_provider::DebugProviderExt;
 use defi_address_book::TokenAddress;
 use defi_blockchain::Blockchain;
-use defi_entities::required_state::RequiredState;
-use defi_entities::{PoolClass, TxSigners};
+use defi_entities::required_state::{RequiredState, RequiredStateReaderConfig};
+use defi_entities::{PoolClass, TxSigners, OP_L1_FEE_SLOTS};
 use defi_pools::PoolsConfig;
 use eyre::{eyre, Result};
 use flashbots::client::RelayConfig;
 use flashbots::Flashbots;
 use loom_actors::{Actor, ActorsManager, SharedState};
 use loom_metrics::{BlockLatencyRecorderActor, InfluxDbWriterActor};
-use loom_multicaller::MulticallerSwapEncoder;
+use loom_multicaller::{FlashLoanSource, MulticallerSwapEncoder};
 use loom_utils::NWETH;
+use rayon::{ThreadPool, ThreadPoolBuilder};
 
 pub struct BlockchainActors<P, T> {
     provider: P,
@@ -37,8 +41,13 @@ pub struct BlockchainActors<P, T> {
     has_mempool: bool,
     has_state_update: bool,
     has_signers: bool,
+    has_preflight: bool,
     mutlicaller_address: Option<Address>,
     relays: Vec<RelayConfig>,
+    searcher_config: StateChangeArbSearcherConfig,
+    pending_tx_simulation: PendingTxSimulation,
+    state_reader_config: RequiredStateReaderConfig,
+    thread_pool: Option<Arc<ThreadPool>>,
     _t: PhantomData<T>,
 }
 
@@ -48,6 +57,7 @@ where
     P: Provider<T, Ethereum> + DebugProviderExt<T, Ethereum> + Send + Sync + Clone + 'static,
 {
     pub fn new(provider: P, bc: Blockchain, relays: Vec<RelayConfig>) -> Self {
+        let mutlicaller_address = bc.chain_parameters().multicaller_address;
         Self {
             provider,
             bc,
@@ -57,8 +67,13 @@ where
             has_mempool: false,
             has_state_update: false,
             has_signers: false,
-            mutlicaller_address: None,
+            has_preflight: false,
+            mutlicaller_address,
             relays,
+            searcher_config: StateChangeArbSearcherConfig::default(),
+            pending_tx_simulation: PendingTxSimulation::default(),
+            state_reader_config: RequiredStateReaderConfig::default(),
+            thread_pool: None,
             _t: PhantomData,
         }
     }
@@ -67,6 +82,16 @@ where
         self.actor_manager.wait().await
     }
 
+    /// Rayon thread pool shared by the searchers
+    fn thread_pool(&mut self) -> Result<Arc<ThreadPool>> {
+        if let Some(thread_pool) = &self.thread_pool {
+            return Ok(thread_pool.clone());
+        }
+        let thread_pool = Arc::new(ThreadPoolBuilder::new().num_threads(num_cpus::get() / 2).build()?);
+        self.thread_pool = Some(thread_pool.clone());
+        Ok(thread_pool)
+    }
+
     /// Start a custom actor
     pub fn start(&mut self, actor: impl Actor + 'static) -> Result<&mut Self> {
         self.actor_manager.start(actor)?;
@@ -130,12 +155,30 @@ where
     pub fn with_signers(&mut self) -> Result<&mut Self> {
         if !self.has_signers {
             self.has_signers = true;
-            self.actor_manager.start(TxSignersActor::new().on_bc(&self.bc))?;
+            let signers_actor = match self.has_preflight {
+                true => TxSignersActor::new().with_preflight(),
+                false => TxSignersActor::new(),
+            };
+            self.actor_manager.start(signers_actor.on_bc(&self.bc))?;
         }
         Ok(self)
     }
 
-    /// Initializes encoder and start encoder actor
+    /// Starts eth_callBundle pre-flight of signed bundles. Must be called before signers are started
+    pub fn with_bundle_preflight(&mut self, max_deviation_pct: u32) -> Result<&mut Self> {
+        if self.has_signers {
+            return Err(eyre!("SIGNERS_ALREADY_STARTED"));
+        }
+        self.has_preflight = true;
+
+        let flashbots = Flashbots::new(self.provider.clone(), "https://relay.flashbots.net", None).with_default_relays();
+
+        self.actor_manager
+            .start(BundlePreflightActor::new(Arc::new(flashbots)).with_max_deviation_pct(max_deviation_pct).on_bc(&self.bc))?;
+        Ok(self)
+    }
+
+    /// Initializes encoder and start encoder actor, multicaller of the chain parameters is used if address is not set
     pub fn with_swap_encoder(&mut self, multicaller_address: Option<Address>) -> Result<&mut Self> {
         let multicaller_address = match multicaller_address {
             Some(multicaller) => multicaller,
@@ -145,7 +188,11 @@ where
             },
         };
 
-        self.encoder = Some(MulticallerSwapEncoder::new(multicaller_address));
+        self.encoder = Some(
+            MulticallerSwapEncoder::new(multicaller_address)
+                .with_wrapped_native_token(self.bc.wrapped_native_token())
+                .with_flash_loan_sources(FlashLoanSource::chain_defaults(self.bc.chain_id())),
+        );
         self.actor_manager.start(SwapRouterActor::new().with_signers(self.signers.clone()).on_bc(&self.bc))?;
         Ok(self)
     }
@@ -158,9 +205,12 @@ where
             address_vec.push(loom_multicaller);
         }
 
-        self.actor_manager.start_and_wait(
-            MarketStatePreloadedOneShotActor::new(self.provider.clone()).with_copied_accounts(address_vec).on_bc(&self.bc),
-        )?;
+        let mut market_state_preloader = MarketStatePreloadedOneShotActor::new(self.provider.clone()).with_copied_accounts(address_vec);
+        if self.bc.chain_parameters().op_stack {
+            market_state_preloader = market_state_preloader.with_copied_storage(OP_L1_FEE_SLOTS.to_vec());
+        }
+
+        self.actor_manager.start_and_wait(market_state_preloader.on_bc(&self.bc))?;
         Ok(self)
     }
 
@@ -180,6 +230,9 @@ where
         }
 
         market_state_preloader = market_state_preloader.with_copied_accounts(address_to_copy);
+        if self.bc.chain_parameters().op_stack {
+            market_state_preloader = market_state_preloader.with_copied_storage(OP_L1_FEE_SLOTS.to_vec());
+        }
 
         market_state_preloader = market_state_preloader.with_new_account(
             loom_multicaller::DEFAULT_VIRTUAL_ADDRESS,
@@ -189,7 +242,7 @@ where
         );
 
         market_state_preloader = market_state_preloader.with_token_balance(
-            TokenAddress::WETH,
+            self.bc.chain_parameters().wrapped_native_token,
             loom_multicaller::DEFAULT_VIRTUAL_ADDRESS,
             NWETH::from_float(10.0),
         );
@@ -217,6 +270,12 @@ where
         Ok(self)
     }
 
+    /// Starts gas price oracle fed from block history
+    pub fn with_gas_price_oracle(&mut self) -> Result<&mut Self> {
+        self.actor_manager.start(GasPriceOracleActor::new().on_bc(&self.bc))?;
+        Ok(self)
+    }
+
     /// Starts token price calculator
     pub fn with_price_station(&mut self) -> Result<&mut Self> {
         self.actor_manager.start(PriceActor::new(self.provider.clone()).on_bc(&self.bc))?;
@@ -270,6 +329,13 @@ where
         Ok(self)
     }
 
+    /// Starts MEV-Share hint stream provider adding hints to mempool as partial txes
+    pub fn with_mev_share_hints(&mut self, url: String) -> Result<&mut Self> {
+        self.mempool()?;
+        self.actor_manager.start(MevShareHintActor::new(url).on_bc(&self.bc))?;
+        Ok(self)
+    }
+
     //TODO : Refactor estimators actors encoder type to SwapEncoders
     pub fn with_geth_estimator(&mut self) -> Result<&mut Self> {
         let flashbots = Flashbots::new(self.provider.clone(), "https://relay.flashbots.net", None).with_default_relays();
@@ -295,6 +361,16 @@ where
         Ok(self)
     }
 
+    /// Starts paper trading broadcaster. Bundles are written to the journal and evaluated at target block instead of broadcasting
+    pub fn with_paper_trading_broadcaster(&mut self, journal_path: Option<String>) -> Result<&mut Self> {
+        let actor = match journal_path {
+            Some(journal_path) => PaperTradingBroadcastActor::new().with_journal(journal_path),
+            None => PaperTradingBroadcastActor::new(),
+        };
+        self.actor_manager.start(actor.on_bc(&self.bc))?;
+        Ok(self)
+    }
+
     /// Start composer : estimator, signer and broadcaster
     pub fn with_composers(&mut self, allow_broadcast: bool) -> Result<&mut Self> {
         self.with_evm_estimator()?.with_signers()?.with_flashbots_broadcaster(true, allow_broadcast)
@@ -317,6 +393,18 @@ where
         Ok(self)
     }
 
+    /// Starts landed bundle feedback monitor collecting per pool pair bid stats
+    pub fn with_health_monitor_bid_feedback(&mut self) -> Result<&mut Self> {
+        self.actor_manager.start(BidFeedbackMonitorActor::new().on_bc(&self.bc))?;
+        Ok(self)
+    }
+
+    /// Batching and concurrency of the required state fetched by the pool loaders started after it
+    pub fn with_state_reader_config(&mut self, state_reader_config: RequiredStateReaderConfig) -> Result<&mut Self> {
+        self.state_reader_config = state_reader_config;
+        Ok(self)
+    }
+
     /// Start pool loader from new block events
     pub fn with_new_pool_loader(&mut self, pools_config: PoolsConfig) -> Result<&mut Self> {
         self.actor_manager.start(NewPoolLoaderActor::new(pools_config).on_bc(&self.bc))?;
@@ -331,13 +419,19 @@ where
 
     /// Start pool loader from new block events
     pub fn with_pool_loader(&mut self) -> Result<&mut Self> {
-        self.actor_manager.start(PoolLoaderActor::new(self.provider.clone()).on_bc(&self.bc))?;
+        self.actor_manager.start(
+            PoolLoaderActor::new(self.provider.clone()).with_state_reader_config(self.state_reader_config.clone()).on_bc(&self.bc),
+        )?;
         Ok(self)
     }
 
     /// Start pool loader for curve + steth + wsteth
     pub fn with_curve_pool_protocol_loader(&mut self) -> Result<&mut Self> {
-        self.actor_manager.start(CurvePoolLoaderOneShotActor::new(self.provider.clone()).on_bc(&self.bc))?;
+        self.actor_manager.start(
+            CurvePoolLoaderOneShotActor::new(self.provider.clone())
+                .with_state_reader_config(self.state_reader_config.clone())
+                .on_bc(&self.bc),
+        )?;
         Ok(self)
     }
 
@@ -354,7 +448,7 @@ where
     }
 
     pub fn with_preloaded_state(&mut self, pools: Vec<(Address, PoolClass)>, state_required: Option<RequiredState>) -> Result<&mut Self> {
-        let mut actor = RequiredPoolLoaderActor::new(self.provider.clone());
+        let mut actor = RequiredPoolLoaderActor::new(self.provider.clone()).with_state_reader_config(self.state_reader_config.clone());
 
         for (pool_address, pool_class) in pools {
             actor = actor.with_pool(pool_address, pool_class);
@@ -392,10 +486,28 @@ where
         self.with_swap_path_merger()?.with_same_path_merger()?.with_diff_path_merger()
     }
 
+    /// Set thresholds and limits of backrun searcher. Must be called before backrun actors are started
+    pub fn with_searcher_config(&mut self, searcher_config: StateChangeArbSearcherConfig) -> Result<&mut Self> {
+        self.searcher_config = searcher_config;
+        Ok(self)
+    }
+
+    /// Set how pending txs are simulated by backrun for pending txs. Must be called before backrun actors are started
+    pub fn with_pending_tx_simulation(&mut self, simulation: PendingTxSimulation) -> Result<&mut Self> {
+        self.pending_tx_simulation = simulation;
+        Ok(self)
+    }
+
     /// Start backrun on block
     pub fn with_backrun_block(&mut self) -> Result<&mut Self> {
         if !self.has_state_update {
-            self.actor_manager.start(StateChangeArbSearcherActor::new(true).on_bc(&self.bc))?;
+            let thread_pool = self.thread_pool()?;
+            self.actor_manager.start(
+                StateChangeArbSearcherActor::new(true)
+                    .with_config(self.searcher_config.clone())
+                    .with_thread_pool(thread_pool)
+                    .on_bc(&self.bc),
+            )?;
             self.has_state_update = true
         }
         self.actor_manager.start(BlockStateChangeProcessorActor::new().on_bc(&self.bc))?;
@@ -405,10 +517,18 @@ where
     /// Start backrun for pending txs
     pub fn with_backrun_mempool(&mut self) -> Result<&mut Self> {
         if !self.has_state_update {
-            self.actor_manager.start(StateChangeArbSearcherActor::new(true).on_bc(&self.bc))?;
+            let thread_pool = self.thread_pool()?;
+            self.actor_manager.start(
+                StateChangeArbSearcherActor::new(true)
+                    .with_config(self.searcher_config.clone())
+                    .with_thread_pool(thread_pool)
+                    .on_bc(&self.bc),
+            )?;
             self.has_state_update = true
         }
-        self.actor_manager.start(PendingTxStateChangeProcessorActor::new(self.provider.clone()).on_bc(&self.bc))?;
+        self.actor_manager.start(
+            PendingTxStateChangeProcessorActor::new(self.provider.clone()).with_simulation(self.pending_tx_simulation).on_bc(&self.bc),
+        )?;
         Ok(self)
     }
 
@@ -417,6 +537,46 @@ where
         self.with_backrun_block()?.with_backrun_mempool()
     }
 
+    /// Start strategy actor hosting given strategies. Requires state updates from backrun block or mempool
+    pub fn with_strategies(&mut self, strategies: Vec<Arc<dyn Strategy>>) -> Result<&mut Self> {
+        let thread_pool = self.thread_pool()?;
+        self.actor_manager.start(StrategyActor::new().with_strategies(strategies).with_thread_pool(thread_pool).on_bc(&self.bc))?;
+        Ok(self)
+    }
+
+    /// Start liquidation searcher for Aave V3 positions. Requires state updates from backrun block or mempool
+    pub fn with_liquidation_searcher(&mut self) -> Result<&mut Self> {
+        let liquidation_searcher =
+            LiquidationSearcherActor::new(self.provider.clone(), LiquidationSearcherConfig::default()).on_bc(&self.bc);
+        let strategy = liquidation_searcher.strategy();
+        self.actor_manager.start(liquidation_searcher)?;
+        self.with_strategies(vec![Arc::new(strategy)])
+    }
+
+    /// Start multicaller approvals and balances keeper. Requires swap encoder and signers
+    pub fn with_multicaller_allowances(&mut self, config: MulticallerAllowanceConfig) -> Result<&mut Self> {
+        let encoder = self.encoder.clone().ok_or(eyre!("NO_ENCODER"))?;
+        self.actor_manager.start(
+            MulticallerAllowanceActor::new(self.provider.clone(), encoder, config)
+                .with_state_reader_config(self.state_reader_config.clone())
+                .with_signers(self.signers.clone())
+                .on_bc(&self.bc),
+        )?;
+        Ok(self)
+    }
+
+    /// Start profit sweeping from multicaller and signers to the treasury. Requires swap encoder and signers
+    pub fn with_profit_sweeper(&mut self, config: ProfitSweeperConfig) -> Result<&mut Self> {
+        let encoder = self.encoder.clone().ok_or(eyre!("NO_ENCODER"))?;
+        self.actor_manager.start(
+            ProfitSweeperActor::new(self.provider.clone(), encoder, config)
+                .with_state_reader_config(self.state_reader_config.clone())
+                .with_signers(self.signers.clone())
+                .on_bc(&self.bc),
+        )?;
+        Ok(self)
+    }
+
     /// Start influxdb writer
     pub fn with_influxdb_writer(&mut self, url: String, database: String, tags: HashMap<String, String>) -> Result<&mut Self> {
         self.actor_manager.start(InfluxDbWriterActor::new(url, database, tags).on_bc(&self.bc))?;
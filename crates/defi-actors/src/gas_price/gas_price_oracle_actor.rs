@@ -0,0 +1,89 @@
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error};
+
+use defi_blockchain::Blockchain;
+use defi_entities::{BlockHistory, GasPriceOracle};
+use defi_events::MarketEvents;
+use loom_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, SharedState, WorkerResult};
+use loom_actors_macros::{Accessor, Consumer};
+
+/// Feeds the gas price oracle with blocks and state updates from block history.
+pub async fn gas_price_oracle_worker(
+    block_history: SharedState<BlockHistory>,
+    gas_price_oracle: SharedState<GasPriceOracle>,
+    market_events_rx: Broadcaster<MarketEvents>,
+) -> WorkerResult {
+    subscribe!(market_events_rx);
+
+    loop {
+        tokio::select! {
+            msg = market_events_rx.recv() => {
+                let market_event_msg : Result<MarketEvents, RecvError> = msg;
+                match market_event_msg {
+                    Ok(MarketEvents::BlockTxUpdate{ block_number, block_hash }) => {
+                        let Some(block) = block_history.read().await.get_block_by_hash(&block_hash) else {
+                            error!(%block_hash, "Block not found in block history");
+                            continue
+                        };
+
+                        let mut oracle = gas_price_oracle.write().await;
+                        oracle.on_block(&block);
+                        debug!(block_number, priority_fee_median = ?oracle.priority_fee(50), "Gas price oracle updated");
+                    }
+                    Ok(MarketEvents::BlockStateUpdate{ block_hash }) => {
+                        let Some(entry) = block_history.read().await.get_entry(&block_hash).cloned() else {
+                            error!(%block_hash, "Block not found in block history");
+                            continue
+                        };
+                        if let Some(state_update) = entry.state_update.as_ref() {
+                            gas_price_oracle.write().await.on_state_update(entry.number(), entry.header.miner, state_update);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e)=>{
+                        error!("market_event_rx error : {e}")
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Accessor, Consumer, Default)]
+pub struct GasPriceOracleActor {
+    #[accessor]
+    block_history: Option<SharedState<BlockHistory>>,
+    #[accessor]
+    gas_price_oracle: Option<SharedState<GasPriceOracle>>,
+    #[consumer]
+    market_events_rx: Option<Broadcaster<MarketEvents>>,
+}
+
+impl GasPriceOracleActor {
+    pub fn new() -> Self {
+        GasPriceOracleActor::default()
+    }
+
+    pub fn on_bc(self, bc: &Blockchain) -> Self {
+        Self {
+            block_history: Some(bc.block_history()),
+            gas_price_oracle: Some(bc.gas_price_oracle()),
+            market_events_rx: Some(bc.market_events_channel()),
+        }
+    }
+}
+
+impl Actor for GasPriceOracleActor {
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(gas_price_oracle_worker(
+            self.block_history.clone().unwrap(),
+            self.gas_price_oracle.clone().unwrap(),
+            self.market_events_rx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "GasPriceOracleActor"
+    }
+}
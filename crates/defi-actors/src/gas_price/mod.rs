@@ -0,0 +1,3 @@
+pub use gas_price_oracle_actor::GasPriceOracleActor;
+
+mod gas_price_oracle_actor;
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use alloy_primitives::{Address, BlockHash, TxHash, U256};
+use eyre::{eyre, Result};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info};
+
+use defi_blockchain::Blockchain;
+use defi_entities::{BidStats, BlockHistory};
+use defi_events::{MarketEvents, MessageTxCompose, TxCompose};
+use defi_types::GethStateUpdateVec;
+use loom_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, SharedState, WorkerResult};
+use loom_actors_macros::{Accessor, Consumer};
+use loom_utils::NWETH;
+
+#[derive(Clone, Debug)]
+struct BroadcastedBid {
+    block: u64,
+    signer: Option<Address>,
+    pools: Vec<Address>,
+    profit_eth: U256,
+    tips_pct: u32,
+}
+
+#[derive(Clone, Debug)]
+struct PendingLoss {
+    bid: BroadcastedBid,
+    competitor_tx: Option<TxHash>,
+    competitor_idx: usize,
+}
+
+/// Coinbase balance change made by the transaction at tx_index. Requires per transaction state update of the block.
+fn coinbase_payment(state_update: &GethStateUpdateVec, coinbase: Address, tx_index: usize) -> Option<U256> {
+    let balance_after = state_update.get(tx_index)?.get(&coinbase)?.balance?;
+    let balance_before = state_update[..tx_index].iter().rev().find_map(|x| x.get(&coinbase).and_then(|a| a.balance))?;
+    Some(balance_after.saturating_sub(balance_before))
+}
+
+fn tips_pct_for_bid(profit_eth: U256, tips: Option<U256>, tips_pct: Option<u32>) -> u32 {
+    match tips {
+        Some(tips) if !profit_eth.is_zero() => (tips.saturating_mul(U256::from(10000)) / profit_eth).min(U256::from(10000)).to::<u32>(),
+        _ => tips_pct.unwrap_or_default(),
+    }
+}
+
+async fn resolve_losses(
+    block_history: &SharedState<BlockHistory>,
+    bid_stats: &SharedState<BidStats>,
+    block_hash: BlockHash,
+    losses: Vec<PendingLoss>,
+) -> Result<Vec<PendingLoss>> {
+    let block_history_guard = block_history.read().await;
+    let entry = block_history_guard.get_entry(&block_hash).ok_or(eyre!("BLOCK_HISTORY_ENTRY_NOT_FOUND"))?;
+    let coinbase = entry.header.miner;
+
+    let Some(state_update) = entry.state_update.as_ref() else {
+        return Ok(losses);
+    };
+
+    let mut bid_stats_guard = bid_stats.write().await;
+    for loss in losses.into_iter() {
+        let payment = coinbase_payment(state_update, coinbase, loss.competitor_idx);
+        info!(
+            competitor_tx = ?loss.competitor_tx,
+            payment = NWETH::to_float(payment.unwrap_or_default()),
+            profit = NWETH::to_float(loss.bid.profit_eth),
+            tips_pct = loss.bid.tips_pct,
+            "Backrun lost"
+        );
+        bid_stats_guard.on_lost(&loss.bid.pools, loss.bid.tips_pct, loss.bid.profit_eth, loss.competitor_tx, payment);
+    }
+    Ok(vec![])
+}
+
+pub async fn bid_feedback_monitor_worker(
+    block_history: SharedState<BlockHistory>,
+    bid_stats: SharedState<BidStats>,
+    tx_compose_channel_rx: Broadcaster<MessageTxCompose>,
+    market_events_rx: Broadcaster<MarketEvents>,
+) -> WorkerResult {
+    subscribe!(tx_compose_channel_rx);
+    subscribe!(market_events_rx);
+
+    let mut bids: HashMap<TxHash, BroadcastedBid> = HashMap::new();
+    let mut pending_losses: HashMap<BlockHash, Vec<PendingLoss>> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            msg = market_events_rx.recv() => {
+                let market_event_msg : Result<MarketEvents, RecvError> = msg;
+                match market_event_msg {
+                    Ok(MarketEvents::BlockTxUpdate{ block_number, block_hash }) => {
+                        let Some(block) = block_history.read().await.get_block_by_hash(&block_hash) else {
+                            error!(%block_hash, "Block not found in block history");
+                            continue
+                        };
+                        let Some(txs) = block.transactions.as_transactions() else {
+                            continue
+                        };
+
+                        let mut losses: Vec<PendingLoss> = Vec::new();
+
+                        for (idx, tx) in txs.iter().enumerate() {
+                            let Some(bid) = bids.remove(&tx.hash) else {
+                                continue
+                            };
+
+                            let next_tx = txs.get(idx + 1);
+                            let landed = match (next_tx, bid.signer) {
+                                (Some(next_tx), Some(signer)) => next_tx.from == signer,
+                                _ => false,
+                            };
+
+                            if landed {
+                                info!(stuffing_tx = %tx.hash, block_number, tips_pct = bid.tips_pct, "Backrun landed");
+                                bid_stats.write().await.on_landed(&bid.pools, bid.tips_pct);
+                            } else {
+                                losses.push(PendingLoss { bid, competitor_tx: next_tx.map(|x| x.hash), competitor_idx: idx + 1 });
+                            }
+                        }
+
+                        bids.retain(|_, bid| bid.block + 2 > block_number);
+
+                        if !losses.is_empty() {
+                            match resolve_losses(&block_history, &bid_stats, block_hash, losses).await {
+                                Ok(losses) => {
+                                    if !losses.is_empty() {
+                                        pending_losses.insert(block_hash, losses);
+                                    }
+                                }
+                                Err(e) => {
+                                    error!("resolve_losses : {}", e)
+                                }
+                            }
+                        }
+                        debug!(bids = bids.len(), pending = pending_losses.len(), block_number, "Bid feedback updated");
+                    }
+                    Ok(MarketEvents::BlockStateUpdate{ block_hash }) => {
+                        if let Some(losses) = pending_losses.remove(&block_hash) {
+                            match resolve_losses(&block_history, &bid_stats, block_hash, losses).await {
+                                Ok(losses) => {
+                                    if !losses.is_empty() {
+                                        error!(%block_hash, "State update not found in block history");
+                                    }
+                                }
+                                Err(e) => {
+                                    error!("resolve_losses : {}", e)
+                                }
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e)=>{
+                        error!("market_event_rx error : {e}")
+                    }
+                }
+            },
+
+            msg = tx_compose_channel_rx.recv() => {
+                let tx_compose_update : Result<MessageTxCompose, RecvError>  = msg;
+                match tx_compose_update {
+                    Ok(tx_compose_msg)=>{
+                        if let TxCompose::Broadcast(broadcast_data) = tx_compose_msg.inner {
+                            let profit_eth = broadcast_data.swap.abs_profit_eth();
+                            let tips_pct = tips_pct_for_bid(profit_eth, broadcast_data.tips, broadcast_data.tips_pct);
+
+                            // the backrun follows the last stuffing transaction of the bundle
+                            if let Some(stuffing_tx_hash) = broadcast_data.stuffing_txs_hashes.last() {
+                                let entry = bids.entry(*stuffing_tx_hash).or_insert(BroadcastedBid {
+                                    block: broadcast_data.next_block_number,
+                                    signer: broadcast_data.signer.as_ref().map(|x| x.address()),
+                                    pools: broadcast_data.swap.get_pool_address_vec(),
+                                    profit_eth: U256::ZERO,
+                                    tips_pct,
+                                });
+
+                                if entry.profit_eth < profit_eth {
+                                    entry.block = broadcast_data.next_block_number;
+                                    entry.signer = broadcast_data.signer.as_ref().map(|x| x.address());
+                                    entry.pools = broadcast_data.swap.get_pool_address_vec();
+                                    entry.profit_eth = profit_eth;
+                                    entry.tips_pct = tips_pct;
+                                }
+                            }
+                        }
+                    }
+                    Err(e)=>{
+                        error!("tx_compose_channel_rx : {e}")
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Accessor, Consumer, Default)]
+pub struct BidFeedbackMonitorActor {
+    #[accessor]
+    block_history: Option<SharedState<BlockHistory>>,
+    #[accessor]
+    bid_stats: Option<SharedState<BidStats>>,
+    #[consumer]
+    tx_compose_channel_rx: Option<Broadcaster<MessageTxCompose>>,
+    #[consumer]
+    market_events_rx: Option<Broadcaster<MarketEvents>>,
+}
+
+impl BidFeedbackMonitorActor {
+    pub fn new() -> Self {
+        BidFeedbackMonitorActor::default()
+    }
+
+    pub fn on_bc(self, bc: &Blockchain) -> Self {
+        Self {
+            block_history: Some(bc.block_history()),
+            bid_stats: Some(bc.bid_stats()),
+            tx_compose_channel_rx: Some(bc.compose_channel()),
+            market_events_rx: Some(bc.market_events_channel()),
+        }
+    }
+}
+
+impl Actor for BidFeedbackMonitorActor {
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(bid_feedback_monitor_worker(
+            self.block_history.clone().unwrap(),
+            self.bid_stats.clone().unwrap(),
+            self.tx_compose_channel_rx.clone().unwrap(),
+            self.market_events_rx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "BidFeedbackMonitorActor"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_rpc_types_trace::geth::AccountState;
+    use std::collections::BTreeMap;
+
+    fn coinbase_state(coinbase: Address, balance: u64) -> BTreeMap<Address, AccountState> {
+        BTreeMap::from([(coinbase, AccountState { balance: Some(U256::from(balance)), ..AccountState::default() })])
+    }
+
+    #[test]
+    fn test_coinbase_payment() {
+        let coinbase = Address::repeat_byte(0xcb);
+        let state_update: GethStateUpdateVec =
+            vec![coinbase_state(coinbase, 100), BTreeMap::new(), coinbase_state(coinbase, 130), coinbase_state(coinbase, 1130)];
+
+        assert_eq!(coinbase_payment(&state_update, coinbase, 3), Some(U256::from(1000)));
+        assert_eq!(coinbase_payment(&state_update, coinbase, 2), Some(U256::from(30)));
+        assert_eq!(coinbase_payment(&state_update, coinbase, 1), None);
+        assert_eq!(coinbase_payment(&state_update, coinbase, 0), None);
+    }
+
+    #[test]
+    fn test_tips_pct_for_bid() {
+        assert_eq!(tips_pct_for_bid(U256::from(1000), Some(U256::from(700)), None), 7000);
+        assert_eq!(tips_pct_for_bid(U256::ZERO, Some(U256::from(700)), Some(5000)), 5000);
+    }
+}
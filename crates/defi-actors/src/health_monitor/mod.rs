@@ -1,7 +1,9 @@
+mod bid_feedback_monitor;
 mod pool_health_monitor;
 mod state_health_monitor;
 mod stuffing_tx_monitor;
 
+pub use bid_feedback_monitor::BidFeedbackMonitorActor;
 pub use pool_health_monitor::PoolHealthMonitorActor;
 pub use state_health_monitor::StateHealthMonitorActor;
 pub use stuffing_tx_monitor::StuffingTxMonitorActor;
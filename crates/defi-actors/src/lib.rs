@@ -1,23 +1,34 @@
 pub use accounts_monitor::NonceAndBalanceMonitorActor;
-pub use backrun::{PendingTxStateChangeProcessorActor, StateChangeArbActor, StateChangeArbSearcherActor, SwapCalculator};
+pub use backrun::{
+    PendingTxSimulation, PendingTxStateChangeProcessorActor, StateChangeArbActor, StateChangeArbSearcherActor,
+    StateChangeArbSearcherConfig, SwapCalculator,
+};
 pub use block_history::BlockHistoryActor;
 pub use blockchain_actors::BlockchainActors;
-pub use health_monitor::{PoolHealthMonitorActor, StateHealthMonitorActor, StuffingTxMonitorActor};
+pub use gas_price::GasPriceOracleActor;
+pub use health_monitor::{BidFeedbackMonitorActor, PoolHealthMonitorActor, StateHealthMonitorActor, StuffingTxMonitorActor};
+pub use liquidation::{LendingPosition, LendingPositions, LiquidationSearcherActor, LiquidationSearcherConfig, LiquidationStrategy};
 pub use market::{
-    fetch_and_add_pool_by_address, fetch_state_and_add_pool, CurvePoolLoaderOneShotActor, HistoryPoolLoaderOneShotActor,
-    NewPoolLoaderActor, PoolLoaderActor, RequiredPoolLoaderActor,
+    fetch_and_add_pool_by_address, fetch_and_add_pools, fetch_pool_data_by_address, fetch_state_and_add_pool, fetch_state_and_add_pools,
+    CurvePoolLoaderOneShotActor, HistoryPoolLoaderOneShotActor, NewPoolLoaderActor, PoolLoaderActor, RequiredPoolLoaderActor,
 };
 pub use market_state::{preload_market_state, MarketStatePreloadedOneShotActor};
-pub use mempool::MempoolActor;
+pub use mempool::{decode_swap_hops, predict_tx_impact, MempoolActor, MevShareHintActor, PredictedImpact, SwapHop};
 pub use mergers::{ArbSwapPathMergerActor, DiffPathMergerActor, SamePathMergerActor};
 pub use node::{loom_exex, mempool_worker, NodeBlockActor, NodeBlockActorConfig, NodeMempoolActor};
 pub use node_exex_grpc::NodeExExGrpcActor;
 pub use node_player::NodeBlockPlayerActor;
 pub use price::PriceActor;
+pub use strategy::{Strategy, StrategyActor, StrategyCandidate, StrategyContext};
 pub use swap_estimators::{EvmEstimatorActor, GethEstimatorActor, HardhatEstimatorActor};
+pub use swap_preflight::BundlePreflightActor;
 pub use swap_routers::SwapRouterActor;
 pub use swap_signers::{InitializeSignersOneShotBlockingActor, TxSignersActor};
-pub use swap_tx_broadcaster::{AnvilBroadcastActor, FlashbotsBroadcastActor};
+pub use swap_tx_broadcaster::{
+    AnvilBroadcastActor, BlockTargetingPolicy, FlashbotsBroadcastActor, PaperTradeOutcome, PaperTradeRecord, PaperTradingBroadcastActor,
+    PrivateTxBroadcastActor, PublicTxBroadcastActor,
+};
+pub use treasury::{BalanceTarget, MulticallerAllowanceActor, MulticallerAllowanceConfig, ProfitSweeperActor, ProfitSweeperConfig};
 
 mod market;
 mod mempool;
@@ -26,6 +37,7 @@ mod node;
 mod accounts_monitor;
 mod block_history;
 
+mod gas_price;
 mod health_monitor;
 mod market_state;
 mod price;
@@ -33,6 +45,8 @@ mod swap_routers;
 
 mod swap_signers;
 
+mod swap_preflight;
+
 mod swap_tx_broadcaster;
 
 mod swap_estimators;
@@ -40,7 +54,10 @@ mod swap_estimators;
 mod mergers;
 
 mod backrun;
+mod liquidation;
 mod node_player;
+mod strategy;
+mod treasury;
 
 mod blockchain_actors;
 mod node_exex_grpc;
@@ -0,0 +1,149 @@
+use std::collections::BTreeSet;
+
+use alloy_network::Network;
+use alloy_primitives::{Address, BlockNumber};
+use alloy_provider::Provider;
+use alloy_rpc_types::Filter;
+use alloy_sol_types::{SolCall, SolEvent};
+use alloy_transport::Transport;
+use eyre::Result;
+use tracing::{debug, info};
+
+use crate::liquidation::lending_positions::{LendingPosition, LendingPositions};
+use crate::liquidation::LiquidationSearcherConfig;
+use defi_abi::aave3::{IAaveOracle, IAaveV3Pool, IAaveV3PoolDataProvider, IChainlinkAggregatorProxy};
+use defi_abi::IERC20;
+use defi_entities::required_state::RequiredState;
+
+const LOGS_CHUNK_BLOCKS: u64 = 10_000;
+
+/// Lending pool reserve with its tokens and the contract its price is written to.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AaveReserve {
+    pub asset: Address,
+    pub a_token: Address,
+    pub variable_debt_token: Address,
+    pub price_aggregator: Address,
+}
+
+/// Fetches lending pool reserves. Price source of the oracle is usually a chainlink proxy, new prices are written to
+/// the aggregator behind it. Sources without a proxy are used as is.
+pub async fn fetch_reserves<P, T, N>(client: P, config: &LiquidationSearcherConfig) -> Result<Vec<AaveReserve>>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + Send + Sync + Clone + 'static,
+{
+    let pool = IAaveV3Pool::new(config.lending_pool, client.clone());
+    let data_provider = IAaveV3PoolDataProvider::new(config.data_provider, client.clone());
+    let oracle = IAaveOracle::new(config.oracle, client.clone());
+
+    let mut reserves = Vec::new();
+    for asset in pool.getReservesList().call().await?._0 {
+        let tokens = data_provider.getReserveTokensAddresses(asset).call().await?;
+        let source = oracle.getSourceOfAsset(asset).call().await?._0;
+        let price_aggregator = match IChainlinkAggregatorProxy::new(source, client.clone()).aggregator().call().await {
+            Ok(aggregator) => aggregator._0,
+            Err(_) => source,
+        };
+        reserves.push(AaveReserve {
+            asset,
+            a_token: tokens.aTokenAddress,
+            variable_debt_token: tokens.variableDebtTokenAddress,
+            price_aggregator,
+        });
+    }
+    debug!(reserves = reserves.len(), "Lending pool reserves fetched");
+    Ok(reserves)
+}
+
+/// Backfills positions from supply and borrow logs of the lending pool in the block range.
+pub async fn fetch_positions<P, T, N>(
+    client: P,
+    positions: &mut LendingPositions,
+    lending_pool: Address,
+    from_block: BlockNumber,
+    to_block: BlockNumber,
+) -> Result<()>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + Send + Sync + Clone + 'static,
+{
+    let mut chunk_start = from_block;
+    while chunk_start <= to_block {
+        let chunk_end = (chunk_start + LOGS_CHUNK_BLOCKS - 1).min(to_block);
+        let filter = Filter::new()
+            .address(lending_pool)
+            .event_signature(vec![IAaveV3Pool::Supply::SIGNATURE_HASH, IAaveV3Pool::Borrow::SIGNATURE_HASH])
+            .from_block(chunk_start)
+            .to_block(chunk_end);
+        for log in client.get_logs(&filter).await? {
+            positions.apply_log(&log.inner);
+        }
+        chunk_start = chunk_end + 1;
+    }
+    info!(from_block, to_block, positions = positions.len(), "Lending positions backfilled");
+    Ok(())
+}
+
+/// State read by the searcher : reserve configurations and prices with their aggregators, aTokens and debt tokens,
+/// account data of the borrowers.
+pub fn aave_required_state(
+    config: &LiquidationSearcherConfig,
+    reserves: &[AaveReserve],
+    borrowers: &[(Address, LendingPosition)],
+) -> RequiredState {
+    let mut required_state = RequiredState::new();
+    required_state.add_call(config.lending_pool, IAaveV3Pool::getReservesListCall {}.abi_encode());
+
+    for reserve in reserves.iter() {
+        required_state
+            .add_call(config.data_provider, IAaveV3PoolDataProvider::getReserveConfigurationDataCall { asset: reserve.asset }.abi_encode())
+            .add_call(config.oracle, IAaveOracle::getAssetPriceCall { asset: reserve.asset }.abi_encode())
+            .add_call(reserve.price_aggregator, IChainlinkAggregatorProxy::latestAnswerCall {}.abi_encode())
+            .add_call(reserve.a_token, IERC20::totalSupplyCall {}.abi_encode())
+            .add_call(reserve.variable_debt_token, IERC20::totalSupplyCall {}.abi_encode());
+    }
+
+    for (user, position) in borrowers.iter() {
+        required_state.add_call(config.lending_pool, IAaveV3Pool::getUserAccountDataCall { user: *user }.abi_encode());
+        let assets: BTreeSet<Address> = position.collateral.union(&position.debt).cloned().collect();
+        for asset in assets {
+            required_state
+                .add_call(config.data_provider, IAaveV3PoolDataProvider::getUserReserveDataCall { asset, user: *user }.abi_encode());
+        }
+    }
+
+    required_state
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_aave_required_state() {
+        let config = LiquidationSearcherConfig::default();
+        let reserve = AaveReserve {
+            asset: Address::repeat_byte(0x01),
+            a_token: Address::repeat_byte(0x02),
+            variable_debt_token: Address::repeat_byte(0x03),
+            price_aggregator: Address::repeat_byte(0x04),
+        };
+        let position = LendingPosition { collateral: BTreeSet::from([reserve.asset]), debt: BTreeSet::from([reserve.asset]) };
+
+        let required_state = aave_required_state(&config, &[reserve.clone()], &[(Address::repeat_byte(0x05), position)]);
+
+        let mut expected = vec![
+            config.lending_pool,
+            config.data_provider,
+            config.oracle,
+            reserve.a_token,
+            reserve.variable_debt_token,
+            reserve.price_aggregator,
+        ];
+        expected.sort();
+        assert_eq!(required_state.accounts(), expected);
+    }
+}
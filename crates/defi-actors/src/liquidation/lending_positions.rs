@@ -0,0 +1,121 @@
+use std::collections::{BTreeSet, HashMap};
+
+use alloy_primitives::{Address, Log};
+use alloy_sol_types::SolEventInterface;
+use defi_abi::aave3::IAaveV3Pool::IAaveV3PoolEvents;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LendingPosition {
+    pub collateral: BTreeSet<Address>,
+    pub debt: BTreeSet<Address>,
+}
+
+/// Reserves supplied and borrowed by users of a lending pool, collected from pool events.
+/// Balances are not tracked here, they are always read from the state.
+#[derive(Clone, Debug, Default)]
+pub struct LendingPositions {
+    lending_pool: Address,
+    positions: HashMap<Address, LendingPosition>,
+}
+
+impl LendingPositions {
+    pub fn new(lending_pool: Address) -> Self {
+        Self { lending_pool, positions: HashMap::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn get(&self, user: &Address) -> Option<&LendingPosition> {
+        self.positions.get(user)
+    }
+
+    pub fn add_collateral(&mut self, user: Address, reserve: Address) {
+        self.positions.entry(user).or_default().collateral.insert(reserve);
+    }
+
+    pub fn add_debt(&mut self, user: Address, reserve: Address) {
+        self.positions.entry(user).or_default().debt.insert(reserve);
+    }
+
+    /// Users with at least one borrowed reserve.
+    pub fn borrowers(&self) -> Vec<(Address, LendingPosition)> {
+        self.positions.iter().filter(|(_, position)| !position.debt.is_empty()).map(|(user, position)| (*user, position.clone())).collect()
+    }
+
+    /// Updates positions from a lending pool log. Returns the user whose position the log is about.
+    pub fn apply_log(&mut self, log: &Log) -> Option<Address> {
+        if log.address != self.lending_pool {
+            return None;
+        }
+        let event = IAaveV3PoolEvents::decode_log(log, false).ok()?;
+
+        match event.data {
+            IAaveV3PoolEvents::Supply(event) => {
+                self.add_collateral(event.onBehalfOf, event.reserve);
+                Some(event.onBehalfOf)
+            }
+            IAaveV3PoolEvents::Borrow(event) => {
+                self.add_debt(event.onBehalfOf, event.reserve);
+                Some(event.onBehalfOf)
+            }
+            IAaveV3PoolEvents::LiquidationCall(event) => {
+                self.add_collateral(event.user, event.collateralAsset);
+                self.add_debt(event.user, event.debtAsset);
+                Some(event.user)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::U256;
+    use alloy_sol_types::SolEvent;
+    use defi_abi::aave3::IAaveV3Pool;
+
+    fn to_log(address: Address, event: &impl SolEvent) -> Log {
+        Log { address, data: event.encode_log_data() }
+    }
+
+    #[test]
+    fn test_apply_log() {
+        let pool = Address::repeat_byte(0x01);
+        let user = Address::repeat_byte(0x02);
+        let weth = Address::repeat_byte(0x03);
+        let usdc = Address::repeat_byte(0x04);
+
+        let mut positions = LendingPositions::new(pool);
+
+        let supply = IAaveV3Pool::Supply { reserve: weth, user, onBehalfOf: user, amount: U256::from(10), referralCode: 0 };
+        let borrow = IAaveV3Pool::Borrow {
+            reserve: usdc,
+            user,
+            onBehalfOf: user,
+            amount: U256::from(5),
+            interestRateMode: 2,
+            borrowRate: U256::ZERO,
+            referralCode: 0,
+        };
+
+        assert_eq!(positions.apply_log(&to_log(Address::repeat_byte(0x05), &supply)), None);
+        assert!(positions.is_empty());
+
+        assert_eq!(positions.apply_log(&to_log(pool, &supply)), Some(user));
+        assert!(positions.borrowers().is_empty());
+
+        assert_eq!(positions.apply_log(&to_log(pool, &borrow)), Some(user));
+        let borrowers = positions.borrowers();
+        assert_eq!(borrowers.len(), 1);
+        assert_eq!(borrowers[0].0, user);
+        assert_eq!(borrowers[0].1.collateral, BTreeSet::from([weth]));
+        assert_eq!(borrowers[0].1.debt, BTreeSet::from([usdc]));
+    }
+}
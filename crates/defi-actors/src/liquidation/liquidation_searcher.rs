@@ -0,0 +1,624 @@
+use std::collections::BTreeSet;
+use std::marker::PhantomData;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use alloy_network::Network;
+use alloy_primitives::{Address, Log, U256};
+use alloy_provider::Provider;
+use alloy_transport::Transport;
+use eyre::{eyre, OptionExt, Result};
+use revm::primitives::Env;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info, trace, warn};
+
+use crate::liquidation::aave_state::{aave_required_state, fetch_positions, fetch_reserves, AaveReserve};
+use crate::liquidation::lending_positions::{LendingPosition, LendingPositions};
+use crate::strategy::{Strategy, StrategyCandidate, StrategyContext};
+use debug_provider::DebugProviderExt;
+use defi_address_book::AaveV3Address;
+use defi_blockchain::Blockchain;
+use defi_entities::required_state::RequiredStateReader;
+use defi_entities::{BlockHistory, Liquidation, Market, MarketState, Swap, SwapAmountType, SwapLine};
+use defi_events::{MarketEvents, StateUpdateEvent};
+use defi_pools::state_readers::AaveV3StateReader;
+use defi_types::GethStateUpdateVec;
+use loom_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, SharedState, WorkerResult};
+use loom_actors_macros::{Accessor, Consumer};
+use loom_revm_db::LoomInMemoryDB;
+use loom_utils::NWETH;
+
+const HEALTH_FACTOR_LIQUIDATION_THRESHOLD: U256 = U256::from_limbs([1_000_000_000_000_000_000, 0, 0, 0]);
+const CLOSE_FACTOR_HF_THRESHOLD: U256 = U256::from_limbs([950_000_000_000_000_000, 0, 0, 0]);
+const PERCENTAGE_FACTOR: u64 = 10000;
+const DEFAULT_CLOSE_FACTOR: u64 = 5000;
+const MAX_CLOSE_FACTOR: u64 = 10000;
+const LIQUIDATION_GAS: u64 = 400_000;
+const DEFAULT_BACKFILL_BLOCKS: u64 = 500_000;
+const PRELOAD_RETRY_DELAY: Duration = Duration::from_secs(1);
+const PRELOAD_MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug)]
+pub struct LiquidationSearcherConfig {
+    pub lending_pool: Address,
+    pub data_provider: Address,
+    pub oracle: Address,
+    /// Additional accounts which state changes trigger health factors recalculation. Price aggregators of the reserves
+    /// are added on start.
+    pub watched: Vec<Address>,
+    /// Blocks of pool logs the positions are backfilled from on start
+    pub backfill_blocks: u64,
+}
+
+impl Default for LiquidationSearcherConfig {
+    fn default() -> Self {
+        Self {
+            lending_pool: AaveV3Address::POOL,
+            data_provider: AaveV3Address::POOL_DATA_PROVIDER,
+            oracle: AaveV3Address::ORACLE,
+            watched: vec![],
+            backfill_blocks: DEFAULT_BACKFILL_BLOCKS,
+        }
+    }
+}
+
+impl LiquidationSearcherConfig {
+    pub fn with_watched(self, watched: Vec<Address>) -> Self {
+        Self { watched, ..self }
+    }
+
+    pub fn with_backfill_blocks(self, backfill_blocks: u64) -> Self {
+        Self { backfill_blocks, ..self }
+    }
+
+    fn is_affected(&self, state_update: &GethStateUpdateVec) -> bool {
+        state_update.iter().any(|account_updates| {
+            account_updates.contains_key(&self.lending_pool) || self.watched.iter().any(|address| account_updates.contains_key(address))
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct ReserveState {
+    asset: Address,
+    balance: U256,
+    price: U256,
+    unit: U256,
+    liquidation_bonus: U256,
+}
+
+impl ReserveState {
+    fn base_value(&self) -> U256 {
+        self.balance * self.price / self.unit
+    }
+}
+
+fn read_reserve_state(
+    config: &LiquidationSearcherConfig,
+    db: &LoomInMemoryDB,
+    env: Env,
+    asset: Address,
+    user: Address,
+    is_debt: bool,
+) -> Result<ReserveState> {
+    let user_reserve = AaveV3StateReader::get_user_reserve_data(db, env.clone(), config.data_provider, asset, user)?;
+    let balance = if is_debt {
+        user_reserve.total_debt()
+    } else if user_reserve.usage_as_collateral_enabled {
+        user_reserve.a_token_balance
+    } else {
+        U256::ZERO
+    };
+    if balance.is_zero() {
+        return Err(eyre!("ZERO_BALANCE"));
+    }
+
+    let reserve_configuration = AaveV3StateReader::get_reserve_configuration(db, env.clone(), config.data_provider, asset)?;
+    let price = AaveV3StateReader::get_asset_price(db, env, config.oracle, asset)?;
+    if price.is_zero() {
+        return Err(eyre!("ZERO_PRICE"));
+    }
+
+    Ok(ReserveState {
+        asset,
+        balance,
+        price,
+        unit: U256::from(10).pow(reserve_configuration.decimals),
+        liquidation_bonus: reserve_configuration.liquidation_bonus,
+    })
+}
+
+/// Aave V3 liquidation amounts. Half of the debt can be covered, whole debt if health factor is below 0.95.
+/// Returns debt to cover and collateral amount received including liquidation bonus.
+fn calc_liquidation_amounts(health_factor: U256, debt: &ReserveState, collateral: &ReserveState) -> (U256, U256) {
+    let close_factor = if health_factor < CLOSE_FACTOR_HF_THRESHOLD { MAX_CLOSE_FACTOR } else { DEFAULT_CLOSE_FACTOR };
+    let mut debt_to_cover = debt.balance * U256::from(close_factor) / U256::from(PERCENTAGE_FACTOR);
+
+    let base_collateral = debt.price * debt_to_cover * collateral.unit / (collateral.price * debt.unit);
+    let mut collateral_amount = base_collateral * collateral.liquidation_bonus / U256::from(PERCENTAGE_FACTOR);
+
+    if collateral_amount > collateral.balance {
+        collateral_amount = collateral.balance;
+        debt_to_cover = collateral.price * collateral_amount * debt.unit / (debt.price * collateral.unit) * U256::from(PERCENTAGE_FACTOR)
+            / collateral.liquidation_bonus;
+    }
+
+    (debt_to_cover, collateral_amount)
+}
+
+fn build_liquidation(
+    config: &LiquidationSearcherConfig,
+    market: &Market,
+    db: &LoomInMemoryDB,
+    env: Env,
+    user: Address,
+    position: &LendingPosition,
+    health_factor: U256,
+) -> Result<Liquidation> {
+    let debt = position
+        .debt
+        .iter()
+        .filter_map(|asset| read_reserve_state(config, db, env.clone(), *asset, user, true).ok())
+        .max_by_key(|reserve| reserve.base_value())
+        .ok_or_eyre("NO_DEBT_RESERVE")?;
+
+    let collateral = position
+        .collateral
+        .iter()
+        .filter(|asset| **asset != debt.asset)
+        .filter_map(|asset| read_reserve_state(config, db, env.clone(), *asset, user, false).ok())
+        .max_by_key(|reserve| reserve.base_value())
+        .ok_or_eyre("NO_COLLATERAL_RESERVE")?;
+
+    if collateral.liquidation_bonus.is_zero() {
+        return Err(eyre!("ZERO_LIQUIDATION_BONUS"));
+    }
+
+    let debt_token = market.get_token(&debt.asset).ok_or_eyre("TOKEN_NOT_FOUND")?;
+    let collateral_token = market.get_token(&collateral.asset).ok_or_eyre("TOKEN_NOT_FOUND")?;
+
+    let (debt_to_cover, collateral_amount) = calc_liquidation_amounts(health_factor, &debt, &collateral);
+    if debt_to_cover.is_zero() || collateral_amount.is_zero() {
+        return Err(eyre!("ZERO_LIQUIDATION_AMOUNT"));
+    }
+
+    let pools = market.get_token_token_pools(&collateral.asset, &debt.asset).ok_or_eyre("NO_SWAP_POOLS")?;
+
+    let mut best: Option<(SwapLine, U256, u64)> = None;
+    for pool_address in pools.into_iter().filter(|pool_address| market.is_pool_ok(pool_address)) {
+        let Ok(path) = market.swap_path(vec![collateral.asset, debt.asset], vec![pool_address]) else {
+            continue;
+        };
+        let swap_line = SwapLine { path, amount_in: SwapAmountType::Set(collateral_amount), ..SwapLine::default() };
+        match swap_line.calculate_with_in_amount(db, env.clone(), collateral_amount) {
+            Ok((amount_out, gas_used)) => {
+                let is_better = match &best {
+                    Some((_, best_amount_out, _)) => amount_out > *best_amount_out,
+                    None => true,
+                };
+                if is_better {
+                    best = Some((swap_line, amount_out, gas_used));
+                }
+            }
+            Err(e) => {
+                trace!("calculate_with_in_amount : {:?}", e)
+            }
+        }
+    }
+
+    let (mut swap_line, amount_out, gas_used) = best.ok_or_eyre("NO_SWAP_PATH")?;
+    if amount_out <= debt_to_cover {
+        return Err(eyre!("NOT_PROFITABLE"));
+    }
+    swap_line.amount_out = SwapAmountType::Set(amount_out);
+    swap_line.gas_used = Some(gas_used);
+
+    Ok(Liquidation {
+        lending_pool: config.lending_pool,
+        user,
+        collateral_token,
+        debt_token,
+        debt_to_cover,
+        collateral_amount,
+        swap_line,
+        gas_used: Some(gas_used + LIQUIDATION_GAS),
+    })
+}
+
+/// Position assets with all pool reserves, collateral supplied before the backfilled blocks is not in the position.
+fn candidate_assets(position: &LendingPosition, reserves: &[Address]) -> LendingPosition {
+    LendingPosition {
+        collateral: position.collateral.iter().chain(reserves.iter()).cloned().collect::<BTreeSet<Address>>(),
+        debt: position.debt.iter().chain(reserves.iter()).cloned().collect::<BTreeSet<Address>>(),
+    }
+}
+
+/// Builds liquidations of the borrowers which health factor is below the liquidation threshold.
+fn search_liquidations(
+    config: &LiquidationSearcherConfig,
+    reserves: &[Address],
+    borrowers: &[(Address, LendingPosition)],
+    market: &Market,
+    db: &LoomInMemoryDB,
+    env: Env,
+) -> Vec<Liquidation> {
+    let mut liquidations: Vec<Liquidation> = Vec::new();
+
+    for (user, position) in borrowers.iter() {
+        let account_data = match AaveV3StateReader::get_user_account_data(db, env.clone(), config.lending_pool, *user) {
+            Ok(account_data) => account_data,
+            Err(e) => {
+                trace!(%user, "get_user_account_data : {}", e);
+                continue;
+            }
+        };
+        if account_data.total_debt_base.is_zero() || account_data.health_factor >= HEALTH_FACTOR_LIQUIDATION_THRESHOLD {
+            continue;
+        }
+
+        debug!(%user, health_factor = NWETH::to_float(account_data.health_factor), "Position is liquidatable");
+
+        let position = candidate_assets(position, reserves);
+        match build_liquidation(config, market, db, env.clone(), *user, &position, account_data.health_factor) {
+            Ok(liquidation) => liquidations.push(liquidation),
+            Err(e) => {
+                debug!(%user, "build_liquidation : {}", e)
+            }
+        }
+    }
+
+    liquidations
+}
+
+/// Reserves and positions of the lending pool, config watches price aggregators of the reserves.
+#[derive(Clone, Debug)]
+struct LiquidationState {
+    config: LiquidationSearcherConfig,
+    reserves: Vec<Address>,
+    positions: LendingPositions,
+}
+
+/// Aave V3 liquidation strategy hosted by `StrategyActor`. Its reserves and positions are kept by `LiquidationSearcherActor`.
+#[derive(Clone)]
+pub struct LiquidationStrategy {
+    state: Arc<RwLock<LiquidationState>>,
+}
+
+impl LiquidationStrategy {
+    fn new(config: LiquidationSearcherConfig) -> Self {
+        let positions = LendingPositions::new(config.lending_pool);
+        Self { state: Arc::new(RwLock::new(LiquidationState { config, reserves: vec![], positions })) }
+    }
+}
+
+impl Strategy for LiquidationStrategy {
+    fn name(&self) -> &'static str {
+        "LiquidationStrategy"
+    }
+
+    fn is_applicable(&self, state_update_event: &StateUpdateEvent) -> bool {
+        match self.state.read() {
+            Ok(state) => !state.positions.is_empty() && state.config.is_affected(state_update_event.state_update()),
+            Err(_) => false,
+        }
+    }
+
+    fn search(&self, context: &StrategyContext<'_>) -> Result<Vec<StrategyCandidate>> {
+        // positions are copied, the lock is not held while searching
+        let (config, reserves, borrowers) = match self.state.read() {
+            Ok(state) => (state.config.clone(), state.reserves.clone(), state.positions.borrowers()),
+            Err(_) => return Err(eyre!("LIQUIDATION_STATE_LOCK_POISONED")),
+        };
+
+        let liquidations = search_liquidations(&config, &reserves, &borrowers, context.market, context.poststate, context.evm_env.clone());
+
+        Ok(liquidations
+            .into_iter()
+            .map(|liquidation| {
+                info!(%liquidation, origin = %context.state_update_event.origin, "Liquidation found");
+                let gas = liquidation.gas_used.unwrap_or(LIQUIDATION_GAS);
+                StrategyCandidate::new(Swap::Liquidation(liquidation)).with_gas(gas)
+            })
+            .collect())
+    }
+}
+
+/// Loads reserves, backfills positions and preloads the state they are read from.
+async fn preload_aave_state<P, T, N>(
+    client: P,
+    config: &LiquidationSearcherConfig,
+    market_state: SharedState<MarketState>,
+) -> Result<(Vec<AaveReserve>, LendingPositions)>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    let reserves = fetch_reserves(client.clone(), config).await?;
+
+    let mut positions = LendingPositions::new(config.lending_pool);
+    let block_number = client.get_block_number().await?;
+    fetch_positions(client.clone(), &mut positions, config.lending_pool, block_number.saturating_sub(config.backfill_blocks), block_number)
+        .await?;
+
+    let required_state = aave_required_state(config, &reserves, &positions.borrowers());
+    let update = RequiredStateReader::fetch_calls_and_slots(client, required_state, Some(block_number)).await?;
+    market_state.write().await.state_db.apply_geth_update(update);
+
+    Ok((reserves, positions))
+}
+
+/// Fetches account and reserve data of the borrowers into the market state. Borrowers failed to load are kept for the next
+/// block.
+async fn load_borrowers_state<P, T, N>(
+    client: P,
+    config: &LiquidationSearcherConfig,
+    strategy: &LiquidationStrategy,
+    market_state: &SharedState<MarketState>,
+    unloaded_users: &mut BTreeSet<Address>,
+) where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    let borrowers: Vec<(Address, LendingPosition)> = match strategy.state.read() {
+        Ok(state) => unloaded_users
+            .iter()
+            .filter_map(|user| {
+                state.positions.get(user).filter(|position| !position.debt.is_empty()).map(|position| (*user, position.clone()))
+            })
+            .collect(),
+        Err(_) => return,
+    };
+    if borrowers.is_empty() {
+        unloaded_users.clear();
+        return;
+    }
+
+    let required_state = aave_required_state(config, &[], &borrowers);
+    match RequiredStateReader::fetch_calls_and_slots(client, required_state, None).await {
+        Ok(update) => {
+            market_state.write().await.state_db.apply_geth_update(update);
+            debug!(borrowers = borrowers.len(), "Borrowers state loaded");
+            unloaded_users.clear();
+        }
+        Err(e) => warn!(borrowers = borrowers.len(), "Borrowers state fetch error : {}", e),
+    }
+}
+
+pub async fn liquidation_searcher_worker<P, T, N>(
+    client: P,
+    strategy: LiquidationStrategy,
+    market_state: SharedState<MarketState>,
+    block_history: SharedState<BlockHistory>,
+    market_events_rx: Broadcaster<MarketEvents>,
+) -> WorkerResult
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    subscribe!(market_events_rx);
+
+    let config = strategy.state.read().map_err(|_| eyre!("LIQUIDATION_STATE_LOCK_POISONED"))?.config.clone();
+
+    let mut retry_delay = PRELOAD_RETRY_DELAY;
+    let (reserves, backfilled_positions) = loop {
+        match preload_aave_state(client.clone(), &config, market_state.clone()).await {
+            Ok(preloaded) => break preloaded,
+            Err(e) => {
+                error!(retry_in = ?retry_delay, "preload_aave_state : {}", e);
+                tokio::time::sleep(retry_delay).await;
+                retry_delay = (retry_delay * 2).min(PRELOAD_MAX_RETRY_DELAY);
+            }
+        }
+    };
+
+    if let Ok(mut state) = strategy.state.write() {
+        let mut watched = config.watched.clone();
+        watched.extend(reserves.iter().map(|reserve| reserve.price_aggregator));
+        state.config = config.with_watched(watched);
+        state.reserves = reserves.into_iter().map(|reserve| reserve.asset).collect();
+        state.positions = backfilled_positions;
+    }
+
+    // users with positions changed after the preload, their state is not in the market state yet
+    let mut unloaded_users: BTreeSet<Address> = BTreeSet::new();
+
+    loop {
+        let market_event_msg: Result<MarketEvents, RecvError> = market_events_rx.recv().await;
+        match market_event_msg {
+            Ok(MarketEvents::BlockLogsUpdate { block_hash, .. }) => {
+                let logs: Vec<Log> = match block_history.read().await.get_entry(&block_hash) {
+                    Some(block_entry) => block_entry
+                        .logs
+                        .iter()
+                        .flatten()
+                        .filter_map(|log_entry| Log::new(log_entry.address(), log_entry.topics().to_vec(), log_entry.data().data.clone()))
+                        .collect(),
+                    None => continue,
+                };
+                if let Ok(mut state) = strategy.state.write() {
+                    for log_entry in logs.iter() {
+                        if let Some(user) = state.positions.apply_log(log_entry) {
+                            unloaded_users.insert(user);
+                        }
+                    }
+                    debug!(positions = state.positions.len(), "Lending positions updated");
+                }
+                if !unloaded_users.is_empty() {
+                    load_borrowers_state(client.clone(), &config, &strategy, &market_state, &mut unloaded_users).await;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!("market_events_rx error : {e}")
+            }
+        }
+    }
+}
+
+/// Keeps reserves and positions of `LiquidationStrategy`, the strategy itself is hosted by `StrategyActor`.
+#[derive(Accessor, Consumer)]
+pub struct LiquidationSearcherActor<P, T, N> {
+    client: P,
+    strategy: LiquidationStrategy,
+    #[accessor]
+    market_state: Option<SharedState<MarketState>>,
+    #[accessor]
+    block_history: Option<SharedState<BlockHistory>>,
+    #[consumer]
+    market_events_rx: Option<Broadcaster<MarketEvents>>,
+    _t: PhantomData<T>,
+    _n: PhantomData<N>,
+}
+
+impl<P, T, N> LiquidationSearcherActor<P, T, N>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    pub fn new(client: P, config: LiquidationSearcherConfig) -> Self {
+        Self {
+            client,
+            strategy: LiquidationStrategy::new(config),
+            market_state: None,
+            block_history: None,
+            market_events_rx: None,
+            _t: PhantomData,
+            _n: PhantomData,
+        }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain) -> Self {
+        Self {
+            market_state: Some(bc.market_state()),
+            block_history: Some(bc.block_history()),
+            market_events_rx: Some(bc.market_events_channel()),
+            ..self
+        }
+    }
+
+    /// Strategy searching for liquidations of the positions kept by the actor
+    pub fn strategy(&self) -> LiquidationStrategy {
+        self.strategy.clone()
+    }
+}
+
+impl<P, T, N> Actor for LiquidationSearcherActor<P, T, N>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(liquidation_searcher_worker(
+            self.client.clone(),
+            self.strategy.clone(),
+            self.market_state.clone().unwrap(),
+            self.block_history.clone().unwrap(),
+            self.market_events_rx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "LiquidationSearcherActor"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_rpc_types_trace::geth::AccountState;
+    use std::collections::BTreeMap;
+
+    fn reserve(balance: u128, price: u64, decimals: u64) -> ReserveState {
+        ReserveState {
+            asset: Address::ZERO,
+            balance: U256::from(balance),
+            price: U256::from(price),
+            unit: U256::from(10).pow(U256::from(decimals)),
+            liquidation_bonus: U256::from(10500),
+        }
+    }
+
+    #[test]
+    fn test_calc_liquidation_amounts_half_debt() {
+        // 1000 USDC debt, 1 WETH collateral at 2000 USD
+        let debt = reserve(1_000_000_000, 100_000_000, 6);
+        let collateral = reserve(1_000_000_000_000_000_000, 200_000_000_000, 18);
+
+        let (debt_to_cover, collateral_amount) = calc_liquidation_amounts(U256::from(980_000_000_000_000_000u64), &debt, &collateral);
+        assert_eq!(debt_to_cover, U256::from(500_000_000));
+        assert_eq!(collateral_amount, U256::from(262_500_000_000_000_000u64));
+    }
+
+    #[test]
+    fn test_calc_liquidation_amounts_capped_by_collateral() {
+        // 1000 USDC debt, 0.5 WETH collateral at 2000 USD
+        let debt = reserve(1_000_000_000, 100_000_000, 6);
+        let collateral = reserve(500_000_000_000_000_000, 200_000_000_000, 18);
+
+        let (debt_to_cover, collateral_amount) = calc_liquidation_amounts(U256::from(900_000_000_000_000_000u64), &debt, &collateral);
+        assert_eq!(collateral_amount, U256::from(500_000_000_000_000_000u64));
+        assert_eq!(debt_to_cover, U256::from(952_380_952));
+    }
+
+    #[test]
+    fn test_is_affected() {
+        let config = LiquidationSearcherConfig::default().with_watched(vec![Address::repeat_byte(0x01)]);
+
+        let state_update: GethStateUpdateVec = vec![BTreeMap::from([(Address::repeat_byte(0x02), AccountState::default())])];
+        assert!(!config.is_affected(&state_update));
+
+        let state_update: GethStateUpdateVec = vec![BTreeMap::from([(Address::repeat_byte(0x01), AccountState::default())])];
+        assert!(config.is_affected(&state_update));
+
+        let state_update: GethStateUpdateVec = vec![BTreeMap::new(), BTreeMap::from([(AaveV3Address::POOL, AccountState::default())])];
+        assert!(config.is_affected(&state_update));
+
+        // prices are written to the aggregators, not the oracle
+        let state_update: GethStateUpdateVec = vec![BTreeMap::from([(AaveV3Address::ORACLE, AccountState::default())])];
+        assert!(!config.is_affected(&state_update));
+    }
+
+    #[test]
+    fn test_liquidation_strategy_is_applicable() {
+        let aggregator = Address::repeat_byte(0x01);
+        let state_update_event = |account: Address| {
+            StateUpdateEvent::new(
+                1,
+                0,
+                0,
+                LoomInMemoryDB::default(),
+                vec![BTreeMap::from([(account, AccountState::default())])],
+                None,
+                BTreeMap::new(),
+                vec![],
+                vec![],
+                "test".to_string(),
+                5000,
+            )
+        };
+
+        let strategy = LiquidationStrategy::new(LiquidationSearcherConfig::default().with_watched(vec![aggregator]));
+        // no positions loaded yet
+        assert!(!strategy.is_applicable(&state_update_event(aggregator)));
+
+        strategy.state.write().unwrap().positions.add_debt(Address::repeat_byte(0x02), Address::repeat_byte(0x03));
+        assert!(strategy.is_applicable(&state_update_event(aggregator)));
+        assert!(!strategy.is_applicable(&state_update_event(Address::repeat_byte(0x04))));
+    }
+
+    #[test]
+    fn test_candidate_assets() {
+        let (weth, usdc, dai) = (Address::repeat_byte(0x01), Address::repeat_byte(0x02), Address::repeat_byte(0x03));
+        let position = LendingPosition { collateral: BTreeSet::new(), debt: BTreeSet::from([usdc]) };
+
+        let candidates = candidate_assets(&position, &[weth, dai]);
+        assert_eq!(candidates.collateral, BTreeSet::from([weth, dai]));
+        assert_eq!(candidates.debt, BTreeSet::from([weth, usdc, dai]));
+    }
+}
@@ -0,0 +1,7 @@
+pub use aave_state::AaveReserve;
+pub use lending_positions::{LendingPosition, LendingPositions};
+pub use liquidation_searcher::{LiquidationSearcherActor, LiquidationSearcherConfig, LiquidationStrategy};
+
+mod aave_state;
+mod lending_positions;
+mod liquidation_searcher;
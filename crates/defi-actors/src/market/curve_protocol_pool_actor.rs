@@ -6,36 +6,37 @@ use alloy_provider::Provider;
 use alloy_transport::Transport;
 use tracing::{debug, error};
 
-use crate::market::pool_loader::fetch_state_and_add_pool;
+use crate::market::pool_loader::fetch_state_and_add_pools;
 use debug_provider::DebugProviderExt;
 use defi_blockchain::Blockchain;
+use defi_entities::required_state::RequiredStateReaderConfig;
 use defi_entities::{Market, MarketState, PoolWrapper};
 use defi_pools::protocols::CurveProtocol;
 use defi_pools::CurvePool;
 use loom_actors::{Accessor, Actor, ActorResult, SharedState, WorkerResult};
 use loom_actors_macros::{Accessor, Consumer};
 
-async fn curve_pool_loader_worker<P, T, N>(client: P, market: SharedState<Market>, market_state: SharedState<MarketState>) -> WorkerResult
+async fn curve_pool_loader_worker<P, T, N>(
+    client: P,
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState>,
+    state_reader_config: RequiredStateReaderConfig,
+) -> WorkerResult
 where
     T: Transport + Clone,
     N: Network,
     P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
 {
+    let mut curve_pools: Vec<PoolWrapper> = Vec::new();
     let curve_contracts = CurveProtocol::get_contracts_vec(client.clone());
     for curve_contract in curve_contracts.into_iter() {
         if let Ok(curve_pool) = CurvePool::fetch_pool_data(client.clone(), curve_contract).await {
-            let pool_wrapped = PoolWrapper::new(Arc::new(curve_pool));
-            match fetch_state_and_add_pool(client.clone(), market.clone(), market_state.clone(), pool_wrapped.clone()).await {
-                Err(e) => {
-                    error!("Curve pool loading error : {}", e)
-                }
-                Ok(_) => {
-                    debug!("Curve pool loaded {:#20x}", pool_wrapped.get_address());
-                }
-            }
+            curve_pools.push(PoolWrapper::new(Arc::new(curve_pool)));
         }
     }
+    add_curve_pools(client.clone(), market.clone(), market_state.clone(), curve_pools, &state_reader_config).await;
 
+    let mut curve_pools: Vec<PoolWrapper> = Vec::new();
     for factory_idx in 0..10 {
         if let Ok(factory_address) = CurveProtocol::get_factory_address(client.clone(), factory_idx).await {
             if let Ok(pool_count) = CurveProtocol::get_pool_count(client.clone(), factory_address).await {
@@ -48,23 +49,7 @@ where
                         match CurveProtocol::get_contract_from_code(client.clone(), addr).await {
                             Ok(curve_contract) => {
                                 if let Ok(curve_pool) = CurvePool::fetch_pool_data(client.clone(), curve_contract).await {
-                                    let pool_wrapped = PoolWrapper::new(Arc::new(curve_pool));
-
-                                    match fetch_state_and_add_pool(
-                                        client.clone(),
-                                        market.clone(),
-                                        market_state.clone(),
-                                        pool_wrapped.clone(),
-                                    )
-                                    .await
-                                    {
-                                        Err(e) => {
-                                            error!("Curve pool loading error {:?} : {}", pool_wrapped.get_address(), e);
-                                        }
-                                        Ok(_) => {
-                                            debug!("Curve pool loaded {:#20x}", pool_wrapped.get_address());
-                                        }
-                                    }
+                                    curve_pools.push(PoolWrapper::new(Arc::new(curve_pool)));
                                 }
                             }
                             Err(e) => {
@@ -76,10 +61,37 @@ where
             }
         }
     }
+    add_curve_pools(client, market, market_state, curve_pools, &state_reader_config).await;
 
     Ok("curve_protocol_loader_worker".to_string())
 }
 
+/// Fetch the required state of the curve pools at once and add them to the market
+async fn add_curve_pools<P, T, N>(
+    client: P,
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState>,
+    curve_pools: Vec<PoolWrapper>,
+    state_reader_config: &RequiredStateReaderConfig,
+) where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    let pool_addresses: Vec<_> = curve_pools.iter().map(|pool| pool.get_address()).collect();
+    let results = fetch_state_and_add_pools(client, market, market_state, curve_pools, state_reader_config).await;
+    for (pool_address, result) in pool_addresses.into_iter().zip(results) {
+        match result {
+            Err(e) => {
+                error!("Curve pool loading error {:?} : {}", pool_address, e);
+            }
+            Ok(_) => {
+                debug!("Curve pool loaded {:#20x}", pool_address);
+            }
+        }
+    }
+}
+
 #[derive(Accessor, Consumer)]
 pub struct CurvePoolLoaderOneShotActor<P, T, N> {
     client: P,
@@ -87,6 +99,7 @@ pub struct CurvePoolLoaderOneShotActor<P, T, N> {
     market: Option<SharedState<Market>>,
     #[accessor]
     market_state: Option<SharedState<MarketState>>,
+    state_reader_config: RequiredStateReaderConfig,
     _t: PhantomData<T>,
     _n: PhantomData<N>,
 }
@@ -98,7 +111,19 @@ where
     P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
 {
     pub fn new(client: P) -> Self {
-        Self { client, market: None, market_state: None, _n: PhantomData, _t: PhantomData }
+        Self {
+            client,
+            market: None,
+            market_state: None,
+            state_reader_config: RequiredStateReaderConfig::default(),
+            _n: PhantomData,
+            _t: PhantomData,
+        }
+    }
+
+    /// Batching and concurrency of the required state fetched for the curve pools
+    pub fn with_state_reader_config(self, state_reader_config: RequiredStateReaderConfig) -> Self {
+        Self { state_reader_config, ..self }
     }
 
     pub fn on_bc(self, bc: &Blockchain) -> Self {
@@ -117,6 +142,7 @@ where
             self.client.clone(),
             self.market.clone().unwrap(),
             self.market_state.clone().unwrap(),
+            self.state_reader_config.clone(),
         ));
 
         Ok(vec![task])
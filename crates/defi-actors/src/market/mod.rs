@@ -1,7 +1,10 @@
 pub use curve_protocol_pool_actor::CurvePoolLoaderOneShotActor;
 pub use history_pool_actor::HistoryPoolLoaderOneShotActor;
 pub use new_pool_actor::NewPoolLoaderActor;
-pub use pool_loader::{fetch_and_add_pool_by_address, fetch_state_and_add_pool, PoolLoaderActor};
+pub use pool_loader::{
+    fetch_and_add_pool_by_address, fetch_and_add_pools, fetch_pool_data_by_address, fetch_state_and_add_pool, fetch_state_and_add_pools,
+    PoolLoaderActor,
+};
 pub use required_pools_actor::RequiredPoolLoaderActor;
 
 mod curve_protocol_pool_actor;
@@ -7,13 +7,13 @@ use alloy_primitives::Address;
 use alloy_provider::Provider;
 use alloy_transport::Transport;
 use eyre::{eyre, Result};
-use futures::stream::FuturesUnordered;
+use futures::stream::{self, FuturesUnordered};
 use futures::StreamExt;
 use tracing::{debug, error};
 
 use debug_provider::DebugProviderExt;
 use defi_blockchain::Blockchain;
-use defi_entities::required_state::RequiredStateReader;
+use defi_entities::required_state::{RequiredState, RequiredStateReader, RequiredStateReaderConfig};
 use defi_entities::{get_protocol_by_factory, Market, MarketState, PoolClass, PoolProtocol, PoolWrapper};
 use defi_events::Task;
 use defi_pools::protocols::{fetch_uni2_factory, fetch_uni3_factory, CurveProtocol};
@@ -27,6 +27,7 @@ pub async fn pool_loader_worker<P, T, N>(
     market: SharedState<Market>,
     market_state: SharedState<MarketState>,
     tasks_rx: Broadcaster<Task>,
+    state_reader_config: RequiredStateReaderConfig,
 ) -> WorkerResult
 where
     T: Transport + Clone,
@@ -38,43 +39,32 @@ where
 
     subscribe!(tasks_rx);
     loop {
-        if let Ok(task) = tasks_rx.recv().await {
-            let pools = match task {
-                Task::FetchAndAddPools(pools) => pools,
-                _ => continue,
-            };
-
-            for (pool_address, pool_class) in pools {
-                // Check if pool already exists
-                if processed_pools.insert(pool_address, true).is_some() {
+        tokio::select! {
+            task = tasks_rx.recv() => {
+                let pools = match task {
+                    Ok(Task::FetchAndAddPools(pools)) => pools,
+                    _ => continue,
+                };
+                // Skip pools already processed
+                let pools: Vec<(Address, PoolClass)> =
+                    pools.into_iter().filter(|(pool_address, _)| processed_pools.insert(*pool_address, true).is_none()).collect();
+                if pools.is_empty() {
                     continue;
                 }
-                // Fetch and add pool
-                fetch_tasks.push(fetch_and_add_pool_by_address(
-                    client.clone(),
-                    market.clone(),
-                    market_state.clone(),
-                    pool_address,
-                    pool_class,
-                ));
 
                 // Limit the number of concurrent fetch tasks
-                if fetch_tasks.len() > 20 {
+                if fetch_tasks.len() >= state_reader_config.concurrency.max(1) {
                     fetch_tasks.next().await;
                 }
+                fetch_tasks.push(fetch_and_add_pools(client.clone(), market.clone(), market_state.clone(), pools, state_reader_config.clone()));
             }
+            Some(_) = fetch_tasks.next(), if !fetch_tasks.is_empty() => {}
         }
     }
 }
 
-/// Fetch pool data, add it to the market and fetch the required state
-pub async fn fetch_and_add_pool_by_address<P, T, N>(
-    client: P,
-    market: SharedState<Market>,
-    market_state: SharedState<MarketState>,
-    pool_address: Address,
-    pool_class: PoolClass,
-) -> Result<()>
+/// Fetch pool data of the pool class at the address
+pub async fn fetch_pool_data_by_address<P, T, N>(client: P, pool_address: Address, pool_class: PoolClass) -> Result<PoolWrapper>
 where
     N: Network,
     T: Transport + Clone,
@@ -85,121 +75,182 @@ where
     match pool_class {
         PoolClass::UniswapV2 => {
             let factory_address = fetch_uni2_factory(client.clone(), pool_address).await?;
-            let fetch_result = match get_protocol_by_factory(factory_address) {
+            match get_protocol_by_factory(factory_address) {
                 PoolProtocol::NomiswapStable | PoolProtocol::Miniswap | PoolProtocol::Integral | PoolProtocol::Safeswap => {
                     Err(eyre!("POOL_PROTOCOL_NOT_SUPPORTED"))
                 }
-
-                _ => {
-                    let pool = UniswapV2Pool::fetch_pool_data(client.clone(), pool_address).await?;
-                    fetch_state_and_add_pool(client.clone(), market.clone(), market_state.clone(), PoolWrapper::new(Arc::new(pool))).await
-                }
-            };
-
-            if let Err(e) = fetch_result {
-                error!("fetch_and_add_pool uni2 error {:#20x} : {}", pool_address, e)
+                _ => Ok(PoolWrapper::new(Arc::new(UniswapV2Pool::fetch_pool_data(client.clone(), pool_address).await?))),
             }
         }
         PoolClass::UniswapV3 => {
-            let factory_address_result = fetch_uni3_factory(client.clone(), pool_address).await;
-            match factory_address_result {
-                Ok(factory_address) => {
-                    let pool_wrapped = match get_protocol_by_factory(factory_address) {
-                        PoolProtocol::PancakeV3 => {
-                            PoolWrapper::new(Arc::new(PancakeV3Pool::fetch_pool_data(client.clone(), pool_address).await?))
-                        }
-                        PoolProtocol::Maverick => {
-                            PoolWrapper::new(Arc::new(MaverickPool::fetch_pool_data(client.clone(), pool_address).await?))
-                        }
-                        _ => PoolWrapper::new(Arc::new(UniswapV3Pool::fetch_pool_data(client.clone(), pool_address).await?)),
-                    };
-
-                    if let Err(e) = fetch_state_and_add_pool(client, market, market_state, pool_wrapped).await {
-                        error!("fetch_and_add_pool uni3 error {:#20x} : {}", pool_address, e)
-                    }
-                }
+            let factory_address = match fetch_uni3_factory(client.clone(), pool_address).await {
+                Ok(factory_address) => factory_address,
                 Err(e) => {
                     error!("Error fetching factory address at {:#20x}: {}", pool_address, e);
                     return Err(eyre!("CANNOT_GET_FACTORY_ADDRESS"));
                 }
-            }
+            };
+            let pool_wrapped = match get_protocol_by_factory(factory_address) {
+                PoolProtocol::PancakeV3 => PoolWrapper::new(Arc::new(PancakeV3Pool::fetch_pool_data(client.clone(), pool_address).await?)),
+                PoolProtocol::Maverick => PoolWrapper::new(Arc::new(MaverickPool::fetch_pool_data(client.clone(), pool_address).await?)),
+                _ => PoolWrapper::new(Arc::new(UniswapV3Pool::fetch_pool_data(client.clone(), pool_address).await?)),
+            };
+            Ok(pool_wrapped)
         }
         PoolClass::Curve => match CurveProtocol::get_contract_from_code(client.clone(), pool_address).await {
-            Ok(curve_contract) => {
-                let curve_pool = CurvePool::fetch_pool_data(client.clone(), curve_contract).await?;
-                let pool_wrapped = PoolWrapper::new(Arc::new(curve_pool));
-
-                match fetch_state_and_add_pool(client.clone(), market.clone(), market_state.clone(), pool_wrapped.clone()).await {
-                    Err(e) => {
-                        error!("Curve pool loading error {:?} : {}", pool_wrapped.get_address(), e);
-                    }
-                    Ok(_) => {
-                        debug!("Curve pool loaded {:#20x}", pool_wrapped.get_address());
-                    }
-                }
-            }
+            Ok(curve_contract) => Ok(PoolWrapper::new(Arc::new(CurvePool::fetch_pool_data(client.clone(), curve_contract).await?))),
             Err(e) => {
-                error!("Error getting curve contract from code {} : {} ", pool_address, e)
+                error!("Error getting curve contract from code {} : {} ", pool_address, e);
+                Err(e)
             }
         },
         _ => {
             error!("Error pool not supported at {:#20x}", pool_address);
-            return Err(eyre!("POOL_CLASS_NOT_SUPPORTED"));
+            Err(eyre!("POOL_CLASS_NOT_SUPPORTED"))
         }
     }
-    Ok(())
 }
 
-pub async fn fetch_state_and_add_pool<P, T, N>(
+/// Fetch pool data, add it to the market and fetch the required state
+pub async fn fetch_and_add_pool_by_address<P, T, N>(
     client: P,
     market: SharedState<Market>,
     market_state: SharedState<MarketState>,
-    pool_wrapped: PoolWrapper,
+    pool_address: Address,
+    pool_class: PoolClass,
 ) -> Result<()>
 where
+    N: Network,
     T: Transport + Clone,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    let pool_wrapped = fetch_pool_data_by_address(client.clone(), pool_address, pool_class).await?;
+    if let Err(e) = fetch_state_and_add_pool(client, market, market_state, pool_wrapped).await {
+        error!("fetch_and_add_pool error {:#20x} : {}", pool_address, e)
+    }
+    Ok(())
+}
+
+/// Fetch pool data of the pools and add them to the market. Required states of all pools are fetched at once, failed pools are
+/// skipped.
+pub async fn fetch_and_add_pools<P, T, N>(
+    client: P,
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState>,
+    pools: Vec<(Address, PoolClass)>,
+    state_reader_config: RequiredStateReaderConfig,
+) -> Result<()>
+where
     N: Network,
+    T: Transport + Clone,
     P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
 {
-    match pool_wrapped.get_state_required() {
-        Ok(required_state) => match RequiredStateReader::fetch_calls_and_slots(client, required_state, None).await {
-            Ok(state) => {
-                let pool_address = pool_wrapped.get_address();
-                {
-                    let mut market_state_write_guard = market_state.write().await;
-                    market_state_write_guard.add_state(&state);
-                    market_state_write_guard.add_force_insert(pool_address);
-                    market_state_write_guard.disable_cell_vec(pool_address, pool_wrapped.get_read_only_cell_vec());
-
-                    drop(market_state_write_guard);
+    let pools_wrapped: Vec<PoolWrapper> = stream::iter(pools.into_iter().map(|(pool_address, pool_class)| {
+        let client = client.clone();
+        async move {
+            match fetch_pool_data_by_address(client, pool_address, pool_class).await {
+                Ok(pool_wrapped) => Some(pool_wrapped),
+                Err(e) => {
+                    error!("fetch_pool_data error {:#20x} : {}", pool_address, e);
+                    None
                 }
+            }
+        }
+    }))
+    .buffer_unordered(state_reader_config.concurrency.max(1))
+    .filter_map(|pool_wrapped| async move { pool_wrapped })
+    .collect()
+    .await;
 
-                let directions_vec = pool_wrapped.get_swap_directions();
-                let mut directions_tree: BTreeMap<PoolWrapper, Vec<(Address, Address)>> = BTreeMap::new();
-
-                directions_tree.insert(pool_wrapped.clone(), directions_vec);
-
-                let mut market_write_guard = market.write().await;
-                // Ignore error if pool already exists because it was maybe already added by e.g. db pool loader
-                let _ = market_write_guard.add_pool(pool_wrapped);
+    fetch_state_and_add_pools(client, market, market_state, pools_wrapped, &state_reader_config).await;
+    Ok(())
+}
 
-                let swap_paths = market_write_guard.build_swap_path_vec(&directions_tree)?;
-                market_write_guard.add_paths(swap_paths);
+/// Fetch the required states of the pools at once and add the pools to the market. Returns the result for every pool.
+pub async fn fetch_state_and_add_pools<P, T, N>(
+    client: P,
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState>,
+    pools_wrapped: Vec<PoolWrapper>,
+    state_reader_config: &RequiredStateReaderConfig,
+) -> Vec<Result<()>>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    let mut results: Vec<Result<()>> = Vec::with_capacity(pools_wrapped.len());
+    let mut required_pools: Vec<(usize, PoolWrapper)> = Vec::new();
+    let mut required_states: Vec<RequiredState> = Vec::new();
 
-                drop(market_write_guard)
+    for pool_wrapped in pools_wrapped.into_iter() {
+        match pool_wrapped.get_state_required() {
+            Ok(required_state) => {
+                required_pools.push((results.len(), pool_wrapped));
+                required_states.push(required_state);
+                results.push(Ok(()));
             }
             Err(e) => {
-                error!("{}", e);
-                return Err(e);
+                error!("get_state_required error {:#20x} : {}", pool_wrapped.get_address(), e);
+                results.push(Err(e));
             }
-        },
-        Err(e) => {
-            error!("{}", e);
-            return Err(e);
         }
     }
 
-    Ok(())
+    let states = RequiredStateReader::fetch_many(client, required_states, None, state_reader_config).await;
+
+    let mut fetched_pools: Vec<(usize, PoolWrapper)> = Vec::new();
+    {
+        let mut market_state_guard = market_state.write().await;
+        for ((idx, pool_wrapped), state) in required_pools.into_iter().zip(states) {
+            match state {
+                Ok(state) => {
+                    let pool_address = pool_wrapped.get_address();
+                    market_state_guard.add_state(&state);
+                    market_state_guard.add_force_insert(pool_address);
+                    market_state_guard.disable_cell_vec(pool_address, pool_wrapped.get_read_only_cell_vec());
+                    fetched_pools.push((idx, pool_wrapped));
+                }
+                Err(e) => {
+                    error!("Required state fetch error {:#20x} : {}", pool_wrapped.get_address(), e);
+                    results[idx] = Err(e);
+                }
+            }
+        }
+    }
+
+    let mut market_guard = market.write().await;
+    for (idx, pool_wrapped) in fetched_pools.into_iter() {
+        let directions_tree: BTreeMap<PoolWrapper, Vec<(Address, Address)>> =
+            BTreeMap::from([(pool_wrapped.clone(), pool_wrapped.get_swap_directions())]);
+
+        // Ignore error if pool already exists because it was maybe already added by e.g. db pool loader
+        let _ = market_guard.add_pool(pool_wrapped);
+
+        match market_guard.build_swap_path_vec(&directions_tree) {
+            Ok(swap_paths) => market_guard.add_paths(swap_paths),
+            Err(e) => results[idx] = Err(e),
+        }
+    }
+
+    results
+}
+
+pub async fn fetch_state_and_add_pool<P, T, N>(
+    client: P,
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState>,
+    pool_wrapped: PoolWrapper,
+) -> Result<()>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    fetch_state_and_add_pools(client, market, market_state, vec![pool_wrapped], &RequiredStateReaderConfig::default())
+        .await
+        .pop()
+        .unwrap_or_else(|| Err(eyre!("NO_POOL")))
 }
 
 #[derive(Accessor, Consumer)]
@@ -211,6 +262,7 @@ pub struct PoolLoaderActor<P, T, N> {
     market_state: Option<SharedState<MarketState>>,
     #[consumer]
     tasks_rx: Option<Broadcaster<Task>>,
+    state_reader_config: RequiredStateReaderConfig,
     _t: PhantomData<T>,
     _n: PhantomData<N>,
 }
@@ -222,7 +274,20 @@ where
     P: Provider<T, N> + Send + Sync + Clone + 'static,
 {
     pub fn new(client: P) -> Self {
-        Self { client, market: None, market_state: None, tasks_rx: None, _t: PhantomData, _n: PhantomData }
+        Self {
+            client,
+            market: None,
+            market_state: None,
+            tasks_rx: None,
+            state_reader_config: RequiredStateReaderConfig::default(),
+            _t: PhantomData,
+            _n: PhantomData,
+        }
+    }
+
+    /// Batching and concurrency of the required state fetched for the loaded pools
+    pub fn with_state_reader_config(self, state_reader_config: RequiredStateReaderConfig) -> Self {
+        Self { state_reader_config, ..self }
     }
 
     pub fn on_bc(self, bc: &Blockchain) -> Self {
@@ -242,6 +307,7 @@ where
             self.market.clone().unwrap(),
             self.market_state.clone().unwrap(),
             self.tasks_rx.clone().unwrap(),
+            self.state_reader_config.clone(),
         ));
         Ok(vec![task])
     }
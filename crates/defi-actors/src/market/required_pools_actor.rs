@@ -4,15 +4,13 @@ use alloy_network::Network;
 use alloy_primitives::Address;
 use alloy_provider::Provider;
 use alloy_transport::Transport;
-use tracing::{debug, error};
+use tracing::debug;
 
-use crate::market::pool_loader::{fetch_and_add_pool_by_address, fetch_state_and_add_pool};
+use crate::market::pool_loader::fetch_and_add_pools;
 use debug_provider::DebugProviderExt;
 use defi_blockchain::Blockchain;
-use defi_entities::required_state::{RequiredState, RequiredStateReader};
+use defi_entities::required_state::{RequiredState, RequiredStateReader, RequiredStateReaderConfig};
 use defi_entities::{Market, MarketState, PoolClass};
-use defi_pools::protocols::CurveProtocol;
-use defi_pools::CurvePool;
 use loom_actors::{Accessor, Actor, ActorResult, SharedState, WorkerResult};
 use loom_actors_macros::{Accessor, Consumer};
 
@@ -22,37 +20,20 @@ async fn required_pools_loader_worker<P, T, N>(
     required_state: Option<RequiredState>,
     market: SharedState<Market>,
     market_state: SharedState<MarketState>,
+    state_reader_config: RequiredStateReaderConfig,
 ) -> WorkerResult
 where
     T: Transport + Clone,
     N: Network,
     P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
 {
-    for (pool_address, pool_class) in pools {
-        match pool_class {
-            PoolClass::UniswapV2 | PoolClass::UniswapV3 => {
-                debug!("Loading uniswap pool");
-                fetch_and_add_pool_by_address(client.clone(), market.clone(), market_state.clone(), pool_address, pool_class).await?;
-                debug!("Loaded uniswap pool ");
-            }
-            PoolClass::Curve => {
-                debug!("Loading curve pool");
-                if let Ok(curve_contract) = CurveProtocol::get_contract_from_code(client.clone(), pool_address).await {
-                    let curve_pool = CurvePool::fetch_pool_data(client.clone(), curve_contract).await?;
-                    fetch_state_and_add_pool(client.clone(), market.clone(), market_state.clone(), curve_pool.into()).await?
-                } else {
-                    error!("CURVE_POOL_NOT_LOADED");
-                }
-                debug!("Loaded curve pool");
-            }
-            _ => {
-                error!("Unknown pool class")
-            }
-        }
-    }
+    debug!(pools = pools.len(), "Loading required pools");
+    fetch_and_add_pools(client.clone(), market.clone(), market_state.clone(), pools, state_reader_config.clone()).await?;
+    debug!("Loaded required pools");
 
     if let Some(required_state) = required_state {
-        let update = RequiredStateReader::fetch_calls_and_slots(client.clone(), required_state, None).await?;
+        let update =
+            RequiredStateReader::fetch_calls_and_slots_with_config(client.clone(), required_state, None, &state_reader_config).await?;
         market_state.write().await.state_db.apply_geth_update(update);
     }
 
@@ -64,6 +45,7 @@ pub struct RequiredPoolLoaderActor<P, T, N> {
     client: P,
     pools: Vec<(Address, PoolClass)>,
     required_state: Option<RequiredState>,
+    state_reader_config: RequiredStateReaderConfig,
     #[accessor]
     market: Option<SharedState<Market>>,
     #[accessor]
@@ -79,7 +61,16 @@ where
     P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
 {
     pub fn new(client: P) -> Self {
-        Self { client, pools: Vec::new(), required_state: None, market: None, market_state: None, _n: PhantomData, _t: PhantomData }
+        Self {
+            client,
+            pools: Vec::new(),
+            required_state: None,
+            state_reader_config: RequiredStateReaderConfig::default(),
+            market: None,
+            market_state: None,
+            _n: PhantomData,
+            _t: PhantomData,
+        }
     }
 
     pub fn with_pool(self, address: Address, pool_class: PoolClass) -> Self {
@@ -95,6 +86,11 @@ where
     pub fn with_required_state(self, required_state: RequiredState) -> Self {
         Self { required_state: Some(required_state), ..self }
     }
+
+    /// Batching and concurrency of the required state fetched for the pools
+    pub fn with_state_reader_config(self, state_reader_config: RequiredStateReaderConfig) -> Self {
+        Self { state_reader_config, ..self }
+    }
 }
 
 impl<P, T, N> Actor for RequiredPoolLoaderActor<P, T, N>
@@ -110,6 +106,7 @@ where
             self.required_state.clone(),
             self.market.clone().unwrap(),
             self.market_state.clone().unwrap(),
+            self.state_reader_config.clone(),
         ));
 
         Ok(vec![task])
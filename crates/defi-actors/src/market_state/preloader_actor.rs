@@ -60,6 +60,7 @@ async fn set_monitor_nonce(account_nonce_balance_state: Option<SharedState<Accou
 pub async fn preload_market_state<P, T, N>(
     client: P,
     copied_accounts_vec: Vec<Address>,
+    copied_storage_vec: Vec<(Address, U256)>,
     new_accounts_vec: Vec<(Address, u64, U256, Option<Bytes>)>,
     token_balances_vec: Vec<(Address, Address, U256)>,
     market_state: SharedState<MarketState>,
@@ -92,6 +93,12 @@ where
         state.insert(address, acc_state);
     }
 
+    for (address, slot) in copied_storage_vec {
+        let value = client.get_storage_at(address, slot).block_id(BlockId::Number(BlockNumberOrTag::Latest)).await?;
+        debug!("Loaded storage : {address} {slot} {value}");
+        state.entry(address).or_default().storage.insert(slot.into(), value.into());
+    }
+
     for (address, nonce, balance, code) in new_accounts_vec {
         debug!("new_accounts added {} {} {}", address, nonce, balance);
         set_monitor_token_balance(account_nonce_balance_state.clone(), address, NWETH::NATIVE_ADDRESS, balance).await;
@@ -134,6 +141,7 @@ pub struct MarketStatePreloadedOneShotActor<P, T, N> {
     name: &'static str,
     client: P,
     copied_accounts: Vec<Address>,
+    copied_storage: Vec<(Address, U256)>,
     new_accounts: Vec<(Address, u64, U256, Option<Bytes>)>,
     token_balances: Vec<(Address, Address, U256)>,
     #[accessor]
@@ -160,6 +168,7 @@ where
             name: "MarketStatePreloadedOneShotActor",
             client,
             copied_accounts: Vec::new(),
+            copied_storage: Vec::new(),
             new_accounts: Vec::new(),
             token_balances: Vec::new(),
             market_state: None,
@@ -203,6 +212,13 @@ where
         Self { copied_accounts, ..self }
     }
 
+    /// Storage slots are copied from the latest block.
+    pub fn with_copied_storage(self, slots: Vec<(Address, U256)>) -> Self {
+        let mut copied_storage = self.copied_storage;
+        copied_storage.extend(slots);
+        Self { copied_storage, ..self }
+    }
+
     pub fn with_new_account(self, address: Address, nonce: u64, balance: U256, code: Option<Bytes>) -> Self {
         let mut new_accounts = self.new_accounts;
         new_accounts.push((address, nonce, balance, code));
@@ -227,6 +243,7 @@ where
         let handler = rt.spawn(preload_market_state(
             self.client.clone(),
             self.copied_accounts.clone(),
+            self.copied_storage.clone(),
             self.new_accounts.clone(),
             self.token_balances.clone(),
             self.market_state.clone().unwrap(),
@@ -0,0 +1,635 @@
+use std::collections::BTreeMap;
+
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_sol_types::{SolInterface, SolValue};
+
+use defi_abi::oneinch::{IAggregationRouterV5, IAggregationRouterV6};
+use defi_abi::uniswap2::IUniswapV2Router;
+use defi_abi::uniswap_periphery::{IPeripheryPayments, ISelfPermit, ISwapRouter, ISwapRouter02, IUniversalRouter, UniversalRouterCommand};
+use defi_abi::zeroex::IZeroExProxy;
+use defi_address_book::PeripheryAddress;
+use defi_entities::{Market, PoolClass, PoolProtocol, PoolWrapper};
+use defi_pools::protocols::{SushiswapProtocol, UniswapV2Protocol, UniswapV3Protocol};
+use defi_types::MempoolTxImpact;
+
+const MAX_DECODE_DEPTH: usize = 3;
+const ADDRESS_LEN: usize = 20;
+// token address + 3 bytes fee
+const V3_PATH_HOP_LEN: usize = 23;
+
+/// Swap hop decoded from router calldata
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SwapHop {
+    /// Uniswap V2 like pool. Pool address is computed when the protocol is known
+    UniswapV2 { protocol: Option<PoolProtocol>, token_in: Address, token_out: Address },
+    /// Uniswap V3 like pool. Pool address is computed when the protocol is known
+    UniswapV3 { protocol: Option<PoolProtocol>, token_in: Address, token_out: Address, fee: u32 },
+    /// Pool address encoded directly in calldata
+    Pool(Address),
+}
+
+/// Hops decoded from calldata. Partial if some of the calls or commands could not be decoded
+#[derive(Clone, Debug, Default)]
+struct DecodedHops {
+    hops: Vec<SwapHop>,
+    partial: bool,
+}
+
+impl DecodedHops {
+    fn complete(hops: Vec<SwapHop>) -> Self {
+        Self { hops, partial: false }
+    }
+
+    fn extend(&mut self, other: DecodedHops) {
+        self.hops.extend(other.hops);
+        self.partial |= other.partial;
+    }
+}
+
+/// Transaction impact predicted from its calldata
+#[derive(Clone, Debug, Default)]
+pub enum PredictedImpact {
+    /// Calldata is not a known router swap, impact can only be found by tracing
+    #[default]
+    Unknown,
+    /// Router swap that does not go through any market pool
+    NoPools,
+    /// Market pools the router swap goes through
+    Pools(BTreeMap<PoolWrapper, Vec<(Address, Address)>>),
+}
+
+impl PredictedImpact {
+    pub fn is_affecting(&self) -> bool {
+        self.impact().is_affecting()
+    }
+
+    /// Impact kept on the mempool transaction
+    pub fn impact(&self) -> MempoolTxImpact {
+        match self {
+            PredictedImpact::Unknown => MempoolTxImpact::Unknown,
+            PredictedImpact::NoPools => MempoolTxImpact::NoPools,
+            PredictedImpact::Pools(_) => MempoolTxImpact::Pools,
+        }
+    }
+}
+
+fn v2_router_protocol(router: Address) -> Option<PoolProtocol> {
+    if router == PeripheryAddress::UNISWAP_V2_ROUTER {
+        Some(PoolProtocol::UniswapV2)
+    } else if router == PeripheryAddress::SUSHISWAP_V2_ROUTER {
+        Some(PoolProtocol::Sushiswap)
+    } else {
+        None
+    }
+}
+
+fn v2_path_hops(protocol: Option<PoolProtocol>, path: &[Address]) -> Vec<SwapHop> {
+    path.windows(2).map(|w| SwapHop::UniswapV2 { protocol, token_in: w[0], token_out: w[1] }).collect()
+}
+
+/// Decodes uniswap v3 path : token | fee | token | fee | token. Exact output paths are encoded from token out to token in
+fn v3_path_hops(protocol: Option<PoolProtocol>, path: &[u8], reversed: bool) -> Option<Vec<SwapHop>> {
+    if path.len() < ADDRESS_LEN + V3_PATH_HOP_LEN || (path.len() - ADDRESS_LEN) % V3_PATH_HOP_LEN != 0 {
+        return None;
+    }
+
+    let mut hops: Vec<SwapHop> = (0..(path.len() - ADDRESS_LEN) / V3_PATH_HOP_LEN)
+        .map(|i| {
+            let offset = i * V3_PATH_HOP_LEN;
+            let token_a = Address::from_slice(&path[offset..offset + ADDRESS_LEN]);
+            let fee = u32::from_be_bytes([0, path[offset + ADDRESS_LEN], path[offset + ADDRESS_LEN + 1], path[offset + ADDRESS_LEN + 2]]);
+            let token_b = Address::from_slice(&path[offset + V3_PATH_HOP_LEN..offset + V3_PATH_HOP_LEN + ADDRESS_LEN]);
+            let (token_in, token_out) = if reversed { (token_b, token_a) } else { (token_a, token_b) };
+            SwapHop::UniswapV3 { protocol, token_in, token_out, fee }
+        })
+        .collect();
+
+    if reversed {
+        hops.reverse();
+    }
+    Some(hops)
+}
+
+/// Pool address is stored in the lowest 160 bits, higher bits are flags
+fn packed_pool_hop(pool: U256) -> SwapHop {
+    SwapHop::Pool(Address::from_word(B256::from(pool)))
+}
+
+/// Router helper calls that do not swap : payments, sweeps and permits
+fn is_periphery_helper_call(call: &[u8]) -> bool {
+    IPeripheryPayments::IPeripheryPaymentsCalls::abi_decode(call, false).is_ok()
+        || ISelfPermit::ISelfPermitCalls::abi_decode(call, false).is_ok()
+}
+
+fn decode_calls(to: Option<Address>, calls: &[Bytes], depth: usize) -> Option<DecodedHops> {
+    let mut decoded: Option<DecodedHops> = None;
+    let mut partial = false;
+
+    for call in calls.iter() {
+        match decode_swap_hops_at_depth(to, call, depth + 1) {
+            Some(hops) => decoded.get_or_insert_with(DecodedHops::default).extend(hops),
+            None => partial |= !is_periphery_helper_call(call),
+        }
+    }
+
+    decoded.map(|mut decoded| {
+        decoded.partial |= partial;
+        decoded
+    })
+}
+
+fn decode_universal_router_commands(commands: &[u8], inputs: &[Bytes], depth: usize) -> Option<DecodedHops> {
+    if depth > MAX_DECODE_DEPTH {
+        return None;
+    }
+
+    let mut decoded: Option<DecodedHops> = None;
+    let mut partial = false;
+
+    for (command, input) in commands.iter().zip(inputs.iter()) {
+        let command_type = command & UniversalRouterCommand::COMMAND_TYPE_MASK;
+        let hops = match command_type {
+            UniversalRouterCommand::V3_SWAP_EXACT_IN | UniversalRouterCommand::V3_SWAP_EXACT_OUT => {
+                <(Address, U256, U256, Bytes, bool)>::abi_decode_params(input, false)
+                    .ok()
+                    .and_then(|(_recipient, _amount, _amount_limit, path, _payer_is_user)| {
+                        v3_path_hops(Some(PoolProtocol::UniswapV3), &path, command_type == UniversalRouterCommand::V3_SWAP_EXACT_OUT)
+                    })
+                    .map(DecodedHops::complete)
+            }
+            UniversalRouterCommand::V2_SWAP_EXACT_IN | UniversalRouterCommand::V2_SWAP_EXACT_OUT => {
+                <(Address, U256, U256, Vec<Address>, bool)>::abi_decode_params(input, false).ok().map(
+                    |(_recipient, _amount, _amount_limit, path, _payer_is_user)| {
+                        DecodedHops::complete(v2_path_hops(Some(PoolProtocol::UniswapV2), &path))
+                    },
+                )
+            }
+            UniversalRouterCommand::EXECUTE_SUB_PLAN => <(Bytes, Vec<Bytes>)>::abi_decode_params(input, false)
+                .ok()
+                .and_then(|(sub_commands, sub_inputs)| decode_universal_router_commands(&sub_commands, &sub_inputs, depth + 1)),
+            command_type if UniversalRouterCommand::is_non_swap(command_type) => continue,
+            // v4 swaps, nft and unknown commands
+            _ => None,
+        };
+
+        match hops {
+            Some(hops) => decoded.get_or_insert_with(DecodedHops::default).extend(hops),
+            None => partial = true,
+        }
+    }
+
+    decoded.map(|mut decoded| {
+        decoded.partial |= partial;
+        decoded
+    })
+}
+
+fn decode_swap_hops_at_depth(to: Option<Address>, input: &[u8], depth: usize) -> Option<DecodedHops> {
+    if depth > MAX_DECODE_DEPTH {
+        return None;
+    }
+
+    if let Ok(call) = IUniswapV2Router::IUniswapV2RouterCalls::abi_decode(input, false) {
+        let path = match call {
+            IUniswapV2Router::IUniswapV2RouterCalls::swapExactTokensForTokens(call) => call.path,
+            IUniswapV2Router::IUniswapV2RouterCalls::swapTokensForExactTokens(call) => call.path,
+            IUniswapV2Router::IUniswapV2RouterCalls::swapExactETHForTokens(call) => call.path,
+            IUniswapV2Router::IUniswapV2RouterCalls::swapTokensForExactETH(call) => call.path,
+            IUniswapV2Router::IUniswapV2RouterCalls::swapExactTokensForETH(call) => call.path,
+            IUniswapV2Router::IUniswapV2RouterCalls::swapETHForExactTokens(call) => call.path,
+            IUniswapV2Router::IUniswapV2RouterCalls::swapExactTokensForTokensSupportingFeeOnTransferTokens(call) => call.path,
+            IUniswapV2Router::IUniswapV2RouterCalls::swapExactETHForTokensSupportingFeeOnTransferTokens(call) => call.path,
+            IUniswapV2Router::IUniswapV2RouterCalls::swapExactTokensForETHSupportingFeeOnTransferTokens(call) => call.path,
+            _ => return None,
+        };
+        return Some(DecodedHops::complete(v2_path_hops(to.and_then(v2_router_protocol), &path)));
+    }
+
+    if let Ok(call) = ISwapRouter02::ISwapRouter02Calls::abi_decode(input, false) {
+        let protocol = Some(PoolProtocol::UniswapV3);
+        let hops = match call {
+            ISwapRouter02::ISwapRouter02Calls::exactInputSingle(call) => Some(vec![SwapHop::UniswapV3 {
+                protocol,
+                token_in: call.params.tokenIn,
+                token_out: call.params.tokenOut,
+                fee: call.params.fee.to(),
+            }]),
+            ISwapRouter02::ISwapRouter02Calls::exactOutputSingle(call) => Some(vec![SwapHop::UniswapV3 {
+                protocol,
+                token_in: call.params.tokenIn,
+                token_out: call.params.tokenOut,
+                fee: call.params.fee.to(),
+            }]),
+            ISwapRouter02::ISwapRouter02Calls::exactInput(call) => v3_path_hops(protocol, &call.params.path, false),
+            ISwapRouter02::ISwapRouter02Calls::exactOutput(call) => v3_path_hops(protocol, &call.params.path, true),
+            ISwapRouter02::ISwapRouter02Calls::swapExactTokensForTokens(call) => {
+                Some(v2_path_hops(Some(PoolProtocol::UniswapV2), &call.path))
+            }
+            ISwapRouter02::ISwapRouter02Calls::swapTokensForExactTokens(call) => {
+                Some(v2_path_hops(Some(PoolProtocol::UniswapV2), &call.path))
+            }
+            ISwapRouter02::ISwapRouter02Calls::multicall_0(call) => return decode_calls(to, &call.data, depth),
+            ISwapRouter02::ISwapRouter02Calls::multicall_1(call) => return decode_calls(to, &call.data, depth),
+            ISwapRouter02::ISwapRouter02Calls::multicall_2(call) => return decode_calls(to, &call.data, depth),
+        };
+        return hops.map(DecodedHops::complete);
+    }
+
+    if let Ok(call) = ISwapRouter::ISwapRouterCalls::abi_decode(input, false) {
+        let protocol = Some(PoolProtocol::UniswapV3);
+        let hops = match call {
+            ISwapRouter::ISwapRouterCalls::exactInputSingle(call) => Some(vec![SwapHop::UniswapV3 {
+                protocol,
+                token_in: call.params.tokenIn,
+                token_out: call.params.tokenOut,
+                fee: call.params.fee.to(),
+            }]),
+            ISwapRouter::ISwapRouterCalls::exactOutputSingle(call) => Some(vec![SwapHop::UniswapV3 {
+                protocol,
+                token_in: call.params.tokenIn,
+                token_out: call.params.tokenOut,
+                fee: call.params.fee.to(),
+            }]),
+            ISwapRouter::ISwapRouterCalls::exactInput(call) => v3_path_hops(protocol, &call.params.path, false),
+            ISwapRouter::ISwapRouterCalls::exactOutput(call) => v3_path_hops(protocol, &call.params.path, true),
+            ISwapRouter::ISwapRouterCalls::multicall(call) => return decode_calls(to, &call.data, depth),
+        };
+        return hops.map(DecodedHops::complete);
+    }
+
+    if let Ok(call) = IUniversalRouter::IUniversalRouterCalls::abi_decode(input, false) {
+        return match call {
+            IUniversalRouter::IUniversalRouterCalls::execute_0(call) => {
+                decode_universal_router_commands(&call.commands, &call.inputs, depth)
+            }
+            IUniversalRouter::IUniversalRouterCalls::execute_1(call) => {
+                decode_universal_router_commands(&call.commands, &call.inputs, depth)
+            }
+        };
+    }
+
+    if let Ok(call) = IAggregationRouterV5::IAggregationRouterV5Calls::abi_decode(input, false) {
+        let pools = match call {
+            IAggregationRouterV5::IAggregationRouterV5Calls::unoswap(call) => call.pools,
+            IAggregationRouterV5::IAggregationRouterV5Calls::unoswapTo(call) => call.pools,
+            IAggregationRouterV5::IAggregationRouterV5Calls::uniswapV3Swap(call) => call.pools,
+            IAggregationRouterV5::IAggregationRouterV5Calls::uniswapV3SwapTo(call) => call.pools,
+        };
+        return Some(DecodedHops::complete(pools.into_iter().map(packed_pool_hop).collect()));
+    }
+
+    if let Ok(call) = IAggregationRouterV6::IAggregationRouterV6Calls::abi_decode(input, false) {
+        let pools = match call {
+            IAggregationRouterV6::IAggregationRouterV6Calls::unoswap(call) => vec![call.dex],
+            IAggregationRouterV6::IAggregationRouterV6Calls::unoswap2(call) => vec![call.dex, call.dex2],
+            IAggregationRouterV6::IAggregationRouterV6Calls::unoswap3(call) => vec![call.dex, call.dex2, call.dex3],
+            IAggregationRouterV6::IAggregationRouterV6Calls::ethUnoswap(call) => vec![call.dex],
+            IAggregationRouterV6::IAggregationRouterV6Calls::ethUnoswap2(call) => vec![call.dex, call.dex2],
+            IAggregationRouterV6::IAggregationRouterV6Calls::ethUnoswap3(call) => vec![call.dex, call.dex2, call.dex3],
+        };
+        return Some(DecodedHops::complete(pools.into_iter().map(packed_pool_hop).collect()));
+    }
+
+    if let Ok(call) = IZeroExProxy::IZeroExProxyCalls::abi_decode(input, false) {
+        let protocol = Some(PoolProtocol::UniswapV3);
+        let hops = match call {
+            IZeroExProxy::IZeroExProxyCalls::sellToUniswap(call) => {
+                let protocol = if call.isSushi { PoolProtocol::Sushiswap } else { PoolProtocol::UniswapV2 };
+                Some(v2_path_hops(Some(protocol), &call.tokens))
+            }
+            IZeroExProxy::IZeroExProxyCalls::sellEthForTokenToUniswapV3(call) => v3_path_hops(protocol, &call.encodedPath, false),
+            IZeroExProxy::IZeroExProxyCalls::sellTokenForEthToUniswapV3(call) => v3_path_hops(protocol, &call.encodedPath, false),
+            IZeroExProxy::IZeroExProxyCalls::sellTokenForTokenToUniswapV3(call) => v3_path_hops(protocol, &call.encodedPath, false),
+        };
+        return hops.map(DecodedHops::complete);
+    }
+
+    None
+}
+
+/// Decodes swap hops from UniswapV2Router02, SwapRouter, SwapRouter02, UniversalRouter, 1inch and 0x calldata.
+/// Returns None if calldata is not a known router swap.
+pub fn decode_swap_hops(to: Option<Address>, input: &[u8]) -> Option<Vec<SwapHop>> {
+    decode_swap_hops_at_depth(to, input, 0).map(|decoded| decoded.hops)
+}
+
+fn token_token_pools(market: &Market, token_in: &Address, token_out: &Address, pool_class: PoolClass) -> Vec<Address> {
+    market
+        .get_token_token_pools_ptr(token_in, token_out)
+        .map(|pools| {
+            pools.iter().filter(|address| market.get_pool(address).map_or(false, |pool| pool.get_class() == pool_class)).cloned().collect()
+        })
+        .unwrap_or_default()
+}
+
+fn hop_pool_addresses(market: &Market, hop: &SwapHop) -> Vec<Address> {
+    match hop {
+        SwapHop::Pool(pool) => vec![*pool],
+        SwapHop::UniswapV2 { protocol: Some(PoolProtocol::UniswapV2), token_in, token_out } => {
+            vec![UniswapV2Protocol::get_pool_address_for_tokens(*token_in, *token_out)]
+        }
+        SwapHop::UniswapV2 { protocol: Some(PoolProtocol::Sushiswap), token_in, token_out } => {
+            vec![SushiswapProtocol::get_pool_address_for_tokens(*token_in, *token_out)]
+        }
+        SwapHop::UniswapV2 { token_in, token_out, .. } => token_token_pools(market, token_in, token_out, PoolClass::UniswapV2),
+        SwapHop::UniswapV3 { protocol: Some(PoolProtocol::UniswapV3), token_in, token_out, fee } => {
+            let address = UniswapV3Protocol::get_pool_address_for_tokens(*token_in, *token_out, *fee);
+            // v3 fork routers share the uniswap abi, their pools are looked up in the market
+            if market.get_pool(&address).is_some() {
+                vec![address]
+            } else {
+                token_token_pools(market, token_in, token_out, PoolClass::UniswapV3)
+            }
+        }
+        SwapHop::UniswapV3 { token_in, token_out, .. } => token_token_pools(market, token_in, token_out, PoolClass::UniswapV3),
+    }
+}
+
+/// Predicts market pools affected by the transaction from its calldata
+pub fn predict_tx_impact(market: &Market, to: Option<Address>, input: &[u8]) -> PredictedImpact {
+    let Some(decoded) = decode_swap_hops_at_depth(to, input, 0) else {
+        return PredictedImpact::Unknown;
+    };
+
+    let mut affected_pools: BTreeMap<PoolWrapper, Vec<(Address, Address)>> = BTreeMap::new();
+
+    for hop in decoded.hops.iter() {
+        for address in hop_pool_addresses(market, hop) {
+            if let Some(pool) = market.get_pool(&address) {
+                if !affected_pools.contains_key(pool) {
+                    affected_pools.insert(pool.clone(), pool.get_swap_directions());
+                }
+            }
+        }
+    }
+
+    if affected_pools.is_empty() {
+        // swaps that were not decoded may still go through market pools
+        if decoded.partial {
+            PredictedImpact::Unknown
+        } else {
+            PredictedImpact::NoPools
+        }
+    } else {
+        PredictedImpact::Pools(affected_pools)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::aliases::U24;
+    use alloy_primitives::U160;
+    use alloy_sol_types::SolCall;
+    use defi_address_book::{FactoryAddress, TokenAddress, UniswapV2PoolAddress, UniswapV3PoolAddress};
+    use defi_pools::{UniswapV2Pool, UniswapV3Pool};
+
+    fn v3_path(tokens: &[Address], fees: &[u32]) -> Bytes {
+        let mut path: Vec<u8> = Vec::new();
+        for (i, token) in tokens.iter().enumerate() {
+            path.extend_from_slice(token.as_slice());
+            if let Some(fee) = fees.get(i) {
+                path.extend_from_slice(&fee.to_be_bytes()[1..]);
+            }
+        }
+        path.into()
+    }
+
+    #[test]
+    fn test_v3_path_hops() {
+        let path = v3_path(&[TokenAddress::WETH, TokenAddress::USDC, TokenAddress::USDT], &[500, 100]);
+
+        let hops = v3_path_hops(None, &path, false).unwrap();
+        assert_eq!(
+            hops,
+            vec![
+                SwapHop::UniswapV3 { protocol: None, token_in: TokenAddress::WETH, token_out: TokenAddress::USDC, fee: 500 },
+                SwapHop::UniswapV3 { protocol: None, token_in: TokenAddress::USDC, token_out: TokenAddress::USDT, fee: 100 },
+            ]
+        );
+
+        let hops = v3_path_hops(None, &path, true).unwrap();
+        assert_eq!(
+            hops,
+            vec![
+                SwapHop::UniswapV3 { protocol: None, token_in: TokenAddress::USDT, token_out: TokenAddress::USDC, fee: 100 },
+                SwapHop::UniswapV3 { protocol: None, token_in: TokenAddress::USDC, token_out: TokenAddress::WETH, fee: 500 },
+            ]
+        );
+
+        assert!(v3_path_hops(None, &path[..40], false).is_none());
+    }
+
+    #[test]
+    fn test_decode_v2_router() {
+        let call = IUniswapV2Router::swapExactTokensForTokensCall {
+            amountIn: U256::from(1),
+            amountOutMin: U256::ZERO,
+            path: vec![TokenAddress::WETH, TokenAddress::USDT],
+            to: Address::ZERO,
+            deadline: U256::ZERO,
+        };
+
+        let hops = decode_swap_hops(Some(PeripheryAddress::UNISWAP_V2_ROUTER), &call.abi_encode()).unwrap();
+        assert_eq!(
+            hops,
+            vec![SwapHop::UniswapV2 {
+                protocol: Some(PoolProtocol::UniswapV2),
+                token_in: TokenAddress::WETH,
+                token_out: TokenAddress::USDT
+            }]
+        );
+        assert_eq!(hop_pool_addresses(&Market::default(), &hops[0]), vec![UniswapV2PoolAddress::WETH_USDT]);
+    }
+
+    #[test]
+    fn test_decode_swap_router02_multicall() {
+        let exact_input_single = ISwapRouter02::exactInputSingleCall {
+            params: ISwapRouter02::ExactInputSingleParams {
+                tokenIn: TokenAddress::USDC,
+                tokenOut: TokenAddress::WETH,
+                fee: U24::from(500),
+                recipient: Address::ZERO,
+                amountIn: U256::from(1),
+                amountOutMinimum: U256::ZERO,
+                sqrtPriceLimitX96: U160::ZERO,
+            },
+        };
+        let call = ISwapRouter02::multicall_1Call {
+            deadline: U256::ZERO,
+            data: vec![exact_input_single.abi_encode().into(), Bytes::from(vec![0x12, 0x34, 0x56, 0x78])],
+        };
+
+        let hops = decode_swap_hops(None, &call.abi_encode()).unwrap();
+        assert_eq!(
+            hops,
+            vec![SwapHop::UniswapV3 {
+                protocol: Some(PoolProtocol::UniswapV3),
+                token_in: TokenAddress::USDC,
+                token_out: TokenAddress::WETH,
+                fee: 500
+            }]
+        );
+
+        let mut market = Market::default();
+        market
+            .add_pool(UniswapV3Pool::new_with_data(
+                UniswapV3PoolAddress::USDC_WETH_500,
+                TokenAddress::USDC,
+                TokenAddress::WETH,
+                0,
+                500,
+                None,
+                FactoryAddress::UNISWAP_V3,
+            ))
+            .unwrap();
+        assert_eq!(hop_pool_addresses(&market, &hops[0]), vec![UniswapV3PoolAddress::USDC_WETH_500]);
+    }
+
+    #[test]
+    fn test_v3_fork_pool_lookup() {
+        let fork_pool = Address::repeat_byte(0x33);
+        let mut market = Market::default();
+        market
+            .add_pool(UniswapV3Pool::new_with_data(
+                fork_pool,
+                TokenAddress::USDC,
+                TokenAddress::WETH,
+                0,
+                500,
+                None,
+                Address::repeat_byte(0x44),
+            ))
+            .unwrap();
+
+        let hop = SwapHop::UniswapV3 {
+            protocol: Some(PoolProtocol::UniswapV3),
+            token_in: TokenAddress::USDC,
+            token_out: TokenAddress::WETH,
+            fee: 500,
+        };
+        assert_eq!(hop_pool_addresses(&market, &hop), vec![fork_pool]);
+    }
+
+    #[test]
+    fn test_decode_universal_router() {
+        let v3_input = (Address::ZERO, U256::from(1), U256::ZERO, v3_path(&[TokenAddress::WETH, TokenAddress::USDC], &[3000]), true)
+            .abi_encode_params();
+        let v2_input = (Address::ZERO, U256::from(1), U256::ZERO, vec![TokenAddress::WETH, TokenAddress::USDT], true).abi_encode_params();
+        let wrap_eth_input = (Address::ZERO, U256::from(1)).abi_encode_params();
+
+        let call = IUniversalRouter::execute_0Call {
+            commands: vec![0x0b, UniversalRouterCommand::V3_SWAP_EXACT_OUT | UniversalRouterCommand::FLAG_ALLOW_REVERT, 0x08].into(),
+            inputs: vec![wrap_eth_input.into(), v3_input.into(), v2_input.into()],
+            deadline: U256::ZERO,
+        };
+
+        let hops = decode_swap_hops(None, &call.abi_encode()).unwrap();
+        assert_eq!(
+            hops,
+            vec![
+                SwapHop::UniswapV3 {
+                    protocol: Some(PoolProtocol::UniswapV3),
+                    token_in: TokenAddress::USDC,
+                    token_out: TokenAddress::WETH,
+                    fee: 3000
+                },
+                SwapHop::UniswapV2 { protocol: Some(PoolProtocol::UniswapV2), token_in: TokenAddress::WETH, token_out: TokenAddress::USDT },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_oneinch() {
+        let reverse_flag = U256::from(1) << 255;
+        let call = IAggregationRouterV5::unoswapCall {
+            srcToken: TokenAddress::WETH,
+            amount: U256::from(1),
+            minReturn: U256::ZERO,
+            pools: vec![reverse_flag | U256::from_be_slice(UniswapV2PoolAddress::WETH_USDT.as_slice())],
+        };
+
+        let hops = decode_swap_hops(None, &call.abi_encode()).unwrap();
+        assert_eq!(hops, vec![SwapHop::Pool(UniswapV2PoolAddress::WETH_USDT)]);
+    }
+
+    #[test]
+    fn test_predict_tx_impact() {
+        let mut market = Market::default();
+        market
+            .add_pool(UniswapV2Pool::new_with_data(
+                UniswapV2PoolAddress::WETH_USDT,
+                TokenAddress::WETH,
+                TokenAddress::USDT,
+                FactoryAddress::UNISWAP_V2,
+                U256::ZERO,
+                U256::ZERO,
+            ))
+            .unwrap();
+
+        let swap = |path: Vec<Address>| {
+            IUniswapV2Router::swapExactTokensForTokensCall {
+                amountIn: U256::from(1),
+                amountOutMin: U256::ZERO,
+                path,
+                to: Address::ZERO,
+                deadline: U256::ZERO,
+            }
+            .abi_encode()
+        };
+
+        let to = Some(PeripheryAddress::UNISWAP_V2_ROUTER);
+        match predict_tx_impact(&market, to, &swap(vec![TokenAddress::USDT, TokenAddress::WETH])) {
+            PredictedImpact::Pools(pools) => assert_eq!(pools.keys().next().unwrap().get_address(), UniswapV2PoolAddress::WETH_USDT),
+            _ => panic!("pool not predicted"),
+        }
+
+        // unknown v2 fork router falls back to market token pools
+        match predict_tx_impact(&market, None, &swap(vec![TokenAddress::WETH, TokenAddress::USDT])) {
+            PredictedImpact::Pools(pools) => assert_eq!(pools.len(), 1),
+            _ => panic!("pool not predicted"),
+        }
+
+        assert!(!predict_tx_impact(&market, to, &swap(vec![TokenAddress::WETH, TokenAddress::DAI])).is_affecting());
+        assert!(matches!(predict_tx_impact(&market, to, &[0x12, 0x34, 0x56, 0x78]), PredictedImpact::Unknown));
+    }
+
+    #[test]
+    fn test_predict_partial_decode() {
+        let market = Market::default();
+        let v2_input = (Address::ZERO, U256::from(1), U256::ZERO, vec![TokenAddress::WETH, TokenAddress::DAI], true).abi_encode_params();
+        let wrap_eth_input = (Address::ZERO, U256::from(1)).abi_encode_params();
+
+        let universal_router_call = |commands: Vec<u8>, inputs: Vec<Bytes>| {
+            IUniversalRouter::execute_0Call { commands: commands.into(), inputs, deadline: U256::ZERO }.abi_encode()
+        };
+
+        // non swap commands are skipped
+        let call = universal_router_call(
+            vec![UniversalRouterCommand::WRAP_ETH, UniversalRouterCommand::V2_SWAP_EXACT_IN],
+            vec![wrap_eth_input.clone().into(), v2_input.clone().into()],
+        );
+        assert!(matches!(predict_tx_impact(&market, None, &call), PredictedImpact::NoPools));
+
+        // v4 swap command is not decoded
+        let call = universal_router_call(vec![0x10, UniversalRouterCommand::V2_SWAP_EXACT_IN], vec![Bytes::new(), v2_input.into()]);
+        assert!(matches!(predict_tx_impact(&market, None, &call), PredictedImpact::Unknown));
+
+        let swap = IUniswapV2Router::swapExactTokensForTokensCall {
+            amountIn: U256::from(1),
+            amountOutMin: U256::ZERO,
+            path: vec![TokenAddress::WETH, TokenAddress::DAI],
+            to: Address::ZERO,
+            deadline: U256::ZERO,
+        };
+        let refund_eth = IPeripheryPayments::refundETHCall {};
+
+        let multicall = |data: Vec<Bytes>| ISwapRouter02::multicall_0Call { data }.abi_encode();
+
+        let call = multicall(vec![swap.abi_encode().into(), refund_eth.abi_encode().into()]);
+        assert!(matches!(predict_tx_impact(&market, None, &call), PredictedImpact::NoPools));
+
+        let call = multicall(vec![swap.abi_encode().into(), Bytes::from(vec![0x12, 0x34, 0x56, 0x78])]);
+        assert!(matches!(predict_tx_impact(&market, None, &call), PredictedImpact::Unknown));
+    }
+}
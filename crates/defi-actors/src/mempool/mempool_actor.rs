@@ -1,18 +1,42 @@
-use alloy_primitives::BlockNumber;
-use alloy_rpc_types::BlockTransactions;
+use std::collections::HashMap;
+
+use alloy_primitives::{BlockNumber, TxHash};
+use alloy_rpc_types::{BlockTransactions, Transaction};
 use chrono::{Duration, Utc};
 use eyre::eyre;
 use tokio::sync::broadcast::error::RecvError;
 use tracing::{debug, error, info, trace};
 
+use super::calldata_decoder::{predict_tx_impact, PredictedImpact};
 use defi_blockchain::Blockchain;
+use defi_entities::Market;
 use defi_events::{MempoolEvents, MessageBlock, MessageBlockHeader, MessageMempoolDataUpdate};
-use defi_types::{ChainParameters, Mempool, MempoolTx};
+use defi_types::{ChainParameters, Mempool, MempoolTx, MempoolTxImpact};
 use loom_actors::{run_async, subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
 use loom_actors_macros::{Accessor, Consumer, Producer};
 
+/// Actual txs collected between flushes are announced in the impact priority order
+const ACTUAL_TXS_FLUSH_INTERVAL_MS: u64 = 20;
+
+async fn predict_impact(market: &Option<SharedState<Market>>, tx: &Transaction) -> PredictedImpact {
+    match market {
+        Some(market) => predict_tx_impact(&*market.read().await, tx.to, &tx.input),
+        None => PredictedImpact::Unknown,
+    }
+}
+
+/// Adds tx to the actual txs queue, txs that do not touch market pools are skipped
+fn queue_actual_tx(actual_txs: &mut HashMap<TxHash, MempoolTxImpact>, impact: MempoolTxImpact, tx_hash: TxHash) {
+    if impact.is_affecting() {
+        actual_txs.insert(tx_hash, impact);
+    } else {
+        trace!("tx does not swap through market pools {:?}", tx_hash);
+    }
+}
+
 pub async fn new_mempool_worker(
     chain_parameters: ChainParameters,
+    market: Option<SharedState<Market>>,
     mempool: SharedState<Mempool>,
     mempool_update_rx: Broadcaster<MessageMempoolDataUpdate>,
     block_header_rx: Broadcaster<MessageBlockHeader>,
@@ -25,6 +49,9 @@ pub async fn new_mempool_worker(
 
     let mut current_gas_price: Option<u128> = None;
     let mut last_cleaning_block: Option<BlockNumber> = None;
+    // txs swapping through market pools go first
+    let mut actual_txs: HashMap<TxHash, MempoolTxImpact> = HashMap::new();
+    let mut flush_interval = tokio::time::interval(std::time::Duration::from_millis(ACTUAL_TXS_FLUSH_INTERVAL_MS));
 
     loop {
         tokio::select! {
@@ -45,6 +72,12 @@ pub async fn new_mempool_worker(
                         }
                     };
 
+                    // calldata is decoded once when the tx is received
+                    let tx_impact = match &mempool_update_msg.mempool_tx.tx {
+                        Some(tx) => predict_impact(&market, tx).await.impact(),
+                        None => MempoolTxImpact::Unknown,
+                    };
+
                     let mut mempool_guard = mempool.write().await;
                     let tx_hash = mempool_update_msg.tx_hash;
                     let mempool_entry = mempool_guard.txs.entry(tx_hash).or_insert( MempoolTx{ tx_hash,  source : mempool_update_msg.source(), ..MempoolTx::default()});
@@ -54,6 +87,12 @@ pub async fn new_mempool_worker(
                             run_async!(broadcaster.send(MempoolEvents::MempoolLogUpdate {tx_hash } ));
                         }
                     }
+                    if let Some(hint) = &mempool_update_msg.mempool_tx.hint {
+                        if mempool_entry.hint.is_none() {
+                            mempool_entry.hint = Some(hint.clone());
+                            run_async!(broadcaster.send(MempoolEvents::MempoolHintUpdate {tx_hash } ));
+                        }
+                    }
                     if let Some(state_update) = &mempool_update_msg.mempool_tx.state_update {
                         if mempool_entry.state_update.is_none() {
                             mempool_entry.state_update = Some(state_update.clone());
@@ -63,10 +102,11 @@ pub async fn new_mempool_worker(
                     if let Some(tx) = &mempool_update_msg.mempool_tx.tx {
                         if mempool_entry.tx.is_none() {
                             mempool_entry.tx = Some(tx.clone());
+                            mempool_entry.impact = tx_impact;
                             if let Some(cur_gas_price) = current_gas_price {
                                 if let Some(tx_gas_price) = if tx.max_fee_per_gas.is_some() {tx.max_fee_per_gas} else{ tx.gas_price } {
                                     if tx.gas > 30000 && tx_gas_price >= cur_gas_price && mempool_guard.is_valid_tx(tx) {
-                                        run_async!(broadcaster.send(MempoolEvents::MempoolActualTxUpdate {tx_hash }));
+                                        queue_actual_tx(&mut actual_txs, tx_impact, tx_hash);
                                     }
                                 }
                             }
@@ -104,15 +144,17 @@ pub async fn new_mempool_worker(
 
                     let ok_txes = mempool_read_guard.filter_ok_by_gas_price(next_base_fee as u128);
                     debug!("Mempool gas update {} {}", next_base_fee, ok_txes.len());
+
                     for mempool_tx in ok_txes {
-                        let tx  = mempool_tx.tx.clone().unwrap();
+                        let Some(tx) = mempool_tx.tx.as_ref() else {
+                            continue
+                        };
                         if tx.gas  < 50000 {
                             continue
                         }
-                        if mempool_read_guard.is_valid_tx(&tx) {
-                            let tx_hash = tx.hash;
-                            trace!("new tx ok {:?}", tx_hash);
-                            run_async!(broadcaster.send(MempoolEvents::MempoolActualTxUpdate { tx_hash }));
+                        if mempool_read_guard.is_valid_tx(tx) {
+                            trace!("new tx ok {:?}", tx.hash);
+                            queue_actual_tx(&mut actual_txs, mempool_tx.impact, tx.hash);
                         } else{
                            trace!("new tx gas change tx not valid {:?}", tx.hash);
                         }
@@ -136,6 +178,13 @@ pub async fn new_mempool_worker(
                     }
 
                 },
+                _ = flush_interval.tick() => {
+                    let mut queued_txs: Vec<(TxHash, MempoolTxImpact)> = actual_txs.drain().collect();
+                    queued_txs.sort_by_key(|(_, impact)| impact.priority());
+                    for (tx_hash, _) in queued_txs {
+                        run_async!(broadcaster.send(MempoolEvents::MempoolActualTxUpdate { tx_hash }));
+                    }
+                },
                 msg = block_with_txs_rx.recv() => {
                     let block_with_txs = match msg {
                         Ok(block_with_txs) => block_with_txs.inner,
@@ -171,6 +220,8 @@ pub async fn new_mempool_worker(
 pub struct MempoolActor {
     chain_parameters: ChainParameters,
     #[accessor]
+    market: Option<SharedState<Market>>,
+    #[accessor]
     mempool: Option<SharedState<Mempool>>,
     #[consumer]
     mempool_update_rx: Option<Broadcaster<MessageMempoolDataUpdate>>,
@@ -190,6 +241,7 @@ impl MempoolActor {
     pub fn on_bc(self, bc: &Blockchain) -> MempoolActor {
         Self {
             chain_parameters: bc.chain_parameters(),
+            market: Some(bc.market()),
             mempool: Some(bc.mempool()),
             mempool_update_rx: Some(bc.new_mempool_tx_channel()),
             block_header_rx: Some(bc.new_block_headers_channel()),
@@ -203,6 +255,7 @@ impl Actor for MempoolActor {
     fn start(&self) -> ActorResult {
         let task = tokio::task::spawn(new_mempool_worker(
             self.chain_parameters.clone(),
+            self.market.clone(),
             self.mempool.clone().unwrap(),
             self.mempool_update_rx.clone().unwrap(),
             self.block_header_rx.clone().unwrap(),
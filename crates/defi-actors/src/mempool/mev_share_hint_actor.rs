@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use alloy_primitives::LogData;
+use alloy_rpc_types::Log;
+use tracing::{debug, error};
+
+use defi_blockchain::Blockchain;
+use defi_events::{MessageMempoolDataUpdate, NodeMempoolDataUpdate};
+use defi_types::{MempoolTx, MempoolTxHint};
+use flashbots::client::{MevShareHint, MevShareHintStream};
+use loom_actors::{Actor, ActorResult, Broadcaster, Producer, WorkerResult};
+use loom_actors_macros::Producer;
+
+/// Converts the hint into a partial mempool transaction with logs and function selector only.
+fn hint_to_mempool_tx(hint: MevShareHint, source: String) -> MempoolTx {
+    let tx_hash = hint.hash;
+
+    let logs: Option<Vec<Log>> = hint.logs.map(|logs| {
+        logs.into_iter()
+            .map(|log| Log {
+                inner: alloy_primitives::Log { address: log.address, data: LogData::new_unchecked(log.topics, log.data) },
+                transaction_hash: Some(tx_hash),
+                ..Log::default()
+            })
+            .collect()
+    });
+
+    let hint_tx = hint.txs.and_then(|txs| txs.into_iter().next());
+
+    let hint = MempoolTxHint {
+        to: hint_tx.as_ref().and_then(|tx| tx.to),
+        function_selector: hint_tx.as_ref().and_then(|tx| tx.function_selector),
+        call_data: hint_tx.and_then(|tx| tx.call_data),
+        mev_gas_price: hint.mev_gas_price.map(|x| x.saturating_to()),
+        gas_used: hint.gas_used.map(|x| x.saturating_to()),
+    };
+
+    MempoolTx { source, tx_hash, logs, hint: Some(hint), ..MempoolTx::default() }
+}
+
+/// Worker consumes the MEV-Share hint stream and broadcasts hints as partial transactions [`MessageMempoolDataUpdate`](MessageMempoolDataUpdate).
+/// Stream is reconnected when closed.
+pub async fn mev_share_hint_worker(url: String, name: String, mempool_tx: Broadcaster<MessageMempoolDataUpdate>) -> WorkerResult {
+    loop {
+        let mut stream = match MevShareHintStream::connect(url.as_str()).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                error!("MEV-Share stream {} connection error : {}", url, e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+
+        loop {
+            match stream.next_hint().await {
+                Ok(Some(hint)) => {
+                    let tx_hash = hint.hash;
+                    let update_msg: MessageMempoolDataUpdate = MessageMempoolDataUpdate::new_with_source(
+                        NodeMempoolDataUpdate { tx_hash, mempool_tx: hint_to_mempool_tx(hint, name.clone()) },
+                        name.clone(),
+                    );
+                    if let Err(e) = mempool_tx.send(update_msg).await {
+                        error!("mempool_tx.send error : {}", e);
+                        return Ok(name);
+                    }
+                }
+                Ok(None) => {
+                    debug!("MEV-Share stream {} closed", url);
+                    break;
+                }
+                Err(e) => {
+                    error!("MEV-Share stream {} error : {}", url, e);
+                    break;
+                }
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+#[derive(Producer)]
+pub struct MevShareHintActor {
+    name: &'static str,
+    url: String,
+    #[producer]
+    mempool_tx: Option<Broadcaster<MessageMempoolDataUpdate>>,
+}
+
+impl MevShareHintActor {
+    pub fn new(url: String) -> MevShareHintActor {
+        MevShareHintActor { name: "MevShareHintActor", url, mempool_tx: None }
+    }
+
+    pub fn with_name(self, name: String) -> Self {
+        Self { name: Box::leak(name.into_boxed_str()), ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain) -> Self {
+        Self { mempool_tx: Some(bc.new_mempool_tx_channel()), ..self }
+    }
+}
+
+impl Actor for MevShareHintActor {
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(mev_share_hint_worker(self.url.clone(), self.name.to_string(), self.mempool_tx.clone().unwrap()));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloy_primitives::{hex, Address, FixedBytes, TxHash};
+    use eyre::Result;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mev_share_hint_worker() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+
+        // Local stand-in for the MEV-Share event stream
+        tokio::task::spawn(async move {
+            if let Ok((mut socket, _)) = listener.accept().await {
+                let hint = r#"{"hash":"0xcc6c61428c6516a252768859d167dc8f5c8c8c682334a184710f898e422530f8","logs":[{"address":"0x0d4a11d5eeaac28ec3f61d100daf4d40471f1852","topics":["0x1c411e9a96e071241c2f21f7726b17ae89e3cab4c78be50e062b03a9fffbbad1"],"data":"0x01"}],"txs":[{"to":"0x7a250d5630b4cf539739df2c5dacb4c659f2488d","functionSelector":"0x7ff36ab5"}]}"#;
+                let body = format!(":ping\n\ndata: {hint}\n\n");
+                let response =
+                    format!("HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+
+        let mempool_tx: Broadcaster<MessageMempoolDataUpdate> = Broadcaster::new(10);
+        let mut mempool_rx = mempool_tx.subscribe().await;
+
+        tokio::task::spawn(mev_share_hint_worker(url, "mevshare".to_string(), mempool_tx));
+
+        let update = tokio::time::timeout(Duration::from_secs(5), mempool_rx.recv()).await??;
+
+        let tx_hash = TxHash::from(hex!("cc6c61428c6516a252768859d167dc8f5c8c8c682334a184710f898e422530f8"));
+        assert_eq!(update.tx_hash, tx_hash);
+        assert_eq!(update.source(), "mevshare");
+        assert!(update.mempool_tx.is_partial());
+
+        let logs = update.mempool_tx.logs.clone().unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].address(), "0x0d4a11d5eeaac28ec3f61d100daf4d40471f1852".parse::<Address>()?);
+        assert_eq!(logs[0].transaction_hash, Some(tx_hash));
+
+        let hint = update.mempool_tx.hint.clone().unwrap();
+        assert_eq!(hint.function_selector, Some(FixedBytes::from(hex!("7ff36ab5"))));
+        assert_eq!(hint.to, Some("0x7a250d5630b4cf539739df2c5dacb4c659f2488d".parse::<Address>()?));
+
+        Ok(())
+    }
+}
@@ -1,3 +1,7 @@
+pub use calldata_decoder::{decode_swap_hops, predict_tx_impact, PredictedImpact, SwapHop};
 pub use mempool_actor::MempoolActor;
+pub use mev_share_hint_actor::MevShareHintActor;
 
+mod calldata_decoder;
 mod mempool_actor;
+mod mev_share_hint_actor;
@@ -74,7 +74,9 @@ async fn diff_path_merger_worker(
                 match msg {
                     Ok(compose_request)=>{
                         if let TxCompose::Sign(sign_request) = compose_request.inner() {
-                            if matches!( sign_request.swap, Swap::BackrunSwapLine(_)) || matches!( sign_request.swap, Swap::BackrunSwapSteps(_)) {
+                            let is_backrun = matches!( sign_request.swap, Swap::BackrunSwapLine(_)) || matches!( sign_request.swap, Swap::BackrunSwapSteps(_));
+                            // txs known from hints only have no signed tx to merge
+                            if is_backrun && sign_request.hint_stuffing_hashes().is_empty() {
                                 let mut merge_list = get_merge_list(sign_request, &swap_paths);
 
                                 if !merge_list.is_empty() {
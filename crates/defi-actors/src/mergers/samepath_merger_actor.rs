@@ -286,7 +286,8 @@ async fn same_path_merger_worker<
                     Ok(compose_request)=>{
                         if let TxCompose::Sign(sign_request) = compose_request.inner() {
 
-                            if sign_request.stuffing_txs_hashes.len() == 1 {
+                            // txs known from hints only have no signed tx to merge
+                            if sign_request.stuffing_txs_hashes.len() == 1 && sign_request.stuffing_txs.len() == 1 {
                                 if let Swap::BackrunSwapLine( _swap_line ) = &sign_request.swap {
                                     let stuffing_tx_hash = sign_request.first_stuffing_hash();
 
@@ -40,7 +40,8 @@ async fn price_worker<N: Network, T: Transport + Clone, P: Provider<T, N> + Clon
     let one_ether = U256::from(10).pow(U256::from(18));
     let weth_amount = one_ether.mul(U256::from(5));
 
-    match market.read().await.get_token(&TokenAddress::WETH) {
+    let wrapped_native_token = market.read().await.wrapped_native_token();
+    match market.read().await.get_token(&wrapped_native_token) {
         Some(token) => {
             token.set_eth_price(Some(one_ether));
         }
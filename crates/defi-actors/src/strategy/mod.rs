@@ -0,0 +1,5 @@
+pub use searcher_strategy::{Strategy, StrategyCandidate, StrategyContext};
+pub use strategy_actor::StrategyActor;
+
+mod searcher_strategy;
+mod strategy_actor;
@@ -0,0 +1,52 @@
+use eyre::Result;
+use revm::primitives::Env;
+
+use defi_entities::{Market, Swap};
+use defi_events::StateUpdateEvent;
+use loom_revm_db::LoomInMemoryDB;
+
+/// Data available to a strategy while searching on a state update. No locks are held during the search.
+pub struct StrategyContext<'a> {
+    pub state_update_event: &'a StateUpdateEvent,
+    /// Snapshot of the market taken when the search started.
+    pub market: &'a Market,
+    /// Market state of the event with its state update applied.
+    pub poststate: &'a LoomInMemoryDB,
+    pub evm_env: Env,
+}
+
+/// Swap found by a strategy with data required for composing a transaction.
+#[derive(Clone, Debug)]
+pub struct StrategyCandidate {
+    pub swap: Swap,
+    /// Gas estimate, swap pre estimate is used if not set.
+    pub gas: Option<u64>,
+    /// Tips percentage, state update event tips percentage is used if not set.
+    pub tips_pct: Option<u32>,
+}
+
+impl StrategyCandidate {
+    pub fn new(swap: Swap) -> Self {
+        Self { swap, gas: None, tips_pct: None }
+    }
+
+    pub fn with_gas(self, gas: u64) -> Self {
+        Self { gas: Some(gas), ..self }
+    }
+
+    pub fn with_tips_pct(self, tips_pct: u32) -> Self {
+        Self { tips_pct: Some(tips_pct), ..self }
+    }
+}
+
+/// Searcher strategy hosted by `StrategyActor`. Search is executed on a rayon thread pool shared by all strategies.
+pub trait Strategy: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Cheap check to skip state updates the strategy is not interested in.
+    fn is_applicable(&self, _state_update_event: &StateUpdateEvent) -> bool {
+        true
+    }
+
+    fn search(&self, context: &StrategyContext<'_>) -> Result<Vec<StrategyCandidate>>;
+}
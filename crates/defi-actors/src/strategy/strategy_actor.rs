@@ -0,0 +1,286 @@
+use std::sync::Arc;
+
+use alloy_primitives::BlockNumber;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info, trace};
+
+use crate::strategy::{Strategy, StrategyCandidate, StrategyContext};
+use defi_blockchain::Blockchain;
+use defi_entities::Market;
+use defi_events::{Message, MessageTxCompose, StateUpdateEvent, TxCompose, TxComposeData};
+use loom_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
+use loom_actors_macros::{Accessor, Consumer, Producer};
+
+/// Runs all applicable strategies in parallel on the thread pool and collects their candidates by strategy name.
+fn search_strategies(
+    thread_pool: &ThreadPool,
+    strategies: &[Arc<dyn Strategy>],
+    context: &StrategyContext<'_>,
+) -> Vec<(&'static str, Vec<StrategyCandidate>)> {
+    thread_pool.install(|| {
+        strategies
+            .par_iter()
+            .filter(|strategy| strategy.is_applicable(context.state_update_event))
+            .filter_map(|strategy| match strategy.search(context) {
+                Ok(candidates) => Some((strategy.name(), candidates)),
+                Err(e) => {
+                    trace!(strategy = strategy.name(), "Strategy search error : {}", e);
+                    None
+                }
+            })
+            .collect()
+    })
+}
+
+async fn strategy_task(
+    thread_pool: Arc<ThreadPool>,
+    strategies: Arc<Vec<Arc<dyn Strategy>>>,
+    state_update_event: StateUpdateEvent,
+    market_snapshot: Arc<Market>,
+    compose_tx: Broadcaster<MessageTxCompose>,
+) -> eyre::Result<()> {
+    let start_time = chrono::Local::now();
+
+    // search runs on the thread pool, runtime worker is not blocked while waiting for it
+    let (state_update_event, db, results) = tokio::task::spawn_blocking(move || {
+        let mut db = state_update_event.market_state().clone();
+        db.apply_geth_update_vec(state_update_event.state_update().clone());
+
+        let context = StrategyContext {
+            state_update_event: &state_update_event,
+            market: &market_snapshot,
+            poststate: &db,
+            evm_env: state_update_event.evm_env(),
+        };
+        let results = search_strategies(&thread_pool, &strategies, &context);
+        (state_update_event, db, results)
+    })
+    .await?;
+
+    let arc_db = Arc::new(db);
+    let mut candidates_count = 0;
+
+    for (strategy_name, candidates) in results.into_iter() {
+        for candidate in candidates.into_iter() {
+            candidates_count += 1;
+
+            let encode_request = TxCompose::Route(TxComposeData {
+                next_block_number: state_update_event.next_block_number,
+                next_block_timestamp: state_update_event.next_block_timestamp,
+                next_block_base_fee: state_update_event.next_base_fee,
+                gas: candidate.gas.unwrap_or(candidate.swap.pre_estimate_gas()),
+                stuffing_txs: state_update_event.stuffing_txs.clone(),
+                stuffing_txs_hashes: state_update_event.stuffing_txs_hashes.clone(),
+                swap: candidate.swap,
+                origin: Some(strategy_name.to_string()),
+                tips_pct: Some(candidate.tips_pct.unwrap_or(state_update_event.tips_pct)),
+                poststate: Some(arc_db.clone()),
+                poststate_update: Some(state_update_event.state_update().clone()),
+                ..TxComposeData::default()
+            });
+
+            if let Err(e) = compose_tx.send(Message::new(encode_request)).await {
+                error!("compose_tx.send {}", e)
+            }
+        }
+    }
+
+    debug!(
+        origin = %state_update_event.origin,
+        candidates_count,
+        elapsed = %(chrono::Local::now() - start_time),
+        "Strategies search finished"
+    );
+
+    Ok(())
+}
+
+pub async fn strategy_worker(
+    strategies: Arc<Vec<Arc<dyn Strategy>>>,
+    thread_pool: Option<Arc<ThreadPool>>,
+    market: SharedState<Market>,
+    state_update_rx: Broadcaster<StateUpdateEvent>,
+    compose_tx: Broadcaster<MessageTxCompose>,
+) -> WorkerResult {
+    subscribe!(state_update_rx);
+
+    let thread_pool = match thread_pool {
+        Some(thread_pool) => thread_pool,
+        None => {
+            let cpus = num_cpus::get();
+            info!("Starting strategy actor strategies={} cpus={cpus}, tasks={}", strategies.len(), cpus / 2);
+            Arc::new(ThreadPoolBuilder::new().num_threads(cpus / 2).build()?)
+        }
+    };
+
+    // market is copied once per block and shared by the searches of the block, the lock is not held while searching
+    let mut market_snapshot: Option<(BlockNumber, Arc<Market>)> = None;
+
+    loop {
+        tokio::select! {
+            msg = state_update_rx.recv() => {
+                let state_update_msg : Result<StateUpdateEvent, RecvError> = msg;
+                match state_update_msg {
+                    Ok(state_update_event) => {
+                        if !strategies.iter().any(|strategy| strategy.is_applicable(&state_update_event)) {
+                            continue
+                        }
+                        let snapshot = match &market_snapshot {
+                            Some((block_number, snapshot)) if *block_number == state_update_event.next_block_number => snapshot.clone(),
+                            _ => {
+                                let snapshot = Arc::new(market.read().await.clone());
+                                market_snapshot = Some((state_update_event.next_block_number, snapshot.clone()));
+                                snapshot
+                            }
+                        };
+                        tokio::task::spawn(
+                            strategy_task(
+                                thread_pool.clone(),
+                                strategies.clone(),
+                                state_update_event,
+                                snapshot,
+                                compose_tx.clone(),
+                            )
+                        );
+                    }
+                    Err(e) => {
+                        error!("state_update_rx error : {e}")
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Hosts any number of strategies searching on state update events.
+#[derive(Accessor, Consumer, Producer, Default)]
+pub struct StrategyActor {
+    strategies: Vec<Arc<dyn Strategy>>,
+    thread_pool: Option<Arc<ThreadPool>>,
+    #[accessor]
+    market: Option<SharedState<Market>>,
+    #[consumer]
+    state_update_rx: Option<Broadcaster<StateUpdateEvent>>,
+    #[producer]
+    compose_tx: Option<Broadcaster<MessageTxCompose>>,
+}
+
+impl StrategyActor {
+    pub fn new() -> Self {
+        StrategyActor::default()
+    }
+
+    pub fn with_strategy(self, strategy: impl Strategy + 'static) -> Self {
+        let mut strategies = self.strategies;
+        strategies.push(Arc::new(strategy));
+        Self { strategies, ..self }
+    }
+
+    pub fn with_strategies(self, strategies: Vec<Arc<dyn Strategy>>) -> Self {
+        let mut current = self.strategies;
+        current.extend(strategies);
+        Self { strategies: current, ..self }
+    }
+
+    /// Searches on the given thread pool instead of creating a new one
+    pub fn with_thread_pool(self, thread_pool: Arc<ThreadPool>) -> Self {
+        Self { thread_pool: Some(thread_pool), ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain) -> Self {
+        Self { market: Some(bc.market()), state_update_rx: Some(bc.state_update_channel()), compose_tx: Some(bc.compose_channel()), ..self }
+    }
+}
+
+impl Actor for StrategyActor {
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(strategy_worker(
+            Arc::new(self.strategies.clone()),
+            self.thread_pool.clone(),
+            self.market.clone().unwrap(),
+            self.state_update_rx.clone().unwrap(),
+            self.compose_tx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "StrategyActor"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use defi_entities::Swap;
+    use eyre::{eyre, Result};
+    use loom_revm_db::LoomInMemoryDB;
+    use std::collections::BTreeMap;
+
+    struct TestStrategy {
+        name: &'static str,
+        applicable: bool,
+        fail: bool,
+    }
+
+    impl Strategy for TestStrategy {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn is_applicable(&self, _state_update_event: &StateUpdateEvent) -> bool {
+            self.applicable
+        }
+
+        fn search(&self, _context: &StrategyContext<'_>) -> Result<Vec<StrategyCandidate>> {
+            if self.fail {
+                return Err(eyre!("TEST_ERROR"));
+            }
+            Ok(vec![StrategyCandidate::new(Swap::None).with_gas(100_000)])
+        }
+    }
+
+    #[test]
+    fn test_search_strategies() -> Result<()> {
+        let strategies: Vec<Arc<dyn Strategy>> = vec![
+            Arc::new(TestStrategy { name: "applicable", applicable: true, fail: false }),
+            Arc::new(TestStrategy { name: "not_applicable", applicable: false, fail: false }),
+            Arc::new(TestStrategy { name: "failed", applicable: true, fail: true }),
+        ];
+
+        let state_update_event = StateUpdateEvent::new(
+            1,
+            0,
+            0,
+            LoomInMemoryDB::default(),
+            vec![],
+            None,
+            BTreeMap::new(),
+            vec![],
+            vec![],
+            "test".to_string(),
+            5000,
+        );
+        let market = Market::default();
+        let db = LoomInMemoryDB::default();
+
+        let context = StrategyContext {
+            state_update_event: &state_update_event,
+            market: &market,
+            poststate: &db,
+            evm_env: state_update_event.evm_env(),
+        };
+
+        let thread_pool = ThreadPoolBuilder::new().num_threads(2).build()?;
+        let results = search_strategies(&thread_pool, &strategies, &context);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "applicable");
+        assert_eq!(results[0].1.len(), 1);
+        assert_eq!(results[0].1[0].gas, Some(100_000));
+
+        Ok(())
+    }
+}
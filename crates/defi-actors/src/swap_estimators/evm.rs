@@ -1,24 +1,32 @@
+use std::sync::Arc;
+
 use alloy_consensus::TxEnvelope;
 use alloy_eips::eip2718::Encodable2718;
-use alloy_primitives::{Bytes, TxKind, U256};
+use alloy_primitives::{Bytes, ChainId, TxKind, U256};
 use alloy_rpc_types::{TransactionInput, TransactionRequest};
 use eyre::{eyre, Result};
 use tokio::sync::broadcast::error::RecvError;
 use tracing::{debug, error, info};
 
 use defi_blockchain::Blockchain;
-use defi_entities::{Swap, SwapEncoder};
+use defi_entities::{AccountNonceAndBalanceState, GasPriceOracle, Swap, SwapEncoder, TxCostModel};
 use loom_utils::NWETH;
 
 use defi_events::{MessageTxCompose, TxCompose, TxComposeData, TxState};
-use loom_actors::{subscribe, Actor, ActorResult, Broadcaster, Consumer, Producer, WorkerResult};
-use loom_actors_macros::{Consumer, Producer};
+use loom_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
+use loom_actors_macros::{Accessor, Consumer, Producer};
+
+use super::{chain_tx_cost_model, max_fee_per_gas, tx_cost_model_or_default, unsigned_tx_data};
+use crate::accounts_monitor::release_nonce_on_error;
 use loom_utils::evm::{env_for_block, evm_access_list};
 
 async fn estimator_task(
     estimate_request: TxComposeData,
     swap_encoder: impl SwapEncoder,
     compose_channel_tx: Broadcaster<MessageTxCompose>,
+    gas_price_oracle: Option<SharedState<GasPriceOracle>>,
+    tx_cost_model: Arc<dyn TxCostModel>,
+    chain_id: ChainId,
 ) -> Result<()> {
     debug!(
         gas_limit = estimate_request.gas,
@@ -33,12 +41,14 @@ async fn estimator_task(
     let tx_signer = estimate_request.signer.clone().ok_or(eyre!("NO_SIGNER"))?;
 
     let gas_price = estimate_request.priority_gas_fee + estimate_request.next_block_base_fee;
+    let max_fee_per_gas = max_fee_per_gas(gas_price_oracle.as_ref(), &estimate_request).await;
     //let gas_cost = U256::from(100_000 * gas_price);
 
     let (to, call_value, call_data, _) = swap_encoder.encode(
         estimate_request.swap.clone(),
         estimate_request.tips_pct,
         Some(estimate_request.next_block_number),
+        Some(max_fee_per_gas),
         None,
         Some(tx_signer.address()),
         Some(estimate_request.eth_balance),
@@ -46,7 +56,7 @@ async fn estimator_task(
 
     let tx_request = TransactionRequest {
         transaction_type: Some(2),
-        chain_id: Some(1),
+        chain_id: Some(chain_id),
         from: Some(tx_signer.address()),
         to: Some(TxKind::Call(to)),
         gas: Some(estimate_request.gas),
@@ -54,7 +64,7 @@ async fn estimator_task(
         input: TransactionInput::new(call_data.clone()),
         nonce: Some(estimate_request.nonce),
         max_priority_fee_per_gas: Some(estimate_request.priority_gas_fee as u128),
-        max_fee_per_gas: Some(estimate_request.next_block_base_fee as u128), // Why not prio + base fee?
+        max_fee_per_gas: Some(max_fee_per_gas),
         ..TransactionRequest::default()
     };
 
@@ -82,7 +92,15 @@ async fn estimator_task(
         return Err(eyre!("TRANSACTION_ESTIMATED_INCORRECTLY"));
     }
 
-    let gas_cost = U256::from(gas_used as u128 * gas_price as u128);
+    // L1 data fee of rollups depends on the transaction size
+    let tx_data = unsigned_tx_data(&TransactionRequest { access_list: Some(access_list.clone()), ..tx_request });
+    let gas_cost = match tx_cost_model.tx_cost(&db, gas_used, gas_price as u128, &tx_data) {
+        Ok(gas_cost) => gas_cost,
+        Err(error) => {
+            error!(%error, %swap, "tx_cost_model.tx_cost");
+            return Err(error);
+        }
+    };
 
     let (to, call_value, call_data, tips_vec) = match &swap {
         Swap::ExchangeSwapLine(_) => (to, None, call_data, vec![]),
@@ -91,6 +109,7 @@ async fn estimator_task(
                 estimate_request.swap.clone(),
                 estimate_request.tips_pct,
                 Some(estimate_request.next_block_number),
+                Some(max_fee_per_gas),
                 Some(gas_cost),
                 Some(tx_signer.address()),
                 Some(estimate_request.eth_balance),
@@ -106,7 +125,7 @@ async fn estimator_task(
 
     let tx_request = TransactionRequest {
         transaction_type: Some(2),
-        chain_id: Some(1),
+        chain_id: Some(chain_id),
         from: Some(tx_signer.address()),
         to: Some(TxKind::Call(to)),
         gas: Some((gas_used * 1200) / 1000),
@@ -115,7 +134,7 @@ async fn estimator_task(
         nonce: Some(estimate_request.nonce),
         access_list: Some(access_list),
         max_priority_fee_per_gas: Some(estimate_request.priority_gas_fee as u128),
-        max_fee_per_gas: Some(estimate_request.next_block_base_fee as u128), // TODO: Why not prio + base fee?
+        max_fee_per_gas: Some(max_fee_per_gas),
         ..TransactionRequest::default()
     };
 
@@ -124,7 +143,10 @@ async fn estimator_task(
 
     let stuffing_txs_rlp: Vec<Bytes> = encoded_txes?.into_iter().map(|x| Bytes::from(x.encoded_2718())).collect();
 
-    let mut tx_with_state: Vec<TxState> = stuffing_txs_rlp.into_iter().map(TxState::ReadyForBroadcastStuffing).collect();
+    // stuffing txs known from hints only are referenced by hash
+    let mut tx_with_state: Vec<TxState> = estimate_request.hint_stuffing_hashes().into_iter().map(TxState::StuffingHash).collect();
+
+    tx_with_state.extend(stuffing_txs_rlp.into_iter().map(TxState::ReadyForBroadcastStuffing));
 
     tx_with_state.push(TxState::SignatureRequired(tx_request));
 
@@ -172,6 +194,10 @@ async fn estimator_worker(
     encoder: impl SwapEncoder + Send + Sync + Clone + 'static,
     compose_channel_rx: Broadcaster<MessageTxCompose>,
     compose_channel_tx: Broadcaster<MessageTxCompose>,
+    gas_price_oracle: Option<SharedState<GasPriceOracle>>,
+    tx_cost_model: Arc<dyn TxCostModel>,
+    chain_id: ChainId,
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
 ) -> WorkerResult {
     subscribe!(compose_channel_rx);
 
@@ -182,12 +208,12 @@ async fn estimator_worker(
                 match compose_request_msg {
                     Ok(compose_request) =>{
                         if let TxCompose::Estimate(estimate_request) = compose_request.inner {
+                            let (encoder, compose_channel_tx, gas_price_oracle, tx_cost_model) =
+                                (encoder.clone(), compose_channel_tx.clone(), gas_price_oracle.clone(), tx_cost_model.clone());
                             tokio::task::spawn(
-                                estimator_task(
-                                    estimate_request,
-                                    encoder.clone(),
-                                    compose_channel_tx.clone(),
-                                )
+                                release_nonce_on_error(account_nonce_balance.clone(), estimate_request, move |request| {
+                                    estimator_task(request, encoder, compose_channel_tx, gas_price_oracle, tx_cost_model, chain_id)
+                                })
                             );
                         }
                     }
@@ -198,9 +224,15 @@ async fn estimator_worker(
     }
 }
 
-#[derive(Consumer, Producer)]
+#[derive(Accessor, Consumer, Producer)]
 pub struct EvmEstimatorActor<E> {
     encoder: E,
+    tx_cost_model: Option<Arc<dyn TxCostModel>>,
+    chain_id: ChainId,
+    #[accessor]
+    gas_price_oracle: Option<SharedState<GasPriceOracle>>,
+    #[accessor]
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
     #[consumer]
     compose_channel_rx: Option<Broadcaster<MessageTxCompose>>,
     #[producer]
@@ -212,11 +244,32 @@ where
     E: SwapEncoder + Send + Sync + Clone + 'static,
 {
     pub fn new(encoder: E) -> Self {
-        Self { encoder, compose_channel_tx: None, compose_channel_rx: None }
+        Self {
+            encoder,
+            tx_cost_model: None,
+            chain_id: 1,
+            gas_price_oracle: None,
+            account_nonce_balance: None,
+            compose_channel_tx: None,
+            compose_channel_rx: None,
+        }
+    }
+
+    /// Transaction cost used for the profit check and tips, the model of the chain is used if it is not set.
+    pub fn with_tx_cost_model(self, tx_cost_model: impl TxCostModel + 'static) -> Self {
+        Self { tx_cost_model: Some(Arc::new(tx_cost_model)), ..self }
     }
 
     pub fn on_bc(self, bc: &Blockchain) -> Self {
-        Self { compose_channel_tx: Some(bc.compose_channel()), compose_channel_rx: Some(bc.compose_channel()), ..self }
+        Self {
+            tx_cost_model: chain_tx_cost_model(self.tx_cost_model.clone(), bc),
+            chain_id: bc.chain_id(),
+            gas_price_oracle: Some(bc.gas_price_oracle()),
+            account_nonce_balance: Some(bc.nonce_and_balance()),
+            compose_channel_tx: Some(bc.compose_channel()),
+            compose_channel_rx: Some(bc.compose_channel()),
+            ..self
+        }
     }
 }
 
@@ -229,6 +282,10 @@ where
             self.encoder.clone(),
             self.compose_channel_rx.clone().unwrap(),
             self.compose_channel_tx.clone().unwrap(),
+            self.gas_price_oracle.clone(),
+            tx_cost_model_or_default(self.tx_cost_model.as_ref()),
+            self.chain_id,
+            self.account_nonce_balance.clone(),
         ));
         Ok(vec![task])
     }
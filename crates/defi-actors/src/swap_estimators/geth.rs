@@ -3,7 +3,7 @@ use std::sync::Arc;
 use alloy_consensus::TxEnvelope;
 use alloy_eips::eip2718::Encodable2718;
 use alloy_network::Ethereum;
-use alloy_primitives::{Bytes, TxKind, U256};
+use alloy_primitives::{Bytes, ChainId, TxKind, U256};
 use alloy_provider::Provider;
 use alloy_rpc_types::{TransactionInput, TransactionRequest};
 use alloy_transport::Transport;
@@ -12,19 +12,25 @@ use tokio::sync::broadcast::error::RecvError;
 use tracing::{debug, error, info};
 
 use defi_blockchain::Blockchain;
-use defi_entities::{Swap, SwapEncoder};
+use defi_entities::{AccountNonceAndBalanceState, GasPriceOracle, Swap, SwapEncoder, TxCostModel};
 use loom_utils::NWETH;
 
 use defi_events::{MessageTxCompose, TxCompose, TxComposeData, TxState};
 use flashbots::Flashbots;
-use loom_actors::{subscribe, Actor, ActorResult, Broadcaster, Consumer, Producer, WorkerResult};
-use loom_actors_macros::{Consumer, Producer};
+use loom_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
+use loom_actors_macros::{Accessor, Consumer, Producer};
+
+use super::{chain_tx_cost_model, max_fee_per_gas, tx_cost_model_or_default, unsigned_tx_data};
+use crate::accounts_monitor::release_nonce_on_error;
 
 async fn estimator_task<T: Transport + Clone, P: Provider<T, Ethereum> + Send + Sync + Clone + 'static>(
     estimate_request: TxComposeData,
     client: Arc<Flashbots<P, T>>,
     swap_encoder: impl SwapEncoder,
     compose_channel_tx: Broadcaster<MessageTxCompose>,
+    gas_price_oracle: Option<SharedState<GasPriceOracle>>,
+    tx_cost_model: Arc<dyn TxCostModel>,
+    chain_id: ChainId,
 ) -> Result<()> {
     let token_in = estimate_request.swap.get_first_token().cloned().ok_or(eyre!("NO_TOKEN"))?;
 
@@ -39,11 +45,13 @@ async fn estimator_task<T: Transport + Clone, P: Provider<T, Ethereum> + Send +
 
     let gas_price = estimate_request.priority_gas_fee + estimate_request.next_block_base_fee;
     let gas_cost = U256::from(100_000 * gas_price);
+    let max_fee_per_gas = max_fee_per_gas(gas_price_oracle.as_ref(), &estimate_request).await;
 
     let (to, _, call_data, _) = swap_encoder.encode(
         estimate_request.swap.clone(),
         estimate_request.tips_pct,
         Some(estimate_request.next_block_number),
+        Some(max_fee_per_gas),
         Some(gas_cost),
         Some(tx_signer.address()),
         Some(estimate_request.eth_balance),
@@ -51,14 +59,14 @@ async fn estimator_task<T: Transport + Clone, P: Provider<T, Ethereum> + Send +
 
     let mut tx_request = TransactionRequest {
         transaction_type: Some(2),
-        chain_id: Some(1),
+        chain_id: Some(chain_id),
         from: Some(tx_signer.address()),
         to: Some(TxKind::Call(to)),
         gas: Some(estimate_request.gas),
         value: Some(U256::from(1000)),
         nonce: Some(estimate_request.nonce),
         max_priority_fee_per_gas: Some(estimate_request.priority_gas_fee as u128),
-        max_fee_per_gas: Some(estimate_request.next_block_base_fee as u128),
+        max_fee_per_gas: Some(max_fee_per_gas),
         input: TransactionInput::new(call_data.clone()),
         ..TransactionRequest::default()
     };
@@ -112,7 +120,15 @@ async fn estimator_task<T: Transport + Clone, P: Provider<T, Ethereum> + Send +
                     let swap = estimate_request.swap.clone();
 
                     tx_request.access_list = Some(access_list.clone());
-                    let gas_cost = U256::from(gas * gas_price);
+                    // L1 data fee of rollups depends on the transaction size
+                    let db = estimate_request.poststate.clone().unwrap_or_default();
+                    let gas_cost = match tx_cost_model.tx_cost(&db, gas, gas_price as u128, &unsigned_tx_data(&tx_request)) {
+                        Ok(gas_cost) => gas_cost,
+                        Err(error) => {
+                            error!(%error, %swap, "tx_cost_model.tx_cost");
+                            return Err(error);
+                        }
+                    };
                     if gas_cost < profit_eth {
                         let (to, call_value, call_data, tips_vec) = match estimate_request.swap {
                             Swap::ExchangeSwapLine(_) => (to, None, call_data, vec![]),
@@ -120,6 +136,7 @@ async fn estimator_task<T: Transport + Clone, P: Provider<T, Ethereum> + Send +
                                 estimate_request.swap.clone(),
                                 estimate_request.tips_pct,
                                 Some(estimate_request.next_block_number),
+                                Some(max_fee_per_gas),
                                 Some(gas_cost),
                                 Some(tx_signer.address()),
                                 Some(estimate_request.eth_balance),
@@ -128,7 +145,7 @@ async fn estimator_task<T: Transport + Clone, P: Provider<T, Ethereum> + Send +
 
                         let tx_request = TransactionRequest {
                             transaction_type: Some(2),
-                            chain_id: Some(1),
+                            chain_id: Some(chain_id),
                             from: Some(tx_signer.address()),
                             to: Some(TxKind::Call(to)),
                             gas: Some((gas * 1200) / 1000),
@@ -137,7 +154,7 @@ async fn estimator_task<T: Transport + Clone, P: Provider<T, Ethereum> + Send +
                             nonce: Some(estimate_request.nonce),
                             access_list: Some(access_list),
                             max_priority_fee_per_gas: Some(estimate_request.priority_gas_fee as u128),
-                            max_fee_per_gas: Some(estimate_request.next_block_base_fee as u128), // TODO: Why not prio + base fee?
+                            max_fee_per_gas: Some(max_fee_per_gas),
                             ..TransactionRequest::default()
                         };
 
@@ -200,6 +217,10 @@ async fn estimator_worker<T: Transport + Clone, P: Provider<T, Ethereum> + Send
     encoder: impl SwapEncoder + Send + Sync + Clone + 'static,
     compose_channel_rx: Broadcaster<MessageTxCompose>,
     compose_channel_tx: Broadcaster<MessageTxCompose>,
+    gas_price_oracle: Option<SharedState<GasPriceOracle>>,
+    tx_cost_model: Arc<dyn TxCostModel>,
+    chain_id: ChainId,
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
 ) -> WorkerResult {
     subscribe!(compose_channel_rx);
 
@@ -210,13 +231,12 @@ async fn estimator_worker<T: Transport + Clone, P: Provider<T, Ethereum> + Send
                 match compose_request_msg {
                     Ok(compose_request) =>{
                         if let TxCompose::Estimate(estimate_request) = compose_request.inner {
+                            let (client, encoder, compose_channel_tx, gas_price_oracle, tx_cost_model) =
+                                (client.clone(), encoder.clone(), compose_channel_tx.clone(), gas_price_oracle.clone(), tx_cost_model.clone());
                             tokio::task::spawn(
-                                estimator_task(
-                                    estimate_request,
-                                    client.clone(),
-                                    encoder.clone(),
-                                    compose_channel_tx.clone(),
-                                )
+                                release_nonce_on_error(account_nonce_balance.clone(), estimate_request, move |request| {
+                                    estimator_task(request, client, encoder, compose_channel_tx, gas_price_oracle, tx_cost_model, chain_id)
+                                })
                             );
                         }
                     }
@@ -227,10 +247,16 @@ async fn estimator_worker<T: Transport + Clone, P: Provider<T, Ethereum> + Send
     }
 }
 
-#[derive(Consumer, Producer)]
+#[derive(Accessor, Consumer, Producer)]
 pub struct GethEstimatorActor<P, T, E> {
     client: Arc<Flashbots<P, T>>,
     encoder: E,
+    tx_cost_model: Option<Arc<dyn TxCostModel>>,
+    chain_id: ChainId,
+    #[accessor]
+    gas_price_oracle: Option<SharedState<GasPriceOracle>>,
+    #[accessor]
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
     #[consumer]
     compose_channel_rx: Option<Broadcaster<MessageTxCompose>>,
     #[producer]
@@ -244,11 +270,33 @@ where
     E: SwapEncoder + Send + Sync + Clone + 'static,
 {
     pub fn new(client: Arc<Flashbots<P, T>>, encoder: E) -> Self {
-        Self { client, encoder, compose_channel_tx: None, compose_channel_rx: None }
+        Self {
+            client,
+            encoder,
+            tx_cost_model: None,
+            chain_id: 1,
+            gas_price_oracle: None,
+            account_nonce_balance: None,
+            compose_channel_tx: None,
+            compose_channel_rx: None,
+        }
+    }
+
+    /// Transaction cost used for the profit check and tips, the model of the chain is used if it is not set.
+    pub fn with_tx_cost_model(self, tx_cost_model: impl TxCostModel + 'static) -> Self {
+        Self { tx_cost_model: Some(Arc::new(tx_cost_model)), ..self }
     }
 
     pub fn on_bc(self, bc: &Blockchain) -> Self {
-        Self { compose_channel_tx: Some(bc.compose_channel()), compose_channel_rx: Some(bc.compose_channel()), ..self }
+        Self {
+            tx_cost_model: chain_tx_cost_model(self.tx_cost_model.clone(), bc),
+            chain_id: bc.chain_id(),
+            gas_price_oracle: Some(bc.gas_price_oracle()),
+            account_nonce_balance: Some(bc.nonce_and_balance()),
+            compose_channel_tx: Some(bc.compose_channel()),
+            compose_channel_rx: Some(bc.compose_channel()),
+            ..self
+        }
     }
 }
 
@@ -264,6 +312,10 @@ where
             self.encoder.clone(),
             self.compose_channel_rx.clone().unwrap(),
             self.compose_channel_tx.clone().unwrap(),
+            self.gas_price_oracle.clone(),
+            tx_cost_model_or_default(self.tx_cost_model.as_ref()),
+            self.chain_id,
+            self.account_nonce_balance.clone(),
         ));
         Ok(vec![task])
     }
@@ -1,6 +1,6 @@
 use alloy_consensus::TxEnvelope;
 use alloy_eips::eip2718::Encodable2718;
-use alloy_primitives::{Bytes, TxKind, U256};
+use alloy_primitives::{Bytes, ChainId, TxKind, U256};
 use alloy_provider::Provider;
 use alloy_rpc_types::{TransactionInput, TransactionRequest};
 use eyre::{eyre, Result};
@@ -8,88 +8,105 @@ use tokio::sync::broadcast::error::RecvError;
 use tracing::{error, info};
 
 use debug_provider::DebugProviderExt;
-use defi_entities::SwapEncoder;
+use defi_blockchain::Blockchain;
+use defi_entities::{AccountNonceAndBalanceState, SwapEncoder};
 use defi_events::{MessageTxCompose, TxCompose, TxComposeData, TxState};
-use loom_actors::{subscribe, Actor, ActorResult, Broadcaster, Consumer, Producer, WorkerResult};
-use loom_actors_macros::{Consumer, Producer};
+use loom_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
+use loom_actors_macros::{Accessor, Consumer, Producer};
 
-async fn estimator_worker(
+use crate::accounts_monitor::release_nonce_on_error;
+
+async fn estimator_task(
+    estimate_request: TxComposeData,
     swap_encoder: impl SwapEncoder,
+    compose_channel_tx: Broadcaster<MessageTxCompose>,
+    chain_id: ChainId,
+) -> Result<()> {
+    info!("Hardhat estimation");
+    let token_in = estimate_request.swap.get_first_token().cloned().ok_or(eyre!("NO_TOKEN"))?;
+
+    let tx_signer = estimate_request.signer.clone().ok_or(eyre!("NO_SIGNER"))?;
+
+    let gas_price = estimate_request.priority_gas_fee + estimate_request.next_block_base_fee;
+    let gas_cost = U256::from(100_000 * gas_price);
+
+    let profit = estimate_request.swap.abs_profit();
+    if profit.is_zero() {
+        return Err(eyre!("NO_PROFIT"));
+    }
+    let profit_eth = token_in.calc_eth_value(profit).ok_or(eyre!("CALC_ETH_VALUE_FAILED"))?;
+
+    let (to, _call_value, call_data, _) = swap_encoder.encode(
+        estimate_request.swap.clone(),
+        estimate_request.tips_pct,
+        Some(estimate_request.next_block_number),
+        Some(gas_price as u128),
+        Some(gas_cost),
+        Some(tx_signer.address()),
+        Some(estimate_request.eth_balance),
+    )?;
+
+    let tx_request = TransactionRequest {
+        transaction_type: Some(2),
+        chain_id: Some(chain_id),
+        from: Some(tx_signer.address()),
+        to: Some(TxKind::Call(to)),
+        gas: Some(estimate_request.gas),
+        value: Some(U256::from(1000)),
+        input: TransactionInput::new(call_data),
+        nonce: Some(estimate_request.nonce),
+        max_priority_fee_per_gas: Some(estimate_request.priority_gas_fee as u128),
+        max_fee_per_gas: Some(estimate_request.next_block_base_fee as u128), // TODO: Why not prio + base fee?
+        ..TransactionRequest::default()
+    };
+
+    let gas_price = estimate_request.priority_gas_fee + estimate_request.next_block_base_fee;
+
+    if U256::from(300_000 * gas_price) > profit_eth {
+        error!("Profit is too small");
+        return Err(eyre!("TOO_SMALL_PROFIT"));
+    }
+
+    let enveloped_txs: Result<Vec<TxEnvelope>, _> = estimate_request.stuffing_txs.iter().map(|item| item.clone().try_into()).collect();
+    let stuffing_txs_rlp: Vec<Bytes> = enveloped_txs?.into_iter().map(|x| Bytes::from(x.encoded_2718())).collect();
+
+    let mut tx_with_state: Vec<TxState> = stuffing_txs_rlp.into_iter().map(TxState::ReadyForBroadcastStuffing).collect();
+
+    tx_with_state.push(TxState::SignatureRequired(tx_request));
+
+    let sign_request = MessageTxCompose::sign(TxComposeData { tx_bundle: Some(tx_with_state), ..estimate_request });
+
+    compose_channel_tx.send(sign_request).await.map(|_| ()).map_err(|e| {
+        error!("{e}");
+        eyre!("COMPOSE_CHANNEL_SEND_ERROR")
+    })
+}
+
+async fn estimator_worker(
+    swap_encoder: impl SwapEncoder + Clone,
     compose_channel_rx: Broadcaster<MessageTxCompose>,
     compose_channel_tx: Broadcaster<MessageTxCompose>,
+    chain_id: ChainId,
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
 ) -> WorkerResult {
     subscribe!(compose_channel_rx);
 
     loop {
         tokio::select! {
-                    msg = compose_channel_rx.recv() => {
-                        let compose_request_msg : Result<MessageTxCompose, RecvError> = msg;
-                        match compose_request_msg {
-                            Ok(compose_request) =>{
-                                if let TxCompose::Estimate(estimate_request) = compose_request.inner {
-                                    info!("Hardhat estimation");
-                                    let token_in = estimate_request.swap.get_first_token().cloned().ok_or(eyre!("NO_TOKEN"))?;
-
-                                    let tx_signer = estimate_request.signer.clone().ok_or(eyre!("NO_SIGNER"))?;
-
-                                    let gas_price = estimate_request.priority_gas_fee + estimate_request.next_block_base_fee;
-                                    let gas_cost = U256::from(100_000 * gas_price);
-
-                                    let profit = estimate_request.swap.abs_profit();
-                                    if profit.is_zero() {
-                                        return Err(eyre!("NO_PROFIT"));
-                                    }
-                                    let profit_eth = token_in.calc_eth_value(profit).ok_or(eyre!("CALC_ETH_VALUE_FAILED"))?;
-
-                                    let (to, _call_value, call_data, _) = swap_encoder.encode(
-                                        estimate_request.swap.clone(),
-                                        estimate_request.tips_pct,
-                                        Some(estimate_request.next_block_number),
-                                        Some(gas_cost),
-                                        Some(tx_signer.address()),
-                                        Some(estimate_request.eth_balance),
-                                    )?;
-
-                                    let tx_request = TransactionRequest {
-                                        transaction_type : Some(2),
-                                        chain_id : Some(1),
-                                        from: Some(tx_signer.address()),
-                                        to: Some(TxKind::Call(to)),
-                                        gas: Some(estimate_request.gas),
-                                        value: Some(U256::from(1000)),
-                                        input: TransactionInput::new(call_data),
-                                        nonce: Some(estimate_request.nonce ),
-                                        max_priority_fee_per_gas: Some(estimate_request.priority_gas_fee as u128),
-                                        max_fee_per_gas: Some(estimate_request.next_block_base_fee as u128), // TODO: Why not prio + base fee?
-                                        ..TransactionRequest::default()
-                                    };
-
-                                    let gas_price = estimate_request.priority_gas_fee + estimate_request.next_block_base_fee;
-
-                                    if U256::from(300_000 * gas_price) > profit_eth {
-                                        error!("Profit is too small");
-                                        return Err(eyre!("TOO_SMALL_PROFIT"));
-                                    }
-
-                                    let enveloped_txs : Result<Vec<TxEnvelope>,_>= estimate_request.stuffing_txs.iter().map(|item| item.clone().try_into()).collect();
-                                    let stuffing_txs_rlp : Vec<Bytes> = enveloped_txs?.into_iter().map(|x| Bytes::from(x.encoded_2718()) ).collect();
-
-                                    let mut tx_with_state: Vec<TxState> = stuffing_txs_rlp.into_iter().map(TxState::ReadyForBroadcastStuffing).collect();
-
-                                    tx_with_state.push(TxState::SignatureRequired(tx_request));
-
-                                    let sign_request = MessageTxCompose::sign(
-                                        TxComposeData{
-                                            tx_bundle : Some(tx_with_state),
-                                            ..estimate_request
-                                        }
-                                    );
-
-                                    if let Err(e) = compose_channel_tx.send(sign_request).await {
-                                        error!("{e}");
-                                    }
-                                }
+            msg = compose_channel_rx.recv() => {
+                let compose_request_msg : Result<MessageTxCompose, RecvError> = msg;
+                match compose_request_msg {
+                    Ok(compose_request) =>{
+                        if let TxCompose::Estimate(estimate_request) = compose_request.inner {
+                            let result = release_nonce_on_error(account_nonce_balance.clone(), estimate_request, |request| {
+                                estimator_task(request, swap_encoder.clone(), compose_channel_tx.clone(), chain_id)
+                            })
+                            .await;
+                            if let Err(e) = result {
+                                error!("Hardhat estimation failed : {e}");
                             }
+                        }
+                    }
                     Err(e)=>{error!("{e}")}
                 }
             }
@@ -98,10 +115,13 @@ async fn estimator_worker(
 }
 
 #[allow(dead_code)]
-#[derive(Consumer, Producer)]
+#[derive(Accessor, Consumer, Producer)]
 pub struct HardhatEstimatorActor<P, E> {
     client: P,
     encoder: E,
+    chain_id: ChainId,
+    #[accessor]
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
     #[consumer]
     compose_channel_rx: Option<Broadcaster<MessageTxCompose>>,
     #[producer]
@@ -114,7 +134,17 @@ where
     E: SwapEncoder + Send + Sync + Clone + 'static,
 {
     pub fn new(client: P, encoder: E) -> Self {
-        Self { client, encoder, compose_channel_tx: None, compose_channel_rx: None }
+        Self { client, encoder, chain_id: 1, account_nonce_balance: None, compose_channel_tx: None, compose_channel_rx: None }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain) -> Self {
+        Self {
+            chain_id: bc.chain_id(),
+            account_nonce_balance: Some(bc.nonce_and_balance()),
+            compose_channel_tx: Some(bc.compose_channel()),
+            compose_channel_rx: Some(bc.compose_channel()),
+            ..self
+        }
     }
 }
 
@@ -128,6 +158,8 @@ where
             self.encoder.clone(),
             self.compose_channel_rx.clone().unwrap(),
             self.compose_channel_tx.clone().unwrap(),
+            self.chain_id,
+            self.account_nonce_balance.clone(),
         ));
         Ok(vec![task])
     }
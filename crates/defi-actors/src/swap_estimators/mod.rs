@@ -5,3 +5,49 @@ mod hardhat;
 pub use evm::EvmEstimatorActor;
 pub use geth::GethEstimatorActor;
 pub use hardhat::HardhatEstimatorActor;
+
+use std::sync::Arc;
+
+use alloy_consensus::{SignableTransaction, TypedTransaction};
+use alloy_primitives::Bytes;
+use alloy_rpc_types::TransactionRequest;
+use defi_blockchain::Blockchain;
+use defi_entities::{ExecutionCostModel, GasPriceOracle, OpStackCostModel, TxCostModel};
+use defi_events::TxComposeData;
+use loom_actors::SharedState;
+
+/// Max fee per gas keeping the swap transaction valid if the base fee of the target block is projected higher by the gas price oracle.
+async fn max_fee_per_gas(gas_price_oracle: Option<&SharedState<GasPriceOracle>>, request: &TxComposeData) -> u128 {
+    let next_block_max_fee = request.next_block_base_fee + request.priority_gas_fee;
+    let oracle_max_fee = match gas_price_oracle {
+        Some(gas_price_oracle) => gas_price_oracle.read().await.max_fee_per_gas(request.next_block_number, request.priority_gas_fee),
+        None => None,
+    };
+    oracle_max_fee.unwrap_or_default().max(next_block_max_fee) as u128
+}
+
+/// Unsigned encoded transaction for the data fee of the cost model, call data is used if the transaction can't be built.
+fn unsigned_tx_data(tx_request: &TransactionRequest) -> Bytes {
+    match tx_request.clone().build_typed_tx() {
+        Ok(TypedTransaction::Eip1559(tx)) => {
+            let mut buf = Vec::new();
+            tx.encode_for_signing(&mut buf);
+            buf.into()
+        }
+        _ => tx_request.input.input().cloned().unwrap_or_default(),
+    }
+}
+
+/// Cost model set with `with_tx_cost_model` or the model of the chain, L1 data fee is added on OP stack chains.
+fn chain_tx_cost_model(tx_cost_model: Option<Arc<dyn TxCostModel>>, bc: &Blockchain) -> Option<Arc<dyn TxCostModel>> {
+    match tx_cost_model {
+        Some(tx_cost_model) => Some(tx_cost_model),
+        None if bc.chain_parameters().op_stack => Some(Arc::new(OpStackCostModel)),
+        None => None,
+    }
+}
+
+/// Execution gas only if no cost model is set.
+fn tx_cost_model_or_default(tx_cost_model: Option<&Arc<dyn TxCostModel>>) -> Arc<dyn TxCostModel> {
+    tx_cost_model.cloned().unwrap_or_else(|| Arc::new(ExecutionCostModel))
+}
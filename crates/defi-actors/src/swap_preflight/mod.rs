@@ -0,0 +1,3 @@
+pub use preflight_actor::BundlePreflightActor;
+
+mod preflight_actor;
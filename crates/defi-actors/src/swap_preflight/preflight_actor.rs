@@ -0,0 +1,448 @@
+use std::sync::Arc;
+
+use alloy_network::Ethereum;
+use alloy_primitives::{keccak256, Address, Bytes, TxHash, I256, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types::TransactionRequest;
+use alloy_sol_types::SolCall;
+use alloy_transport::Transport;
+use eyre::{eyre, Result};
+use revm::primitives::{Env, ExecutionResult, CANCUN};
+use revm::{DatabaseRef, Evm};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info};
+
+use defi_abi::IERC20;
+use defi_blockchain::Blockchain;
+use defi_entities::AccountNonceAndBalanceState;
+use defi_events::{
+    BundlePreflightMismatch, HealthEvent, Message, MessageHealthEvent, MessageTxCompose, RlpState, TxCompose, TxComposeData, TxState,
+};
+use flashbots::client::SimulatedBundle;
+use flashbots::Flashbots;
+use loom_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
+use loom_actors_macros::{Accessor, Consumer, Producer};
+use loom_revm_db::LoomInMemoryDB;
+use loom_utils::evm::{env_for_block, evm_call, tx_env_from_request};
+
+use crate::accounts_monitor::release_nonce_on_error;
+
+/// Deviation of the simulated value from the expected one, 10000 = 100%.
+fn deviation_pct(expected: I256, simulated: I256) -> u32 {
+    let diff = (simulated - expected).unsigned_abs();
+    if diff.is_zero() {
+        0
+    } else if expected.is_zero() {
+        10000
+    } else {
+        (diff * U256::from(10000) / expected.unsigned_abs()).saturating_to()
+    }
+}
+
+fn to_i256(value: U256) -> I256 {
+    I256::try_from(value).unwrap_or(I256::MAX)
+}
+
+/// ETH balances of the signer and the multicaller and the profit token balance of the multicaller.
+#[derive(Clone, Copy, Debug, Default)]
+struct BundleBalances {
+    signer_eth: U256,
+    multicaller_eth: U256,
+    multicaller_token: U256,
+}
+
+impl BundleBalances {
+    fn read(db: &LoomInMemoryDB, env: &Env, signer: Address, multicaller: Address, token: Option<Address>) -> Result<Self> {
+        let eth_balance = |address: Address| db.basic_ref(address).ok().flatten().map(|account| account.balance).unwrap_or_default();
+        let multicaller_token = match token {
+            Some(token) => {
+                let (output, _) = evm_call(db, env.clone(), token, IERC20::balanceOfCall { account: multicaller }.abi_encode())?;
+                IERC20::balanceOfCall::abi_decode_returns(&output, false)?._0
+            }
+            None => U256::ZERO,
+        };
+        Ok(Self { signer_eth: eth_balance(signer), multicaller_eth: eth_balance(multicaller), multicaller_token })
+    }
+}
+
+/// Executes our transactions of the request on the state it was estimated on and returns the change of the signer and
+/// the multicaller balances in ETH. Token balance change is converted with the profit rate of the swap.
+fn replay_balance_delta(request: &TxComposeData) -> Result<I256> {
+    let db = request.poststate.as_ref().ok_or(eyre!("NO_POSTSTATE"))?.as_ref().clone();
+    let backrun_txs: Vec<TransactionRequest> = request
+        .tx_bundle
+        .iter()
+        .flatten()
+        .filter_map(|tx_state| match tx_state {
+            TxState::SignatureRequired(tx) => Some(tx.clone()),
+            _ => None,
+        })
+        .collect();
+    let first_tx = backrun_txs.first().ok_or(eyre!("NO_BACKRUN_TXS"))?;
+    let signer = first_tx.from.unwrap_or_default();
+    let multicaller = first_tx.to.and_then(|to| to.to().cloned()).unwrap_or_default();
+    let token = request.swap.get_first_token().map(|token| token.get_address());
+
+    let mut env = env_for_block(request.next_block_number, request.next_block_timestamp);
+    env.block.basefee = U256::from(request.next_block_base_fee);
+
+    let before = BundleBalances::read(&db, &env, signer, multicaller, token)?;
+
+    let mut evm = Evm::builder().with_spec_id(CANCUN).with_db(db).with_env(Box::new(env.clone())).build();
+    for tx in backrun_txs.iter() {
+        *evm.tx_mut() = tx_env_from_request(tx);
+        match evm.transact_commit().map_err(|e| eyre!("TRANSACT_ERROR: {}", e))? {
+            ExecutionResult::Success { .. } => {}
+            ExecutionResult::Revert { .. } => return Err(eyre!("EXECUTION_REVERTED")),
+            ExecutionResult::Halt { reason, .. } => return Err(eyre!("EXECUTION_HALT: {:?}", reason)),
+        }
+    }
+    let (db, _) = evm.into_db_and_env_with_handler_cfg();
+
+    let after = BundleBalances::read(&db, &env, signer, multicaller, token)?;
+
+    let eth_delta =
+        to_i256(after.signer_eth) - to_i256(before.signer_eth) + to_i256(after.multicaller_eth) - to_i256(before.multicaller_eth);
+    let token_delta = to_i256(after.multicaller_token) - to_i256(before.multicaller_token);
+    Ok(eth_delta + token_delta_eth(token_delta, request.swap.abs_profit(), request.swap.abs_profit_eth()))
+}
+
+/// Converts the token balance change to ETH with the rate of the swap profit.
+fn token_delta_eth(token_delta: I256, profit: U256, profit_eth: U256) -> I256 {
+    if profit.is_zero() {
+        I256::ZERO
+    } else {
+        token_delta.saturating_mul(to_i256(profit_eth)) / to_i256(profit)
+    }
+}
+
+/// Compares the simulated bundle with the estimation, returns the mismatch if our transactions reverted
+/// or the coinbase diff or the balance delta deviate by more than max_deviation_pct.
+///
+/// Relay simulation does not return balances, so the balance delta is taken from the replay of our transactions
+/// on the estimation state, see [`replay_balance_delta`].
+fn check_simulation(
+    request: &TxComposeData,
+    tx_hashes: &[TxHash],
+    sim_result: &SimulatedBundle,
+    replayed_balance_delta: Result<I256>,
+    max_deviation_pct: u32,
+) -> Option<BundlePreflightMismatch> {
+    let tips = request.tips.unwrap_or_default();
+    let profit_eth = request.swap.abs_profit_eth();
+
+    let mut revert: Option<String> = None;
+    let mut gas_used = U256::ZERO;
+    let mut simulated_coinbase_diff = U256::ZERO;
+
+    for tx_hash in tx_hashes.iter() {
+        match sim_result.find_tx(*tx_hash) {
+            Some(tx_sim_result) => {
+                if revert.is_none() {
+                    revert = tx_sim_result.error.clone().or(tx_sim_result.revert.clone());
+                }
+                gas_used += tx_sim_result.gas_used;
+                simulated_coinbase_diff += tx_sim_result.coinbase_diff;
+            }
+            None => {
+                if revert.is_none() {
+                    revert = Some(format!("TX_NOT_FOUND_IN_SIMULATION {tx_hash}"));
+                }
+            }
+        }
+    }
+
+    // base fee is burnt and does not reach coinbase
+    let expected_coinbase_diff = tips.saturating_sub(gas_used * U256::from(request.next_block_base_fee));
+    let expected_balance_delta = to_i256(profit_eth) - to_i256(tips);
+    let simulated_balance_delta = match replayed_balance_delta {
+        Ok(balance_delta) => balance_delta,
+        Err(e) => {
+            revert = revert.or(Some(format!("BALANCE_REPLAY_FAILED {e}")));
+            I256::ZERO
+        }
+    };
+
+    if revert.is_none()
+        && deviation_pct(to_i256(expected_coinbase_diff), to_i256(simulated_coinbase_diff)) <= max_deviation_pct
+        && deviation_pct(expected_balance_delta, simulated_balance_delta) <= max_deviation_pct
+    {
+        return None;
+    }
+
+    Some(BundlePreflightMismatch {
+        block: request.next_block_number,
+        tx_hashes: tx_hashes.to_vec(),
+        swap: request.swap.to_string(),
+        revert,
+        expected_coinbase_diff,
+        simulated_coinbase_diff,
+        expected_balance_delta,
+        simulated_balance_delta,
+    })
+}
+
+async fn preflight_task<P, T>(
+    preflight_request: TxComposeData,
+    client: Arc<Flashbots<P, T>>,
+    max_deviation_pct: u32,
+    compose_channel_tx: Broadcaster<MessageTxCompose>,
+    pool_health_monitor_tx: Option<Broadcaster<MessageHealthEvent>>,
+) -> Result<()>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    let rlp_bundle = preflight_request.rlp_bundle.clone().ok_or(eyre!("NO_RLP_BUNDLE"))?;
+
+    // eth_callBundle cannot simulate transactions referenced by hash
+    if rlp_bundle.iter().any(|item| item.is_hash()) {
+        debug!(swap=%preflight_request.swap, "Bundle with hint hashes skips pre-flight");
+        return compose_channel_tx
+            .send(MessageTxCompose::broadcast(preflight_request))
+            .await
+            .map(|_| ())
+            .map_err(|_| eyre!("BROADCAST_ERROR"));
+    }
+
+    let txs: Vec<Bytes> = rlp_bundle.iter().map(|item| item.unwrap()).collect();
+    let tx_hashes: Vec<TxHash> =
+        rlp_bundle.iter().filter(|item| matches!(item, RlpState::Backrun(_))).map(|item| keccak256(item.unwrap())).collect();
+
+    let start_time = chrono::Local::now();
+
+    // simulate on the latest block state targeting the next block
+    let sim_result = match client.simulate_txes(txs, preflight_request.next_block_number.saturating_sub(1), None).await {
+        Ok(sim_result) => sim_result,
+        Err(e) => {
+            error!("Pre-flight simulation error {}", e);
+            return Err(eyre!("PREFLIGHT_SIMULATION_ERROR"));
+        }
+    };
+
+    let sim_duration = chrono::Local::now() - start_time;
+
+    let replayed_balance_delta = replay_balance_delta(&preflight_request);
+
+    match check_simulation(&preflight_request, &tx_hashes, &sim_result, replayed_balance_delta, max_deviation_pct) {
+        None => {
+            info!(
+                swap=%preflight_request.swap,
+                coinbase_diff=%sim_result.coinbase_diff,
+                duration=sim_duration.num_milliseconds(),
+                " +++ Pre-flight successful",
+            );
+            compose_channel_tx.send(MessageTxCompose::broadcast(preflight_request)).await.map(|_| ()).map_err(|_| eyre!("BROADCAST_ERROR"))
+        }
+        Some(mismatch) => {
+            error!(
+                swap=%mismatch.swap,
+                revert=?mismatch.revert,
+                expected_coinbase_diff=%mismatch.expected_coinbase_diff,
+                simulated_coinbase_diff=%mismatch.simulated_coinbase_diff,
+                expected_balance_delta=%mismatch.expected_balance_delta,
+                simulated_balance_delta=%mismatch.simulated_balance_delta,
+                " --- Pre-flight mismatch, bundle dropped",
+            );
+            if let Some(pool_health_monitor_tx) = pool_health_monitor_tx {
+                if let Err(e) = pool_health_monitor_tx.send(Message::new(HealthEvent::BundlePreflightMismatch(mismatch))).await {
+                    error!("pool_health_monitor_tx.send error : {}", e)
+                }
+            }
+            Err(eyre!("PREFLIGHT_MISMATCH"))
+        }
+    }
+}
+
+async fn preflight_worker<P, T>(
+    client: Arc<Flashbots<P, T>>,
+    max_deviation_pct: u32,
+    compose_channel_rx: Broadcaster<MessageTxCompose>,
+    compose_channel_tx: Broadcaster<MessageTxCompose>,
+    pool_health_monitor_tx: Option<Broadcaster<MessageHealthEvent>>,
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
+) -> WorkerResult
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    subscribe!(compose_channel_rx);
+
+    loop {
+        tokio::select! {
+            msg = compose_channel_rx.recv() => {
+                let compose_request_msg : Result<MessageTxCompose, RecvError> = msg;
+                match compose_request_msg {
+                    Ok(compose_request) =>{
+                        if let TxCompose::Preflight(preflight_request) = compose_request.inner {
+                            let (client, compose_channel_tx, pool_health_monitor_tx) =
+                                (client.clone(), compose_channel_tx.clone(), pool_health_monitor_tx.clone());
+                            tokio::task::spawn(
+                                release_nonce_on_error(account_nonce_balance.clone(), preflight_request, move |request| {
+                                    preflight_task(request, client, max_deviation_pct, compose_channel_tx, pool_health_monitor_tx)
+                                })
+                            );
+                        }
+                    }
+                    Err(e)=>{error!("{e}")}
+                }
+            }
+        }
+    }
+}
+
+/// Simulates signed bundles with eth_callBundle before broadcasting. Bundles that revert or deviate from
+/// the estimation are dropped and recorded as [`HealthEvent`](HealthEvent).
+/// Requires signers started [`with_preflight`](crate::TxSignersActor::with_preflight).
+#[derive(Accessor, Consumer, Producer)]
+pub struct BundlePreflightActor<P, T> {
+    client: Arc<Flashbots<P, T>>,
+    /// Maximum deviation of the simulation from the estimation, 10000 = 100%
+    max_deviation_pct: u32,
+    #[accessor]
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
+    #[consumer]
+    compose_channel_rx: Option<Broadcaster<MessageTxCompose>>,
+    #[producer]
+    compose_channel_tx: Option<Broadcaster<MessageTxCompose>>,
+    #[producer]
+    pool_health_monitor_tx: Option<Broadcaster<MessageHealthEvent>>,
+}
+
+impl<P, T> BundlePreflightActor<P, T>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    pub fn new(client: Arc<Flashbots<P, T>>) -> Self {
+        Self {
+            client,
+            max_deviation_pct: 1000,
+            account_nonce_balance: None,
+            compose_channel_rx: None,
+            compose_channel_tx: None,
+            pool_health_monitor_tx: None,
+        }
+    }
+
+    pub fn with_max_deviation_pct(self, max_deviation_pct: u32) -> Self {
+        Self { max_deviation_pct, ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain) -> Self {
+        Self {
+            account_nonce_balance: Some(bc.nonce_and_balance()),
+            compose_channel_rx: Some(bc.compose_channel()),
+            compose_channel_tx: Some(bc.compose_channel()),
+            pool_health_monitor_tx: Some(bc.pool_health_monitor_channel()),
+            ..self
+        }
+    }
+}
+
+impl<P, T> Actor for BundlePreflightActor<P, T>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(preflight_worker(
+            self.client.clone(),
+            self.max_deviation_pct,
+            self.compose_channel_rx.clone().unwrap(),
+            self.compose_channel_tx.clone().unwrap(),
+            self.pool_health_monitor_tx.clone(),
+            self.account_nonce_balance.clone(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "BundlePreflightActor"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloy_primitives::{Address, U64};
+    use flashbots::client::SimulatedTransaction;
+
+    use super::*;
+
+    fn sim_tx(hash: TxHash, coinbase_tip: u64, gas_fees: u64, revert: Option<String>) -> SimulatedTransaction {
+        SimulatedTransaction {
+            hash,
+            coinbase_diff: U256::from(coinbase_tip + gas_fees / 2),
+            coinbase_tip: U256::from(coinbase_tip),
+            gas_price: U256::from(20),
+            gas_used: U256::from(gas_fees / 20),
+            gas_fees: U256::from(gas_fees),
+            from: Address::ZERO,
+            to: None,
+            value: None,
+            error: None,
+            revert,
+            access_list: None,
+            logs: None,
+        }
+    }
+
+    fn sim_bundle(transactions: Vec<SimulatedTransaction>) -> SimulatedBundle {
+        SimulatedBundle {
+            hash: Default::default(),
+            coinbase_diff: transactions.iter().map(|tx| tx.coinbase_diff).sum(),
+            coinbase_tip: transactions.iter().map(|tx| tx.coinbase_tip).sum(),
+            gas_price: U256::from(20),
+            gas_used: transactions.iter().map(|tx| tx.gas_used).sum(),
+            gas_fees: transactions.iter().map(|tx| tx.gas_fees).sum(),
+            simulation_block: U64::from(1),
+            transactions,
+        }
+    }
+
+    #[test]
+    fn test_deviation_pct() {
+        assert_eq!(deviation_pct(I256::try_from(100).unwrap(), I256::try_from(100).unwrap()), 0);
+        assert_eq!(deviation_pct(I256::try_from(100).unwrap(), I256::try_from(90).unwrap()), 1000);
+        assert_eq!(deviation_pct(I256::try_from(-100).unwrap(), I256::try_from(-150).unwrap()), 5000);
+        assert_eq!(deviation_pct(I256::ZERO, I256::try_from(1).unwrap()), 10000);
+    }
+
+    #[test]
+    fn test_token_delta_eth() {
+        let delta = I256::try_from(-200).unwrap();
+        assert_eq!(token_delta_eth(delta, U256::from(100), U256::from(50)), I256::try_from(-100).unwrap());
+        assert_eq!(token_delta_eth(delta, U256::ZERO, U256::from(50)), I256::ZERO);
+    }
+
+    #[test]
+    fn test_check_simulation() {
+        let tx_hash = TxHash::repeat_byte(1);
+        // base fee 10, priority fee 10, 100000 gas used, 1000000 sent to coinbase
+        let request = TxComposeData { next_block_base_fee: 10, tips: Some(U256::from(3_000_000)), ..TxComposeData::default() };
+        let replayed = || Ok(I256::try_from(-3_000_000).unwrap());
+
+        let sim_result = sim_bundle(vec![sim_tx(TxHash::repeat_byte(2), 0, 2_000_000, None), sim_tx(tx_hash, 1_000_000, 2_000_000, None)]);
+        assert!(check_simulation(&request, &[tx_hash], &sim_result, replayed(), 1000).is_none());
+
+        // balances changed more than the estimation
+        let mismatch = check_simulation(&request, &[tx_hash], &sim_result, Ok(I256::try_from(-4_000_000).unwrap()), 1000).unwrap();
+        assert_eq!(mismatch.simulated_balance_delta, I256::try_from(-4_000_000).unwrap());
+        assert!(mismatch.revert.is_none());
+
+        let mismatch = check_simulation(&request, &[tx_hash], &sim_result, Err(eyre!("EXECUTION_REVERTED")), 1000).unwrap();
+        assert!(mismatch.revert.is_some());
+
+        let sim_result = sim_bundle(vec![sim_tx(tx_hash, 500_000, 2_000_000, None)]);
+        let mismatch = check_simulation(&request, &[tx_hash], &sim_result, replayed(), 1000).unwrap();
+        assert_eq!(mismatch.expected_coinbase_diff, U256::from(2_000_000));
+        assert_eq!(mismatch.simulated_coinbase_diff, U256::from(1_500_000));
+        assert!(mismatch.revert.is_none());
+
+        let sim_result = sim_bundle(vec![sim_tx(tx_hash, 1_000_000, 2_000_000, Some("REVERT".to_string()))]);
+        assert_eq!(check_simulation(&request, &[tx_hash], &sim_result, replayed(), 1000).unwrap().revert, Some("REVERT".to_string()));
+
+        let sim_result = sim_bundle(vec![]);
+        assert!(check_simulation(&request, &[tx_hash], &sim_result, replayed(), 1000).unwrap().revert.is_some());
+    }
+}
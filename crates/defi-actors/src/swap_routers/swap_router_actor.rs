@@ -1,7 +1,8 @@
 use alloy_primitives::utils::parse_units;
 use alloy_primitives::U256;
 use defi_blockchain::Blockchain;
-use defi_entities::{AccountNonceAndBalanceState, TxSigners};
+use defi_entities::tips::tips_pct_advanced;
+use defi_entities::{AccountNonceAndBalanceState, BidStats, GasPriceOracle, TxSigners};
 use defi_events::{MessageTxCompose, TxCompose, TxComposeData};
 use eyre::{eyre, Result};
 use lazy_static::lazy_static;
@@ -12,49 +13,90 @@ use tokio::sync::broadcast::Receiver;
 use tracing::{debug, error, info};
 
 lazy_static! {
-    static ref PRIORITY_GAS_FEE: u64 = parse_units("9", "gwei").unwrap().get_absolute().to::<u64>();
+    /// Used until the gas price oracle has priority fees of recent blocks
+    static ref DEFAULT_PRIORITY_GAS_FEE: u64 = parse_units("9", "gwei").unwrap().get_absolute().to::<u64>();
 }
 
+/// Median priority fee of the recent blocks transactions
+const DEFAULT_PRIORITY_FEE_PCT: u32 = 50;
+
 /// encoder task performs encode for request
 async fn router_task(
     route_request: TxComposeData,
     compose_channel_tx: Broadcaster<MessageTxCompose>,
     signers: SharedState<TxSigners>,
     account_monitor: SharedState<AccountNonceAndBalanceState>,
+    gas_price_oracle: Option<SharedState<GasPriceOracle>>,
+    bid_stats: Option<SharedState<BidStats>>,
+    priority_fee_pct: u32,
 ) -> Result<()> {
     debug!("Routing started {}", route_request.swap);
 
-    let signer = signers.read().await.get_randon_signer();
+    let signer = {
+        let signers = signers.read().await;
+        let accounts = account_monitor.read().await;
+        // signers with a transaction waiting for inclusion are used only if all of them are busy
+        signers
+            .get_address_vec()
+            .into_iter()
+            .find(|address| accounts.get_account(address).is_some_and(|account| account.has_free_nonce()))
+            .and_then(|address| signers.get_signer_by_address(&address).ok())
+            .or_else(|| signers.get_randon_signer())
+    };
     match signer {
         Some(signer) => {
-            let nonce = account_monitor.read().await.get_account(&signer.address()).unwrap().get_nonce();
-            let eth_balance = account_monitor.read().await.get_account(&signer.address()).unwrap().get_eth_balance();
-
             if route_request.next_block_base_fee == 0 {
                 error!("Block base fee is not set");
-                Err(eyre!("NO_BLOCK_GAS_FEE"))
-            } else {
-                let gas = (route_request.swap.pre_estimate_gas()) * 2;
-                let value = U256::ZERO;
-
-                let priority_gas_fee: u64 = if route_request.next_block_base_fee > *PRIORITY_GAS_FEE {
-                    *PRIORITY_GAS_FEE
-                } else {
-                    route_request.next_block_base_fee
-                };
-
-                let estimate_request =
-                    TxComposeData { signer: Some(signer), nonce, eth_balance, gas, priority_gas_fee, value, ..route_request };
-
-                let estimate_request = MessageTxCompose::estimate(estimate_request);
-
-                match compose_channel_tx.send(estimate_request).await {
-                    Err(e) => {
-                        error!("{e}");
-                        Err(eyre!(e))
-                    }
-                    Ok(_) => Ok(()),
+                return Err(eyre!("NO_BLOCK_GAS_FEE"));
+            }
+
+            let (reserved_nonce, eth_balance) = {
+                let mut accounts = account_monitor.write().await;
+                let account = accounts.get_mut_account(&signer.address()).ok_or(eyre!("SIGNER_ACCOUNT_NOT_MONITORED"))?;
+                (account.reserve_nonce(route_request.next_block_number), account.get_eth_balance())
+            };
+            debug!(signer = %signer.address(), nonce = reserved_nonce.nonce, "Nonce reserved");
+
+            let gas = (route_request.swap.pre_estimate_gas()) * 2;
+            let value = U256::ZERO;
+
+            let oracle_priority_gas_fee = match &gas_price_oracle {
+                Some(gas_price_oracle) => gas_price_oracle.read().await.priority_fee(priority_fee_pct),
+                None => None,
+            };
+            let priority_gas_fee: u64 =
+                oracle_priority_gas_fee.unwrap_or_else(|| (*DEFAULT_PRIORITY_GAS_FEE).min(route_request.next_block_base_fee));
+
+            // tips of pool pairs with lost backruns are raised
+            let tips_pct = match &bid_stats {
+                Some(bid_stats) => {
+                    let tips_pct = route_request.tips_pct.unwrap_or_else(|| tips_pct_advanced(&route_request.swap.abs_profit_eth()));
+                    Some(bid_stats.read().await.adjusted_tips_pct(&route_request.swap.get_pool_address_vec(), tips_pct))
+                }
+                None => route_request.tips_pct,
+            };
+
+            let estimate_request = TxComposeData {
+                signer: Some(signer.clone()),
+                nonce: reserved_nonce.nonce,
+                reserved_nonce: Some(reserved_nonce),
+                eth_balance,
+                gas,
+                priority_gas_fee,
+                value,
+                tips_pct,
+                ..route_request
+            };
+
+            let estimate_request = MessageTxCompose::estimate(estimate_request);
+
+            match compose_channel_tx.send(estimate_request).await {
+                Err(e) => {
+                    error!("{e}");
+                    account_monitor.write().await.release_nonce(&signer.address(), reserved_nonce);
+                    Err(eyre!(e))
                 }
+                Ok(_) => Ok(()),
             }
         }
         None => Err(eyre!("NO_SIGNER_AVAILABLE")),
@@ -64,6 +106,9 @@ async fn router_task(
 async fn swap_router_worker(
     signers: SharedState<TxSigners>,
     account_monitor: SharedState<AccountNonceAndBalanceState>,
+    gas_price_oracle: Option<SharedState<GasPriceOracle>>,
+    bid_stats: Option<SharedState<BidStats>>,
+    priority_fee_pct: u32,
     compose_channel_rx: Broadcaster<MessageTxCompose>,
     compose_channel_tx: Broadcaster<MessageTxCompose>,
 ) -> WorkerResult {
@@ -85,6 +130,9 @@ async fn swap_router_worker(
                                     compose_channel_tx.clone(),
                                     signers.clone(),
                                     account_monitor.clone(),
+                                    gas_price_oracle.clone(),
+                                    bid_stats.clone(),
+                                    priority_fee_pct,
                                 )
                             );
                         }
@@ -96,12 +144,17 @@ async fn swap_router_worker(
     }
 }
 
-#[derive(Consumer, Producer, Accessor, Default)]
+#[derive(Consumer, Producer, Accessor)]
 pub struct SwapRouterActor {
     #[accessor]
     signers: Option<SharedState<TxSigners>>,
     #[accessor]
     account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
+    #[accessor]
+    gas_price_oracle: Option<SharedState<GasPriceOracle>>,
+    #[accessor]
+    bid_stats: Option<SharedState<BidStats>>,
+    priority_fee_pct: u32,
     #[consumer]
     compose_channel_rx: Option<Broadcaster<MessageTxCompose>>,
     #[producer]
@@ -110,7 +163,20 @@ pub struct SwapRouterActor {
 
 impl SwapRouterActor {
     pub fn new() -> SwapRouterActor {
-        SwapRouterActor { signers: None, account_nonce_balance: None, compose_channel_rx: None, compose_channel_tx: None }
+        SwapRouterActor {
+            signers: None,
+            account_nonce_balance: None,
+            gas_price_oracle: None,
+            bid_stats: None,
+            priority_fee_pct: DEFAULT_PRIORITY_FEE_PCT,
+            compose_channel_rx: None,
+            compose_channel_tx: None,
+        }
+    }
+
+    /// Priority fee percentile of the recent blocks transactions used for the swap transactions.
+    pub fn with_priority_fee_pct(self, priority_fee_pct: u32) -> Self {
+        Self { priority_fee_pct, ..self }
     }
 
     pub fn with_signers(self, signers: SharedState<TxSigners>) -> Self {
@@ -120,6 +186,8 @@ impl SwapRouterActor {
     pub fn on_bc(self, bc: &Blockchain) -> Self {
         Self {
             account_nonce_balance: Some(bc.nonce_and_balance()),
+            gas_price_oracle: Some(bc.gas_price_oracle()),
+            bid_stats: Some(bc.bid_stats()),
             compose_channel_rx: Some(bc.compose_channel()),
             compose_channel_tx: Some(bc.compose_channel()),
             ..self
@@ -127,11 +195,20 @@ impl SwapRouterActor {
     }
 }
 
+impl Default for SwapRouterActor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Actor for SwapRouterActor {
     fn start(&self) -> ActorResult {
         let task = tokio::task::spawn(swap_router_worker(
             self.signers.clone().unwrap(),
             self.account_nonce_balance.clone().unwrap(),
+            self.gas_price_oracle.clone(),
+            self.bid_stats.clone(),
+            self.priority_fee_pct,
             self.compose_channel_rx.clone().unwrap(),
             self.compose_channel_tx.clone().unwrap(),
         ));
@@ -6,11 +6,14 @@ use tokio::sync::broadcast::Receiver;
 use tracing::{error, info};
 
 use defi_blockchain::Blockchain;
+use defi_entities::AccountNonceAndBalanceState;
 use defi_events::{MessageTxCompose, RlpState, TxCompose, TxComposeData, TxState};
-use loom_actors::{Actor, ActorResult, Broadcaster, Consumer, Producer, WorkerResult};
+use loom_actors::{Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
 use loom_actors_macros::{Accessor, Consumer, Producer};
 
-async fn sign_task(sign_request: TxComposeData, compose_channel_tx: Broadcaster<MessageTxCompose>) -> Result<()> {
+use crate::accounts_monitor::release_nonce_on_error;
+
+async fn sign_task(sign_request: TxComposeData, preflight: bool, compose_channel_tx: Broadcaster<MessageTxCompose>) -> Result<()> {
     let signer = match sign_request.signer.clone() {
         Some(signer) => signer,
         None => {
@@ -22,7 +25,7 @@ async fn sign_task(sign_request: TxComposeData, compose_channel_tx: Broadcaster<
     let rlp_bundle: Vec<RlpState> = sign_request
         .tx_bundle
         .clone()
-        .unwrap()
+        .ok_or(eyre!("NO_TX_BUNDLE"))?
         .iter()
         .map(|tx_request| match &tx_request {
             TxState::Stuffing(t) => {
@@ -37,13 +40,19 @@ async fn sign_task(sign_request: TxComposeData, compose_channel_tx: Broadcaster<
                     _ => RlpState::None,
                 }
             }
-            TxState::SignatureRequired(t) => {
-                let (tx_hash, signed_tx_bytes) = signer.sign_sync(t.clone()).unwrap();
-                info!("Tx signed {tx_hash:?}");
-                RlpState::Backrun(signed_tx_bytes)
-            }
+            TxState::SignatureRequired(t) => match signer.sign_sync(t.clone()) {
+                Ok((tx_hash, signed_tx_bytes)) => {
+                    info!("Tx signed {tx_hash:?}");
+                    RlpState::Backrun(signed_tx_bytes)
+                }
+                Err(e) => {
+                    error!("Signing failed : {e}");
+                    RlpState::None
+                }
+            },
             TxState::ReadyForBroadcast(t) => RlpState::Backrun(t.clone()),
             TxState::ReadyForBroadcastStuffing(t) => RlpState::Stuffing(t.clone()),
+            TxState::StuffingHash(tx_hash) => RlpState::StuffingHash(*tx_hash),
         })
         .collect();
 
@@ -54,7 +63,12 @@ async fn sign_task(sign_request: TxComposeData, compose_channel_tx: Broadcaster<
 
     let broadcast_request = TxComposeData { rlp_bundle: Some(rlp_bundle), ..sign_request };
 
-    match compose_channel_tx.send(MessageTxCompose::broadcast(broadcast_request)).await {
+    let broadcast_msg = match preflight {
+        true => MessageTxCompose::preflight(broadcast_request),
+        false => MessageTxCompose::broadcast(broadcast_request),
+    };
+
+    match compose_channel_tx.send(broadcast_msg).await {
         Err(e) => {
             error!("{e}");
             Err(eyre!("BROADCAST_ERROR"))
@@ -64,8 +78,10 @@ async fn sign_task(sign_request: TxComposeData, compose_channel_tx: Broadcaster<
 }
 
 async fn request_listener_worker(
+    preflight: bool,
     compose_channel_rx: Broadcaster<MessageTxCompose>,
     compose_channel_tx: Broadcaster<MessageTxCompose>,
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
 ) -> WorkerResult {
     let mut compose_channel_rx: Receiver<MessageTxCompose> = compose_channel_rx.subscribe().await;
 
@@ -77,11 +93,11 @@ async fn request_listener_worker(
                     Ok(compose_request) =>{
 
                         if let TxCompose::Sign( sign_request)= compose_request.inner {
+                            let compose_channel_tx = compose_channel_tx.clone();
                             tokio::task::spawn(
-                                sign_task(
-                                    sign_request,
-                                    compose_channel_tx.clone(),
-                                )
+                                release_nonce_on_error(account_nonce_balance.clone(), sign_request, move |request| {
+                                    sign_task(request, preflight, compose_channel_tx)
+                                })
                             );
                         }
                     }
@@ -94,6 +110,9 @@ async fn request_listener_worker(
 
 #[derive(Accessor, Consumer, Producer, Default)]
 pub struct TxSignersActor {
+    preflight: bool,
+    #[accessor]
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
     #[consumer]
     compose_channel_rx: Option<Broadcaster<MessageTxCompose>>,
     #[producer]
@@ -105,15 +124,29 @@ impl TxSignersActor {
         TxSignersActor::default()
     }
 
+    /// Signed bundles are sent to pre-flight simulation instead of broadcasting
+    pub fn with_preflight(self) -> Self {
+        Self { preflight: true, ..self }
+    }
+
     pub fn on_bc(self, bc: &Blockchain) -> Self {
-        Self { compose_channel_rx: Some(bc.compose_channel()), compose_channel_tx: Some(bc.compose_channel()) }
+        Self {
+            account_nonce_balance: Some(bc.nonce_and_balance()),
+            compose_channel_rx: Some(bc.compose_channel()),
+            compose_channel_tx: Some(bc.compose_channel()),
+            ..self
+        }
     }
 }
 
 impl Actor for TxSignersActor {
     fn start(&self) -> ActorResult {
-        let task =
-            tokio::task::spawn(request_listener_worker(self.compose_channel_rx.clone().unwrap(), self.compose_channel_tx.clone().unwrap()));
+        let task = tokio::task::spawn(request_listener_worker(
+            self.preflight,
+            self.compose_channel_rx.clone().unwrap(),
+            self.compose_channel_tx.clone().unwrap(),
+            self.account_nonce_balance.clone(),
+        ));
 
         Ok(vec![task])
     }
@@ -2,26 +2,42 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use alloy_network::Ethereum;
-use alloy_primitives::{Bytes, U256};
+use alloy_primitives::{Address, Bytes, TxHash, U256, U64};
 use alloy_provider::Provider;
 use alloy_transport::Transport;
 use eyre::{eyre, Result};
+use influxdb::{Timestamp, WriteQuery};
+use revm::primitives::CANCUN;
+use revm::Evm;
+use tokio::sync::broadcast;
 use tokio::sync::broadcast::error::RecvError;
-use tracing::{error, info};
+use tracing::{debug, error, info, warn};
 
+use super::targeting::{
+    cancelled_uuids, take_gone, take_superseded, BlockTargetingPolicy, PendingBundle, ReplacementUuids, ResubmitDecision,
+};
+use crate::accounts_monitor::{confirm_request_nonce, release_nonce_on_error, release_request_nonce};
 use defi_blockchain::Blockchain;
-use defi_events::{BestTxCompose, MessageTxCompose, RlpState, TxCompose, TxComposeData};
-use flashbots::Flashbots;
-use loom_actors::{subscribe, Actor, ActorResult, Broadcaster, Consumer, WorkerResult};
-use loom_actors_macros::{Accessor, Consumer};
+use defi_entities::{AccountNonceAndBalanceState, BlockHistory, BlockHistoryEntry};
+use defi_events::{BestTxCompose, MarketEvents, MessageTxCompose, RlpState, TxCompose, TxComposeData};
+use defi_types::ChainParameters;
+use flashbots::client::MevSendBundleRequest;
+use flashbots::{Flashbots, RelaySubmission};
+use loom_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
+use loom_actors_macros::{Accessor, Consumer, Producer};
+use loom_utils::evm::{env_for_block, evm_transact};
 
-async fn broadcast_task<P, T>(broadcast_request: TxComposeData, client: Arc<Flashbots<P, T>>) -> Result<()>
+/// Broadcasts the backrun only and the stuffing bundles for the block
+async fn broadcast_task<P, T>(
+    broadcast_request: TxComposeData,
+    client: Arc<Flashbots<P, T>>,
+    block_number: u64,
+    replacement_uuids: ReplacementUuids,
+) -> Result<()>
 where
     T: Transport + Clone,
     P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
 {
-    let block_number = broadcast_request.next_block_number;
-
     if let Some(rlp_bundle) = broadcast_request.rlp_bundle.clone() {
         let stuffing_rlp_bundle: Vec<Bytes> = rlp_bundle.iter().map(|item| item.unwrap()).collect();
         let backrun_rlp_bundle: Vec<Bytes> =
@@ -30,12 +46,8 @@ where
         if stuffing_rlp_bundle.iter().any(|i| i.is_empty()) || backrun_rlp_bundle.iter().any(|i| i.is_empty()) {
             Err(eyre!("RLP_BUNDLE_IS_INCORRECT"))
         } else {
-            client.broadcast_txes(backrun_rlp_bundle.clone(), block_number).await?;
-            client.broadcast_txes(stuffing_rlp_bundle.clone(), block_number).await?;
-
-            tokio::time::sleep(Duration::from_millis(300)).await;
-            client.broadcast_txes(stuffing_rlp_bundle, block_number + 1).await?;
-            client.broadcast_txes(backrun_rlp_bundle.clone(), block_number + 1).await?;
+            client.broadcast_txes_with_replacement(backrun_rlp_bundle, block_number, Some(replacement_uuids.backrun)).await?;
+            client.broadcast_txes_with_replacement(stuffing_rlp_bundle, block_number, Some(replacement_uuids.stuffing)).await?;
             Ok(())
         }
     } else {
@@ -44,10 +56,200 @@ where
     }
 }
 
+/// Confirms the nonce reservation of the request until the last target block if the broadcast succeeded, releases it otherwise
+async fn update_nonce_reservation(
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
+    request: &TxComposeData,
+    max_block: u64,
+    broadcasted: bool,
+) {
+    if broadcasted {
+        confirm_request_nonce(account_nonce_balance.as_ref(), request, max_block).await;
+    } else {
+        release_request_nonce(account_nonce_balance.as_ref(), request).await;
+    }
+}
+
+/// Broadcasts the request and updates the nonce reservation of its signer with the outcome
+async fn broadcast_reserved_task<P, T>(
+    broadcast_request: TxComposeData,
+    client: Arc<Flashbots<P, T>>,
+    max_block_number: u64,
+    replacement_uuids: ReplacementUuids,
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
+) -> Result<()>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    let block_number = broadcast_request.next_block_number;
+    let result = match broadcast_request.rlp_bundle.clone().filter(|rlp_bundle| rlp_bundle.iter().any(|item| item.is_hash())) {
+        // mev_sendBundle targets the block range at once
+        Some(rlp_bundle) => broadcast_mev_share_task(rlp_bundle, block_number, max_block_number, client).await,
+        None => broadcast_task(broadcast_request.clone(), client, block_number, replacement_uuids).await,
+    };
+    update_nonce_reservation(account_nonce_balance, &broadcast_request, max_block_number, result.is_ok()).await;
+    result
+}
+
+/// Resubmits the signed bundle for a later block after the wait
+async fn resubmit_task<P, T>(
+    broadcast_request: TxComposeData,
+    client: Arc<Flashbots<P, T>>,
+    block_number: u64,
+    wait: Duration,
+    replacement_uuids: ReplacementUuids,
+) -> Result<()>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    tokio::time::sleep(wait).await;
+    debug!(block_number, swap=%broadcast_request.swap, "Resubmitting bundle");
+    broadcast_task(broadcast_request, client, block_number, replacement_uuids).await
+}
+
+/// Cancels the dropped bundles on relays supporting cancellation and releases their nonce reservations. Bundles replaced by
+/// the request keep its UUIDs and nonce reservation.
+async fn drop_bundles<P, T>(
+    client: &Flashbots<P, T>,
+    account_nonce_balance: Option<&SharedState<AccountNonceAndBalanceState>>,
+    dropped: Vec<PendingBundle>,
+    replaced_by: Option<(&TxComposeData, &ReplacementUuids)>,
+    reason: &str,
+) where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    for replacement_uuid in cancelled_uuids(&dropped, replaced_by.map(|(_, uuids)| uuids)).iter().flat_map(|uuids| uuids.to_vec()) {
+        debug!(%replacement_uuid, reason, "Cancelling bundle");
+        if let Err(e) = client.cancel_bundle(replacement_uuid).await {
+            error!("Bundle cancellation failed : {}", e);
+        }
+    }
+    for bundle in dropped.iter() {
+        let same_reservation = replaced_by.is_some_and(|(request, _)| {
+            request.reserved_nonce.is_some()
+                && request.reserved_nonce == bundle.request.reserved_nonce
+                && request.signer.as_ref().map(|signer| signer.address()) == bundle.request.signer.as_ref().map(|signer| signer.address())
+        });
+        if !same_reservation {
+            release_request_nonce(account_nonce_balance, &bundle.request).await;
+        }
+    }
+}
+
+/// Sends the request to estimation for the block following the history entry. Stuffing txs are applied on top of the block state,
+/// the opportunity is gone if any of them fails.
+async fn re_estimate_task(
+    request: TxComposeData,
+    entry: BlockHistoryEntry,
+    chain_parameters: ChainParameters,
+    blocks_left: u64,
+    compose_channel_tx: Broadcaster<MessageTxCompose>,
+) -> Result<()> {
+    let state_db = entry.state_db.clone().ok_or(eyre!("STATE_DB_NOT_FOUND"))?;
+    let next_block_number = entry.number() + 1;
+    let next_block_timestamp = chain_parameters.next_block_timestamp(entry.timestamp());
+    let next_base_fee = chain_parameters.calc_next_block_base_fee_from_header(&entry.header);
+
+    let env = env_for_block(next_block_number, next_block_timestamp);
+    let mut evm = Evm::builder().with_spec_id(CANCUN).with_db(state_db).with_env(Box::new(env)).build();
+    for tx in request.stuffing_txs.iter() {
+        if let Err(e) = evm_transact(&mut evm, tx) {
+            debug!(tx_hash = %tx.hash, "Stuffing tx failed on the new state : {}", e);
+            return Err(eyre!("STUFFING_TX_FAILED"));
+        }
+    }
+    let (poststate, _) = evm.into_db_and_env_with_handler_cfg();
+
+    let estimate_request = TxComposeData {
+        next_block_number,
+        next_block_timestamp,
+        next_block_base_fee: next_base_fee,
+        poststate: Some(Arc::new(poststate)),
+        poststate_update: None,
+        tx_bundle: None,
+        rlp_bundle: None,
+        tips: None,
+        resubmit_blocks_left: Some(blocks_left),
+        ..request
+    };
+
+    match compose_channel_tx.send(MessageTxCompose::estimate(estimate_request)).await {
+        Err(e) => {
+            error!("{e}");
+            Err(eyre!("COMPOSE_CHANNEL_SEND_ERROR"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Bundle with stuffing txs referenced by hint hash is sent with `mev_sendBundle`
+async fn broadcast_mev_share_task<P, T>(
+    rlp_bundle: Vec<RlpState>,
+    block_number: u64,
+    max_block_number: u64,
+    client: Arc<Flashbots<P, T>>,
+) -> Result<()>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    let mut request = MevSendBundleRequest::new().set_block(U64::from(block_number)).set_max_block(U64::from(max_block_number));
+
+    for item in rlp_bundle.into_iter() {
+        request = match item {
+            RlpState::StuffingHash(tx_hash) => request.push_hash(tx_hash),
+            RlpState::Stuffing(rlp) | RlpState::Backrun(rlp) if !rlp.is_empty() => request.push_transaction(rlp, false),
+            _ => return Err(eyre!("RLP_BUNDLE_IS_INCORRECT")),
+        };
+    }
+
+    client.send_mev_bundle(request).await
+}
+
+/// Broadcasts the request for its target block. Returns the pending bundle if the request is resubmitted for later blocks.
+fn broadcast_new_request<P, T>(
+    broadcast_request: TxComposeData,
+    client: Arc<Flashbots<P, T>>,
+    policy: &BlockTargetingPolicy,
+    replacement_uuids: ReplacementUuids,
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
+) -> Option<PendingBundle>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    let block_number = broadcast_request.next_block_number;
+    let resubmit_blocks = policy.resubmit_blocks(&broadcast_request);
+
+    let is_mev_share = broadcast_request.rlp_bundle.as_ref().is_some_and(|rlp_bundle| rlp_bundle.iter().any(|item| item.is_hash()));
+
+    let pending_bundle = (resubmit_blocks > 0 && !is_mev_share)
+        .then(|| PendingBundle::new(broadcast_request.clone(), resubmit_blocks, replacement_uuids.clone()));
+    tokio::task::spawn(broadcast_reserved_task(
+        broadcast_request,
+        client,
+        block_number + resubmit_blocks,
+        replacement_uuids,
+        account_nonce_balance,
+    ));
+    pending_bundle
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn flashbots_broadcaster_worker<P, T>(
     client: Arc<Flashbots<P, T>>,
     smart_mode: bool,
+    smart_pct: u32,
+    policy: BlockTargetingPolicy,
+    chain_parameters: ChainParameters,
+    block_history: SharedState<BlockHistory>,
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
     bundle_rx: Broadcaster<MessageTxCompose>,
+    market_events_rx: Broadcaster<MarketEvents>,
+    compose_channel_tx: Broadcaster<MessageTxCompose>,
     allow_broadcast: bool,
 ) -> WorkerResult
 where
@@ -55,9 +257,13 @@ where
     P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
 {
     subscribe!(bundle_rx);
+    subscribe!(market_events_rx);
 
     let mut current_block: u64 = 0;
     let mut best_request: BestTxCompose = Default::default();
+    // Best bundles are sent with the replacement UUIDs of their target block, so they supersede the previous best ones.
+    let mut replacement_uuids = ReplacementUuids::new();
+    let mut pending_bundles: Vec<PendingBundle> = Vec::new();
 
     loop {
         tokio::select! {
@@ -68,31 +274,56 @@ where
                         if let TxCompose::Broadcast(broadcast_request)  = compose_request.inner {
                             if smart_mode {
                                 if current_block < broadcast_request.next_block_number {
+                                    replacement_uuids = ReplacementUuids::new();
                                     current_block = broadcast_request.next_block_number;
-                                    best_request = BestTxCompose::new_with_pct( U256::from(8000));
+                                    best_request = BestTxCompose::new_with_pct(U256::from(smart_pct));
                                 }
 
+                                let is_better_profit = best_request.is_better_profit(&broadcast_request);
+
                                 if best_request.check(&broadcast_request) {
                                     if allow_broadcast {
-                                         tokio::task::spawn(
-                                            broadcast_task(
-                                            broadcast_request,
-                                            client.clone(),
+                                        let request_uuids = if is_better_profit { replacement_uuids.clone() } else { ReplacementUuids::new() };
+                                        let superseded = take_superseded(&mut pending_bundles, &broadcast_request);
+                                        drop_bundles(
+                                            &client,
+                                            account_nonce_balance.as_ref(),
+                                            superseded,
+                                            Some((&broadcast_request, &request_uuids)),
+                                            "superseded",
+                                        ).await;
+                                        pending_bundles.extend(
+                                            broadcast_new_request(
+                                                broadcast_request,
+                                                client.clone(),
+                                                &policy,
+                                                request_uuids,
+                                                account_nonce_balance.clone(),
                                             )
                                         );
                                     } else {
                                        info!("broadcast_request (best_request): {:?}", broadcast_request);
+                                       update_nonce_reservation(account_nonce_balance.clone(), &broadcast_request, 0, false).await;
                                     }
+                                } else {
+                                    update_nonce_reservation(account_nonce_balance.clone(), &broadcast_request, 0, false).await;
                                 }
                             } else if allow_broadcast {
-                                      tokio::task::spawn(
-                                        broadcast_task(
-                                            broadcast_request,
-                                            client.clone(),
-                                        )
-                                    );
+                                let request_uuids = ReplacementUuids::new();
+                                let superseded = take_superseded(&mut pending_bundles, &broadcast_request);
+                                drop_bundles(
+                                    &client,
+                                    account_nonce_balance.as_ref(),
+                                    superseded,
+                                    Some((&broadcast_request, &request_uuids)),
+                                    "superseded",
+                                ).await;
+                                pending_bundles.extend(
+                                    broadcast_new_request(broadcast_request, client.clone(), &policy, request_uuids, account_nonce_balance.clone())
+                                );
                             } else {
                                 info!("broadcast_request: {:?}", broadcast_request);
+                                update_nonce_reservation(account_nonce_balance.clone(), &broadcast_request, 0, false).await;
                             }
                         }
                     }
@@ -101,16 +332,150 @@ where
                     }
                 }
             }
+
+            msg = market_events_rx.recv() => {
+                let market_event_msg : Result<MarketEvents, RecvError> = msg;
+                match market_event_msg {
+                    Ok(MarketEvents::BlockStateUpdate{ block_hash }) => {
+                        if pending_bundles.is_empty() {
+                            continue
+                        }
+
+                        let Some(entry) = block_history.read().await.get_entry(&block_hash).cloned() else {
+                            error!(%block_hash, "Block not found in block history");
+                            continue
+                        };
+
+                        let block_number = entry.number();
+                        let block_txs: Vec<TxHash> = entry.block.as_ref().map(|block| block.transactions.hashes().collect()).unwrap_or_default();
+                        let changed_addresses: Vec<Address> = entry
+                            .state_update
+                            .as_ref()
+                            .map(|state_update| state_update.iter().flat_map(|state| state.keys().cloned()).collect())
+                            .unwrap_or_default();
+
+                        let gone = take_gone(&mut pending_bundles, block_number, &block_txs, &changed_addresses);
+                        drop_bundles(&client, account_nonce_balance.as_ref(), gone, None, "opportunity gone").await;
+
+                        for pending_bundle in std::mem::take(&mut pending_bundles) {
+                            if pending_bundle.target_block > block_number {
+                                pending_bundles.push(pending_bundle);
+                                continue
+                            }
+                            if pending_bundle.target_block < block_number {
+                                warn!(target_block = pending_bundle.target_block, "Target block state update not received");
+                                release_request_nonce(account_nonce_balance.as_ref(), &pending_bundle.request).await;
+                                continue
+                            }
+
+                            let decision = pending_bundle.decision(&block_txs, &changed_addresses);
+                            let blocks_left = pending_bundle.blocks_left - 1;
+
+                            match decision {
+                                ResubmitDecision::Landed => {
+                                    info!(block_number, swap=%pending_bundle.request.swap, "Bundle transactions landed, resubmission stopped");
+                                    release_request_nonce(account_nonce_balance.as_ref(), &pending_bundle.request).await;
+                                }
+                                ResubmitDecision::PoolsChanged if !policy.re_estimate => {
+                                    info!(block_number, swap=%pending_bundle.request.swap, "Swap pools changed, resubmission stopped");
+                                    release_request_nonce(account_nonce_balance.as_ref(), &pending_bundle.request).await;
+                                }
+                                _ if policy.re_estimate => {
+                                    // re-estimated request holds the reservation of the bundle until it is broadcasted or dropped
+                                    let (entry, chain_parameters, compose_channel_tx) =
+                                        (entry.clone(), chain_parameters.clone(), compose_channel_tx.clone());
+                                    tokio::task::spawn(
+                                        release_nonce_on_error(account_nonce_balance.clone(), pending_bundle.request, move |request| {
+                                            re_estimate_task(request, entry, chain_parameters, blocks_left, compose_channel_tx)
+                                        })
+                                    );
+                                }
+                                _ => {
+                                    tokio::task::spawn(
+                                        resubmit_task(
+                                            pending_bundle.request.clone(),
+                                            client.clone(),
+                                            block_number + 1,
+                                            policy.resubmit_wait(entry.timestamp()),
+                                            pending_bundle.replacement_uuids.clone(),
+                                        )
+                                    );
+                                    if blocks_left > 0 {
+                                        pending_bundles.push(PendingBundle { target_block: block_number + 1, blocks_left, ..pending_bundle });
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e)=>{
+                        error!("market_events_rx error : {e}")
+                    }
+                }
+            }
         }
     }
 }
 
-#[derive(Accessor, Consumer)]
+/// Worker writes relay responses of every submission to influxdb
+async fn relay_submissions_worker(
+    mut submissions_rx: broadcast::Receiver<RelaySubmission>,
+    influxdb_write_channel_tx: Broadcaster<WriteQuery>,
+) -> WorkerResult {
+    loop {
+        let submission = match submissions_rx.recv().await {
+            Ok(submission) => submission,
+            Err(RecvError::Lagged(lag)) => {
+                warn!("Relay submissions channel lagged by {} messages", lag);
+                continue;
+            }
+            Err(RecvError::Closed) => {
+                error!("Relay submissions channel closed");
+                break Err(eyre!("RELAY_SUBMISSIONS_RX_CLOSED"));
+            }
+        };
+
+        let mut write_query = WriteQuery::new(Timestamp::from(chrono::Utc::now()), "relay_submission")
+            .add_tag("relay", submission.relay.clone())
+            .add_tag("method", submission.method.clone())
+            .add_field("ok", submission.is_ok())
+            .add_field("latency_ms", submission.latency.as_millis() as u64)
+            .add_field("attempts", submission.attempts);
+        if let Some(block) = submission.block {
+            write_query = write_query.add_field("block_number", block);
+        }
+        if let Some(bundle_hash) = submission.bundle_hash {
+            write_query = write_query.add_field("bundle_hash", bundle_hash.to_string());
+        }
+        if let Some(error) = submission.error {
+            write_query = write_query.add_field("error", error);
+        }
+
+        if let Err(e) = influxdb_write_channel_tx.send(write_query).await {
+            error!("Failed to send relay submission to influxdb: {:?}", e);
+        }
+    }
+}
+
+#[derive(Accessor, Consumer, Producer)]
 pub struct FlashbotsBroadcastActor<P, T> {
     client: Arc<Flashbots<P, T>>,
     smart: bool,
+    smart_pct: u32,
+    policy: BlockTargetingPolicy,
+    chain_parameters: ChainParameters,
+    #[accessor]
+    block_history: Option<SharedState<BlockHistory>>,
+    #[accessor]
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
     #[consumer]
     tx_compose_channel_rx: Option<Broadcaster<MessageTxCompose>>,
+    #[consumer]
+    market_events_rx: Option<Broadcaster<MarketEvents>>,
+    #[producer]
+    compose_channel_tx: Option<Broadcaster<MessageTxCompose>>,
+    #[producer]
+    influxdb_write_channel_tx: Option<Broadcaster<WriteQuery>>,
     allow_broadcast: bool,
 }
 
@@ -120,11 +485,49 @@ where
     P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
 {
     pub fn new(client: Flashbots<P, T>, smart: bool, allow_broadcast: bool) -> FlashbotsBroadcastActor<P, T> {
-        FlashbotsBroadcastActor { client: Arc::new(client), smart, tx_compose_channel_rx: None, allow_broadcast }
+        FlashbotsBroadcastActor {
+            client: Arc::new(client),
+            smart,
+            smart_pct: 8000,
+            policy: BlockTargetingPolicy::default(),
+            chain_parameters: ChainParameters::ethereum(),
+            block_history: None,
+            account_nonce_balance: None,
+            tx_compose_channel_rx: None,
+            market_events_rx: None,
+            compose_channel_tx: None,
+            influxdb_write_channel_tx: None,
+            allow_broadcast,
+        }
+    }
+
+    /// Bundles with profit above this percentage of the best one are broadcasted in smart mode, 10000 = 100%.
+    /// A bundle with the best profit replaces the previous best one on relays supporting replacement.
+    pub fn with_smart_pct(self, smart_pct: u32) -> Self {
+        Self { smart_pct, ..self }
+    }
+
+    /// Bundles are resubmitted for later blocks until their transactions land or the swap pools change.
+    pub fn with_targeting_policy(self, policy: BlockTargetingPolicy) -> Self {
+        Self { policy, ..self }
+    }
+
+    /// Block time and base fee rules of re-estimated bundles, set by [`on_bc`](Self::on_bc).
+    pub fn with_chain_parameters(self, chain_parameters: ChainParameters) -> Self {
+        Self { chain_parameters, ..self }
     }
 
     pub fn on_bc(self, bc: &Blockchain) -> Self {
-        Self { tx_compose_channel_rx: Some(bc.compose_channel()), ..self }
+        Self {
+            chain_parameters: bc.chain_parameters(),
+            block_history: Some(bc.block_history()),
+            account_nonce_balance: Some(bc.nonce_and_balance()),
+            tx_compose_channel_rx: Some(bc.compose_channel()),
+            market_events_rx: Some(bc.market_events_channel()),
+            compose_channel_tx: Some(bc.compose_channel()),
+            influxdb_write_channel_tx: Some(bc.influxdb_write_channel()),
+            ..self
+        }
     }
 }
 
@@ -137,10 +540,24 @@ where
         let task = tokio::task::spawn(flashbots_broadcaster_worker(
             self.client.clone(),
             self.smart,
+            self.smart_pct,
+            self.policy.clone(),
+            self.chain_parameters.clone(),
+            self.block_history.clone().unwrap(),
+            self.account_nonce_balance.clone(),
             self.tx_compose_channel_rx.clone().unwrap(),
+            self.market_events_rx.clone().unwrap(),
+            self.compose_channel_tx.clone().unwrap(),
             self.allow_broadcast,
         ));
-        Ok(vec![task])
+        let mut tasks = vec![task];
+
+        // relay responses are written to influxdb if the channel is set
+        if let Some(influxdb_write_channel_tx) = self.influxdb_write_channel_tx.clone() {
+            tasks.push(tokio::task::spawn(relay_submissions_worker(self.client.subscribe_submissions(), influxdb_write_channel_tx)));
+        }
+
+        Ok(tasks)
     }
 
     fn name(&self) -> &'static str {
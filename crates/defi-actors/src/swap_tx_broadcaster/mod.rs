@@ -1,5 +1,14 @@
 pub use anvil::AnvilBroadcastActor;
 pub use flashbots::FlashbotsBroadcastActor;
+pub use paper_trading::{PaperTradeOutcome, PaperTradeRecord, PaperTradingBroadcastActor};
+pub use private_tx::PrivateTxBroadcastActor;
+pub use public_tx::PublicTxBroadcastActor;
+pub use targeting::BlockTargetingPolicy;
 
 mod anvil;
 mod flashbots;
+mod paper_trading;
+mod private_tx;
+mod public_tx;
+mod replacement;
+mod targeting;
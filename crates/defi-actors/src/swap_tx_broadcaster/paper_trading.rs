@@ -0,0 +1,422 @@
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::Arc;
+
+use alloy_primitives::{BlockHash, BlockNumber, TxHash, U256};
+use alloy_rpc_types::{Header, Transaction, TransactionRequest};
+use eyre::{eyre, Result};
+use revm::primitives::{BlockEnv, Env, ExecutionResult, CANCUN};
+use revm::Evm;
+use serde::Serialize;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info, warn};
+
+use defi_blockchain::Blockchain;
+use defi_entities::BlockHistory;
+use defi_events::{MarketEvents, MessageTxCompose, TxCompose, TxComposeData, TxState};
+use loom_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, SharedState, WorkerResult};
+use loom_actors_macros::{Accessor, Consumer};
+use loom_revm_db::LoomInMemoryDB;
+use loom_utils::evm::tx_env_from_request;
+use loom_utils::NWETH;
+
+/// Result of the bundle simulation at the target block
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PaperTradeOutcome {
+    pub block_hash: BlockHash,
+    /// All stuffing txs of the bundle are included in the target block
+    pub stuffing_included: bool,
+    /// Backrun txs succeed when the bundle is executed at the start of the target block
+    pub succeeded: bool,
+    pub gas_used: u64,
+    pub error: Option<String>,
+    /// Bundle succeeds at the start of the block but backrun txs fail after the block, so the opportunity was taken by others
+    pub captured_by_other: bool,
+    /// Transaction that followed the last stuffing tx in the target block
+    pub competitor_tx: Option<TxHash>,
+}
+
+/// Journal record of a would-be bundle. Written once when bundle is received and once more with the outcome.
+#[derive(Clone, Debug, Serialize)]
+pub struct PaperTradeRecord {
+    pub target_block: BlockNumber,
+    pub origin: Option<String>,
+    pub swap: String,
+    pub stuffing_txs_hashes: Vec<TxHash>,
+    pub gas: u64,
+    pub expected_profit_eth: f64,
+    pub expected_tips_eth: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<PaperTradeOutcome>,
+}
+
+#[derive(Clone, Debug)]
+struct PaperTrade {
+    record: PaperTradeRecord,
+    stuffing_txs: Vec<Transaction>,
+    backrun_txs: Vec<TransactionRequest>,
+    /// State the bundle was estimated on, used if the parent block state is not in block history
+    latest_state: Option<Arc<LoomInMemoryDB>>,
+}
+
+impl From<&TxComposeData> for PaperTrade {
+    fn from(request: &TxComposeData) -> Self {
+        let backrun_txs = request
+            .tx_bundle
+            .iter()
+            .flatten()
+            .filter_map(|tx| match tx {
+                TxState::SignatureRequired(tx_request) => Some(tx_request.clone()),
+                _ => None,
+            })
+            .collect();
+
+        PaperTrade {
+            record: PaperTradeRecord {
+                target_block: request.next_block_number,
+                origin: request.origin.clone(),
+                swap: request.swap.to_string(),
+                stuffing_txs_hashes: request.stuffing_txs_hashes.clone(),
+                gas: request.gas,
+                expected_profit_eth: NWETH::to_float(request.swap.abs_profit_eth()),
+                expected_tips_eth: NWETH::to_float(request.tips.unwrap_or_default()),
+                outcome: None,
+            },
+            stuffing_txs: request.stuffing_txs.clone(),
+            backrun_txs,
+            latest_state: request.prestate.clone(),
+        }
+    }
+}
+
+/// Appends paper trade records as json lines. Records are only logged if the file is not set.
+struct PaperTradingJournal {
+    file: Option<File>,
+}
+
+impl PaperTradingJournal {
+    fn open(path: Option<String>) -> Result<Self> {
+        let file = match path {
+            Some(path) => Some(OpenOptions::new().create(true).append(true).open(path)?),
+            None => None,
+        };
+        Ok(Self { file })
+    }
+
+    fn write(&mut self, record: &PaperTradeRecord) -> Result<()> {
+        let line = serde_json::to_string(record)?;
+        match self.file.as_mut() {
+            Some(file) => {
+                writeln!(file, "{line}")?;
+                file.flush()?;
+            }
+            None => info!("Paper trade : {line}"),
+        }
+        Ok(())
+    }
+}
+
+fn block_env(header: &Header) -> BlockEnv {
+    BlockEnv {
+        number: U256::from(header.number),
+        coinbase: header.miner,
+        timestamp: U256::from(header.timestamp),
+        gas_limit: U256::from(header.gas_limit),
+        basefee: U256::from(header.base_fee_per_gas.unwrap_or_default()),
+        difficulty: header.difficulty,
+        prevrandao: Some(header.parent_hash),
+        blob_excess_gas_and_price: None,
+    }
+}
+
+/// Executes stuffing txs and then backrun txs on top of the state. Returns gas used by backrun txs or the first backrun error.
+/// Failed stuffing txs are ignored as they are included in the block anyway.
+fn simulate_bundle(db: LoomInMemoryDB, header: &Header, stuffing_txs: &[Transaction], backrun_txs: &[TransactionRequest]) -> Result<u64> {
+    if backrun_txs.is_empty() {
+        return Err(eyre!("NO_BACKRUN_TXS"));
+    }
+
+    let env = Env { block: block_env(header), ..Env::default() };
+    let mut evm = Evm::builder().with_spec_id(CANCUN).with_db(db).with_env(Box::new(env)).build();
+
+    for tx in stuffing_txs.iter() {
+        *evm.tx_mut() = tx_env_from_request(&TransactionRequest::from(tx.clone()));
+        if let Err(e) = evm.transact_commit() {
+            debug!(tx_hash = %tx.hash, "Stuffing tx error : {}", e);
+        }
+    }
+
+    let mut gas_used = 0;
+    for tx in backrun_txs.iter() {
+        *evm.tx_mut() = tx_env_from_request(tx);
+        match evm.transact_commit().map_err(|e| eyre!("TRANSACT_ERROR: {}", e))? {
+            ExecutionResult::Success { gas_used: tx_gas_used, .. } => gas_used += tx_gas_used,
+            ExecutionResult::Revert { .. } => return Err(eyre!("EXECUTION_REVERTED")),
+            ExecutionResult::Halt { reason, .. } => return Err(eyre!("EXECUTION_HALT: {:?}", reason)),
+        }
+    }
+
+    Ok(gas_used)
+}
+
+fn evaluate_trade(
+    trade: &PaperTrade,
+    header: &Header,
+    block_txs: &[TxHash],
+    prestate: &LoomInMemoryDB,
+    poststate: &LoomInMemoryDB,
+) -> PaperTradeOutcome {
+    let stuffing_idx: Vec<Option<usize>> =
+        trade.record.stuffing_txs_hashes.iter().map(|hash| block_txs.iter().position(|x| x == hash)).collect();
+    let stuffing_included = stuffing_idx.iter().all(|idx| idx.is_some());
+    let competitor_tx = stuffing_idx.iter().flatten().max().and_then(|idx| block_txs.get(idx + 1)).cloned();
+
+    let mut outcome = PaperTradeOutcome { block_hash: header.hash, stuffing_included, competitor_tx, ..PaperTradeOutcome::default() };
+
+    match simulate_bundle(prestate.clone(), header, &trade.stuffing_txs, &trade.backrun_txs) {
+        Ok(gas_used) => {
+            outcome.succeeded = true;
+            outcome.gas_used = gas_used;
+        }
+        Err(e) => outcome.error = Some(e.to_string()),
+    }
+
+    // stuffing txs that did not make it into the block cannot tell if the opportunity was taken
+    if outcome.succeeded && stuffing_included {
+        outcome.captured_by_other = simulate_bundle(poststate.clone(), header, &[], &trade.backrun_txs).is_err();
+    }
+
+    outcome
+}
+
+async fn evaluate_block(
+    block_history: &SharedState<BlockHistory>,
+    block_hash: BlockHash,
+    trades: Vec<PaperTrade>,
+    journal: &mut PaperTradingJournal,
+) -> Result<()> {
+    let block_history_guard = block_history.read().await;
+    let entry = block_history_guard.get_entry(&block_hash).ok_or(eyre!("BLOCK_HISTORY_ENTRY_NOT_FOUND"))?;
+    // state db of the entry is the state after the block, the bundle is executed on top of the parent block state
+    let poststate = entry.state_db.clone().ok_or(eyre!("STATE_DB_NOT_FOUND"))?;
+    let parent_state = block_history_guard.get_entry(&entry.parent_hash()).and_then(|parent| parent.state_db.clone());
+    if parent_state.is_none() {
+        warn!(%block_hash, "Parent block state not found, trades are evaluated on the latest state");
+    }
+    let block_txs: Vec<TxHash> = entry.block.as_ref().map(|block| block.transactions.hashes().collect()).unwrap_or_default();
+    let header = entry.header.clone();
+    drop(block_history_guard);
+
+    let (mut succeeded, mut captured) = (0, 0);
+    for trade in trades.into_iter() {
+        let outcome = match parent_state.as_ref().or(trade.latest_state.as_deref()) {
+            Some(prestate) => evaluate_trade(&trade, &header, &block_txs, prestate, &poststate),
+            None => PaperTradeOutcome {
+                block_hash: header.hash,
+                error: Some("PARENT_STATE_DB_NOT_FOUND".to_string()),
+                ..PaperTradeOutcome::default()
+            },
+        };
+        succeeded += outcome.succeeded as usize;
+        captured += outcome.captured_by_other as usize;
+
+        let record = PaperTradeRecord { outcome: Some(outcome), ..trade.record };
+        if let Err(e) = journal.write(&record) {
+            error!("journal.write : {}", e)
+        }
+    }
+
+    info!(block_number = header.number, %block_hash, succeeded, captured, "Paper trades evaluated");
+    Ok(())
+}
+
+pub async fn paper_trading_broadcaster_worker(
+    journal_path: Option<String>,
+    block_history: SharedState<BlockHistory>,
+    tx_compose_channel_rx: Broadcaster<MessageTxCompose>,
+    market_events_rx: Broadcaster<MarketEvents>,
+) -> WorkerResult {
+    subscribe!(tx_compose_channel_rx);
+    subscribe!(market_events_rx);
+
+    let mut journal = PaperTradingJournal::open(journal_path)?;
+    let mut trades: BTreeMap<BlockNumber, Vec<PaperTrade>> = BTreeMap::new();
+
+    loop {
+        tokio::select! {
+            msg = market_events_rx.recv() => {
+                let market_event_msg : Result<MarketEvents, RecvError> = msg;
+                match market_event_msg {
+                    Ok(MarketEvents::BlockStateUpdate{ block_hash }) => {
+                        let Some(block_number) = block_history.read().await.get_entry(&block_hash).map(|entry| entry.number()) else {
+                            error!(%block_hash, "Block not found in block history");
+                            continue
+                        };
+
+                        let missed_blocks: Vec<BlockNumber> = trades.range(..block_number).map(|(block, _)| *block).collect();
+                        for missed_block in missed_blocks {
+                            if let Some(missed) = trades.remove(&missed_block) {
+                                error!(block_number = missed_block, trades = missed.len(), "Target block state update not received");
+                            }
+                        }
+
+                        if let Some(block_trades) = trades.remove(&block_number) {
+                            if let Err(e) = evaluate_block(&block_history, block_hash, block_trades, &mut journal).await {
+                                error!("evaluate_block : {}", e)
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e)=>{
+                        error!("market_event_rx error : {e}")
+                    }
+                }
+            },
+
+            msg = tx_compose_channel_rx.recv() => {
+                let tx_compose_update : Result<MessageTxCompose, RecvError>  = msg;
+                match tx_compose_update {
+                    Ok(tx_compose_msg)=>{
+                        if let TxCompose::Broadcast(broadcast_data) = tx_compose_msg.inner {
+                            let trade = PaperTrade::from(&broadcast_data);
+                            if let Err(e) = journal.write(&trade.record) {
+                                error!("journal.write : {}", e)
+                            }
+                            trades.entry(trade.record.target_block).or_default().push(trade);
+                        }
+                    }
+                    Err(e)=>{
+                        error!("tx_compose_channel_rx : {e}")
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Broadcaster for paper trading. Bundles are never sent, instead they are written to the journal with expected profit
+/// and re-simulated at the start of the target block to record if they would succeed and if others captured the opportunity.
+#[derive(Accessor, Consumer, Default)]
+pub struct PaperTradingBroadcastActor {
+    journal_path: Option<String>,
+    #[accessor]
+    block_history: Option<SharedState<BlockHistory>>,
+    #[consumer]
+    tx_compose_channel_rx: Option<Broadcaster<MessageTxCompose>>,
+    #[consumer]
+    market_events_rx: Option<Broadcaster<MarketEvents>>,
+}
+
+impl PaperTradingBroadcastActor {
+    pub fn new() -> Self {
+        PaperTradingBroadcastActor::default()
+    }
+
+    pub fn with_journal(self, journal_path: String) -> Self {
+        Self { journal_path: Some(journal_path), ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain) -> Self {
+        Self {
+            block_history: Some(bc.block_history()),
+            tx_compose_channel_rx: Some(bc.compose_channel()),
+            market_events_rx: Some(bc.market_events_channel()),
+            ..self
+        }
+    }
+}
+
+impl Actor for PaperTradingBroadcastActor {
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(paper_trading_broadcaster_worker(
+            self.journal_path.clone(),
+            self.block_history.clone().unwrap(),
+            self.tx_compose_channel_rx.clone().unwrap(),
+            self.market_events_rx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "PaperTradingBroadcastActor"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::{Address, B256};
+    use revm::primitives::AccountInfo;
+
+    fn transfer(from: Address, to: Address, value: u64) -> TransactionRequest {
+        TransactionRequest::default().from(from).to(to).value(U256::from(value)).gas_limit(21000).max_fee_per_gas(10)
+    }
+
+    fn trade(backrun_txs: Vec<TransactionRequest>, stuffing_txs_hashes: Vec<TxHash>) -> PaperTrade {
+        PaperTrade {
+            record: PaperTradeRecord {
+                target_block: 1,
+                origin: None,
+                swap: String::new(),
+                stuffing_txs_hashes,
+                gas: 21000,
+                expected_profit_eth: 0.0,
+                expected_tips_eth: 0.0,
+                outcome: None,
+            },
+            stuffing_txs: vec![],
+            backrun_txs,
+            latest_state: None,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_trade() {
+        let sender = Address::repeat_byte(0x01);
+        let receiver = Address::repeat_byte(0x02);
+        let header = Header { number: 1, base_fee_per_gas: Some(1), gas_limit: 30_000_000, ..Header::default() };
+
+        let mut prestate = LoomInMemoryDB::default();
+        prestate.insert_account_info(sender, AccountInfo { balance: U256::from(1_000_000), ..AccountInfo::default() });
+        let mut poststate = LoomInMemoryDB::default();
+        poststate.insert_account_info(sender, AccountInfo { balance: U256::from(100_000), ..AccountInfo::default() });
+
+        let outcome = evaluate_trade(&trade(vec![transfer(sender, receiver, 500_000)], vec![]), &header, &[], &prestate, &poststate);
+        assert!(outcome.succeeded);
+        assert_eq!(outcome.gas_used, 21000);
+        assert!(outcome.captured_by_other);
+
+        let outcome = evaluate_trade(&trade(vec![transfer(sender, receiver, 10_000)], vec![]), &header, &[], &prestate, &poststate);
+        assert!(outcome.succeeded);
+        assert!(!outcome.captured_by_other);
+
+        let outcome = evaluate_trade(&trade(vec![transfer(sender, receiver, 2_000_000)], vec![]), &header, &[], &prestate, &poststate);
+        assert!(!outcome.succeeded);
+        assert!(outcome.error.is_some());
+
+        // stuffing tx is not in the block
+        let stuffing_hash = B256::repeat_byte(0x11);
+        let outcome = evaluate_trade(
+            &trade(vec![transfer(sender, receiver, 500_000)], vec![stuffing_hash]),
+            &header,
+            &[B256::repeat_byte(0x22)],
+            &prestate,
+            &poststate,
+        );
+        assert!(!outcome.stuffing_included);
+        assert!(!outcome.captured_by_other);
+
+        let block_txs = [B256::repeat_byte(0x22), stuffing_hash, B256::repeat_byte(0x33)];
+        let outcome = evaluate_trade(
+            &trade(vec![transfer(sender, receiver, 500_000)], vec![stuffing_hash]),
+            &header,
+            &block_txs,
+            &prestate,
+            &poststate,
+        );
+        assert!(outcome.stuffing_included);
+        assert!(outcome.captured_by_other);
+        assert_eq!(outcome.competitor_tx, Some(B256::repeat_byte(0x33)));
+    }
+}
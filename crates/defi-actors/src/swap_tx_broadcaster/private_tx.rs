@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use alloy_network::Ethereum;
+use alloy_provider::Provider;
+use alloy_transport::Transport;
+use eyre::{eyre, Result};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+use tracing::{error, info};
+
+use crate::accounts_monitor::{confirm_request_nonce, release_request_nonce};
+use defi_blockchain::Blockchain;
+use defi_entities::AccountNonceAndBalanceState;
+use defi_events::{MessageTxCompose, TxCompose, TxComposeData};
+use flashbots::Flashbots;
+use loom_actors::{Accessor, Actor, ActorResult, Broadcaster, Consumer, SharedState, WorkerResult};
+use loom_actors_macros::{Accessor, Consumer};
+
+use crate::swap_tx_broadcaster::replacement::{NonceTracker, DEFAULT_FEE_BUMP_PCT};
+
+/// Returns true if the transaction of the request was sent and is pending until `max_block`
+async fn broadcast_task<P, T>(client: &Flashbots<P, T>, tracker: &mut NonceTracker, max_block: u64, request: &TxComposeData) -> Result<bool>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    let signer = request.signer.clone().ok_or(eyre!("NO_SIGNER"))?.address();
+    tracker.update_account_nonce(signer, request.nonce);
+
+    let Some((tx_bytes, pending_tx)) = tracker.sign_request(request, max_block)? else {
+        return Ok(false);
+    };
+
+    client.send_private_transaction(tx_bytes, max_block).await?;
+    info!(tx_hash = %pending_tx.tx_hash, nonce = request.nonce, max_block, swap = %request.swap, "Private tx sent");
+    tracker.insert(signer, request.nonce, pending_tx);
+
+    Ok(true)
+}
+
+async fn private_tx_broadcaster_worker<P, T>(
+    client: Arc<Flashbots<P, T>>,
+    fee_bump_pct: u32,
+    target_blocks: u64,
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
+    bundle_rx: Broadcaster<MessageTxCompose>,
+) -> WorkerResult
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    let mut bundle_rx: Receiver<MessageTxCompose> = bundle_rx.subscribe().await;
+    let mut tracker = NonceTracker::new(fee_bump_pct);
+
+    loop {
+        tokio::select! {
+            msg = bundle_rx.recv() => {
+                let broadcast_msg : Result<MessageTxCompose, RecvError> = msg;
+                match broadcast_msg {
+                    Ok(compose_request) => {
+                        if let TxCompose::Broadcast(broadcast_request) = compose_request.inner {
+                            let max_block = broadcast_request.next_block_number + target_blocks.saturating_sub(1);
+                            match broadcast_task(client.as_ref(), &mut tracker, max_block, &broadcast_request).await {
+                                Ok(true) => confirm_request_nonce(account_nonce_balance.as_ref(), &broadcast_request, max_block).await,
+                                Ok(false) => release_request_nonce(account_nonce_balance.as_ref(), &broadcast_request).await,
+                                Err(e) => {
+                                    release_request_nonce(account_nonce_balance.as_ref(), &broadcast_request).await;
+                                    error!("Private broadcast error : {}", e)
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        error!("{}", e)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Sends backruns without stuffing transactions with `eth_sendPrivateTransaction` to relays supporting private transactions.
+///
+/// Transaction is valid for `target_blocks` blocks, pending transaction is replaced by fee when a more profitable swap gets
+/// the same nonce.
+#[derive(Accessor, Consumer)]
+pub struct PrivateTxBroadcastActor<P, T> {
+    client: Arc<Flashbots<P, T>>,
+    fee_bump_pct: u32,
+    target_blocks: u64,
+    #[accessor]
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
+    #[consumer]
+    tx_compose_rx: Option<Broadcaster<MessageTxCompose>>,
+}
+
+impl<P, T> PrivateTxBroadcastActor<P, T>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    pub fn new(client: Flashbots<P, T>) -> PrivateTxBroadcastActor<P, T> {
+        Self {
+            client: Arc::new(client),
+            fee_bump_pct: DEFAULT_FEE_BUMP_PCT,
+            target_blocks: 1,
+            account_nonce_balance: None,
+            tx_compose_rx: None,
+        }
+    }
+
+    pub fn with_fee_bump_pct(self, fee_bump_pct: u32) -> Self {
+        Self { fee_bump_pct, ..self }
+    }
+
+    pub fn with_target_blocks(self, target_blocks: u64) -> Self {
+        Self { target_blocks, ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain) -> Self {
+        Self { account_nonce_balance: Some(bc.nonce_and_balance()), tx_compose_rx: Some(bc.compose_channel()), ..self }
+    }
+}
+
+impl<P, T> Actor for PrivateTxBroadcastActor<P, T>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(private_tx_broadcaster_worker(
+            self.client.clone(),
+            self.fee_bump_pct,
+            self.target_blocks,
+            self.account_nonce_balance.clone(),
+            self.tx_compose_rx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "PrivateTxBroadcastActor"
+    }
+}
@@ -0,0 +1,148 @@
+use std::marker::PhantomData;
+
+use alloy_network::Ethereum;
+use alloy_provider::Provider;
+use alloy_transport::Transport;
+use eyre::{eyre, Result};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+use tracing::{debug, error, info, warn};
+
+use crate::accounts_monitor::{confirm_request_nonce, release_request_nonce};
+use defi_blockchain::Blockchain;
+use defi_entities::AccountNonceAndBalanceState;
+use defi_events::{MessageTxCompose, TxCompose, TxComposeData};
+use loom_actors::{Accessor, Actor, ActorResult, Broadcaster, Consumer, SharedState, WorkerResult};
+use loom_actors_macros::{Accessor, Consumer};
+
+use crate::swap_tx_broadcaster::replacement::{NonceTracker, SendErrorKind, DEFAULT_FEE_BUMP_PCT};
+
+/// Returns true if the transaction of the request is pending in the mempool
+async fn broadcast_task<P, T>(client: &P, tracker: &mut NonceTracker, request: &TxComposeData) -> Result<bool>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    let signer = request.signer.clone().ok_or(eyre!("NO_SIGNER"))?.address();
+    tracker.update_account_nonce(signer, request.nonce);
+
+    let Some((tx_bytes, pending_tx)) = tracker.sign_request(request, request.next_block_number)? else {
+        return Ok(false);
+    };
+
+    match client.send_raw_transaction(&tx_bytes).await {
+        Ok(_) => {
+            info!(tx_hash = %pending_tx.tx_hash, nonce = request.nonce, swap = %request.swap, "Public tx sent");
+            tracker.insert(signer, request.nonce, pending_tx);
+            Ok(true)
+        }
+        Err(e) => match SendErrorKind::from_message(&e.to_string()) {
+            SendErrorKind::NonceTooLow => {
+                debug!(nonce = request.nonce, "Nonce is already used");
+                tracker.update_account_nonce(signer, request.nonce + 1);
+                Ok(false)
+            }
+            SendErrorKind::ReplacementUnderpriced => {
+                warn!(nonce = request.nonce, "Replacement tx underpriced : {}", e);
+                Ok(false)
+            }
+            SendErrorKind::AlreadyKnown => Ok(true),
+            SendErrorKind::Other => {
+                error!("send_raw_transaction error : {}", e);
+                Err(eyre!("SEND_RAW_TRANSACTION_ERROR"))
+            }
+        },
+    }
+}
+
+async fn public_tx_broadcaster_worker<P, T>(
+    client: P,
+    fee_bump_pct: u32,
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
+    bundle_rx: Broadcaster<MessageTxCompose>,
+) -> WorkerResult
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    let mut bundle_rx: Receiver<MessageTxCompose> = bundle_rx.subscribe().await;
+    let mut tracker = NonceTracker::new(fee_bump_pct);
+
+    loop {
+        tokio::select! {
+            msg = bundle_rx.recv() => {
+                let broadcast_msg : Result<MessageTxCompose, RecvError> = msg;
+                match broadcast_msg {
+                    Ok(compose_request) => {
+                        if let TxCompose::Broadcast(broadcast_request) = compose_request.inner {
+                            let max_block = broadcast_request.next_block_number;
+                            match broadcast_task(&client, &mut tracker, &broadcast_request).await {
+                                Ok(true) => confirm_request_nonce(account_nonce_balance.as_ref(), &broadcast_request, max_block).await,
+                                Ok(false) => release_request_nonce(account_nonce_balance.as_ref(), &broadcast_request).await,
+                                Err(e) => {
+                                    release_request_nonce(account_nonce_balance.as_ref(), &broadcast_request).await;
+                                    error!("Public broadcast error : {}", e)
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        error!("{}", e)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Sends backruns without stuffing transactions to the public mempool with `eth_sendRawTransaction`.
+///
+/// Pending transaction is replaced by fee when a more profitable swap gets the same nonce.
+#[derive(Accessor, Consumer)]
+pub struct PublicTxBroadcastActor<P, T> {
+    client: P,
+    fee_bump_pct: u32,
+    #[accessor]
+    account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
+    #[consumer]
+    tx_compose_rx: Option<Broadcaster<MessageTxCompose>>,
+    _t: PhantomData<T>,
+}
+
+impl<P, T> PublicTxBroadcastActor<P, T>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    pub fn new(client: P) -> PublicTxBroadcastActor<P, T> {
+        Self { client, fee_bump_pct: DEFAULT_FEE_BUMP_PCT, account_nonce_balance: None, tx_compose_rx: None, _t: PhantomData }
+    }
+
+    pub fn with_fee_bump_pct(self, fee_bump_pct: u32) -> Self {
+        Self { fee_bump_pct, ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain) -> Self {
+        Self { account_nonce_balance: Some(bc.nonce_and_balance()), tx_compose_rx: Some(bc.compose_channel()), ..self }
+    }
+}
+
+impl<P, T> Actor for PublicTxBroadcastActor<P, T>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(public_tx_broadcaster_worker(
+            self.client.clone(),
+            self.fee_bump_pct,
+            self.account_nonce_balance.clone(),
+            self.tx_compose_rx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "PublicTxBroadcastActor"
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+use alloy_primitives::{keccak256, Address, BlockNumber, Bytes, TxHash, U256};
+use alloy_rpc_types::TransactionRequest;
+use eyre::{eyre, Result};
+use tracing::debug;
+
+use defi_events::{TxComposeData, TxState};
+
+/// Default fee bump of a replacement transaction, nodes reject replacements bumping the fees by less than 10%.
+pub const DEFAULT_FEE_BUMP_PCT: u32 = 10;
+
+/// Signed transaction waiting for inclusion.
+#[derive(Clone, Debug)]
+pub struct PendingTx {
+    pub tx_hash: TxHash,
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+    pub profit: U256,
+    /// Last block the swap of the transaction was estimated for
+    pub max_block: BlockNumber,
+}
+
+/// Error of a transaction submission reported by the node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SendErrorKind {
+    /// Transaction with the nonce is already mined
+    NonceTooLow,
+    /// Transaction with the nonce is pending and the fees are not bumped enough
+    ReplacementUnderpriced,
+    AlreadyKnown,
+    Other,
+}
+
+impl SendErrorKind {
+    pub fn from_message(message: &str) -> Self {
+        let message = message.to_lowercase();
+        if message.contains("nonce too low") {
+            SendErrorKind::NonceTooLow
+        } else if message.contains("replacement transaction underpriced") {
+            SendErrorKind::ReplacementUnderpriced
+        } else if message.contains("already known") {
+            SendErrorKind::AlreadyKnown
+        } else {
+            SendErrorKind::Other
+        }
+    }
+}
+
+/// Pending transactions of the signers by nonce.
+///
+/// Swaps sharing the nonce of a pending transaction replace it by fee when they are more profitable, or when the pending
+/// transaction is past its block.
+#[derive(Clone, Debug)]
+pub struct NonceTracker {
+    pending: HashMap<(Address, u64), PendingTx>,
+    fee_bump_pct: u32,
+}
+
+impl Default for NonceTracker {
+    fn default() -> Self {
+        Self { pending: HashMap::new(), fee_bump_pct: DEFAULT_FEE_BUMP_PCT }
+    }
+}
+
+impl NonceTracker {
+    pub fn new(fee_bump_pct: u32) -> Self {
+        Self { fee_bump_pct, ..Self::default() }
+    }
+
+    pub fn get(&self, signer: &Address, nonce: u64) -> Option<&PendingTx> {
+        self.pending.get(&(*signer, nonce))
+    }
+
+    pub fn insert(&mut self, signer: Address, nonce: u64, pending_tx: PendingTx) {
+        self.pending.insert((signer, nonce), pending_tx);
+    }
+
+    /// Removes transactions with nonces below the account nonce, they are mined or replaced by other ones.
+    pub fn update_account_nonce(&mut self, signer: Address, account_nonce: u64) {
+        self.pending.retain(|(address, nonce), _| *address != signer || *nonce >= account_nonce);
+    }
+
+    fn bump(&self, fee: u128) -> u128 {
+        fee + (fee * self.fee_bump_pct as u128).div_ceil(100)
+    }
+
+    /// Fees `(max_fee_per_gas, max_priority_fee_per_gas)` of the transaction with the nonce targeting the block.
+    /// Returns None if the pending transaction with the same nonce should be kept.
+    pub fn fees(
+        &self,
+        signer: &Address,
+        nonce: u64,
+        block: BlockNumber,
+        profit: U256,
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: u128,
+    ) -> Option<(u128, u128)> {
+        match self.get(signer, nonce) {
+            None => Some((max_fee_per_gas, max_priority_fee_per_gas)),
+            Some(pending_tx) if pending_tx.max_block >= block && pending_tx.profit >= profit => None,
+            Some(pending_tx) => Some((
+                max_fee_per_gas.max(self.bump(pending_tx.max_fee_per_gas)),
+                max_priority_fee_per_gas.max(self.bump(pending_tx.max_priority_fee_per_gas)),
+            )),
+        }
+    }
+
+    /// Signs the backrun transaction of the request with fees replacing the pending transaction with the same nonce.
+    /// Returns None if the request needs a bundle, the pending transaction is kept or the bumped fees exceed the profit.
+    pub fn sign_request(&self, request: &TxComposeData, max_block: BlockNumber) -> Result<Option<(Bytes, PendingTx)>> {
+        if !request.stuffing_txs_hashes.is_empty() {
+            debug!(swap = %request.swap, "Backrun of stuffing txs requires a bundle");
+            return Ok(None);
+        }
+
+        let signer = request.signer.clone().ok_or(eyre!("NO_SIGNER"))?;
+
+        let tx_request = request
+            .tx_bundle
+            .iter()
+            .flatten()
+            .find_map(|tx| match tx {
+                TxState::SignatureRequired(tx_request) => Some(tx_request.clone()),
+                _ => None,
+            })
+            .ok_or(eyre!("NO_TX_REQUEST"))?;
+
+        let nonce = tx_request.nonce.unwrap_or(request.nonce);
+        let profit = request.swap.abs_profit_eth();
+        let base_fee = request.next_block_base_fee as u128;
+        let priority_fee = tx_request.max_priority_fee_per_gas.unwrap_or(request.priority_gas_fee as u128);
+        // transaction stays valid if the base fee increases in the next block
+        let max_fee = tx_request.max_fee_per_gas.unwrap_or_default().max(base_fee + base_fee / 8 + priority_fee);
+
+        let Some((max_fee_per_gas, max_priority_fee_per_gas)) =
+            self.fees(&signer.address(), nonce, request.next_block_number, profit, max_fee, priority_fee)
+        else {
+            debug!(%nonce, swap = %request.swap, "Pending tx with the same nonce is kept");
+            return Ok(None);
+        };
+
+        let gas_cost = U256::from(tx_request.gas.unwrap_or(request.gas) as u128 * (base_fee + max_priority_fee_per_gas));
+        if gas_cost >= profit {
+            debug!(%nonce, %gas_cost, %profit, "Replacement fees exceed profit");
+            return Ok(None);
+        }
+
+        let tx_request = TransactionRequest {
+            max_fee_per_gas: Some(max_fee_per_gas),
+            max_priority_fee_per_gas: Some(max_priority_fee_per_gas),
+            ..tx_request
+        };
+        let (_, tx_bytes) = signer.sign_sync(tx_request)?;
+
+        let pending_tx = PendingTx { tx_hash: keccak256(&tx_bytes), max_fee_per_gas, max_priority_fee_per_gas, profit, max_block };
+
+        Ok(Some((tx_bytes, pending_tx)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pending_tx(profit: u64, max_block: BlockNumber) -> PendingTx {
+        PendingTx {
+            tx_hash: TxHash::repeat_byte(1),
+            max_fee_per_gas: 100,
+            max_priority_fee_per_gas: 10,
+            profit: U256::from(profit),
+            max_block,
+        }
+    }
+
+    #[test]
+    fn test_fees() {
+        let signer = Address::repeat_byte(1);
+        let mut tracker = NonceTracker::default();
+
+        assert_eq!(tracker.fees(&signer, 5, 10, U256::from(1000), 50, 5), Some((50, 5)));
+
+        tracker.insert(signer, 5, pending_tx(1000, 10));
+        // less profitable swap keeps the pending tx
+        assert_eq!(tracker.fees(&signer, 5, 10, U256::from(1000), 50, 5), None);
+        // more profitable swap replaces it with bumped fees
+        assert_eq!(tracker.fees(&signer, 5, 10, U256::from(2000), 50, 5), Some((110, 11)));
+        assert_eq!(tracker.fees(&signer, 5, 10, U256::from(2000), 200, 20), Some((200, 20)));
+        // pending tx past its block is replaced by any swap
+        assert_eq!(tracker.fees(&signer, 5, 11, U256::from(1), 50, 5), Some((110, 11)));
+        // other nonce is not affected
+        assert_eq!(tracker.fees(&signer, 6, 10, U256::from(1), 50, 5), Some((50, 5)));
+
+        let tracker = NonceTracker::new(12);
+        assert_eq!(tracker.bump(100), 112);
+        assert_eq!(tracker.bump(101), 114);
+    }
+
+    #[test]
+    fn test_update_account_nonce() {
+        let signer = Address::repeat_byte(1);
+        let other_signer = Address::repeat_byte(2);
+        let mut tracker = NonceTracker::default();
+
+        tracker.insert(signer, 5, pending_tx(1000, 10));
+        tracker.insert(signer, 6, pending_tx(1000, 10));
+        tracker.insert(other_signer, 5, pending_tx(1000, 10));
+
+        tracker.update_account_nonce(signer, 6);
+        assert!(tracker.get(&signer, 5).is_none());
+        assert!(tracker.get(&signer, 6).is_some());
+        assert!(tracker.get(&other_signer, 5).is_some());
+    }
+
+    #[test]
+    fn test_send_error_kind() {
+        assert_eq!(
+            SendErrorKind::from_message("server returned an error response: error code -32000: nonce too low"),
+            SendErrorKind::NonceTooLow
+        );
+        assert_eq!(SendErrorKind::from_message("replacement transaction underpriced"), SendErrorKind::ReplacementUnderpriced);
+        assert_eq!(SendErrorKind::from_message("Already Known"), SendErrorKind::AlreadyKnown);
+        assert_eq!(SendErrorKind::from_message("insufficient funds for gas * price + value"), SendErrorKind::Other);
+    }
+}
@@ -0,0 +1,244 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy_primitives::{keccak256, Address, BlockNumber, TxHash};
+use uuid::Uuid;
+
+use defi_events::{RlpState, TxComposeData};
+
+/// Block targeting and resubmission policy of broadcasted bundles.
+#[derive(Clone, Debug)]
+pub struct BlockTargetingPolicy {
+    /// Number of blocks the bundle is sent for, starting from the next block
+    pub target_blocks: u64,
+    /// Bundle for a later block is resubmitted this time after the timestamp of the previous target block
+    pub resubmit_delay: Duration,
+    /// Bundles for later blocks are re-estimated against the new state instead of resubmitting the signed ones
+    pub re_estimate: bool,
+}
+
+impl Default for BlockTargetingPolicy {
+    fn default() -> Self {
+        Self { target_blocks: 2, resubmit_delay: Duration::from_millis(300), re_estimate: false }
+    }
+}
+
+impl BlockTargetingPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_target_blocks(self, target_blocks: u64) -> Self {
+        Self { target_blocks, ..self }
+    }
+
+    pub fn with_resubmit_delay(self, resubmit_delay: Duration) -> Self {
+        Self { resubmit_delay, ..self }
+    }
+
+    pub fn with_re_estimate(self, re_estimate: bool) -> Self {
+        Self { re_estimate, ..self }
+    }
+
+    /// Number of blocks after the first target block the request is resubmitted for.
+    pub fn resubmit_blocks(&self, request: &TxComposeData) -> u64 {
+        request.resubmit_blocks_left.unwrap_or(self.target_blocks.saturating_sub(1))
+    }
+
+    /// Time left until the resubmission following the block with the timestamp.
+    pub fn resubmit_wait(&self, block_timestamp: u64) -> Duration {
+        let resubmit_at = Duration::from_secs(block_timestamp) + self.resubmit_delay;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        resubmit_at.saturating_sub(now)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResubmitDecision {
+    Resubmit,
+    /// Stuffing or backrun transaction of the bundle landed in the block
+    Landed,
+    /// Pools of the swap were changed by the block
+    PoolsChanged,
+}
+
+/// Replacement UUIDs of the backrun only and the stuffing bundles. Bundles sent with them are replaced or cancelled on relays.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplacementUuids {
+    pub backrun: String,
+    pub stuffing: String,
+}
+
+impl Default for ReplacementUuids {
+    fn default() -> Self {
+        Self { backrun: Uuid::new_v4().to_string(), stuffing: Uuid::new_v4().to_string() }
+    }
+}
+
+impl ReplacementUuids {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn to_vec(&self) -> Vec<String> {
+        vec![self.backrun.clone(), self.stuffing.clone()]
+    }
+}
+
+/// Broadcasted bundle waiting for its target block.
+#[derive(Clone, Debug)]
+pub struct PendingBundle {
+    pub request: TxComposeData,
+    pub target_block: BlockNumber,
+    /// Number of later blocks the bundle is resubmitted for
+    pub blocks_left: u64,
+    /// UUIDs the bundle and its resubmissions are sent with
+    pub replacement_uuids: ReplacementUuids,
+}
+
+impl PendingBundle {
+    pub fn new(request: TxComposeData, blocks_left: u64, replacement_uuids: ReplacementUuids) -> Self {
+        Self { target_block: request.next_block_number, request, blocks_left, replacement_uuids }
+    }
+
+    /// Request for the same target block spending a stuffing tx of the bundle or the nonce of its signer supersedes the bundle,
+    /// only one of them can land.
+    pub fn is_superseded_by(&self, request: &TxComposeData) -> bool {
+        if self.target_block != request.next_block_number {
+            return false;
+        }
+        let same_nonce = match (self.request.signer.as_ref(), request.signer.as_ref()) {
+            (Some(signer), Some(other)) => signer.address() == other.address() && self.request.nonce == request.nonce,
+            _ => false,
+        };
+        same_nonce || self.request.stuffing_txs_hashes.iter().any(|tx_hash| request.stuffing_txs_hashes.contains(tx_hash))
+    }
+
+    fn backrun_hashes(&self) -> Vec<TxHash> {
+        self.request
+            .rlp_bundle
+            .iter()
+            .flatten()
+            .filter(|item| matches!(item, RlpState::Backrun(_)))
+            .map(|item| keccak256(item.unwrap()))
+            .collect()
+    }
+
+    /// Decides whether the bundle is resubmitted after the target block with the transactions and the changed addresses.
+    pub fn decision(&self, block_txs: &[TxHash], changed_addresses: &[Address]) -> ResubmitDecision {
+        if self.request.stuffing_txs_hashes.iter().chain(self.backrun_hashes().iter()).any(|tx_hash| block_txs.contains(tx_hash)) {
+            ResubmitDecision::Landed
+        } else if self.request.cross_pools(changed_addresses) {
+            ResubmitDecision::PoolsChanged
+        } else {
+            ResubmitDecision::Resubmit
+        }
+    }
+}
+
+/// Removes the pending bundles superseded by the request and returns them.
+pub fn take_superseded(pending_bundles: &mut Vec<PendingBundle>, request: &TxComposeData) -> Vec<PendingBundle> {
+    let (superseded, kept): (Vec<_>, Vec<_>) =
+        std::mem::take(pending_bundles).into_iter().partition(|bundle| bundle.is_superseded_by(request));
+    *pending_bundles = kept;
+    superseded
+}
+
+/// Removes the pending bundles for blocks after the block whose opportunity is gone with the block and returns them.
+pub fn take_gone(
+    pending_bundles: &mut Vec<PendingBundle>,
+    block_number: BlockNumber,
+    block_txs: &[TxHash],
+    changed_addresses: &[Address],
+) -> Vec<PendingBundle> {
+    let (gone, kept): (Vec<_>, Vec<_>) = std::mem::take(pending_bundles).into_iter().partition(|bundle| {
+        bundle.target_block > block_number && bundle.decision(block_txs, changed_addresses) != ResubmitDecision::Resubmit
+    });
+    *pending_bundles = kept;
+    gone
+}
+
+/// UUIDs of the dropped bundles to cancel. UUIDs the replacing request is sent with are kept, relays replace those bundles.
+pub fn cancelled_uuids(dropped: &[PendingBundle], request_uuids: Option<&ReplacementUuids>) -> Vec<ReplacementUuids> {
+    dropped.iter().map(|bundle| bundle.replacement_uuids.clone()).filter(|uuids| Some(uuids) != request_uuids).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use alloy_primitives::Bytes;
+
+    use super::*;
+
+    #[test]
+    fn test_resubmit_wait() {
+        let policy = BlockTargetingPolicy::default().with_resubmit_delay(Duration::from_secs(1));
+        assert_eq!(policy.resubmit_wait(0), Duration::ZERO);
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let wait = policy.resubmit_wait(now + 12);
+        assert!(wait > Duration::from_secs(11) && wait <= Duration::from_secs(13));
+    }
+
+    #[test]
+    fn test_resubmit_blocks() {
+        let policy = BlockTargetingPolicy::default().with_target_blocks(3);
+        assert_eq!(policy.resubmit_blocks(&TxComposeData::default()), 2);
+        assert_eq!(policy.resubmit_blocks(&TxComposeData { resubmit_blocks_left: Some(1), ..TxComposeData::default() }), 1);
+        assert_eq!(BlockTargetingPolicy::default().with_target_blocks(0).resubmit_blocks(&TxComposeData::default()), 0);
+    }
+
+    #[test]
+    fn test_decision() {
+        let backrun = Bytes::from(vec![1, 2, 3]);
+        let stuffing_hash = TxHash::repeat_byte(1);
+        let request = TxComposeData {
+            next_block_number: 10,
+            stuffing_txs_hashes: vec![stuffing_hash],
+            rlp_bundle: Some(vec![RlpState::Stuffing(Bytes::from(vec![4])), RlpState::Backrun(backrun.clone())]),
+            ..TxComposeData::default()
+        };
+        let pending = PendingBundle::new(request, 1, ReplacementUuids::new());
+        assert_eq!(pending.target_block, 10);
+
+        assert_eq!(pending.decision(&[TxHash::repeat_byte(2)], &[]), ResubmitDecision::Resubmit);
+        assert_eq!(pending.decision(&[TxHash::repeat_byte(2), stuffing_hash], &[]), ResubmitDecision::Landed);
+        assert_eq!(pending.decision(&[keccak256(&backrun)], &[]), ResubmitDecision::Landed);
+    }
+
+    #[test]
+    fn test_cancel_superseded_and_gone() {
+        let stuffing_hash = TxHash::repeat_byte(1);
+        let pool = Address::repeat_byte(3);
+        let request = TxComposeData { next_block_number: 10, stuffing_txs_hashes: vec![stuffing_hash], ..TxComposeData::default() };
+        let best_uuids = ReplacementUuids::new();
+        let pending = PendingBundle::new(request.clone(), 1, ReplacementUuids::new());
+        let best = PendingBundle::new(request.clone(), 1, best_uuids.clone());
+        let other = PendingBundle::new(
+            TxComposeData { stuffing_txs_hashes: vec![TxHash::repeat_byte(2)], ..request.clone() },
+            1,
+            ReplacementUuids::new(),
+        );
+
+        // newer request spending the same stuffing tx supersedes the bundles, the one sent with its uuids is replaced by relays
+        let mut pending_bundles = vec![pending.clone(), best, other.clone()];
+        let superseded = take_superseded(&mut pending_bundles, &request);
+        assert_eq!(superseded.len(), 2);
+        assert_eq!(cancelled_uuids(&superseded, Some(&best_uuids)), vec![pending.replacement_uuids.clone()]);
+        assert_eq!(pending_bundles.len(), 1);
+        assert_eq!(pending_bundles[0].replacement_uuids, other.replacement_uuids);
+
+        // request for another block supersedes nothing
+        let next_block_request = TxComposeData { next_block_number: 11, ..request.clone() };
+        assert!(take_superseded(&mut pending_bundles, &next_block_request).is_empty());
+
+        // opportunity of the bundle for a later block is gone with the stuffing tx landed
+        let mut pending_bundles = vec![pending.clone(), other.clone()];
+        let gone = take_gone(&mut pending_bundles, 9, &[stuffing_hash], &[pool]);
+        assert_eq!(cancelled_uuids(&gone, None), vec![pending.replacement_uuids.clone()]);
+        assert_eq!(pending_bundles.len(), 1);
+
+        // bundles for the block itself are decided after it, not cancelled
+        let mut pending_bundles = vec![pending];
+        assert!(take_gone(&mut pending_bundles, 10, &[stuffing_hash], &[]).is_empty());
+        assert_eq!(pending_bundles.len(), 1);
+    }
+}
@@ -0,0 +1,6 @@
+pub use multicaller_allowance_actor::{BalanceTarget, MulticallerAllowanceActor, MulticallerAllowanceConfig};
+pub use profit_sweeper_actor::{ProfitSweeperActor, ProfitSweeperConfig};
+
+mod multicaller_allowance_actor;
+mod profit_sweeper_actor;
+mod treasury_tx;
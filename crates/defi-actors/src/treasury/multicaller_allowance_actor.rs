@@ -0,0 +1,415 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::marker::PhantomData;
+
+use alloy_network::Network;
+use alloy_primitives::{Address, Bytes, U256};
+use alloy_provider::Provider;
+use alloy_sol_types::SolCall;
+use alloy_transport::Transport;
+use eyre::Result;
+use revm::primitives::{BlockEnv, Env};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info, warn};
+
+use crate::treasury::treasury_tx::{
+    balance_of_call, fetch_token_calls, select_signer, send_treasury_tx, NextBlock, PendingTxs, TreasuryTx, DEFAULT_PRIORITY_GAS_FEE,
+    DO_CALLS_GAS, TRANSFER_GAS,
+};
+use debug_provider::DebugProviderExt;
+use defi_abi::IERC20;
+use defi_blockchain::Blockchain;
+use defi_entities::required_state::RequiredStateReaderConfig;
+use defi_entities::{AccountNonceAndBalanceState, Market, MarketState, PreswapRequirement, TxSigners};
+use defi_events::{HealthEvent, MarketEvents, Message, MessageHealthEvent, MessageTxCompose, MissingApproval};
+use defi_pools::state_readers::ERC20StateReader;
+use defi_types::{ChainParameters, MulticallerCall, MulticallerCalls};
+use loom_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
+use loom_actors_macros::{Accessor, Consumer, Producer};
+use loom_multicaller::{EncoderHelper, MulticallerEncoder, MulticallerSwapEncoder};
+
+const APPROVE_GAS: u64 = 60_000;
+
+/// Multicaller balance of the token is topped up to `target` when it drops below `min`.
+#[derive(Clone, Debug)]
+pub struct BalanceTarget {
+    pub min: U256,
+    pub target: U256,
+}
+
+#[derive(Clone, Debug)]
+pub struct MulticallerAllowanceConfig {
+    /// Allowance below this amount is reported and approved again
+    pub min_allowance: U256,
+    /// Multicaller balances topped up from signers, token -> target
+    pub balance_targets: HashMap<Address, BalanceTarget>,
+    /// Blocks a sent approval or top up is waited for before it is sent again
+    pub retry_blocks: u64,
+    pub priority_gas_fee: u64,
+}
+
+impl Default for MulticallerAllowanceConfig {
+    fn default() -> Self {
+        Self {
+            min_allowance: U256::MAX >> 1,
+            balance_targets: HashMap::new(),
+            retry_blocks: 20,
+            priority_gas_fee: DEFAULT_PRIORITY_GAS_FEE,
+        }
+    }
+}
+
+impl MulticallerAllowanceConfig {
+    pub fn with_balance_target(mut self, token: Address, min: U256, target: U256) -> Self {
+        self.balance_targets.insert(token, BalanceTarget { min, target: target.max(min) });
+        self
+    }
+
+    pub fn with_retry_blocks(self, retry_blocks: u64) -> Self {
+        Self { retry_blocks, ..self }
+    }
+
+    pub fn with_priority_gas_fee(self, priority_gas_fee: u64) -> Self {
+        Self { priority_gas_fee, ..self }
+    }
+}
+
+/// Token and spender pairs of pools with allowance requirement on active swap paths.
+fn required_approvals(market: &Market) -> BTreeSet<(Address, Address)> {
+    market
+        .pools()
+        .values()
+        .filter(|pool| pool.get_encoder().preswap_requirement() == PreswapRequirement::Allowance)
+        .filter(|pool| market.is_pool_ok(&pool.get_address()))
+        .filter(|pool| market.get_pool_paths(&pool.get_address()).is_some_and(|paths| !paths.is_empty()))
+        .flat_map(|pool| {
+            let is_native = pool.get_encoder().is_native();
+            pool.get_swap_directions()
+                .into_iter()
+                // native ETH is sent as call value
+                .filter(move |(token_from, _)| !(is_native && market.is_weth(token_from)))
+                .map(|(token_from, _)| (token_from, pool.get_address()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn allowance_call(owner: Address, spender: Address) -> Bytes {
+    IERC20::allowanceCall { owner, spender }.abi_encode().into()
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn multicaller_allowance_task<P, T, N>(
+    client: &P,
+    config: &MulticallerAllowanceConfig,
+    state_reader_config: &RequiredStateReaderConfig,
+    chain_id: u64,
+    encoder: &MulticallerSwapEncoder,
+    next_block: NextBlock,
+    pending_approvals: &mut PendingTxs<(Address, Address)>,
+    pending_top_ups: &mut PendingTxs<Address>,
+    fetched_calls: &mut HashSet<(Address, Bytes)>,
+    market: &SharedState<Market>,
+    market_state: &SharedState<MarketState>,
+    signers: &SharedState<TxSigners>,
+    accounts: &SharedState<AccountNonceAndBalanceState>,
+    compose_tx: &Broadcaster<MessageTxCompose>,
+    health_monitor_tx: &Broadcaster<MessageHealthEvent>,
+) -> Result<()>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    let multicaller = encoder.get_contract_address();
+    let approvals = required_approvals(&*market.read().await);
+    let signer_addresses = signers.read().await.get_address_vec();
+
+    // allowance and balance slots are not part of the pool state, they are read from the node until they are sufficient
+    let mut calls: Vec<(Address, Bytes)> =
+        approvals.iter().map(|(token, spender)| (*token, allowance_call(multicaller, *spender))).collect();
+    for token in config.balance_targets.keys() {
+        calls.extend([multicaller].iter().chain(signer_addresses.iter()).map(|account| (*token, balance_of_call(*account))));
+    }
+    calls.retain(|call| !fetched_calls.contains(call));
+    fetch_token_calls(client, calls, fetched_calls, market_state, state_reader_config).await;
+
+    let env = Env {
+        block: BlockEnv { number: U256::from(next_block.number), timestamp: U256::from(next_block.timestamp), ..BlockEnv::default() },
+        ..Env::default()
+    };
+
+    let mut missing_approvals: Vec<MissingApproval> = Vec::new();
+    // token, amount, signer holding the amount
+    let mut top_ups: Vec<(Address, U256, Option<Address>)> = Vec::new();
+    {
+        let market_state_guard = market_state.read().await;
+        let db = &market_state_guard.state_db;
+
+        for (token, spender) in approvals.into_iter() {
+            match ERC20StateReader::allowance(db, env.clone(), token, multicaller, spender) {
+                Ok(allowance) if allowance < config.min_allowance => {
+                    fetched_calls.remove(&(token, allowance_call(multicaller, spender)));
+                    missing_approvals.push(MissingApproval { token, owner: multicaller, spender, allowance })
+                }
+                Ok(_) => pending_approvals.remove(&(token, spender)),
+                Err(e) => debug!(%token, %spender, "ERC20StateReader::allowance : {}", e),
+            }
+        }
+
+        for (token, balance_target) in config.balance_targets.iter() {
+            match ERC20StateReader::balance_of(db, env.clone(), *token, multicaller) {
+                Ok(balance) if balance < balance_target.min => {
+                    for account in [multicaller].iter().chain(signer_addresses.iter()) {
+                        fetched_calls.remove(&(*token, balance_of_call(*account)));
+                    }
+                    let amount = balance_target.target - balance;
+                    let funding_signer = signer_addresses.iter().find(|signer| {
+                        ERC20StateReader::balance_of(db, env.clone(), *token, **signer).is_ok_and(|signer_balance| signer_balance >= amount)
+                    });
+                    top_ups.push((*token, amount, funding_signer.cloned()));
+                }
+                Ok(_) => pending_top_ups.remove(token),
+                Err(e) => debug!(%token, "ERC20StateReader::balance_of : {}", e),
+            }
+        }
+    }
+
+    let mut approve_calls = MulticallerCalls::new();
+    for missing_approval in missing_approvals.into_iter() {
+        if !pending_approvals.try_send((missing_approval.token, missing_approval.spender), next_block.number) {
+            continue;
+        }
+        warn!(token = %missing_approval.token, spender = %missing_approval.spender, allowance = %missing_approval.allowance, "Multicaller approval missing");
+        // USDT like tokens revert on approval change from non zero allowance
+        if !missing_approval.allowance.is_zero() {
+            approve_calls.add(MulticallerCall::new_call(
+                missing_approval.token,
+                &EncoderHelper::encode_erc20_approve(missing_approval.spender, U256::ZERO),
+            ));
+        }
+        approve_calls.add(MulticallerCall::new_call(
+            missing_approval.token,
+            &EncoderHelper::encode_erc20_approve(missing_approval.spender, U256::MAX),
+        ));
+        if let Err(e) = health_monitor_tx.send(Message::new(HealthEvent::MissingApproval(missing_approval))).await {
+            error!("health_monitor_tx.send {}", e)
+        }
+    }
+
+    if !approve_calls.opcodes_vec.is_empty() {
+        match select_signer(signers, accounts).await {
+            Some(signer) => {
+                let gas = DO_CALLS_GAS + APPROVE_GAS * approve_calls.opcodes_vec.len() as u64;
+                let (to, call_data) = encoder.encode_calls(approve_calls)?;
+                let tx = TreasuryTx { signer, to, value: U256::ZERO, call_data, gas, origin: "multicaller_approve" };
+                send_treasury_tx(chain_id, tx, &next_block, config.priority_gas_fee, accounts, compose_tx).await?;
+            }
+            None => error!("No signer available for multicaller approvals"),
+        }
+    }
+
+    for (token, amount, funding_signer) in top_ups.into_iter() {
+        let Some(funding_signer) = funding_signer else {
+            warn!(%token, %amount, "No signer holds balance to top up multicaller");
+            continue;
+        };
+        if !pending_top_ups.try_send(token, next_block.number) {
+            continue;
+        }
+        info!(%token, %amount, signer = %funding_signer, "Topping up multicaller balance");
+        let signer = signers.read().await.get_signer_by_address(&funding_signer)?;
+        let call_data = EncoderHelper::encode_erc20_transfer(multicaller, amount);
+        let tx = TreasuryTx { signer, to: token, value: U256::ZERO, call_data, gas: TRANSFER_GAS, origin: "multicaller_top_up" };
+        send_treasury_tx(chain_id, tx, &next_block, config.priority_gas_fee, accounts, compose_tx).await?;
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn multicaller_allowance_worker<P, T, N>(
+    client: P,
+    config: MulticallerAllowanceConfig,
+    state_reader_config: RequiredStateReaderConfig,
+    chain_id: u64,
+    chain_parameters: ChainParameters,
+    encoder: MulticallerSwapEncoder,
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState>,
+    signers: SharedState<TxSigners>,
+    accounts: SharedState<AccountNonceAndBalanceState>,
+    market_events_rx: Broadcaster<MarketEvents>,
+    compose_tx: Broadcaster<MessageTxCompose>,
+    health_monitor_tx: Broadcaster<MessageHealthEvent>,
+) -> WorkerResult
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    subscribe!(market_events_rx);
+
+    let mut pending_approvals: PendingTxs<(Address, Address)> = PendingTxs::new(config.retry_blocks);
+    let mut pending_top_ups: PendingTxs<Address> = PendingTxs::new(config.retry_blocks);
+    let mut fetched_calls: HashSet<(Address, Bytes)> = HashSet::new();
+
+    loop {
+        tokio::select! {
+            msg = market_events_rx.recv() => {
+                let market_event_msg : Result<MarketEvents, RecvError> = msg;
+                match market_event_msg {
+                    Ok(MarketEvents::BlockHeaderUpdate { block_number, timestamp, next_base_fee, .. }) => {
+                        let next_block = NextBlock {
+                            number: block_number + 1,
+                            timestamp: chain_parameters.next_block_timestamp(timestamp),
+                            base_fee: next_base_fee,
+                        };
+                        if let Err(e) = multicaller_allowance_task(
+                            &client,
+                            &config,
+                            &state_reader_config,
+                            chain_id,
+                            &encoder,
+                            next_block,
+                            &mut pending_approvals,
+                            &mut pending_top_ups,
+                            &mut fetched_calls,
+                            &market,
+                            &market_state,
+                            &signers,
+                            &accounts,
+                            &compose_tx,
+                            &health_monitor_tx,
+                        ).await {
+                            error!("multicaller_allowance_task : {}", e)
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        error!("market_events_rx error : {e}")
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Keeps the multicaller approvals and balances required by pools on active swap paths.
+#[derive(Accessor, Consumer, Producer)]
+pub struct MulticallerAllowanceActor<P, T, N> {
+    client: P,
+    config: MulticallerAllowanceConfig,
+    state_reader_config: RequiredStateReaderConfig,
+    chain_id: u64,
+    chain_parameters: ChainParameters,
+    encoder: MulticallerSwapEncoder,
+    #[accessor]
+    market: Option<SharedState<Market>>,
+    #[accessor]
+    market_state: Option<SharedState<MarketState>>,
+    #[accessor]
+    signers: Option<SharedState<TxSigners>>,
+    #[accessor]
+    accounts: Option<SharedState<AccountNonceAndBalanceState>>,
+    #[consumer]
+    market_events_rx: Option<Broadcaster<MarketEvents>>,
+    #[producer]
+    compose_tx: Option<Broadcaster<MessageTxCompose>>,
+    #[producer]
+    health_monitor_tx: Option<Broadcaster<MessageHealthEvent>>,
+    _t: PhantomData<T>,
+    _n: PhantomData<N>,
+}
+
+impl<P, T, N> MulticallerAllowanceActor<P, T, N>
+where
+    N: Network,
+    T: Transport + Clone,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    pub fn new(client: P, encoder: MulticallerSwapEncoder, config: MulticallerAllowanceConfig) -> Self {
+        Self {
+            client,
+            config,
+            state_reader_config: RequiredStateReaderConfig::default(),
+            chain_id: 1,
+            chain_parameters: ChainParameters::ethereum(),
+            encoder,
+            market: None,
+            market_state: None,
+            signers: None,
+            accounts: None,
+            market_events_rx: None,
+            compose_tx: None,
+            health_monitor_tx: None,
+            _t: PhantomData,
+            _n: PhantomData,
+        }
+    }
+
+    /// Batching and concurrency of the allowance and balance state fetched from the node
+    pub fn with_state_reader_config(self, state_reader_config: RequiredStateReaderConfig) -> Self {
+        Self { state_reader_config, ..self }
+    }
+
+    pub fn with_signers(self, signers: SharedState<TxSigners>) -> Self {
+        Self { signers: Some(signers), ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain) -> Self {
+        Self {
+            chain_id: bc.chain_id(),
+            chain_parameters: bc.chain_parameters(),
+            market: Some(bc.market()),
+            market_state: Some(bc.market_state()),
+            accounts: Some(bc.nonce_and_balance()),
+            market_events_rx: Some(bc.market_events_channel()),
+            compose_tx: Some(bc.compose_channel()),
+            health_monitor_tx: Some(bc.pool_health_monitor_channel()),
+            ..self
+        }
+    }
+}
+
+impl<P, T, N> Actor for MulticallerAllowanceActor<P, T, N>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(multicaller_allowance_worker(
+            self.client.clone(),
+            self.config.clone(),
+            self.state_reader_config.clone(),
+            self.chain_id,
+            self.chain_parameters.clone(),
+            self.encoder.clone(),
+            self.market.clone().unwrap(),
+            self.market_state.clone().unwrap(),
+            self.signers.clone().unwrap(),
+            self.accounts.clone().unwrap(),
+            self.market_events_rx.clone().unwrap(),
+            self.compose_tx.clone().unwrap(),
+            self.health_monitor_tx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "MulticallerAllowanceActor"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_balance_target_not_below_min() {
+        let token = Address::repeat_byte(1);
+        let config = MulticallerAllowanceConfig::default().with_balance_target(token, U256::from(10), U256::from(5));
+
+        assert_eq!(config.balance_targets[&token].target, U256::from(10));
+    }
+}
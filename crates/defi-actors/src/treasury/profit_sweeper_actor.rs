@@ -0,0 +1,460 @@
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use alloy_network::Network;
+use alloy_primitives::{Address, Bytes, U256};
+use alloy_provider::Provider;
+use alloy_transport::Transport;
+use eyre::{OptionExt, Result};
+use revm::primitives::{BlockEnv, Env};
+use tracing::{debug, error, info};
+
+use crate::treasury::multicaller_allowance_actor::BalanceTarget;
+use crate::treasury::treasury_tx::{
+    balance_of_call, fetch_token_calls, select_signer, send_treasury_tx, NextBlock, PendingTxs, TreasuryTx, DEFAULT_PRIORITY_GAS_FEE,
+    DO_CALLS_GAS, TRANSFER_GAS,
+};
+use debug_provider::DebugProviderExt;
+use defi_blockchain::Blockchain;
+use defi_entities::required_state::RequiredStateReaderConfig;
+use defi_entities::{AccountNonceAndBalanceState, LatestBlock, MarketState, TxSigners};
+use defi_events::MessageTxCompose;
+use defi_pools::state_readers::ERC20StateReader;
+use defi_types::{ChainParameters, MulticallerCall, MulticallerCalls};
+use loom_actors::{Accessor, Actor, ActorResult, Broadcaster, Producer, SharedState, WorkerResult};
+use loom_actors_macros::{Accessor, Producer};
+use loom_multicaller::{EncoderHelper, MulticallerEncoder, MulticallerSwapEncoder};
+use loom_revm_db::LoomInMemoryDB;
+use loom_utils::NWETH;
+
+const CALL_GAS: u64 = 60_000;
+const ETH_TRANSFER_GAS: u64 = 50_000;
+
+#[derive(Clone, Debug)]
+pub struct ProfitSweeperConfig {
+    /// Address the profits are sent to
+    pub treasury: Address,
+    /// Balances above the threshold are swept, ETH threshold is set for `Address::ZERO`
+    pub thresholds: HashMap<Address, U256>,
+    /// ETH kept on each signer for gas
+    pub eth_float: U256,
+    /// Multicaller balances kept for swaps, ETH float is set for `Address::ZERO`
+    pub multicaller_floats: HashMap<Address, U256>,
+    /// WETH is unwrapped and swept as ETH
+    pub unwrap_weth: bool,
+    pub interval: Duration,
+    /// Blocks a sent withdrawal is waited for before it is sent again
+    pub retry_blocks: u64,
+    pub priority_gas_fee: u64,
+}
+
+impl ProfitSweeperConfig {
+    pub fn new(treasury: Address) -> Self {
+        Self {
+            treasury,
+            thresholds: HashMap::new(),
+            eth_float: NWETH::from_float(0.1),
+            multicaller_floats: HashMap::new(),
+            unwrap_weth: false,
+            interval: Duration::from_secs(600),
+            retry_blocks: 20,
+            priority_gas_fee: DEFAULT_PRIORITY_GAS_FEE,
+        }
+    }
+
+    pub fn with_threshold(mut self, token: Address, threshold: U256) -> Self {
+        self.thresholds.insert(token, threshold);
+        self
+    }
+
+    pub fn with_eth_float(self, eth_float: U256) -> Self {
+        Self { eth_float, ..self }
+    }
+
+    pub fn with_multicaller_float(mut self, token: Address, float: U256) -> Self {
+        self.multicaller_floats.insert(token, float);
+        self
+    }
+
+    /// Keeps the multicaller balances topped up by the allowance actor at their targets
+    pub fn with_balance_targets(mut self, balance_targets: &HashMap<Address, BalanceTarget>) -> Self {
+        for (token, balance_target) in balance_targets.iter() {
+            self.multicaller_floats.insert(*token, balance_target.target);
+        }
+        self
+    }
+
+    pub fn with_unwrap_weth(self) -> Self {
+        Self { unwrap_weth: true, ..self }
+    }
+
+    pub fn with_interval(self, interval: Duration) -> Self {
+        Self { interval, ..self }
+    }
+
+    pub fn with_priority_gas_fee(self, priority_gas_fee: u64) -> Self {
+        Self { priority_gas_fee, ..self }
+    }
+
+    fn is_unwrapped(&self, token: Address, wrapped_native_token: Address) -> bool {
+        self.unwrap_weth && token == wrapped_native_token
+    }
+}
+
+/// ETH of the signer above the float and the cost of the sweeping transaction, None if it is below the threshold.
+fn signer_eth_sweep_amount(balance: U256, eth_float: U256, threshold: U256, gas_cost: U256) -> Option<U256> {
+    let amount = balance.checked_sub(eth_float)?.checked_sub(gas_cost)?;
+    (amount > threshold).then_some(amount)
+}
+
+/// Multicaller balance above the float, None if it is below the threshold.
+fn multicaller_sweep_amount(balance: U256, float: U256, threshold: U256) -> Option<U256> {
+    let amount = balance.checked_sub(float)?;
+    (amount > threshold).then_some(amount)
+}
+
+fn token_balance(db: &LoomInMemoryDB, env: &Env, eth_balances: &HashMap<Address, U256>, token: Address, account: Address) -> Option<U256> {
+    if token.is_zero() {
+        eth_balances.get(&account).cloned()
+    } else {
+        match ERC20StateReader::balance_of(db, env.clone(), token, account) {
+            Ok(balance) => Some(balance),
+            Err(e) => {
+                debug!(%token, %account, "ERC20StateReader::balance_of : {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Balances above thresholds by owner and token, multicaller balances are reduced by their floats. Token balances are read
+/// from the state, ETH balances are passed by owner.
+fn balances_to_sweep(
+    config: &ProfitSweeperConfig,
+    db: &LoomInMemoryDB,
+    env: &Env,
+    eth_balances: &HashMap<Address, U256>,
+    signers: &[Address],
+    multicaller: Address,
+) -> Vec<(Address, Address, U256)> {
+    let mut balances = Vec::new();
+    for (token, threshold) in config.thresholds.iter() {
+        for signer in signers.iter() {
+            if let Some(balance) = token_balance(db, env, eth_balances, *token, *signer) {
+                if balance > *threshold {
+                    balances.push((*signer, *token, balance));
+                }
+            }
+        }
+        if let Some(balance) = token_balance(db, env, eth_balances, *token, multicaller) {
+            let float = config.multicaller_floats.get(token).cloned().unwrap_or_default();
+            if let Some(amount) = multicaller_sweep_amount(balance, float, *threshold) {
+                balances.push((multicaller, *token, amount));
+            }
+        }
+    }
+    balances
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn profit_sweeper_task<P, T, N>(
+    client: &P,
+    config: &ProfitSweeperConfig,
+    state_reader_config: &RequiredStateReaderConfig,
+    chain_parameters: &ChainParameters,
+    encoder: &MulticallerSwapEncoder,
+    pending: &mut PendingTxs<(Address, Address)>,
+    latest_block: &SharedState<LatestBlock>,
+    market_state: &SharedState<MarketState>,
+    signers: &SharedState<TxSigners>,
+    accounts: &SharedState<AccountNonceAndBalanceState>,
+    compose_tx: &Broadcaster<MessageTxCompose>,
+) -> Result<()>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    let next_block = {
+        let latest_block_guard = latest_block.read().await;
+        let header = latest_block_guard.block_header.as_ref().ok_or_eyre("NO_BLOCK_HEADER")?;
+        NextBlock {
+            number: header.number + 1,
+            timestamp: chain_parameters.next_block_timestamp(header.timestamp),
+            base_fee: chain_parameters.calc_next_block_base_fee_from_header(header),
+        }
+    };
+    let env = Env {
+        block: BlockEnv { number: U256::from(next_block.number), timestamp: U256::from(next_block.timestamp), ..BlockEnv::default() },
+        ..Env::default()
+    };
+
+    let multicaller = encoder.get_contract_address();
+    let signer_addresses = signers.read().await.get_address_vec();
+
+    // signer and multicaller token balances are not part of the pool state, they are read from the node before every sweep
+    let calls: Vec<(Address, Bytes)> = config
+        .thresholds
+        .keys()
+        .filter(|token| !token.is_zero())
+        .flat_map(|token| {
+            [multicaller].iter().chain(signer_addresses.iter()).map(|account| (*token, balance_of_call(*account))).collect::<Vec<_>>()
+        })
+        .collect();
+    fetch_token_calls(client, calls, &mut HashSet::new(), market_state, state_reader_config).await;
+
+    let mut eth_balances: HashMap<Address, U256> = {
+        let accounts_guard = accounts.read().await;
+        signer_addresses
+            .iter()
+            .filter_map(|signer| accounts_guard.get_account(signer).map(|account| (*signer, account.get_eth_balance())))
+            .collect()
+    };
+    if config.thresholds.contains_key(&Address::ZERO) {
+        eth_balances.insert(multicaller, client.get_balance(multicaller).await?);
+    }
+
+    let balances = {
+        let market_state_guard = market_state.read().await;
+        balances_to_sweep(config, &market_state_guard.state_db, &env, &eth_balances, &signer_addresses, multicaller)
+    };
+
+    let max_fee_per_gas = U256::from(next_block.base_fee as u128 * 2 + config.priority_gas_fee as u128);
+
+    let mut multicaller_calls = MulticallerCalls::new();
+    let mut signer_txs: Vec<TreasuryTx> = Vec::new();
+    let mut signer_eth: Vec<(Address, U256)> = Vec::new();
+
+    for (owner, token, balance) in balances.into_iter() {
+        if !pending.try_send((owner, token), next_block.number) {
+            continue;
+        }
+        info!(%owner, %token, balance = NWETH::to_float(balance), "Sweeping balance");
+
+        if owner == multicaller {
+            if token.is_zero() {
+                multicaller_calls.add(MulticallerCall::new_call_with_value(config.treasury, &Bytes::new(), balance));
+            } else if config.is_unwrapped(token, chain_parameters.wrapped_native_token) {
+                multicaller_calls.add(MulticallerCall::new_call(token, &EncoderHelper::encode_weth_withdraw(balance)));
+                multicaller_calls.add(MulticallerCall::new_call_with_value(config.treasury, &Bytes::new(), balance));
+            } else {
+                multicaller_calls.add(MulticallerCall::new_call(token, &EncoderHelper::encode_erc20_transfer(config.treasury, balance)));
+            }
+        } else if token.is_zero() {
+            signer_eth.push((owner, balance));
+        } else {
+            let signer = signers.read().await.get_signer_by_address(&owner)?;
+            // unwrapped WETH stays on the signer and is swept with its ETH balance
+            let call_data = if config.is_unwrapped(token, chain_parameters.wrapped_native_token) {
+                EncoderHelper::encode_weth_withdraw(balance)
+            } else {
+                EncoderHelper::encode_erc20_transfer(config.treasury, balance)
+            };
+            signer_txs.push(TreasuryTx { signer, to: token, value: U256::ZERO, call_data, gas: TRANSFER_GAS, origin: "profit_sweep" });
+        }
+    }
+
+    for (owner, balance) in signer_eth.into_iter() {
+        // ETH balance is swept when no other transaction of the signer spends gas
+        if signer_txs.iter().any(|tx| tx.signer.address() == owner) {
+            pending.remove(&(owner, Address::ZERO));
+            continue;
+        }
+        let gas_cost = U256::from(ETH_TRANSFER_GAS) * max_fee_per_gas;
+        let threshold = config.thresholds.get(&Address::ZERO).cloned().unwrap_or_default();
+        match signer_eth_sweep_amount(balance, config.eth_float, threshold, gas_cost) {
+            Some(amount) => {
+                let signer = signers.read().await.get_signer_by_address(&owner)?;
+                signer_txs.push(TreasuryTx {
+                    signer,
+                    to: config.treasury,
+                    value: amount,
+                    call_data: Bytes::new(),
+                    gas: ETH_TRANSFER_GAS,
+                    origin: "profit_sweep",
+                });
+            }
+            None => pending.remove(&(owner, Address::ZERO)),
+        }
+    }
+
+    if !multicaller_calls.opcodes_vec.is_empty() {
+        match select_signer(signers, accounts).await {
+            Some(signer) => {
+                let gas = DO_CALLS_GAS + CALL_GAS * multicaller_calls.opcodes_vec.len() as u64;
+                let (to, call_data) = encoder.encode_calls(multicaller_calls)?;
+                signer_txs.push(TreasuryTx { signer, to, value: U256::ZERO, call_data, gas, origin: "profit_sweep" });
+            }
+            None => error!("No signer available for multicaller withdrawal"),
+        }
+    }
+
+    for tx in signer_txs.into_iter() {
+        send_treasury_tx(chain_parameters.chain_id, tx, &next_block, config.priority_gas_fee, accounts, compose_tx).await?;
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn profit_sweeper_worker<P, T, N>(
+    client: P,
+    config: ProfitSweeperConfig,
+    state_reader_config: RequiredStateReaderConfig,
+    chain_parameters: ChainParameters,
+    encoder: MulticallerSwapEncoder,
+    latest_block: SharedState<LatestBlock>,
+    market_state: SharedState<MarketState>,
+    signers: SharedState<TxSigners>,
+    accounts: SharedState<AccountNonceAndBalanceState>,
+    compose_tx: Broadcaster<MessageTxCompose>,
+) -> WorkerResult
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    let mut pending: PendingTxs<(Address, Address)> = PendingTxs::new(config.retry_blocks);
+    let mut interval = tokio::time::interval(config.interval);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = profit_sweeper_task(
+            &client,
+            &config,
+            &state_reader_config,
+            &chain_parameters,
+            &encoder,
+            &mut pending,
+            &latest_block,
+            &market_state,
+            &signers,
+            &accounts,
+            &compose_tx,
+        )
+        .await
+        {
+            error!("profit_sweeper_task : {}", e)
+        }
+    }
+}
+
+/// Sweeps multicaller and signers balances above thresholds to the treasury on schedule.
+#[derive(Accessor, Producer)]
+pub struct ProfitSweeperActor<P, T, N> {
+    client: P,
+    config: ProfitSweeperConfig,
+    state_reader_config: RequiredStateReaderConfig,
+    chain_parameters: ChainParameters,
+    encoder: MulticallerSwapEncoder,
+    #[accessor]
+    latest_block: Option<SharedState<LatestBlock>>,
+    #[accessor]
+    market_state: Option<SharedState<MarketState>>,
+    #[accessor]
+    signers: Option<SharedState<TxSigners>>,
+    #[accessor]
+    accounts: Option<SharedState<AccountNonceAndBalanceState>>,
+    #[producer]
+    compose_tx: Option<Broadcaster<MessageTxCompose>>,
+    _t: PhantomData<T>,
+    _n: PhantomData<N>,
+}
+
+impl<P, T, N> ProfitSweeperActor<P, T, N>
+where
+    N: Network,
+    T: Transport + Clone,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    pub fn new(client: P, encoder: MulticallerSwapEncoder, config: ProfitSweeperConfig) -> Self {
+        Self {
+            client,
+            config,
+            state_reader_config: RequiredStateReaderConfig::default(),
+            chain_parameters: ChainParameters::ethereum(),
+            encoder,
+            latest_block: None,
+            market_state: None,
+            signers: None,
+            accounts: None,
+            compose_tx: None,
+            _t: PhantomData,
+            _n: PhantomData,
+        }
+    }
+
+    /// Batching and concurrency of the balance state fetched from the node
+    pub fn with_state_reader_config(self, state_reader_config: RequiredStateReaderConfig) -> Self {
+        Self { state_reader_config, ..self }
+    }
+
+    pub fn with_signers(self, signers: SharedState<TxSigners>) -> Self {
+        Self { signers: Some(signers), ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain) -> Self {
+        Self {
+            chain_parameters: bc.chain_parameters(),
+            latest_block: Some(bc.latest_block()),
+            market_state: Some(bc.market_state()),
+            accounts: Some(bc.nonce_and_balance()),
+            compose_tx: Some(bc.compose_channel()),
+            ..self
+        }
+    }
+}
+
+impl<P, T, N> Actor for ProfitSweeperActor<P, T, N>
+where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(profit_sweeper_worker(
+            self.client.clone(),
+            self.config.clone(),
+            self.state_reader_config.clone(),
+            self.chain_parameters.clone(),
+            self.encoder.clone(),
+            self.latest_block.clone().unwrap(),
+            self.market_state.clone().unwrap(),
+            self.signers.clone().unwrap(),
+            self.accounts.clone().unwrap(),
+            self.compose_tx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "ProfitSweeperActor"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_signer_eth_sweep_amount() {
+        let eth_float = U256::from(100);
+        let gas_cost = U256::from(10);
+
+        assert_eq!(signer_eth_sweep_amount(U256::from(200), eth_float, U256::from(50), gas_cost), Some(U256::from(90)));
+        assert_eq!(signer_eth_sweep_amount(U256::from(150), eth_float, U256::from(50), gas_cost), None);
+        assert_eq!(signer_eth_sweep_amount(U256::from(105), eth_float, U256::ZERO, gas_cost), None);
+        assert_eq!(signer_eth_sweep_amount(U256::from(50), eth_float, U256::ZERO, gas_cost), None);
+    }
+
+    #[test]
+    fn test_multicaller_sweep_amount() {
+        let float = U256::from(100);
+
+        assert_eq!(multicaller_sweep_amount(U256::from(200), float, U256::from(50)), Some(U256::from(100)));
+        assert_eq!(multicaller_sweep_amount(U256::from(150), float, U256::from(50)), None);
+        assert_eq!(multicaller_sweep_amount(U256::from(50), float, U256::ZERO), None);
+    }
+}
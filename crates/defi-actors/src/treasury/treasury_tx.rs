@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use alloy_network::Network;
+use alloy_primitives::{Address, BlockNumber, Bytes, TxKind, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types::{TransactionInput, TransactionRequest};
+use alloy_sol_types::SolCall;
+use alloy_transport::Transport;
+use eyre::{eyre, Result};
+use tracing::{debug, error};
+
+use debug_provider::DebugProviderExt;
+use defi_abi::IERC20;
+use defi_entities::required_state::{RequiredState, RequiredStateReader, RequiredStateReaderConfig};
+use defi_entities::{AccountNonceAndBalanceState, MarketState, TxSigner, TxSigners};
+use defi_events::{MessageTxCompose, TxComposeData, TxState};
+use loom_actors::{Broadcaster, SharedState};
+
+pub(crate) const DO_CALLS_GAS: u64 = 50_000;
+pub(crate) const TRANSFER_GAS: u64 = 100_000;
+pub(crate) const DEFAULT_PRIORITY_GAS_FEE: u64 = 1_000_000_000;
+
+/// Block the treasury transactions are built for.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NextBlock {
+    pub number: BlockNumber,
+    pub timestamp: u64,
+    pub base_fee: u64,
+}
+
+/// Transaction of the signer sent to the signing and broadcasting pipeline.
+#[derive(Clone, Debug)]
+pub(crate) struct TreasuryTx {
+    pub signer: TxSigner,
+    pub to: Address,
+    pub value: U256,
+    pub call_data: Bytes,
+    pub gas: u64,
+    pub origin: &'static str,
+}
+
+/// Keys of sent transactions with the block they were sent at.
+pub(crate) struct PendingTxs<K> {
+    sent: HashMap<K, BlockNumber>,
+    retry_blocks: u64,
+}
+
+impl<K: Eq + Hash> PendingTxs<K> {
+    pub(crate) fn new(retry_blocks: u64) -> Self {
+        Self { sent: HashMap::new(), retry_blocks }
+    }
+
+    /// Returns true and marks the key as sent if no transaction is waited for.
+    pub(crate) fn try_send(&mut self, key: K, block: BlockNumber) -> bool {
+        match self.sent.get(&key) {
+            Some(sent_block) if block < sent_block + self.retry_blocks => false,
+            _ => {
+                self.sent.insert(key, block);
+                true
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, key: &K) {
+        self.sent.remove(key);
+    }
+}
+
+pub(crate) fn balance_of_call(account: Address) -> Bytes {
+    IERC20::balanceOfCall { account }.abi_encode().into()
+}
+
+/// Loads the token storage read by the calls into the market state, the slots are updated by block state updates afterwards.
+pub(crate) async fn fetch_token_calls<P, T, N>(
+    client: &P,
+    calls: Vec<(Address, Bytes)>,
+    fetched_calls: &mut HashSet<(Address, Bytes)>,
+    market_state: &SharedState<MarketState>,
+    state_reader_config: &RequiredStateReaderConfig,
+) where
+    T: Transport + Clone,
+    N: Network,
+    P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
+{
+    if calls.is_empty() {
+        return;
+    }
+
+    let required_states: Vec<RequiredState> = calls
+        .iter()
+        .map(|(token, call_data)| {
+            let mut required_state = RequiredState::new();
+            required_state.add_call(*token, call_data.clone());
+            required_state
+        })
+        .collect();
+
+    let states = RequiredStateReader::fetch_many(client.clone(), required_states, None, state_reader_config).await;
+
+    let mut market_state_guard = market_state.write().await;
+    for (call, state) in calls.into_iter().zip(states) {
+        match state {
+            Ok(state) => {
+                market_state_guard.add_state(&state);
+                fetched_calls.insert(call);
+            }
+            Err(e) => error!(token = %call.0, "Token state fetch error : {}", e),
+        }
+    }
+}
+
+/// Signer without a transaction waiting for inclusion, any signer if all of them are busy.
+pub(crate) async fn select_signer(
+    signers: &SharedState<TxSigners>,
+    accounts: &SharedState<AccountNonceAndBalanceState>,
+) -> Option<TxSigner> {
+    let signers = signers.read().await;
+    let accounts = accounts.read().await;
+    signers
+        .get_address_vec()
+        .into_iter()
+        .find(|address| accounts.get_account(address).is_some_and(|account| account.has_free_nonce()))
+        .and_then(|address| signers.get_signer_by_address(&address).ok())
+        .or_else(|| signers.get_randon_signer())
+}
+
+/// Reserves the next free nonce of the signer and sends the transaction for signing, several transactions of the signer
+/// sent at one block get consecutive nonces. The nonce is released if sending fails.
+pub(crate) async fn send_treasury_tx(
+    chain_id: u64,
+    tx: TreasuryTx,
+    next_block: &NextBlock,
+    priority_gas_fee: u64,
+    accounts: &SharedState<AccountNonceAndBalanceState>,
+    compose_tx: &Broadcaster<MessageTxCompose>,
+) -> Result<()> {
+    if next_block.base_fee == 0 {
+        return Err(eyre!("NO_BLOCK_GAS_FEE"));
+    }
+
+    let signer_address = tx.signer.address();
+    let (reserved_nonce, eth_balance) = {
+        let mut accounts = accounts.write().await;
+        let account = accounts.get_mut_account(&signer_address).ok_or(eyre!("SIGNER_ACCOUNT_NOT_MONITORED"))?;
+        (account.reserve_next_nonce(next_block.number), account.get_eth_balance())
+    };
+    let nonce = reserved_nonce.nonce;
+    debug!(signer = %signer_address, nonce, origin = tx.origin, "Nonce reserved");
+
+    // base fee can double in a few blocks, the transaction stays valid while it is resubmitted
+    let max_fee_per_gas = next_block.base_fee as u128 * 2 + priority_gas_fee as u128;
+
+    let tx_request = TransactionRequest {
+        transaction_type: Some(2),
+        chain_id: Some(chain_id),
+        from: Some(signer_address),
+        to: Some(TxKind::Call(tx.to)),
+        gas: Some(tx.gas),
+        value: Some(tx.value),
+        input: TransactionInput::new(tx.call_data),
+        nonce: Some(nonce),
+        max_priority_fee_per_gas: Some(priority_gas_fee as u128),
+        max_fee_per_gas: Some(max_fee_per_gas),
+        ..TransactionRequest::default()
+    };
+
+    let sign_request = TxComposeData {
+        signer: Some(tx.signer),
+        nonce,
+        reserved_nonce: Some(reserved_nonce),
+        eth_balance,
+        value: tx.value,
+        gas: tx.gas,
+        priority_gas_fee,
+        next_block_number: next_block.number,
+        next_block_timestamp: next_block.timestamp,
+        next_block_base_fee: next_block.base_fee,
+        tx_bundle: Some(vec![TxState::SignatureRequired(tx_request)]),
+        origin: Some(tx.origin.to_string()),
+        ..TxComposeData::default()
+    };
+
+    if let Err(e) = compose_tx.send(MessageTxCompose::sign(sign_request)).await {
+        error!("compose_tx.send {}", e);
+        accounts.write().await.release_nonce(&signer_address, reserved_nonce);
+        return Err(eyre!(e));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pending_txs_retry() {
+        let token = Address::repeat_byte(1);
+        let mut pending: PendingTxs<Address> = PendingTxs::new(3);
+
+        assert!(pending.try_send(token, 100));
+        assert!(!pending.try_send(token, 101));
+        assert!(!pending.try_send(token, 102));
+        assert!(pending.try_send(token, 103));
+
+        pending.remove(&token);
+        assert!(pending.try_send(token, 104));
+    }
+}
@@ -45,6 +45,7 @@ pub struct PeripheryAddress;
 
 impl PeripheryAddress {
     pub const UNISWAP_V2_ROUTER: Address = address!("7a250d5630b4cf539739df2c5dacb4c659f2488d");
+    pub const SUSHISWAP_V2_ROUTER: Address = address!("d9e1ce17f2641f24ae83637ab66a2cca9c378b9f");
     pub const UNISWAP_V3_QUOTER_V2: Address = address!("61ffe014ba17989e743c5f6cb21bf9697530b21e");
     pub const UNISWAP_V3_TICK_LENS: Address = address!("bfd8137f7d1516d3ea5ca83523914859ec47f573");
     pub const PANCAKE_V3_QUOTER: Address = address!("b048bbc1ee6b733fffcfb9e9cef7375518e25997");
@@ -52,6 +53,36 @@ impl PeripheryAddress {
     pub const MAVERICK_QUOTER: Address = address!("9980ce3b5570e41324904f46a06ce7b466925e23");
 }
 
+#[non_exhaustive]
+pub struct AaveV3Address;
+
+impl AaveV3Address {
+    pub const POOL: Address = address!("87870bca3f3fd6335c3f4ce8392d69350b4fa4e2");
+    pub const POOL_DATA_PROVIDER: Address = address!("7b4eb56e7cd4b454ba8ff71e4518426369a138a3");
+    pub const ORACLE: Address = address!("54586be62e3c3580375ae3723c145253060ca0c2");
+}
+
+#[non_exhaustive]
+pub struct BalancerAddress;
+
+impl BalancerAddress {
+    pub const VAULT: Address = address!("ba12222222228d8ba445958a75a0704d566bf2c8");
+}
+
+#[non_exhaustive]
+pub struct MakerAddress;
+
+impl MakerAddress {
+    pub const DSS_FLASH: Address = address!("60744434d6339a6b27d73d9eda62b6f66a0a04fa");
+}
+
+#[non_exhaustive]
+pub struct UniswapV4Address;
+
+impl UniswapV4Address {
+    pub const POOL_MANAGER: Address = address!("000000000004444c5dc75cb358380d2e3de08a90");
+}
+
 #[non_exhaustive]
 pub struct UniswapV2PoolAddress;
 
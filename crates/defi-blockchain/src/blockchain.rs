@@ -1,7 +1,7 @@
 use alloy::primitives::BlockHash;
-use alloy::primitives::ChainId;
+use alloy::primitives::{Address, ChainId};
 use defi_address_book::TokenAddress;
-use defi_entities::{AccountNonceAndBalanceState, BlockHistory, LatestBlock, Market, MarketState, Token};
+use defi_entities::{AccountNonceAndBalanceState, BidStats, BlockHistory, GasPriceOracle, LatestBlock, Market, MarketState, Token};
 use defi_events::{
     MarketEvents, MempoolEvents, MessageBlock, MessageBlockHeader, MessageBlockLogs, MessageBlockStateUpdate, MessageHealthEvent,
     MessageMempoolDataUpdate, MessageTxCompose, StateUpdateEvent, Task,
@@ -20,6 +20,8 @@ pub struct Blockchain {
     block_history_state: SharedState<BlockHistory>,
     mempool: SharedState<Mempool>,
     account_nonce_and_balance: SharedState<AccountNonceAndBalanceState>,
+    bid_stats: SharedState<BidStats>,
+    gas_price_oracle: SharedState<GasPriceOracle>,
 
     new_block_headers_channel: Broadcaster<MessageBlockHeader>,
     new_block_with_tx_channel: Broadcaster<MessageBlock>,
@@ -37,6 +39,11 @@ pub struct Blockchain {
 
 impl Blockchain {
     pub fn new(chain_id: ChainId) -> Blockchain {
+        Self::new_with_chain_parameters(ChainParameters::from(chain_id))
+    }
+
+    /// Market is seeded with the tokens of the chain parameters.
+    pub fn new_with_chain_parameters(chain_parameters: ChainParameters) -> Blockchain {
         let new_block_headers_channel: Broadcaster<MessageBlockHeader> = Broadcaster::new(10);
         let new_block_with_tx_channel: Broadcaster<MessageBlock> = Broadcaster::new(10);
         let new_block_state_update_channel: Broadcaster<MessageBlockStateUpdate> = Broadcaster::new(10);
@@ -52,31 +59,26 @@ impl Blockchain {
         let influx_write_channel: Broadcaster<WriteQuery> = Broadcaster::new(1000);
         let tasks_channel: Broadcaster<Task> = Broadcaster::new(1000);
 
-        let mut market_instance = Market::default();
+        let mut market_instance = Market::default().with_wrapped_native_token(Self::chain_wrapped_native_token(&chain_parameters));
 
-        let weth_token = Token::new_with_data(TokenAddress::WETH, Some("WETH".to_string()), None, Some(18), true, false);
-        let usdc_token = Token::new_with_data(TokenAddress::USDC, Some("USDC".to_string()), None, Some(6), true, false);
-        let usdt_token = Token::new_with_data(TokenAddress::USDT, Some("USDT".to_string()), None, Some(6), true, false);
-        let dai_token = Token::new_with_data(TokenAddress::DAI, Some("DAI".to_string()), None, Some(18), true, false);
-        let wbtc_token = Token::new_with_data(TokenAddress::WBTC, Some("WBTC".to_string()), None, Some(8), true, false);
-        let threecrv_token = Token::new_with_data(TokenAddress::THREECRV, Some("3Crv".to_string()), None, Some(18), false, true);
-
-        market_instance.add_token(weth_token).unwrap();
-        market_instance.add_token(usdc_token).unwrap();
-        market_instance.add_token(usdt_token).unwrap();
-        market_instance.add_token(dai_token).unwrap();
-        market_instance.add_token(wbtc_token).unwrap();
-        market_instance.add_token(threecrv_token).unwrap();
+        for token in chain_parameters.tokens.iter() {
+            let token =
+                Token::new_with_data(token.address, Some(token.symbol.clone()), None, Some(token.decimals), token.basic, token.middle)
+                    .with_wrapped_native(market_instance.is_weth(&token.address));
+            market_instance.add_token(token).unwrap();
+        }
 
         Blockchain {
-            chain_id,
-            chain_parameters: ChainParameters::ethereum(),
+            chain_id: chain_parameters.chain_id,
+            gas_price_oracle: SharedState::new(GasPriceOracle::new(chain_parameters.clone())),
+            chain_parameters,
             market: SharedState::new(market_instance),
             market_state: SharedState::new(MarketState::new(Default::default())),
             mempool: SharedState::new(Mempool::new()),
             latest_block: SharedState::new(LatestBlock::new(0, BlockHash::ZERO)),
             block_history_state: SharedState::new(BlockHistory::new(10)),
             account_nonce_and_balance: SharedState::new(AccountNonceAndBalanceState::new()),
+            bid_stats: SharedState::new(BidStats::new()),
             new_block_headers_channel,
             new_block_with_tx_channel,
             new_block_state_update_channel,
@@ -100,6 +102,19 @@ impl Blockchain {
         self.chain_parameters.clone()
     }
 
+    /// Wrapped native token of the chain, mainnet WETH if it is not set in the chain parameters.
+    pub fn wrapped_native_token(&self) -> Address {
+        Self::chain_wrapped_native_token(&self.chain_parameters)
+    }
+
+    fn chain_wrapped_native_token(chain_parameters: &ChainParameters) -> Address {
+        if chain_parameters.wrapped_native_token.is_zero() {
+            TokenAddress::WETH
+        } else {
+            chain_parameters.wrapped_native_token
+        }
+    }
+
     pub fn market(&self) -> SharedState<Market> {
         self.market.clone()
     }
@@ -124,6 +139,14 @@ impl Blockchain {
         self.account_nonce_and_balance.clone()
     }
 
+    pub fn bid_stats(&self) -> SharedState<BidStats> {
+        self.bid_stats.clone()
+    }
+
+    pub fn gas_price_oracle(&self) -> SharedState<GasPriceOracle> {
+        self.gas_price_oracle.clone()
+    }
+
     pub fn new_block_headers_channel(&self) -> Broadcaster<MessageBlockHeader> {
         self.new_block_headers_channel.clone()
     }
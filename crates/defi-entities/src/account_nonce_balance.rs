@@ -1,11 +1,31 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
-use alloy_primitives::{Address, U256};
+use alloy_primitives::{Address, BlockNumber, U256};
+
+/// Nonce reserved for one opportunity. Transactions composed for the opportunity hold the reservation until it is
+/// broadcasted or dropped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReservedNonce {
+    pub nonce: u64,
+    /// Key of the opportunity in the reservation
+    pub key: u64,
+}
+
+/// Nonce reserved by composed transactions that are not mined yet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NonceReservation {
+    /// Opportunities sharing the nonce
+    pub keys: BTreeSet<u64>,
+    /// Last block the transactions are targeted for, the reservation expires after it
+    pub max_block: BlockNumber,
+}
 
 #[derive(Debug, Clone, Default)]
 pub struct AccountNonceAndBalances {
     nonce: u64,
     balance: HashMap<Address, U256>,
+    reservations: BTreeMap<u64, NonceReservation>,
+    next_reservation_key: u64,
 }
 
 impl AccountNonceAndBalances {
@@ -50,6 +70,63 @@ impl AccountNonceAndBalances {
     pub fn get_balance(&self, token_address: &Address) -> U256 {
         self.balance.get(token_address).cloned().unwrap_or_default()
     }
+
+    /// True if no composed transaction of the account is waiting for inclusion.
+    pub fn has_free_nonce(&self) -> bool {
+        self.reservations.is_empty()
+    }
+
+    pub fn get_reservation(&self, nonce: u64) -> Option<&NonceReservation> {
+        self.reservations.get(&nonce)
+    }
+
+    /// Reserves the next nonce of the account for an opportunity targeting the block.
+    /// Opportunities composed while the nonce is reserved share it and compete for inclusion.
+    pub fn reserve_nonce(&mut self, block: BlockNumber) -> ReservedNonce {
+        self.next_reservation_key += 1;
+        let reserved = ReservedNonce { nonce: self.nonce, key: self.next_reservation_key };
+        let reservation = self.reservations.entry(self.nonce).or_default();
+        reservation.keys.insert(reserved.key);
+        reservation.max_block = reservation.max_block.max(block);
+        reserved
+    }
+
+    /// Reserves the first nonce of the account that is not reserved yet for a transaction targeting the block. Transactions
+    /// sent one after another by the same account get consecutive nonces instead of replacing each other.
+    pub fn reserve_next_nonce(&mut self, block: BlockNumber) -> ReservedNonce {
+        let mut nonce = self.nonce;
+        while self.reservations.contains_key(&nonce) {
+            nonce += 1;
+        }
+        self.next_reservation_key += 1;
+        let reserved = ReservedNonce { nonce, key: self.next_reservation_key };
+        self.reservations.insert(nonce, NonceReservation { keys: BTreeSet::from([reserved.key]), max_block: block });
+        reserved
+    }
+
+    /// Extends the reservation of the broadcasted opportunity until its last target block.
+    pub fn confirm_nonce(&mut self, reserved: ReservedNonce, max_block: BlockNumber) {
+        if let Some(reservation) = self.reservations.get_mut(&reserved.nonce).filter(|reservation| reservation.keys.contains(&reserved.key))
+        {
+            reservation.max_block = reservation.max_block.max(max_block);
+        }
+    }
+
+    /// Releases the reservation of the opportunity that is dropped. Releasing it again has no effect.
+    pub fn release_nonce(&mut self, reserved: ReservedNonce) {
+        if let Some(reservation) = self.reservations.get_mut(&reserved.nonce) {
+            reservation.keys.remove(&reserved.key);
+            if reservation.keys.is_empty() {
+                self.reservations.remove(&reserved.nonce);
+            }
+        }
+    }
+
+    /// Removes reservations of mined nonces and reservations expired at the block.
+    pub fn reconcile(&mut self, block: BlockNumber) {
+        let nonce = self.nonce;
+        self.reservations.retain(|reserved_nonce, reservation| *reserved_nonce >= nonce && reservation.max_block > block);
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -85,4 +162,86 @@ impl AccountNonceAndBalanceState {
     pub fn get_entry_or_default(&mut self, account: Address) -> &mut AccountNonceAndBalances {
         self.accounts.entry(account).or_default()
     }
+
+    pub fn confirm_nonce(&mut self, account: &Address, reserved: ReservedNonce, max_block: BlockNumber) {
+        if let Some(acc) = self.accounts.get_mut(account) {
+            acc.confirm_nonce(reserved, max_block);
+        }
+    }
+
+    pub fn release_nonce(&mut self, account: &Address, reserved: ReservedNonce) {
+        if let Some(acc) = self.accounts.get_mut(account) {
+            acc.release_nonce(reserved);
+        }
+    }
+
+    /// Reconciles nonce reservations of all accounts after the block.
+    pub fn reconcile(&mut self, block: BlockNumber) {
+        self.accounts.values_mut().for_each(|acc| acc.reconcile(block));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_nonce_reservation() {
+        let mut account = AccountNonceAndBalances::new();
+        account.set_nonce(5);
+        assert!(account.has_free_nonce());
+
+        let first = account.reserve_nonce(10);
+        assert_eq!(first.nonce, 5);
+        assert!(!account.has_free_nonce());
+        // opportunities composed while the nonce is reserved share it
+        let second = account.reserve_nonce(10);
+        assert_eq!(second.nonce, 5);
+        assert_ne!(first.key, second.key);
+        assert_eq!(account.get_reservation(5).unwrap().keys.len(), 2);
+
+        account.release_nonce(first);
+        // releasing the same opportunity twice keeps the reservation of the other one
+        account.release_nonce(first);
+        assert_eq!(account.get_reservation(5).unwrap().keys, BTreeSet::from([second.key]));
+        account.release_nonce(second);
+        assert!(account.has_free_nonce());
+    }
+
+    #[test]
+    fn test_reserve_next_nonce() {
+        let mut account = AccountNonceAndBalances::new();
+        account.set_nonce(5);
+
+        assert_eq!(account.reserve_next_nonce(10).nonce, 5);
+        let second = account.reserve_next_nonce(10);
+        assert_eq!(second.nonce, 6);
+        assert_eq!(account.reserve_next_nonce(10).nonce, 7);
+
+        // released nonce is reserved again
+        account.release_nonce(second);
+        assert_eq!(account.reserve_next_nonce(10).nonce, 6);
+        assert_eq!(account.reserve_next_nonce(10).nonce, 8);
+    }
+
+    #[test]
+    fn test_nonce_reconcile() {
+        let mut account = AccountNonceAndBalances::new();
+        account.set_nonce(5);
+        let reserved = account.reserve_nonce(10);
+        account.confirm_nonce(reserved, 12);
+        // opportunity without the reservation does not extend it
+        account.confirm_nonce(ReservedNonce { nonce: 5, key: 100 }, 20);
+
+        account.reconcile(11);
+        assert!(!account.has_free_nonce());
+        account.reconcile(12);
+        assert!(account.has_free_nonce());
+
+        // mined nonce is released before its target block
+        account.reserve_nonce(20);
+        account.set_nonce(6);
+        account.reconcile(15);
+        assert!(account.has_free_nonce());
+    }
 }
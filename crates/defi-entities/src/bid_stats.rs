@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use alloy_primitives::{Address, TxHash, U256};
+
+const MAX_TIPS_PCT: u32 = 9900;
+const MIN_TIPS_PCT: u32 = 1000;
+const LOST_MARGIN_PCT: u32 = 100;
+const LANDED_DECAY_PCT: u32 = 50;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PoolPair(Address, Address);
+
+impl PoolPair {
+    pub fn new(pool0: Address, pool1: Address) -> Self {
+        if pool0 <= pool1 {
+            PoolPair(pool0, pool1)
+        } else {
+            PoolPair(pool1, pool0)
+        }
+    }
+
+    /// Pool pairs of consecutive pools of a swap. A swap with a single pool is paired with itself.
+    pub fn from_pools(pools: &[Address]) -> Vec<PoolPair> {
+        match pools.len() {
+            0 => Vec::new(),
+            1 => vec![PoolPair::new(pools[0], pools[0])],
+            _ => pools.windows(2).map(|w| PoolPair::new(w[0], w[1])).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PoolPairBidStats {
+    pub landed: u64,
+    pub lost: u64,
+    pub last_competitor_tx: Option<TxHash>,
+    pub last_competitor_payment: Option<U256>,
+    pub tips_pct: Option<u32>,
+}
+
+impl PoolPairBidStats {
+    pub fn on_landed(&mut self, tips_pct: u32) {
+        self.landed += 1;
+        let current = self.tips_pct.unwrap_or(tips_pct);
+        self.tips_pct = Some(current.saturating_sub(LANDED_DECAY_PCT).max(MIN_TIPS_PCT));
+    }
+
+    pub fn on_lost(&mut self, tips_pct: u32, profit_eth: U256, competitor_tx: Option<TxHash>, competitor_payment: Option<U256>) {
+        self.lost += 1;
+        self.last_competitor_tx = competitor_tx;
+        self.last_competitor_payment = competitor_payment;
+
+        let current = self.tips_pct.unwrap_or(tips_pct);
+
+        let required = match competitor_payment {
+            Some(payment) if !profit_eth.is_zero() => {
+                let pct = payment.saturating_mul(U256::from(10000)) / profit_eth;
+                if pct > U256::from(MAX_TIPS_PCT) {
+                    MAX_TIPS_PCT
+                } else {
+                    pct.to::<u32>() + LOST_MARGIN_PCT
+                }
+            }
+            _ => current + LOST_MARGIN_PCT,
+        };
+
+        self.tips_pct = Some(current.max(required).min(MAX_TIPS_PCT));
+    }
+
+    pub fn landed_ratio(&self) -> f64 {
+        let total = self.landed + self.lost;
+        if total == 0 {
+            0.0
+        } else {
+            self.landed as f64 / total as f64
+        }
+    }
+}
+
+/// Landed and lost backrun statistics per pool pair
+#[derive(Clone, Debug, Default)]
+pub struct BidStats {
+    pairs: HashMap<PoolPair, PoolPairBidStats>,
+}
+
+impl BidStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    pub fn get(&self, pair: &PoolPair) -> Option<&PoolPairBidStats> {
+        self.pairs.get(pair)
+    }
+
+    pub fn on_landed(&mut self, pools: &[Address], tips_pct: u32) {
+        for pair in PoolPair::from_pools(pools) {
+            self.pairs.entry(pair).or_default().on_landed(tips_pct);
+        }
+    }
+
+    pub fn on_lost(
+        &mut self,
+        pools: &[Address],
+        tips_pct: u32,
+        profit_eth: U256,
+        competitor_tx: Option<TxHash>,
+        competitor_payment: Option<U256>,
+    ) {
+        for pair in PoolPair::from_pools(pools) {
+            self.pairs.entry(pair).or_default().on_lost(tips_pct, profit_eth, competitor_tx, competitor_payment);
+        }
+    }
+
+    /// Returns tips_pct adjusted by the history of the pool pairs the swap goes through. Highest pair value wins.
+    pub fn adjusted_tips_pct(&self, pools: &[Address], tips_pct: u32) -> u32 {
+        PoolPair::from_pools(pools).iter().filter_map(|pair| self.pairs.get(pair).and_then(|x| x.tips_pct)).max().unwrap_or(tips_pct)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pool_pair() {
+        let a = Address::repeat_byte(1);
+        let b = Address::repeat_byte(2);
+        assert_eq!(PoolPair::new(a, b), PoolPair::new(b, a));
+        assert_eq!(PoolPair::from_pools(&[a, b, a]).len(), 2);
+        assert_eq!(PoolPair::from_pools(&[a]), vec![PoolPair::new(a, a)]);
+    }
+
+    #[test]
+    fn test_adjusted_tips_pct() {
+        let a = Address::repeat_byte(1);
+        let b = Address::repeat_byte(2);
+        let mut stats = BidStats::new();
+
+        assert_eq!(stats.adjusted_tips_pct(&[a, b], 5000), 5000);
+
+        // competitor paid 80% of our profit
+        stats.on_lost(&[a, b], 5000, U256::from(1000), None, Some(U256::from(800)));
+        assert_eq!(stats.adjusted_tips_pct(&[b, a], 5000), 8100);
+
+        stats.on_landed(&[a, b], 5000);
+        assert_eq!(stats.adjusted_tips_pct(&[a, b], 5000), 8050);
+
+        stats.on_lost(&[a, b], 5000, U256::from(1000), None, Some(U256::from(2000)));
+        assert_eq!(stats.adjusted_tips_pct(&[a, b], 5000), MAX_TIPS_PCT);
+
+        let pair_stats = stats.get(&PoolPair::new(a, b)).unwrap();
+        assert_eq!(pair_stats.landed, 1);
+        assert_eq!(pair_stats.lost, 2);
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+
+use alloy_primitives::{Address, BlockNumber, U256};
+use alloy_rpc_types::{Block, Header, Transaction};
+
+use defi_types::{ChainParameters, GethStateUpdateVec};
+
+const DEFAULT_MAX_BLOCKS: usize = 20;
+
+/// Gas stats of a block collected from block history.
+#[derive(Clone, Debug, Default)]
+pub struct BlockGasStats {
+    pub base_fee: u64,
+    pub gas_used: u64,
+    pub gas_limit: u64,
+    /// Effective priority fees of the block transactions, sorted ascending
+    pub priority_fees: Vec<u64>,
+    /// Coinbase balance increase per gas used, includes direct transfers to the builder
+    pub builder_payment_per_gas: Option<u64>,
+}
+
+/// Priority fee paid to the block builder by the transaction included with the base fee.
+pub fn effective_priority_fee(tx: &Transaction, base_fee: u64) -> Option<u64> {
+    let base_fee = base_fee as u128;
+    let fee = match (tx.max_priority_fee_per_gas, tx.max_fee_per_gas) {
+        (Some(max_priority_fee), Some(max_fee)) => max_priority_fee.min(max_fee.saturating_sub(base_fee)),
+        _ => tx.gas_price?.saturating_sub(base_fee),
+    };
+    Some(u64::try_from(fee).unwrap_or(u64::MAX))
+}
+
+/// Coinbase balance increase over the block. Payouts from the coinbase to the proposer are not subtracted.
+pub fn builder_payment(state_update: &GethStateUpdateVec, coinbase: Address) -> Option<U256> {
+    let balances: Vec<U256> = state_update.iter().filter_map(|state| state.get(&coinbase).and_then(|account| account.balance)).collect();
+    if balances.len() < 2 {
+        return None;
+    }
+    Some(balances.windows(2).map(|w| w[1].saturating_sub(w[0])).fold(U256::ZERO, |acc, x| acc + x))
+}
+
+fn percentile(sorted: &[u64], pct: u32) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let idx = (sorted.len() - 1) * pct.min(100) as usize / 100;
+    Some(sorted[idx])
+}
+
+/// Gas price oracle fed from block history.
+///
+/// Provides percentile priority fees of recent blocks, effective builder payments and base fee projections for the next blocks.
+#[derive(Clone, Debug)]
+pub struct GasPriceOracle {
+    chain_parameters: ChainParameters,
+    max_blocks: usize,
+    blocks: BTreeMap<BlockNumber, BlockGasStats>,
+}
+
+impl Default for GasPriceOracle {
+    fn default() -> Self {
+        Self::new(ChainParameters::ethereum())
+    }
+}
+
+impl GasPriceOracle {
+    pub fn new(chain_parameters: ChainParameters) -> Self {
+        Self { chain_parameters, max_blocks: DEFAULT_MAX_BLOCKS, blocks: BTreeMap::new() }
+    }
+
+    /// Number of recent blocks the stats are collected for.
+    pub fn with_max_blocks(self, max_blocks: usize) -> Self {
+        Self { max_blocks: max_blocks.max(1), ..self }
+    }
+
+    pub fn last_block_number(&self) -> Option<BlockNumber> {
+        self.blocks.last_key_value().map(|(number, _)| *number)
+    }
+
+    pub fn get_block_stats(&self, block_number: BlockNumber) -> Option<&BlockGasStats> {
+        self.blocks.get(&block_number)
+    }
+
+    pub fn on_block_header(&mut self, header: &Header) {
+        let stats = self.blocks.entry(header.number).or_default();
+        stats.base_fee = header.base_fee_per_gas.unwrap_or_default();
+        stats.gas_used = header.gas_used;
+        stats.gas_limit = header.gas_limit;
+
+        while self.blocks.len() > self.max_blocks {
+            self.blocks.pop_first();
+        }
+    }
+
+    pub fn on_block(&mut self, block: &Block) {
+        self.on_block_header(&block.header);
+
+        let base_fee = block.header.base_fee_per_gas.unwrap_or_default();
+        if let (Some(txs), Some(stats)) = (block.transactions.as_transactions(), self.blocks.get_mut(&block.header.number)) {
+            stats.priority_fees = txs.iter().filter_map(|tx| effective_priority_fee(tx, base_fee)).collect();
+            stats.priority_fees.sort_unstable();
+        }
+    }
+
+    /// Builder payment is calculated from the per transaction state update of the block.
+    pub fn on_state_update(&mut self, block_number: BlockNumber, coinbase: Address, state_update: &GethStateUpdateVec) {
+        if let Some(stats) = self.blocks.get_mut(&block_number) {
+            if stats.gas_used > 0 {
+                stats.builder_payment_per_gas =
+                    builder_payment(state_update, coinbase).map(|payment| (payment / U256::from(stats.gas_used)).saturating_to::<u64>());
+            }
+        }
+    }
+
+    /// Priority fee percentile of the recent blocks transactions, 50 is the median.
+    pub fn priority_fee(&self, pct: u32) -> Option<u64> {
+        let mut fees: Vec<u64> = self.blocks.values().flat_map(|stats| stats.priority_fees.iter().cloned()).collect();
+        fees.sort_unstable();
+        percentile(&fees, pct)
+    }
+
+    /// Builder payment per gas percentile of the recent blocks.
+    pub fn builder_payment_per_gas(&self, pct: u32) -> Option<u64> {
+        let mut payments: Vec<u64> = self.blocks.values().filter_map(|stats| stats.builder_payment_per_gas).collect();
+        payments.sort_unstable();
+        percentile(&payments, pct)
+    }
+
+    /// Base fees of the next blocks assuming the average gas usage of the recent blocks.
+    pub fn base_fee_projection(&self, blocks_ahead: usize) -> Vec<u64> {
+        let Some(last) = self.blocks.last_key_value().map(|(_, stats)| stats) else {
+            return Vec::new();
+        };
+
+        let gas_limit_total: u128 = self.blocks.values().map(|stats| stats.gas_limit as u128).sum();
+        let gas_used_total: u128 = self.blocks.values().map(|stats| stats.gas_used as u128).sum();
+        let projected_gas_used = if gas_limit_total > 0 { (last.gas_limit as u128 * gas_used_total / gas_limit_total) as u64 } else { 0 };
+
+        let mut base_fee = self.chain_parameters.calc_next_block_base_fee(last.gas_used, last.gas_limit, last.base_fee);
+        let mut projection = Vec::with_capacity(blocks_ahead);
+        for _ in 0..blocks_ahead {
+            projection.push(base_fee);
+            base_fee = self.chain_parameters.calc_next_block_base_fee(projected_gas_used, last.gas_limit, base_fee);
+        }
+        projection
+    }
+
+    /// Highest possible base fee of the block, all blocks before it are assumed to be full.
+    pub fn max_base_fee(&self, block_number: BlockNumber) -> Option<u64> {
+        let (last_number, last) = self.blocks.last_key_value()?;
+        if block_number <= *last_number {
+            return None;
+        }
+
+        let mut base_fee = self.chain_parameters.calc_next_block_base_fee(last.gas_used, last.gas_limit, last.base_fee);
+        for _ in last_number + 1..block_number {
+            base_fee = self.chain_parameters.calc_next_block_base_fee(last.gas_limit, last.gas_limit, base_fee);
+        }
+        Some(base_fee)
+    }
+
+    /// Max fee per gas keeping the transaction valid in the block.
+    pub fn max_fee_per_gas(&self, block_number: BlockNumber, priority_fee: u64) -> Option<u64> {
+        self.max_base_fee(block_number).map(|base_fee| base_fee + priority_fee)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use alloy_rpc_types::BlockTransactions;
+    use alloy_rpc_types_trace::geth::AccountState;
+
+    use super::*;
+
+    fn header(number: BlockNumber, base_fee: u64, gas_used: u64) -> Header {
+        Header { number, base_fee_per_gas: Some(base_fee), gas_used, gas_limit: 30_000_000, ..Header::default() }
+    }
+
+    fn tx_1559(max_fee: u128, max_priority_fee: u128) -> Transaction {
+        Transaction { max_fee_per_gas: Some(max_fee), max_priority_fee_per_gas: Some(max_priority_fee), ..Transaction::default() }
+    }
+
+    #[test]
+    fn test_effective_priority_fee() {
+        assert_eq!(effective_priority_fee(&tx_1559(100, 10), 50), Some(10));
+        assert_eq!(effective_priority_fee(&tx_1559(55, 10), 50), Some(5));
+        assert_eq!(effective_priority_fee(&Transaction { gas_price: Some(70), ..Transaction::default() }, 50), Some(20));
+        assert_eq!(effective_priority_fee(&Transaction::default(), 50), None);
+    }
+
+    #[test]
+    fn test_priority_fee_percentile() {
+        let mut oracle = GasPriceOracle::default();
+        let block = Block {
+            header: header(1, 50, 15_000_000),
+            transactions: BlockTransactions::Full((1..=5).map(|fee| tx_1559(1000, fee)).collect()),
+            ..Block::default()
+        };
+        oracle.on_block(&block);
+
+        assert_eq!(oracle.priority_fee(0), Some(1));
+        assert_eq!(oracle.priority_fee(50), Some(3));
+        assert_eq!(oracle.priority_fee(100), Some(5));
+        assert_eq!(GasPriceOracle::default().priority_fee(50), None);
+    }
+
+    #[test]
+    fn test_builder_payment() {
+        let coinbase = Address::repeat_byte(0xcb);
+        let coinbase_state =
+            |balance: u64| BTreeMap::from([(coinbase, AccountState { balance: Some(U256::from(balance)), ..AccountState::default() })]);
+        // last transaction is the payout to the proposer
+        let state_update = vec![coinbase_state(100), BTreeMap::new(), coinbase_state(130), coinbase_state(1130), coinbase_state(10)];
+        assert_eq!(builder_payment(&state_update, coinbase), Some(U256::from(1030)));
+
+        let mut oracle = GasPriceOracle::default();
+        oracle.on_block_header(&header(1, 50, 103));
+        oracle.on_state_update(1, coinbase, &state_update);
+        assert_eq!(oracle.builder_payment_per_gas(50), Some(10));
+    }
+
+    #[test]
+    fn test_base_fee_projection() {
+        let mut oracle = GasPriceOracle::default().with_max_blocks(2);
+        oracle.on_block_header(&header(1, 1000, 30_000_000));
+        oracle.on_block_header(&header(2, 1000, 30_000_000));
+        oracle.on_block_header(&header(3, 1000, 15_000_000));
+        assert!(oracle.get_block_stats(1).is_none());
+        assert_eq!(oracle.last_block_number(), Some(3));
+
+        // average usage of blocks 2 and 3 is 75%
+        assert_eq!(oracle.base_fee_projection(3), vec![1000, 1062, 1128]);
+
+        assert_eq!(oracle.max_base_fee(3), None);
+        assert_eq!(oracle.max_base_fee(4), Some(1000));
+        assert_eq!(oracle.max_base_fee(5), Some(1125));
+        assert_eq!(oracle.max_fee_per_gas(5, 10), Some(1135));
+    }
+}
@@ -2,11 +2,14 @@
 
 extern crate core;
 
-pub use account_nonce_balance::{AccountNonceAndBalanceState, AccountNonceAndBalances};
+pub use account_nonce_balance::{AccountNonceAndBalanceState, AccountNonceAndBalances, NonceReservation, ReservedNonce};
+pub use bid_stats::{BidStats, PoolPair, PoolPairBidStats};
 pub use block_history::{apply_state_update, BlockHistory, BlockHistoryEntry, BlockHistoryManager};
 pub use datafetcher::{DataFetcher, FetchState};
+pub use gas_price_oracle::{builder_payment, effective_priority_fee, BlockGasStats, GasPriceOracle};
 pub use keystore::KeyStore;
 pub use latest_block::LatestBlock;
+pub use liquidation::Liquidation;
 pub use market::Market;
 pub use market_state::MarketState;
 pub use pool::{get_protocol_by_factory, AbiSwapEncoder, Pool, PoolClass, PoolProtocol, PoolWrapper, PreswapRequirement};
@@ -19,15 +22,20 @@ pub use swappath::{SwapPath, SwapPaths};
 pub use swappath_builder::build_swap_path_vec;
 pub use swapstep::SwapStep;
 pub use token::{Token, TokenWrapper};
+pub use tx_cost::{ExecutionCostModel, OpL1FeeParams, OpStackCostModel, TxCostModel, OP_GAS_PRICE_ORACLE, OP_L1_BLOCK, OP_L1_FEE_SLOTS};
 
+mod bid_stats;
 mod block_history;
+mod gas_price_oracle;
 mod latest_block;
+mod liquidation;
 mod market;
 mod market_state;
 mod pool;
 mod swapline;
 mod swappath;
 mod token;
+mod tx_cost;
 
 pub mod account_nonce_balance;
 pub mod required_state;
@@ -0,0 +1,75 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use alloy_primitives::{Address, U256};
+
+use crate::{SwapAmountType, SwapLine, Token};
+
+/// Flash loaned liquidation of a lending position. Debt token is borrowed, used to repay `debt_to_cover`
+/// of the user's debt, seized collateral is swapped back to the debt token with `swap_line`.
+#[derive(Clone, Debug)]
+pub struct Liquidation {
+    pub lending_pool: Address,
+    pub user: Address,
+    pub collateral_token: Arc<Token>,
+    pub debt_token: Arc<Token>,
+    pub debt_to_cover: U256,
+    pub collateral_amount: U256,
+    pub swap_line: SwapLine,
+    pub gas_used: Option<u64>,
+}
+
+impl Display for Liquidation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Liquidation user {:?} cover {} {} collateral {} {} profit {} swap {}",
+            self.user,
+            self.debt_token.to_float(self.debt_to_cover),
+            self.debt_token.get_symbol(),
+            self.collateral_token.to_float(self.collateral_amount),
+            self.collateral_token.get_symbol(),
+            self.debt_token.to_float(self.abs_profit()),
+            self.swap_line
+        )
+    }
+}
+
+impl Liquidation {
+    pub fn abs_profit(&self) -> U256 {
+        let SwapAmountType::Set(amount_out) = self.swap_line.amount_out else {
+            return U256::ZERO;
+        };
+        amount_out.saturating_sub(self.debt_to_cover)
+    }
+
+    pub fn abs_profit_eth(&self) -> U256 {
+        self.debt_token.calc_eth_value(self.abs_profit()).unwrap_or(U256::ZERO)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_abs_profit() {
+        let mut liquidation = Liquidation {
+            lending_pool: Address::repeat_byte(1),
+            user: Address::repeat_byte(2),
+            collateral_token: Arc::new(Token::new(Address::repeat_byte(3))),
+            debt_token: Arc::new(Token::new(Address::repeat_byte(4))),
+            debt_to_cover: U256::from(1000),
+            collateral_amount: U256::from(500),
+            swap_line: SwapLine::default(),
+            gas_used: None,
+        };
+        assert_eq!(liquidation.abs_profit(), U256::ZERO);
+
+        liquidation.swap_line.amount_out = SwapAmountType::Set(U256::from(1050));
+        assert_eq!(liquidation.abs_profit(), U256::from(50));
+
+        liquidation.swap_line.amount_out = SwapAmountType::Set(U256::from(900));
+        assert_eq!(liquidation.abs_profit(), U256::ZERO);
+    }
+}
@@ -28,12 +28,18 @@ pub struct Market {
     token_pools: HashMap<Address, Vec<Address>>,
     // swap_paths
     swap_paths: SwapPaths,
+    // wrapped native token of the chain, mainnet WETH if not set
+    wrapped_native_token: Option<Address>,
 }
 
 impl Market {
     /// Add a [`Token`](crate::Token) reference to the market.
     pub fn add_token<T: Into<Arc<Token>>>(&mut self, token: T) -> Result<()> {
-        let arc_token: Arc<Token> = token.into();
+        let mut arc_token: Arc<Token> = token.into();
+        let is_weth = self.is_weth(&arc_token.get_address());
+        if arc_token.is_weth() != is_weth {
+            arc_token = Arc::new(arc_token.as_ref().clone().with_wrapped_native(is_weth));
+        }
         self.tokens.insert(arc_token.get_address(), arc_token);
         Ok(())
     }
@@ -47,9 +53,19 @@ impl Market {
         }
     }
 
-    /// Check if the given address is the WETH address.
-    pub fn is_weth(address: &Address) -> bool {
-        address.eq(&TokenAddress::WETH)
+    /// Set the wrapped native token of the chain.
+    pub fn with_wrapped_native_token(self, wrapped_native_token: Address) -> Self {
+        Self { wrapped_native_token: Some(wrapped_native_token), ..self }
+    }
+
+    /// Get the wrapped native token of the chain.
+    pub fn wrapped_native_token(&self) -> Address {
+        self.wrapped_native_token.unwrap_or(TokenAddress::WETH)
+    }
+
+    /// Check if the given address is the wrapped native token of the chain.
+    pub fn is_weth(&self, address: &Address) -> bool {
+        address.eq(&self.wrapped_native_token())
     }
 
     /// Add a new pool to the market if it does not exist or the class is unknown.
@@ -138,7 +154,7 @@ impl Market {
 
     /// Get a [`Token`](crate::Token) reference from the market by the address of the token or create a new one.
     pub fn get_token_or_default(&self, address: &Address) -> Arc<Token> {
-        self.tokens.get(address).map_or(Arc::new(Token::new(*address)), |t| t.clone())
+        self.tokens.get(address).map_or_else(|| Arc::new(Token::new(*address).with_wrapped_native(self.is_weth(address))), |t| t.clone())
     }
 
     /// Get a [`Token`](crate::Token) reference from the market by the address of the token.
@@ -54,6 +54,9 @@ pub enum PoolClass {
     #[serde(rename = "uniswap3")]
     #[strum(serialize = "uniswap3")]
     UniswapV3,
+    #[serde(rename = "uniswap4")]
+    #[strum(serialize = "uniswap4")]
+    UniswapV4,
     #[serde(rename = "curve")]
     #[strum(serialize = "curve")]
     Curve,
@@ -87,6 +90,7 @@ pub enum PoolProtocol {
     UniswapV3,
     UniswapV3Like,
     PancakeV3,
+    UniswapV4,
     Integral,
     Maverick,
     Curve,
@@ -104,6 +108,7 @@ impl Display for PoolProtocol {
             Self::UniswapV2Like => "UniswapV2Like",
             Self::UniswapV3 => "UniswapV3",
             Self::PancakeV3 => "PancakeV3",
+            Self::UniswapV4 => "UniswapV4",
             Self::UniswapV3Like => "UniswapV3Like",
             Self::NomiswapStable => "NomiswapStable",
             Self::Sushiswap => "Sushiswap",
@@ -312,6 +317,26 @@ pub trait AbiSwapEncoder {
     fn swap_in_amount_return_script(&self, _token_from_address: Address, _token_to_address: Address) -> Option<Bytes> {
         None
     }
+
+    /// Singleton holding the pool, swaps are done inside its unlock callback and settled at the end. Uniswap V4 pool manager.
+    fn singleton_address(&self) -> Option<Address> {
+        None
+    }
+    fn encode_singleton_unlock(&self, _payload: Bytes) -> Result<Bytes> {
+        Err(eyre!("NOT_IMPLEMENTED"))
+    }
+    fn encode_singleton_sync(&self, _token_address: Address) -> Result<Bytes> {
+        Err(eyre!("NOT_IMPLEMENTED"))
+    }
+    fn encode_singleton_settle(&self) -> Result<Bytes> {
+        Err(eyre!("NOT_IMPLEMENTED"))
+    }
+    fn encode_singleton_take(&self, _token_address: Address, _recipient: Address, _amount: U256) -> Result<Bytes> {
+        Err(eyre!("NOT_IMPLEMENTED"))
+    }
+    fn singleton_take_amount_offset(&self) -> Option<u32> {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -1,13 +1,15 @@
 use std::collections::BTreeMap;
 use std::fmt::Debug;
 
-use alloy_network::Network;
-use alloy_primitives::{Address, BlockNumber, Bytes, TxKind, U256};
+use alloy_network::{Network, TransactionBuilder};
+use alloy_primitives::{hex, Address, BlockNumber, Bytes, TxKind, U256};
 use alloy_provider::Provider;
+use alloy_rpc_types::state::{AccountOverride, StateOverride};
 use alloy_rpc_types::{BlockId, BlockNumberOrTag, TransactionInput, TransactionRequest};
 use alloy_rpc_types_trace::geth::AccountState;
-use alloy_transport::Transport;
+use alloy_transport::{Transport, TransportResult};
 use eyre::{eyre, Result};
+use futures::{stream, StreamExt};
 use tracing::{error, trace};
 
 use debug_provider::DebugProviderExt;
@@ -62,6 +64,64 @@ impl RequiredState {
         }
         self
     }
+
+    /// Accounts which state is read by required calls and slots.
+    pub fn accounts(&self) -> Vec<Address> {
+        let mut accounts: Vec<Address> = self
+            .calls
+            .iter()
+            .filter_map(|req| req.to.as_ref().and_then(|to| to.to().copied()))
+            .chain(self.slots.iter().map(|(address, _)| *address))
+            .chain(self.empty_slots.iter().map(|(address, _)| *address))
+            .collect();
+        accounts.sort();
+        accounts.dedup();
+        accounts
+    }
+}
+
+/// Code of the slot reader. Returns storage values of the slots passed as 32 bytes words in call data. The account code is
+/// overridden with it in `eth_call`, so the slots are read from the account storage.
+pub const SLOT_READER_CODE: [u8; 25] = hex!("60005b368110600d57366000f35b8035548152602001600256");
+
+#[derive(Clone, Debug)]
+pub struct RequiredStateReaderConfig {
+    /// Slots read with one JSON-RPC batch or one slot reader call
+    pub batch_size: usize,
+    /// Calls and batches processed concurrently
+    pub concurrency: usize,
+    /// Slots of an account are read with a single `eth_call` to the slot reader instead of `eth_getStorageAt`
+    pub slot_reader: bool,
+}
+
+impl Default for RequiredStateReaderConfig {
+    fn default() -> Self {
+        Self { batch_size: 100, concurrency: 8, slot_reader: false }
+    }
+}
+
+impl RequiredStateReaderConfig {
+    pub fn with_batch_size(self, batch_size: usize) -> Self {
+        Self { batch_size, ..self }
+    }
+
+    pub fn with_concurrency(self, concurrency: usize) -> Self {
+        Self { concurrency, ..self }
+    }
+
+    pub fn with_slot_reader(self, slot_reader: bool) -> Self {
+        Self { slot_reader, ..self }
+    }
+}
+
+fn merge_state_update(target: &mut GethStateUpdate, update: GethStateUpdate) {
+    for (address, account_state) in update.into_iter() {
+        let entry = target.entry(address).or_insert(account_state.clone());
+        for (slot, value) in account_state.storage.into_iter() {
+            entry.storage.insert(slot, value);
+            trace!("Inserting storage {:#20x} {} {}", address, slot, value);
+        }
+    }
 }
 
 pub struct RequiredStateReader {}
@@ -72,59 +132,210 @@ impl RequiredStateReader {
         required_state: RequiredState,
         block_number: Option<BlockNumber>,
     ) -> Result<GethStateUpdate> {
-        let block_id = if block_number.is_none() {
-            BlockId::Number(BlockNumberOrTag::Latest)
-        } else {
-            BlockId::Number(BlockNumberOrTag::Number(block_number.unwrap_or_default()))
+        Self::fetch_calls_and_slots_with_config(client, required_state, block_number, &RequiredStateReaderConfig::default()).await
+    }
+
+    pub async fn fetch_calls_and_slots_with_config<
+        T: Transport + Clone,
+        N: Network,
+        C: DebugProviderExt<T, N> + Provider<T, N> + Clone + 'static,
+    >(
+        client: C,
+        required_state: RequiredState,
+        block_number: Option<BlockNumber>,
+        config: &RequiredStateReaderConfig,
+    ) -> Result<GethStateUpdate> {
+        Self::fetch_many(client, vec![required_state], block_number, config).await.pop().unwrap_or_else(|| Err(eyre!("NO_REQUIRED_STATE")))
+    }
+
+    /// Fetches the required states of many pools at once. Calls and slots of all of them are sent with bounded concurrency,
+    /// slots are read with batched requests. Returns the result for every required state, a failed call or slot read fails
+    /// only the required state it belongs to.
+    pub async fn fetch_many<T: Transport + Clone, N: Network, C: DebugProviderExt<T, N> + Provider<T, N> + Clone + 'static>(
+        client: C,
+        required_states: Vec<RequiredState>,
+        block_number: Option<BlockNumber>,
+        config: &RequiredStateReaderConfig,
+    ) -> Vec<Result<GethStateUpdate>> {
+        let block_id = match block_number {
+            Some(block_number) => BlockId::Number(BlockNumberOrTag::Number(block_number)),
+            None => BlockId::Number(BlockNumberOrTag::Latest),
         };
+        let concurrency = config.concurrency.max(1);
+
+        let mut results: Vec<Result<GethStateUpdate>> = required_states.iter().map(|_| Ok(GethStateUpdate::new())).collect();
 
-        let mut ret: GethStateUpdate = GethStateUpdate::new();
-        for req in required_state.calls.into_iter() {
-            let to = req.to.unwrap_or_default().to().map_or(Address::ZERO, |x| *x);
+        let calls: Vec<(usize, TransactionRequest)> =
+            required_states.iter().enumerate().flat_map(|(idx, state)| state.calls.iter().map(move |req| (idx, req.clone()))).collect();
+
+        let mut call_results = stream::iter(calls.into_iter().map(|(idx, req)| {
+            let client = client.clone();
+            async move {
+                let to = req.to.unwrap_or_default().to().map_or(Address::ZERO, |x| *x);
+                let call_result = debug_trace_call_pre_state(client, req, block_id, None).await;
+                trace!("trace_call_result: {:?}", call_result);
+                (idx, to, call_result)
+            }
+        }))
+        .buffer_unordered(concurrency);
 
-            let call_result = debug_trace_call_pre_state(client.clone(), req, block_id, None).await;
-            trace!("trace_call_result: {:?}", call_result);
+        while let Some((idx, to, call_result)) = call_results.next().await {
+            let Ok(state) = &mut results[idx] else {
+                continue;
+            };
             match call_result {
-                Ok(update) => {
-                    for (address, account_state) in update.into_iter() {
-                        let entry = ret.entry(address).or_insert(account_state.clone());
-                        for (slot, value) in account_state.storage.clone().into_iter() {
-                            entry.storage.insert(slot, value);
-                            trace!("Inserting storage {:#20x} {} {}", address, slot, value);
-                        }
-                    }
-                }
+                Ok(update) => merge_state_update(state, update),
                 Err(e) => {
                     error!("Contract call failed {} {}", to, e);
-                    return Err(eyre!("CONTRACT_CALL_FAILED"));
+                    results[idx] = Err(eyre!("CONTRACT_CALL_FAILED"));
                 }
             }
         }
-        for (address, slot) in required_state.slots.into_iter() {
-            let value_result = client.get_storage_at(address, slot).block_id(block_id).await;
-            trace!("get_storage_at_result {} slot {} :  {:?}", address, slot, value_result);
-            match value_result {
+
+        let slots: Vec<(usize, Address, U256)> = required_states
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, state)| state.slots.iter().map(move |(address, slot)| (idx, *address, *slot)))
+            .collect();
+
+        let slot_values = if config.slot_reader {
+            Self::read_slots_with_reader(&client, slots, block_id, config).await
+        } else {
+            Self::read_slots(&client, slots, block_id, config).await
+        };
+
+        for (idx, address, slot, value) in slot_values.into_iter() {
+            let Ok(state) = &mut results[idx] else {
+                continue;
+            };
+            match value {
                 Ok(value) => {
-                    let entry = ret.entry(address).or_default();
-                    entry.storage.insert(slot.into(), value.into());
+                    state.entry(address).or_default().storage.insert(slot.into(), value.into());
                 }
-                Err(e) => {
-                    error!("{}", e)
+                Err(e) => results[idx] = Err(e),
+            }
+        }
+
+        for (idx, required_state) in required_states.into_iter().enumerate() {
+            if let Ok(state) = &mut results[idx] {
+                for (address, slot) in required_state.empty_slots.into_iter() {
+                    state.entry(address).or_default().storage.insert(slot.into(), U256::ZERO.into());
                 }
             }
         }
 
-        for (address, slot) in required_state.empty_slots.into_iter() {
-            let value = U256::ZERO;
+        results
+    }
 
-            let entry = ret.entry(address).or_default();
-            entry.storage.insert(slot.into(), value.into());
+    /// Reads slots with `eth_getStorageAt` batch requests. Every slot of a failed batch gets an error.
+    async fn read_slots<T: Transport + Clone, N: Network, C: Provider<T, N> + Clone + 'static>(
+        client: &C,
+        slots: Vec<(usize, Address, U256)>,
+        block_id: BlockId,
+        config: &RequiredStateReaderConfig,
+    ) -> Vec<(usize, Address, U256, Result<U256>)> {
+        let batch_size = config.batch_size.max(1);
+
+        stream::iter(slots.chunks(batch_size).map(|chunk| async move {
+            if chunk.len() == 1 {
+                let (idx, address, slot) = chunk[0];
+                return match client.get_storage_at(address, slot).block_id(block_id).await {
+                    Ok(value) => vec![(idx, address, slot, Ok(value))],
+                    Err(e) => {
+                        error!("get_storage_at error {} slot {} : {}", address, slot, e);
+                        vec![(idx, address, slot, Err(eyre!("STORAGE_READ_FAILED")))]
+                    }
+                };
+            }
+
+            let mut batch = client.client().new_batch();
+            let waiters: Vec<_> = match chunk
+                .iter()
+                .map(|(_, address, slot)| batch.add_call("eth_getStorageAt", &(*address, *slot, block_id)))
+                .collect::<TransportResult<Vec<_>>>()
+            {
+                Ok(waiters) => waiters,
+                Err(e) => {
+                    error!("eth_getStorageAt batch error : {}", e);
+                    return failed_slots(chunk);
+                }
+            };
+            if let Err(e) = batch.send().await {
+                error!("eth_getStorageAt batch send error : {}", e);
+                return failed_slots(chunk);
+            }
+
+            let mut values = Vec::with_capacity(chunk.len());
+            for ((idx, address, slot), waiter) in chunk.iter().zip(waiters) {
+                match waiter.await {
+                    Ok(value) => values.push((*idx, *address, *slot, Ok(value))),
+                    Err(e) => {
+                        error!("get_storage_at error {} slot {} : {}", address, slot, e);
+                        values.push((*idx, *address, *slot, Err(eyre!("STORAGE_READ_FAILED"))))
+                    }
+                }
+            }
+            values
+        }))
+        .buffer_unordered(config.concurrency.max(1))
+        .flat_map(stream::iter)
+        .collect()
+        .await
+    }
+
+    /// Reads slots of every account with `eth_call` overriding the account code with the slot reader. Every slot of a failed
+    /// call gets an error.
+    async fn read_slots_with_reader<T: Transport + Clone, N: Network, C: Provider<T, N> + Clone + 'static>(
+        client: &C,
+        slots: Vec<(usize, Address, U256)>,
+        block_id: BlockId,
+        config: &RequiredStateReaderConfig,
+    ) -> Vec<(usize, Address, U256, Result<U256>)> {
+        let mut account_slots: BTreeMap<Address, Vec<(usize, U256)>> = BTreeMap::new();
+        for (idx, address, slot) in slots.into_iter() {
+            account_slots.entry(address).or_default().push((idx, slot));
         }
 
-        Ok(ret)
+        let requests: Vec<(Address, Vec<(usize, U256)>)> = account_slots
+            .into_iter()
+            .flat_map(|(address, slots)| slots.chunks(config.batch_size.max(1)).map(|chunk| (address, chunk.to_vec())).collect::<Vec<_>>())
+            .collect();
+
+        stream::iter(requests.into_iter().map(|(address, slots)| async move {
+            let call_data: Vec<u8> = slots.iter().flat_map(|(_, slot)| slot.to_be_bytes::<32>()).collect();
+            let tx = N::TransactionRequest::default().with_to(address).with_input(call_data);
+            let overrides: StateOverride = StateOverride::from_iter([(
+                address,
+                AccountOverride { code: Some(Bytes::from_static(&SLOT_READER_CODE)), ..Default::default() },
+            )]);
+
+            match client.call(&tx).block(block_id).overrides(&overrides).await {
+                Ok(output) if output.len() == slots.len() * 32 => slots
+                    .into_iter()
+                    .zip(output.chunks(32))
+                    .map(|((idx, slot), value)| (idx, address, slot, Ok(U256::from_be_slice(value))))
+                    .collect(),
+                Ok(output) => {
+                    error!("Slot reader output length {} is incorrect for {} slots of {}", output.len(), slots.len(), address);
+                    slots.into_iter().map(|(idx, slot)| (idx, address, slot, Err(eyre!("STORAGE_READ_FAILED")))).collect()
+                }
+                Err(e) => {
+                    error!("Slot reader call failed {} : {}", address, e);
+                    slots.into_iter().map(|(idx, slot)| (idx, address, slot, Err(eyre!("STORAGE_READ_FAILED")))).collect()
+                }
+            }
+        }))
+        .buffer_unordered(config.concurrency.max(1))
+        .flat_map(stream::iter)
+        .collect()
+        .await
     }
 }
 
+fn failed_slots(chunk: &[(usize, Address, U256)]) -> Vec<(usize, Address, U256, Result<U256>)> {
+    chunk.iter().map(|(idx, address, slot)| (*idx, *address, *slot, Err(eyre!("STORAGE_READ_FAILED")))).collect()
+}
+
 pub fn accounts_len(state: &BTreeMap<Address, AccountState>) -> (usize, usize) {
     let accounts = state.len();
     let storage = state.values().map(|item| item.storage.clone().len()).sum();
@@ -134,3 +345,34 @@ pub fn accounts_len(state: &BTreeMap<Address, AccountState>) -> (usize, usize) {
 pub fn accounts_vec_len(state: &GethStateUpdateVec) -> usize {
     state.iter().map(|item| accounts_len(item).1).sum()
 }
+
+#[cfg(test)]
+mod test {
+    use loom_revm_db::LoomInMemoryDB;
+    use loom_utils::evm::evm_call;
+    use revm::primitives::{AccountInfo, Bytecode, Env};
+
+    use super::*;
+
+    #[test]
+    fn test_slot_reader_code() {
+        let address = Address::repeat_byte(1);
+        let mut db = LoomInMemoryDB::default();
+        db.insert_account_info(
+            address,
+            AccountInfo { code: Some(Bytecode::new_raw(Bytes::from_static(&SLOT_READER_CODE))), ..Default::default() },
+        );
+        db.insert_account_storage(address, U256::from(1), U256::from(100)).unwrap();
+        db.insert_account_storage(address, U256::MAX, U256::from(200)).unwrap();
+
+        let slots = [U256::from(1), U256::from(2), U256::MAX];
+        let call_data: Vec<u8> = slots.iter().flat_map(|slot| slot.to_be_bytes::<32>()).collect();
+
+        let (output, _) = evm_call(&db, Env::default(), address, call_data).unwrap();
+        let values: Vec<U256> = output.chunks(32).map(U256::from_be_slice).collect();
+        assert_eq!(values, vec![U256::from(100), U256::ZERO, U256::from(200)]);
+
+        let (output, _) = evm_call(&db, Env::default(), address, vec![]).unwrap();
+        assert!(output.is_empty());
+    }
+}
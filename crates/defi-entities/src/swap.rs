@@ -3,7 +3,7 @@ use std::sync::Arc;
 
 use alloy_primitives::{Address, U256};
 
-use crate::{SwapAmountType, SwapLine, SwapStep, Token};
+use crate::{Liquidation, SwapAmountType, SwapLine, SwapStep, Token};
 
 #[derive(Clone, Debug)]
 pub enum Swap {
@@ -12,6 +12,7 @@ pub enum Swap {
     BackrunSwapSteps((SwapStep, SwapStep)),
     BackrunSwapLine(SwapLine),
     Multiple(Vec<Swap>),
+    Liquidation(Liquidation),
 }
 
 impl Display for Swap {
@@ -21,6 +22,7 @@ impl Display for Swap {
             Swap::BackrunSwapLine(path) => write!(f, "{path}"),
             Swap::BackrunSwapSteps((sp0, sp1)) => write!(f, "{sp0} {sp1}"),
             Swap::Multiple(_) => write!(f, "MULTIPLE_SWAP"),
+            Swap::Liquidation(liquidation) => write!(f, "{liquidation}"),
             Swap::None => write!(f, "UNKNOWN_SWAP_TYPE"),
         }
     }
@@ -68,6 +70,7 @@ impl Swap {
             Swap::BackrunSwapLine(path) => path.abs_profit(),
             Swap::BackrunSwapSteps((sp0, sp1)) => SwapStep::abs_profit(sp0, sp1),
             Swap::Multiple(swap_vec) => swap_vec.iter().map(|x| x.abs_profit()).sum(),
+            Swap::Liquidation(liquidation) => liquidation.abs_profit(),
             Swap::None => U256::ZERO,
             Swap::ExchangeSwapLine(_) => U256::ZERO,
         }
@@ -82,6 +85,7 @@ impl Swap {
                     + sp1.swap_line_vec().iter().map(|i| i.gas_used.unwrap_or_default()).sum::<u64>()
             }
             Swap::Multiple(swap_vec) => swap_vec.iter().map(|x| x.pre_estimate_gas()).sum(),
+            Swap::Liquidation(liquidation) => liquidation.gas_used.unwrap_or_default(),
             Swap::None => 0,
         }
     }
@@ -92,6 +96,7 @@ impl Swap {
             Swap::BackrunSwapLine(path) => path.abs_profit_eth(),
             Swap::BackrunSwapSteps((sp0, sp1)) => SwapStep::abs_profit_eth(sp0, sp1),
             Swap::Multiple(swap_vec) => swap_vec.iter().map(|x| x.abs_profit_eth()).sum(),
+            Swap::Liquidation(liquidation) => liquidation.abs_profit_eth(),
             Swap::None => U256::ZERO,
         }
     }
@@ -102,6 +107,7 @@ impl Swap {
             Swap::BackrunSwapLine(swap_path) => swap_path.get_first_token(),
             Swap::BackrunSwapSteps((sp0, _sp1)) => sp0.get_first_token(),
             Swap::Multiple(_) => None,
+            Swap::Liquidation(liquidation) => Some(&liquidation.debt_token),
             Swap::None => None,
         }
     }
@@ -114,6 +120,7 @@ impl Swap {
                 sp0.swap_line_vec().iter().flat_map(|item| item.pools().iter().map(|p| p.get_address()).collect::<Vec<_>>()).collect()
             }
             Swap::Multiple(swap_vec) => swap_vec.iter().flat_map(|x| x.get_pool_address_vec()).collect(),
+            Swap::Liquidation(liquidation) => liquidation.swap_line.pools().iter().map(|item| item.get_address()).collect(),
             Swap::None => Vec::new(),
         }
     }
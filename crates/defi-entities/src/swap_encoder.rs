@@ -9,17 +9,20 @@ pub trait SwapEncoder {
     /// Encodes Swap
     ///
     /// - next_block_number - number of the next block
-    /// - next_block_gas_price - base_fee + priority fee for transaction
+    /// - next_block_gas_price - max fee per gas of the transaction, flash loan sources are compared with it
+    /// - gas_cost - cost of the transaction for tips
     /// - sender_address - EOA of of the transaction
     /// - sender_eth_balance - balance of EOA
     ///
     /// returns (to. value, call_data) for transaction
 
+    #[allow(clippy::too_many_arguments)]
     fn encode(
         &self,
         swap: Swap,
         tips_pct: Option<u32>,
         next_block_number: Option<BlockNumber>,
+        next_block_gas_price: Option<u128>,
         gas_cost: Option<U256>,
         sender_address: Option<Address>,
         sender_eth_balance: Option<U256>,
@@ -434,7 +434,7 @@ pub fn build_swap_path_vec(market: &Market, directions: &BTreeMap<PoolWrapper, V
             }
 
             if (!market.is_basic_token(&token_from_address) && !market.is_basic_token(&token_to_address))
-                || (!Market::is_weth(&token_from_address) && !Market::is_weth(&token_to_address))
+                || (!market.is_weth(&token_from_address) && !market.is_weth(&token_to_address))
             {
                 ret_map.extend(build_swap_path_three_hopes_no_basic(market, pool, token_from_address, token_to_address)?);
             }
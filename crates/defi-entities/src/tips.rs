@@ -61,6 +61,7 @@ pub fn randomize_tips_pct(tips_pct: u32) -> u32 {
     tips_pct - rnd
 }
 
+/// `gas_cost` is the total transaction cost of the [`crate::TxCostModel`], including the L1 data fee on rollups.
 pub fn tips_and_value_for_swap_type(
     swap: &Swap,
     tips_pct: Option<u32>,
@@ -83,7 +84,7 @@ pub fn tips_and_value_for_swap_type(
     }
 
     match swap {
-        Swap::BackrunSwapLine(_) | Swap::BackrunSwapSteps(_) => {
+        Swap::BackrunSwapLine(_) | Swap::BackrunSwapSteps(_) | Swap::Liquidation(_) => {
             let profit = swap.abs_profit();
             if profit.is_zero() {
                 error!(profit = NWETH::to_float(profit), %swap, "Zero profit");
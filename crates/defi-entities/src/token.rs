@@ -18,6 +18,8 @@ pub struct Token {
     decimals: u8,
     name: Option<String>,
     symbol: Option<String>,
+    /// Wrapped native token of the chain, mainnet WETH by default
+    wrapped_native: bool,
     eth_price: Arc<RwLock<Option<U256>>>,
 }
 
@@ -51,7 +53,7 @@ impl PartialOrd for Token {
 
 impl Token {
     pub fn new(address: Address) -> Token {
-        Token { address, decimals: 18, ..Token::default() }
+        Token { address, decimals: 18, wrapped_native: address == TokenAddress::WETH, ..Token::default() }
     }
 
     pub fn new_with_data(
@@ -62,7 +64,20 @@ impl Token {
         basic: bool,
         middle: bool,
     ) -> Token {
-        Token { address, symbol, name, decimals: decimals.unwrap_or(18), basic, middle, ..Default::default() }
+        Token {
+            address,
+            symbol,
+            name,
+            decimals: decimals.unwrap_or(18),
+            basic,
+            middle,
+            wrapped_native: address == TokenAddress::WETH,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_wrapped_native(self, wrapped_native: bool) -> Token {
+        Token { wrapped_native, ..self }
     }
 
     pub fn get_eth_price(&self) -> Option<U256> {
@@ -168,7 +183,7 @@ impl Token {
     }
 
     pub fn is_weth(&self) -> bool {
-        self.address == TokenAddress::WETH
+        self.wrapped_native
     }
 }
 
@@ -0,0 +1,251 @@
+use alloy_primitives::{address, Address, U256};
+use eyre::{eyre, Result};
+use revm::DatabaseRef;
+
+use loom_revm_db::LoomInMemoryDB;
+
+/// Cost of the transaction paid by the sender.
+pub trait TxCostModel: Send + Sync {
+    /// Total cost in wei. `tx_data` is the unsigned encoded transaction, the signature is accounted by the model.
+    fn tx_cost(&self, db: &LoomInMemoryDB, gas_used: u64, gas_price: u128, tx_data: &[u8]) -> Result<U256>;
+}
+
+/// Execution gas only, used on L1.
+#[derive(Clone, Debug, Default)]
+pub struct ExecutionCostModel;
+
+impl TxCostModel for ExecutionCostModel {
+    fn tx_cost(&self, _db: &LoomInMemoryDB, gas_used: u64, gas_price: u128, _tx_data: &[u8]) -> Result<U256> {
+        Ok(U256::from(gas_used) * U256::from(gas_price))
+    }
+}
+
+pub const OP_L1_BLOCK: Address = address!("4200000000000000000000000000000000000015");
+pub const OP_GAS_PRICE_ORACLE: Address = address!("420000000000000000000000000000000000000f");
+
+const L1_BASE_FEE_SLOT: U256 = U256::from_limbs([1, 0, 0, 0]);
+const L1_FEE_SCALARS_SLOT: U256 = U256::from_limbs([3, 0, 0, 0]);
+const L1_OVERHEAD_SLOT: U256 = U256::from_limbs([5, 0, 0, 0]);
+const L1_SCALAR_SLOT: U256 = U256::from_limbs([6, 0, 0, 0]);
+const L1_BLOB_BASE_FEE_SLOT: U256 = U256::from_limbs([7, 0, 0, 0]);
+
+/// Predeploy slots read by the OP stack cost model, they must be preloaded to the market state.
+pub const OP_L1_FEE_SLOTS: [(Address, U256); 6] = [
+    (OP_GAS_PRICE_ORACLE, U256::ZERO),
+    (OP_L1_BLOCK, L1_BASE_FEE_SLOT),
+    (OP_L1_BLOCK, L1_FEE_SCALARS_SLOT),
+    (OP_L1_BLOCK, L1_OVERHEAD_SLOT),
+    (OP_L1_BLOCK, L1_SCALAR_SLOT),
+    (OP_L1_BLOCK, L1_BLOB_BASE_FEE_SLOT),
+];
+
+// 68 bytes of the signature, all are assumed non zero
+const SIGNATURE_SIZE: u64 = 68;
+const FJORD_COST_INTERCEPT: i64 = -42_585_600;
+const FJORD_COST_FASTLZ_COEF: u64 = 836_500;
+const FJORD_MIN_TX_SIZE_SCALED: u64 = 100 * 1_000_000;
+
+/// L1 fee params of the OP stack chain read from the L1Block and GasPriceOracle predeploys.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpL1FeeParams {
+    pub is_ecotone: bool,
+    pub is_fjord: bool,
+    pub l1_base_fee: U256,
+    pub l1_blob_base_fee: U256,
+    pub base_fee_scalar: U256,
+    pub blob_base_fee_scalar: U256,
+    /// Bedrock overhead and scalar
+    pub l1_fee_overhead: U256,
+    pub l1_fee_scalar: U256,
+}
+
+impl OpL1FeeParams {
+    pub fn from_db(db: &LoomInMemoryDB) -> Result<Self> {
+        let storage = |address: Address, slot: U256| db.storage_ref(address, slot).unwrap_or_default();
+
+        let l1_base_fee = storage(OP_L1_BLOCK, L1_BASE_FEE_SLOT);
+        if l1_base_fee.is_zero() {
+            return Err(eyre!("L1_BLOCK_STATE_NOT_FOUND"));
+        }
+
+        // isEcotone and isFjord are packed in the first slot of GasPriceOracle
+        let flags = storage(OP_GAS_PRICE_ORACLE, U256::ZERO).to_be_bytes::<32>();
+        // sequenceNumber, blobBaseFeeScalar and baseFeeScalar are packed in the same slot of L1Block
+        let scalars = storage(OP_L1_BLOCK, L1_FEE_SCALARS_SLOT).to_be_bytes::<32>();
+
+        Ok(Self {
+            is_ecotone: flags[31] != 0,
+            is_fjord: flags[30] != 0,
+            l1_base_fee,
+            l1_blob_base_fee: storage(OP_L1_BLOCK, L1_BLOB_BASE_FEE_SLOT),
+            base_fee_scalar: U256::from_be_slice(&scalars[16..20]),
+            blob_base_fee_scalar: U256::from_be_slice(&scalars[20..24]),
+            l1_fee_overhead: storage(OP_L1_BLOCK, L1_OVERHEAD_SLOT),
+            l1_fee_scalar: storage(OP_L1_BLOCK, L1_SCALAR_SLOT),
+        })
+    }
+
+    /// Same as GasPriceOracle.getL1Fee for the unsigned transaction.
+    pub fn l1_fee(&self, tx_data: &[u8]) -> U256 {
+        if self.is_fjord {
+            let fastlz_size = flz_compress_len(tx_data) as u64 + SIGNATURE_SIZE;
+            let estimated_size =
+                (FJORD_COST_INTERCEPT + (FJORD_COST_FASTLZ_COEF * fastlz_size) as i64).max(FJORD_MIN_TX_SIZE_SCALED as i64);
+            U256::from(estimated_size as u64) * self.l1_fee_scaled() / U256::from(1_000_000_000_000u64)
+        } else if self.is_ecotone {
+            U256::from(calldata_gas(tx_data)) * self.l1_fee_scaled() / U256::from(16_000_000)
+        } else {
+            (U256::from(calldata_gas(tx_data)) + self.l1_fee_overhead) * self.l1_base_fee * self.l1_fee_scalar / U256::from(1_000_000)
+        }
+    }
+
+    fn l1_fee_scaled(&self) -> U256 {
+        self.base_fee_scalar * U256::from(16) * self.l1_base_fee + self.blob_base_fee_scalar * self.l1_blob_base_fee
+    }
+}
+
+/// Execution gas and the L1 data fee of OP stack chains.
+#[derive(Clone, Debug, Default)]
+pub struct OpStackCostModel;
+
+impl TxCostModel for OpStackCostModel {
+    fn tx_cost(&self, db: &LoomInMemoryDB, gas_used: u64, gas_price: u128, tx_data: &[u8]) -> Result<U256> {
+        let l1_fee = OpL1FeeParams::from_db(db)?.l1_fee(tx_data);
+        Ok(U256::from(gas_used) * U256::from(gas_price) + l1_fee)
+    }
+}
+
+fn calldata_gas(data: &[u8]) -> u64 {
+    let zeros = data.iter().filter(|x| **x == 0).count() as u64;
+    zeros * 4 + (data.len() as u64 - zeros) * 16 + SIGNATURE_SIZE * 16
+}
+
+/// Length of the FastLZ compressed data, ported from op-geth FlzCompressLen.
+fn flz_compress_len(ib: &[u8]) -> u32 {
+    let mut n: u32 = 0;
+    let mut ht = vec![0u32; 8192];
+
+    let u24 = |i: u32| -> u32 { ib[i as usize] as u32 | (ib[i as usize + 1] as u32) << 8 | (ib[i as usize + 2] as u32) << 16 };
+    let cmp = |p: u32, q: u32, e: u32| -> u32 {
+        let mut l = 0u32;
+        let mut e = e - q;
+        while l < e {
+            if ib[(p + l) as usize] != ib[(q + l) as usize] {
+                e = 0;
+            }
+            l += 1;
+        }
+        l
+    };
+    let literals = |r: u32, n: &mut u32| {
+        *n += 0x21 * (r / 0x20);
+        let r = r % 0x20;
+        if r != 0 {
+            *n += r + 1;
+        }
+    };
+    let match_len = |l: u32, n: &mut u32| {
+        let l = l - 1;
+        *n += 3 * (l / 262);
+        *n += if l % 262 >= 6 { 3 } else { 2 };
+    };
+    let hash = |v: u32| -> usize { ((2654435769u32.wrapping_mul(v) >> 19) & 0x1fff) as usize };
+
+    let mut a: u32 = 0;
+    let ip_limit: u32 = if ib.len() < 13 { 0 } else { ib.len() as u32 - 13 };
+    let mut ip = a + 2;
+    while ip < ip_limit {
+        let mut r;
+        loop {
+            let s = u24(ip);
+            let h = hash(s);
+            r = ht[h];
+            ht[h] = ip;
+            let d = ip - r;
+            if ip >= ip_limit {
+                break;
+            }
+            ip += 1;
+            if d <= 0x1fff && s == u24(r) {
+                break;
+            }
+        }
+        if ip >= ip_limit {
+            break;
+        }
+        ip -= 1;
+        if ip > a {
+            literals(ip - a, &mut n);
+        }
+        let l = cmp(r + 3, ip + 3, ip_limit + 9);
+        match_len(l, &mut n);
+
+        ip += l;
+        ht[hash(u24(ip))] = ip;
+        ip += 1;
+        ht[hash(u24(ip))] = ip;
+        ip += 1;
+        a = ip;
+    }
+    literals(ib.len() as u32 - a, &mut n);
+    n
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn l1_block_db(is_fjord: bool) -> LoomInMemoryDB {
+        let mut db = LoomInMemoryDB::default();
+        let flags = if is_fjord { U256::from(0x0101) } else { U256::from(1) };
+        // baseFeeScalar = 1368, blobBaseFeeScalar = 810949
+        let scalars = (U256::from(1368) << 96) | (U256::from(810949) << 64) | U256::from(5);
+        db.insert_account_storage(OP_GAS_PRICE_ORACLE, U256::ZERO, flags).unwrap();
+        db.insert_account_storage(OP_L1_BLOCK, L1_BASE_FEE_SLOT, U256::from(10_000_000_000u64)).unwrap();
+        db.insert_account_storage(OP_L1_BLOCK, L1_BLOB_BASE_FEE_SLOT, U256::from(1_000_000_000u64)).unwrap();
+        db.insert_account_storage(OP_L1_BLOCK, L1_FEE_SCALARS_SLOT, scalars).unwrap();
+        db
+    }
+
+    #[test]
+    fn test_flz_compress_len() {
+        assert_eq!(flz_compress_len(&[]), 0);
+        assert_eq!(flz_compress_len(&[1u8; 10]), 11);
+        assert_eq!(flz_compress_len(&[0u8; 1000]), 21);
+
+        // xorshift32 bytes are not compressible, literals only
+        let mut x = 1u32;
+        let random_data: Vec<u8> = (0..1000)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                x as u8
+            })
+            .collect();
+        assert_eq!(flz_compress_len(&random_data), 1032);
+    }
+
+    #[test]
+    fn test_op_l1_fee() -> Result<()> {
+        let params = OpL1FeeParams::from_db(&l1_block_db(false))?;
+        assert!(params.is_ecotone && !params.is_fjord);
+        assert_eq!(params.base_fee_scalar, U256::from(1368));
+        assert_eq!(params.blob_base_fee_scalar, U256::from(810949));
+
+        // (100 + 68) * 16 * (1368 * 16 * 10 gwei + 810949 * 1 gwei) / 16e6
+        let tx_data = [1u8; 100];
+        assert_eq!(params.l1_fee(&tx_data), U256::from(173_011_272_000u64));
+
+        let cost = OpStackCostModel.tx_cost(&l1_block_db(false), 100_000, 1_000_000, &tx_data)?;
+        assert_eq!(cost, U256::from(100_000_000_000u64 + 173_011_272_000u64));
+
+        // minimal estimated size for the short tx
+        let fjord_params = OpL1FeeParams::from_db(&l1_block_db(true))?;
+        assert_eq!(fjord_params.l1_fee(&tx_data[..10]), U256::from(102_982_900_000u64));
+
+        assert!(OpStackCostModel.tx_cost(&LoomInMemoryDB::default(), 100_000, 1_000_000, &tx_data).is_err());
+        assert_eq!(ExecutionCostModel.tx_cost(&LoomInMemoryDB::default(), 100_000, 1_000_000, &tx_data)?, U256::from(100_000_000_000u64));
+        Ok(())
+    }
+}
@@ -16,6 +16,14 @@ impl BestTxCompose {
         BestTxCompose { validity_pct: Some(validity_pct.into()), ..Default::default() }
     }
 
+    /// Returns true if the request has higher profit than the best one
+    pub fn is_better_profit(&self, request: &TxComposeData) -> bool {
+        match &self.best_profit_swap {
+            Some(best_swap) => best_swap.swap.abs_profit_eth() < request.swap.abs_profit_eth(),
+            None => true,
+        }
+    }
+
     pub fn check(&mut self, request: &TxComposeData) -> bool {
         let mut is_ok = false;
 
@@ -24,4 +24,8 @@ pub enum MempoolEvents {
     MempoolLogUpdate {
         tx_hash: TxHash,
     },
+    /// The partial transaction has been received from an order flow hint.
+    MempoolHintUpdate {
+        tx_hash: TxHash,
+    },
 }
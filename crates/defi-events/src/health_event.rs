@@ -1,13 +1,39 @@
-use alloy_primitives::TxHash;
+use alloy_primitives::{Address, BlockNumber, TxHash, I256, U256};
 
 use defi_types::SwapError;
 
 use crate::Message;
 
+/// Bundle pre-flight simulation result that does not match the estimation.
+#[derive(Clone, Debug)]
+pub struct BundlePreflightMismatch {
+    pub block: BlockNumber,
+    /// Hashes of our transactions in the bundle
+    pub tx_hashes: Vec<TxHash>,
+    pub swap: String,
+    /// Revert or error of the simulated transaction
+    pub revert: Option<String>,
+    pub expected_coinbase_diff: U256,
+    pub simulated_coinbase_diff: U256,
+    pub expected_balance_delta: I256,
+    pub simulated_balance_delta: I256,
+}
+
+/// Token allowance of the multicaller required by a pool on an active swap path is below the expected amount.
+#[derive(Clone, Debug)]
+pub struct MissingApproval {
+    pub token: Address,
+    pub owner: Address,
+    pub spender: Address,
+    pub allowance: U256,
+}
+
 #[derive(Clone, Debug)]
 pub enum HealthEvent {
     PoolSwapError(SwapError),
     MonitorTx(TxHash),
+    BundlePreflightMismatch(BundlePreflightMismatch),
+    MissingApproval(MissingApproval),
 }
 
 pub type MessageHealthEvent = Message<HealthEvent>;
@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 use std::fmt::Debug;
 
-use alloy_primitives::{Address, BlockNumber, TxHash};
+use alloy_primitives::{Address, BlockHash, BlockNumber, TxHash};
 use alloy_rpc_types::Transaction;
 use revm::primitives::Env;
 
@@ -15,6 +15,8 @@ pub struct StateUpdateEvent {
     pub next_block_number: BlockNumber,
     pub next_block_timestamp: u64,
     pub next_base_fee: u64,
+    /// Hash of the block the market state is built on
+    pub parent_block_hash: BlockHash,
     market_state: LoomInMemoryDB,
     state_update: GethStateUpdateVec,
     state_required: Option<GethStateUpdateVec>,
@@ -44,6 +46,7 @@ impl StateUpdateEvent {
             next_block_number: next_block,
             next_block_timestamp,
             next_base_fee,
+            parent_block_hash: BlockHash::ZERO,
             state_update,
             state_required,
             market_state,
@@ -55,6 +58,10 @@ impl StateUpdateEvent {
         }
     }
 
+    pub fn with_parent_block_hash(self, parent_block_hash: BlockHash) -> Self {
+        Self { parent_block_hash, ..self }
+    }
+
     pub fn evm_env(&self) -> Env {
         env_for_block(self.next_block_number, self.next_block_timestamp)
     }
@@ -8,7 +8,7 @@ use alloy_rlp::Encodable;
 use alloy_rpc_types::{Transaction, TransactionRequest};
 use eyre::{eyre, Result};
 
-use defi_entities::{Swap, TxSigner};
+use defi_entities::{ReservedNonce, Swap, TxSigner};
 use defi_types::GethStateUpdateVec;
 use loom_revm_db::LoomInMemoryDB;
 
@@ -20,6 +20,8 @@ pub enum TxState {
     SignatureRequired(TransactionRequest),
     ReadyForBroadcast(Bytes),
     ReadyForBroadcastStuffing(Bytes),
+    /// Stuffing transaction referenced by hash, its signed transaction is not available
+    StuffingHash(TxHash),
 }
 
 impl TxState {
@@ -42,6 +44,7 @@ pub enum TxCompose {
     Route(TxComposeData),
     Estimate(TxComposeData),
     Sign(TxComposeData),
+    Preflight(TxComposeData),
     Broadcast(TxComposeData),
 }
 
@@ -56,7 +59,7 @@ impl Deref for TxCompose {
 impl TxCompose {
     pub fn data(&self) -> &TxComposeData {
         match self {
-            TxCompose::Route(x) | TxCompose::Broadcast(x) | TxCompose::Sign(x) | TxCompose::Estimate(x) => x,
+            TxCompose::Route(x) | TxCompose::Broadcast(x) | TxCompose::Sign(x) | TxCompose::Estimate(x) | TxCompose::Preflight(x) => x,
         }
     }
 }
@@ -64,6 +67,7 @@ impl TxCompose {
 #[derive(Debug, Clone)]
 pub enum RlpState {
     Stuffing(Bytes),
+    StuffingHash(TxHash),
     Backrun(Bytes),
     None,
 }
@@ -73,10 +77,14 @@ impl RlpState {
         matches!(self, RlpState::None)
     }
 
+    pub fn is_hash(&self) -> bool {
+        matches!(self, RlpState::StuffingHash(_))
+    }
+
     pub fn unwrap(&self) -> Bytes {
         match self.clone() {
             RlpState::Backrun(val) | RlpState::Stuffing(val) => val,
-            RlpState::None => Bytes::new(),
+            RlpState::StuffingHash(_) | RlpState::None => Bytes::new(),
         }
     }
 }
@@ -85,6 +93,8 @@ impl RlpState {
 pub struct TxComposeData {
     pub signer: Option<TxSigner>,
     pub nonce: u64,
+    /// Reservation of the nonce held by the opportunity until it is broadcasted or dropped
+    pub reserved_nonce: Option<ReservedNonce>,
     pub eth_balance: U256,
     pub value: U256,
     pub gas: u64,
@@ -103,6 +113,8 @@ pub struct TxComposeData {
     pub origin: Option<String>,
     pub tips_pct: Option<u32>,
     pub tips: Option<U256>,
+    /// Number of later blocks a re-estimated bundle is still targeted for, None for a new bundle
+    pub resubmit_blocks_left: Option<u64>,
 }
 
 impl TxComposeData {
@@ -122,6 +134,11 @@ impl TxComposeData {
         self.swap.get_pool_address_vec().iter().any(|x| others_pools.contains(x))
     }
 
+    /// Hashes of stuffing transactions known from hints only, without a signed transaction.
+    pub fn hint_stuffing_hashes(&self) -> Vec<TxHash> {
+        self.stuffing_txs_hashes.iter().filter(|hash| !self.stuffing_txs.iter().any(|tx| tx.hash == **hash)).cloned().collect()
+    }
+
     pub fn first_stuffing_hash(&self) -> TxHash {
         self.stuffing_txs_hashes.first().map_or(TxHash::default(), |x| *x)
     }
@@ -156,6 +173,7 @@ impl Default for TxComposeData {
         Self {
             signer: None,
             nonce: Default::default(),
+            reserved_nonce: None,
             eth_balance: Default::default(),
             next_block_base_fee: Default::default(),
             value: Default::default(),
@@ -174,6 +192,7 @@ impl Default for TxComposeData {
             origin: None,
             tips_pct: None,
             tips: None,
+            resubmit_blocks_left: None,
         }
     }
 }
@@ -193,6 +212,10 @@ impl MessageTxCompose {
         Message::new(TxCompose::Estimate(data))
     }
 
+    pub fn preflight(data: TxComposeData) -> Self {
+        Message::new(TxCompose::Preflight(data))
+    }
+
     pub fn broadcast(data: TxComposeData) -> Self {
         Message::new(TxCompose::Broadcast(data))
     }
@@ -212,4 +235,16 @@ mod test {
             println!("{c:?}");
         }
     }
+
+    #[test]
+    fn test_hint_stuffing_hashes() {
+        let tx = Transaction { hash: TxHash::repeat_byte(1), ..Default::default() };
+        let request = TxComposeData {
+            stuffing_txs_hashes: vec![TxHash::repeat_byte(1), TxHash::repeat_byte(2)],
+            stuffing_txs: vec![tx],
+            ..TxComposeData::default()
+        };
+
+        assert_eq!(request.hint_stuffing_hashes(), vec![TxHash::repeat_byte(2)]);
+    }
 }
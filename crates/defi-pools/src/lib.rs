@@ -6,12 +6,14 @@ pub use maverickpool::MaverickPool;
 pub use pancakev3pool::PancakeV3Pool;
 pub use uniswapv2pool::UniswapV2Pool;
 pub use uniswapv3pool::{Slot0, UniswapV3Pool};
+pub use uniswapv4_encoder::UniswapV4AbiSwapEncoder;
 
 pub mod db_reader;
 mod maverickpool;
 pub mod state_readers;
 mod uniswapv2pool;
 mod uniswapv3pool;
+mod uniswapv4_encoder;
 
 mod curvepool;
 pub mod protocols;
@@ -0,0 +1,197 @@
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::SolCall;
+use alloy_sol_types::SolInterface;
+use defi_abi::aave3::{IAaveOracle, IAaveV3Pool, IAaveV3PoolDataProvider};
+use eyre::Result;
+use loom_revm_db::LoomInMemoryDB;
+use loom_utils::evm::evm_call;
+use revm::primitives::Env;
+
+#[derive(Clone, Debug, Default)]
+pub struct AaveV3UserAccountData {
+    pub total_collateral_base: U256,
+    pub total_debt_base: U256,
+    pub current_liquidation_threshold: U256,
+    pub health_factor: U256,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AaveV3UserReserveData {
+    pub a_token_balance: U256,
+    pub stable_debt: U256,
+    pub variable_debt: U256,
+    pub usage_as_collateral_enabled: bool,
+}
+
+impl AaveV3UserReserveData {
+    pub fn total_debt(&self) -> U256 {
+        self.stable_debt + self.variable_debt
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AaveV3ReserveConfiguration {
+    pub decimals: U256,
+    pub liquidation_threshold: U256,
+    pub liquidation_bonus: U256,
+    pub is_active: bool,
+    pub is_frozen: bool,
+}
+
+pub struct AaveV3StateReader {}
+
+impl AaveV3StateReader {
+    pub fn get_user_account_data(db: &LoomInMemoryDB, env: Env, pool: Address, user: Address) -> Result<AaveV3UserAccountData> {
+        let call_data_result = evm_call(
+            db,
+            env,
+            pool,
+            IAaveV3Pool::IAaveV3PoolCalls::getUserAccountData(IAaveV3Pool::getUserAccountDataCall { user }).abi_encode(),
+        )?
+        .0;
+        let call_return = IAaveV3Pool::getUserAccountDataCall::abi_decode_returns(&call_data_result, false)?;
+        Ok(AaveV3UserAccountData {
+            total_collateral_base: call_return.totalCollateralBase,
+            total_debt_base: call_return.totalDebtBase,
+            current_liquidation_threshold: call_return.currentLiquidationThreshold,
+            health_factor: call_return.healthFactor,
+        })
+    }
+
+    pub fn get_reserves_list(db: &LoomInMemoryDB, env: Env, pool: Address) -> Result<Vec<Address>> {
+        let call_data_result =
+            evm_call(db, env, pool, IAaveV3Pool::IAaveV3PoolCalls::getReservesList(IAaveV3Pool::getReservesListCall {}).abi_encode())?.0;
+        let call_return = IAaveV3Pool::getReservesListCall::abi_decode_returns(&call_data_result, false)?;
+        Ok(call_return._0)
+    }
+
+    pub fn get_user_reserve_data(
+        db: &LoomInMemoryDB,
+        env: Env,
+        data_provider: Address,
+        asset: Address,
+        user: Address,
+    ) -> Result<AaveV3UserReserveData> {
+        let call_data_result = evm_call(
+            db,
+            env,
+            data_provider,
+            IAaveV3PoolDataProvider::IAaveV3PoolDataProviderCalls::getUserReserveData(IAaveV3PoolDataProvider::getUserReserveDataCall {
+                asset,
+                user,
+            })
+            .abi_encode(),
+        )?
+        .0;
+        let call_return = IAaveV3PoolDataProvider::getUserReserveDataCall::abi_decode_returns(&call_data_result, false)?;
+        Ok(AaveV3UserReserveData {
+            a_token_balance: call_return.currentATokenBalance,
+            stable_debt: call_return.currentStableDebt,
+            variable_debt: call_return.currentVariableDebt,
+            usage_as_collateral_enabled: call_return.usageAsCollateralEnabled,
+        })
+    }
+
+    pub fn get_reserve_configuration(
+        db: &LoomInMemoryDB,
+        env: Env,
+        data_provider: Address,
+        asset: Address,
+    ) -> Result<AaveV3ReserveConfiguration> {
+        let call_data_result = evm_call(
+            db,
+            env,
+            data_provider,
+            IAaveV3PoolDataProvider::IAaveV3PoolDataProviderCalls::getReserveConfigurationData(
+                IAaveV3PoolDataProvider::getReserveConfigurationDataCall { asset },
+            )
+            .abi_encode(),
+        )?
+        .0;
+        let call_return = IAaveV3PoolDataProvider::getReserveConfigurationDataCall::abi_decode_returns(&call_data_result, false)?;
+        Ok(AaveV3ReserveConfiguration {
+            decimals: call_return.decimals,
+            liquidation_threshold: call_return.liquidationThreshold,
+            liquidation_bonus: call_return.liquidationBonus,
+            is_active: call_return.isActive,
+            is_frozen: call_return.isFrozen,
+        })
+    }
+
+    pub fn get_asset_price(db: &LoomInMemoryDB, env: Env, oracle: Address, asset: Address) -> Result<U256> {
+        let call_data_result =
+            evm_call(db, env, oracle, IAaveOracle::IAaveOracleCalls::getAssetPrice(IAaveOracle::getAssetPriceCall { asset }).abi_encode())?
+                .0;
+        let call_return = IAaveOracle::getAssetPriceCall::abi_decode_returns(&call_data_result, false)?;
+        Ok(call_return._0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::BlockNumber;
+    use alloy_provider::Provider;
+    use alloy_rpc_types::BlockId;
+    use debug_provider::AnvilDebugProviderFactory;
+    use defi_address_book::{AaveV3Address, TokenAddress};
+    use defi_entities::required_state::{RequiredState, RequiredStateReader};
+    use eyre::OptionExt;
+    use loom_utils::evm::env_for_block;
+    use std::env;
+
+    #[tokio::test]
+    async fn test_aave3_state_reader() -> Result<()> {
+        let block_number = 20935488u64;
+
+        let node_url = env::var("MAINNET_WS")?;
+        let client = AnvilDebugProviderFactory::from_node_on_block(node_url, BlockNumber::from(block_number)).await?;
+        let block = client.get_block_by_number(block_number.into(), false).await?.ok_or_eyre("BLOCK_NOT_FOUND")?;
+
+        let user = TokenAddress::WETH;
+
+        let mut state_required = RequiredState::new();
+        state_required
+            .add_call(
+                AaveV3Address::POOL,
+                IAaveV3Pool::IAaveV3PoolCalls::getUserAccountData(IAaveV3Pool::getUserAccountDataCall { user }).abi_encode(),
+            )
+            .add_call(
+                AaveV3Address::POOL_DATA_PROVIDER,
+                IAaveV3PoolDataProvider::IAaveV3PoolDataProviderCalls::getReserveConfigurationData(
+                    IAaveV3PoolDataProvider::getReserveConfigurationDataCall { asset: TokenAddress::WETH },
+                )
+                .abi_encode(),
+            )
+            .add_call(
+                AaveV3Address::ORACLE,
+                IAaveOracle::IAaveOracleCalls::getAssetPrice(IAaveOracle::getAssetPriceCall { asset: TokenAddress::WETH }).abi_encode(),
+            );
+        let state_update = RequiredStateReader::fetch_calls_and_slots(client.clone(), state_required, Some(block_number)).await?;
+
+        let mut state_db = LoomInMemoryDB::default();
+        state_db.apply_geth_update(state_update);
+        let env = env_for_block(block_number, block.header.timestamp);
+
+        let pool_contract = IAaveV3Pool::new(AaveV3Address::POOL, client.clone());
+        let original_account_data = pool_contract.getUserAccountData(user).call().block(BlockId::from(block_number)).await?;
+        let account_data = AaveV3StateReader::get_user_account_data(&state_db, env.clone(), AaveV3Address::POOL, user)?;
+        assert_eq!(account_data.total_debt_base, original_account_data.totalDebtBase);
+        assert_eq!(account_data.health_factor, original_account_data.healthFactor);
+
+        let data_provider_contract = IAaveV3PoolDataProvider::new(AaveV3Address::POOL_DATA_PROVIDER, client.clone());
+        let original_configuration =
+            data_provider_contract.getReserveConfigurationData(TokenAddress::WETH).call().block(BlockId::from(block_number)).await?;
+        let configuration =
+            AaveV3StateReader::get_reserve_configuration(&state_db, env.clone(), AaveV3Address::POOL_DATA_PROVIDER, TokenAddress::WETH)?;
+        assert_eq!(configuration.decimals, original_configuration.decimals);
+        assert_eq!(configuration.liquidation_bonus, original_configuration.liquidationBonus);
+
+        let oracle_contract = IAaveOracle::new(AaveV3Address::ORACLE, client.clone());
+        let original_price = oracle_contract.getAssetPrice(TokenAddress::WETH).call().block(BlockId::from(block_number)).await?._0;
+        let price = AaveV3StateReader::get_asset_price(&state_db, env, AaveV3Address::ORACLE, TokenAddress::WETH)?;
+        assert_eq!(price, original_price);
+
+        Ok(())
+    }
+}
@@ -1,8 +1,10 @@
+pub use aave3::{AaveV3ReserveConfiguration, AaveV3StateReader, AaveV3UserAccountData, AaveV3UserReserveData};
 pub use erc20::ERC20StateReader;
 pub use uniswapv2::UniswapV2StateReader;
 pub use uniswapv3::UniswapV3StateReader;
 pub use uniswapv3_quoter::{UniswapV3QuoterV2Encoder, UniswapV3QuoterV2StateReader};
 
+mod aave3;
 mod uniswapv2;
 mod uniswapv3;
 
@@ -0,0 +1,159 @@
+use alloy_primitives::aliases::{I24, U24};
+use alloy_primitives::{Address, Bytes, I256, U256};
+use alloy_sol_types::SolCall;
+use defi_abi::uniswap4::{IUniswapV4PoolManager, PoolKey};
+use defi_entities::{AbiSwapEncoder, PreswapRequirement};
+use eyre::{eyre, Result};
+
+use crate::UniswapV3Pool;
+
+/// Encoder for Uniswap V4 pools, the swaps are executed against the pool manager inside of its unlock callback.
+/// Native currency pools are not supported.
+#[derive(Clone, Debug)]
+pub struct UniswapV4AbiSwapEncoder {
+    pool_manager: Address,
+    pool_key: PoolKey,
+}
+
+impl UniswapV4AbiSwapEncoder {
+    pub fn new(pool_manager: Address, currency0: Address, currency1: Address, fee: u32, tick_spacing: i32, hooks: Address) -> Self {
+        let pool_key =
+            PoolKey { currency0, currency1, fee: U24::from(fee), tickSpacing: I24::try_from(tick_spacing).unwrap_or_default(), hooks };
+        Self { pool_manager, pool_key }
+    }
+
+    fn get_zero_for_one(&self, token_from_address: Address, token_to_address: Address) -> Result<bool> {
+        if token_from_address == self.pool_key.currency0 && token_to_address == self.pool_key.currency1 {
+            Ok(true)
+        } else if token_from_address == self.pool_key.currency1 && token_to_address == self.pool_key.currency0 {
+            Ok(false)
+        } else {
+            Err(eyre!("TOKEN_NOT_IN_POOL"))
+        }
+    }
+
+    fn encode_swap(
+        &self,
+        token_from_address: Address,
+        token_to_address: Address,
+        amount_specified: I256,
+        hook_data: Bytes,
+    ) -> Result<Bytes> {
+        let swap_call = IUniswapV4PoolManager::swapCall {
+            key: self.pool_key.clone(),
+            params: IUniswapV4PoolManager::SwapParams {
+                zeroForOne: self.get_zero_for_one(token_from_address, token_to_address)?,
+                amountSpecified: amount_specified,
+                sqrtPriceLimitX96: UniswapV3Pool::get_price_limit(&token_from_address, &token_to_address),
+            },
+            hookData: hook_data,
+        };
+
+        Ok(Bytes::from(swap_call.abi_encode()))
+    }
+}
+
+impl AbiSwapEncoder for UniswapV4AbiSwapEncoder {
+    // Negative amount specified is exact input, recipient is not used as the output is taken from the pool manager
+    fn encode_swap_in_amount_provided(
+        &self,
+        token_from_address: Address,
+        token_to_address: Address,
+        amount: U256,
+        _recipient: Address,
+        payload: Bytes,
+    ) -> Result<Bytes> {
+        self.encode_swap(token_from_address, token_to_address, I256::ZERO - I256::from_raw(amount), payload)
+    }
+
+    fn encode_swap_out_amount_provided(
+        &self,
+        token_from_address: Address,
+        token_to_address: Address,
+        amount: U256,
+        _recipient: Address,
+        payload: Bytes,
+    ) -> Result<Bytes> {
+        self.encode_swap(token_from_address, token_to_address, I256::from_raw(amount), payload)
+    }
+
+    fn preswap_requirement(&self) -> PreswapRequirement {
+        PreswapRequirement::Callback
+    }
+
+    fn swap_in_amount_offset(&self, _token_from_address: Address, _token_to_address: Address) -> Option<u32> {
+        Some(0xC4)
+    }
+
+    fn swap_out_amount_offset(&self, _token_from_address: Address, _token_to_address: Address) -> Option<u32> {
+        Some(0xC4)
+    }
+
+    fn swap_in_amount_return_offset(&self, _token_from_address: Address, _token_to_address: Address) -> Option<u32> {
+        Some(0x0)
+    }
+
+    // BalanceDelta packs amount0 to the upper and amount1 to the lower 128 bits, the script extracts the positive amount out
+    fn swap_in_amount_return_script(&self, token_from_address: Address, token_to_address: Address) -> Option<Bytes> {
+        match self.get_zero_for_one(token_from_address, token_to_address) {
+            Ok(true) => Some(Bytes::from(vec![0x8, 0x1A, 0x80, 0x1B, 0x80, 0x00])),
+            Ok(false) => Some(Bytes::from(vec![0x8, 0x1B, 0x80, 0x00])),
+            Err(_) => None,
+        }
+    }
+
+    fn singleton_address(&self) -> Option<Address> {
+        Some(self.pool_manager)
+    }
+
+    fn encode_singleton_unlock(&self, payload: Bytes) -> Result<Bytes> {
+        Ok(Bytes::from(IUniswapV4PoolManager::unlockCall { data: payload }.abi_encode()))
+    }
+
+    fn encode_singleton_sync(&self, token_address: Address) -> Result<Bytes> {
+        Ok(Bytes::from(IUniswapV4PoolManager::syncCall { currency: token_address }.abi_encode()))
+    }
+
+    fn encode_singleton_settle(&self) -> Result<Bytes> {
+        Ok(Bytes::from(IUniswapV4PoolManager::settleCall {}.abi_encode()))
+    }
+
+    fn encode_singleton_take(&self, token_address: Address, recipient: Address, amount: U256) -> Result<Bytes> {
+        Ok(Bytes::from(IUniswapV4PoolManager::takeCall { currency: token_address, to: recipient, amount }.abi_encode()))
+    }
+
+    fn singleton_take_amount_offset(&self) -> Option<u32> {
+        Some(0x44)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use defi_address_book::{TokenAddress, UniswapV4Address};
+
+    #[test]
+    fn test_encode_swap() -> Result<()> {
+        let encoder =
+            UniswapV4AbiSwapEncoder::new(UniswapV4Address::POOL_MANAGER, TokenAddress::USDC, TokenAddress::WETH, 500, 10, Address::ZERO);
+        let amount = U256::from(1000);
+
+        let data = encoder.encode_swap_in_amount_provided(TokenAddress::WETH, TokenAddress::USDC, amount, Address::ZERO, Bytes::new())?;
+        let offset = encoder.swap_in_amount_offset(TokenAddress::WETH, TokenAddress::USDC).unwrap() as usize;
+        assert_eq!(I256::try_from_be_slice(&data[offset..offset + 0x20]), Some(I256::ZERO - I256::from_raw(amount)));
+
+        let swap_call = IUniswapV4PoolManager::swapCall::abi_decode(&data, false)?;
+        assert!(!swap_call.params.zeroForOne);
+        assert_eq!(swap_call.key.fee, U24::from(500));
+
+        let data = encoder.encode_singleton_take(TokenAddress::USDC, Address::ZERO, amount)?;
+        let offset = encoder.singleton_take_amount_offset().unwrap() as usize;
+        assert_eq!(U256::from_be_slice(&data[offset..offset + 0x20]), amount);
+
+        assert!(encoder
+            .encode_swap_in_amount_provided(TokenAddress::WETH, TokenAddress::DAI, amount, Address::ZERO, Bytes::new())
+            .is_err());
+
+        Ok(())
+    }
+}
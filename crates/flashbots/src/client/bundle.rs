@@ -86,6 +86,10 @@ pub struct BundleRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "baseFee")]
     simulation_basefee: Option<u64>,
+
+    #[serde(rename = "replacementUuid")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replacement_uuid: Option<String>,
 }
 
 pub fn serialize_txs<S>(txs: &[BundleTransaction], s: S) -> Result<S::Ok, S::Error>
@@ -279,6 +283,68 @@ impl BundleRequest {
         self.max_timestamp = Some(timestamp);
         self
     }
+
+    /// Get the replacement UUID of the bundle (if any).
+    pub fn replacement_uuid(&self) -> Option<&String> {
+        self.replacement_uuid.as_ref()
+    }
+
+    /// Set the replacement UUID of the bundle.
+    ///
+    /// A bundle sent with the same replacement UUID by the same signer replaces
+    /// the previous one, and it can be cancelled with [`CancelBundleRequest`].
+    pub fn set_replacement_uuid(mut self, uuid: String) -> Self {
+        self.replacement_uuid = Some(uuid);
+        self
+    }
+}
+
+/// A request to cancel bundles previously sent with the given replacement UUID.
+#[derive(Clone, Debug, Serialize)]
+pub struct CancelBundleRequest {
+    #[serde(rename = "replacementUuid")]
+    replacement_uuid: String,
+}
+
+impl CancelBundleRequest {
+    /// Creates a cancel request for the replacement UUID.
+    pub fn new(replacement_uuid: String) -> Self {
+        Self { replacement_uuid }
+    }
+
+    /// Get the replacement UUID of the cancelled bundles.
+    pub fn replacement_uuid(&self) -> &String {
+        &self.replacement_uuid
+    }
+}
+
+/// A single signed transaction sent with `eth_sendPrivateTransaction`.
+///
+/// The transaction is kept out of the public mempool and is included by the builders until `maxBlockNumber`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivateTransactionRequest {
+    tx: Bytes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_block_number: Option<U64>,
+}
+
+impl PrivateTransactionRequest {
+    /// Creates a request for the signed transaction.
+    pub fn new(tx: Bytes) -> Self {
+        Self { tx, max_block_number: None }
+    }
+
+    /// Get the signed transaction.
+    pub fn tx(&self) -> &Bytes {
+        &self.tx
+    }
+
+    /// Set the last block the transaction can be included in.
+    pub fn set_max_block_number(mut self, max_block_number: U64) -> Self {
+        self.max_block_number = Some(max_block_number);
+        self
+    }
 }
 
 /// Details of a simulated transaction.
@@ -457,6 +523,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn bundle_serialize_replacement_uuid() {
+        let bundle = BundleRequest::new()
+            .push_transaction(Bytes::from(vec![0x1]))
+            .set_block(U64::from(2))
+            .set_replacement_uuid("e2c5a4a0-7f3b-4b8e-9d6a-0c1f2e3d4b5a".to_string());
+
+        assert_eq!(
+            &serde_json::to_string(&bundle).unwrap(),
+            r#"{"txs":["0x01"],"blockNumber":"0x2","replacementUuid":"e2c5a4a0-7f3b-4b8e-9d6a-0c1f2e3d4b5a"}"#
+        );
+    }
+
+    #[test]
+    fn cancel_bundle_serialize() {
+        let request = CancelBundleRequest::new("e2c5a4a0-7f3b-4b8e-9d6a-0c1f2e3d4b5a".to_string());
+
+        assert_eq!(&serde_json::to_string(&request).unwrap(), r#"{"replacementUuid":"e2c5a4a0-7f3b-4b8e-9d6a-0c1f2e3d4b5a"}"#);
+    }
+
+    #[test]
+    fn private_transaction_serialize() {
+        let request = PrivateTransactionRequest::new(Bytes::from(vec![0x1]));
+        assert_eq!(&serde_json::to_string(&request).unwrap(), r#"{"tx":"0x01"}"#);
+
+        let request = request.set_max_block_number(U64::from(25));
+        assert_eq!(&serde_json::to_string(&request).unwrap(), r#"{"tx":"0x01","maxBlockNumber":"0x19"}"#);
+    }
+
     #[test]
     fn simulated_bundle_deserialize() {
         let simulated_bundle: SimulatedBundle = serde_json::from_str(
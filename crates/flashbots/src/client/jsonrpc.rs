@@ -98,6 +98,17 @@ pub enum SendBundleResponseType {
     Null(Option<()>),
 }
 
+impl SendBundleResponseType {
+    /// Bundle hash if returned by the relay
+    pub fn bundle_hash(&self) -> Option<BundleHash> {
+        match self {
+            SendBundleResponseType::BundleHash(bundle_hash) => Some(*bundle_hash),
+            SendBundleResponseType::SendBundleResponse(response) => response.bundle_hash,
+            _ => None,
+        }
+    }
+}
+
 #[derive(Deserialize, Debug, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct SendBundleResponse {
@@ -176,5 +187,17 @@ mod tests {
         )
     }
 
+    #[test]
+    fn response_bundle_hash() {
+        let bundle_hash = TxHash::from(hex!("cc6c61428c6516a252768859d167dc8f5c8c8c682334a184710f898e422530f8"));
+
+        assert_eq!(SendBundleResponseType::BundleHash(bundle_hash).bundle_hash(), Some(bundle_hash));
+        assert_eq!(
+            SendBundleResponseType::SendBundleResponse(SendBundleResponse { bundle_hash: Some(bundle_hash) }).bundle_hash(),
+            Some(bundle_hash)
+        );
+        assert_eq!(SendBundleResponseType::String("nil".to_string()).bundle_hash(), None);
+    }
+
     //
 }
@@ -0,0 +1,284 @@
+use alloy_primitives::{Address, Bytes, FixedBytes, TxHash, B256, U256, U64};
+use eyre::{eyre, Result};
+use reqwest::{Client, Response};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+/// A log disclosed by an MEV-Share hint.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct HintLog {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    #[serde(default)]
+    pub data: Bytes,
+}
+
+/// A partial transaction disclosed by an MEV-Share hint.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HintTx {
+    pub to: Option<Address>,
+    pub function_selector: Option<FixedBytes<4>>,
+    pub call_data: Option<Bytes>,
+}
+
+/// A pending transaction hint from the MEV-Share event stream.
+///
+/// Only the fields the sender decided to share are set, the signed transaction is never disclosed.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MevShareHint {
+    pub hash: TxHash,
+    #[serde(default)]
+    pub logs: Option<Vec<HintLog>>,
+    #[serde(default)]
+    pub txs: Option<Vec<HintTx>>,
+    #[serde(default)]
+    pub mev_gas_price: Option<U256>,
+    #[serde(default)]
+    pub gas_used: Option<U256>,
+}
+
+/// Returns the data of a server-sent event, multiple data lines are joined with a newline.
+pub fn parse_sse_event(event: &str) -> Option<String> {
+    let data: Vec<&str> =
+        event.lines().filter_map(|line| line.strip_prefix("data:")).map(|data| data.strip_prefix(' ').unwrap_or(data)).collect();
+
+    if data.is_empty() {
+        None
+    } else {
+        Some(data.join("\n"))
+    }
+}
+
+/// MEV-Share hint stream consumed over server-sent events.
+pub struct MevShareHintStream {
+    response: Response,
+    buffer: String,
+}
+
+impl MevShareHintStream {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let response = Client::new().get(url).header("Accept", "text/event-stream").send().await?;
+
+        if !response.status().is_success() {
+            error!("MEV-Share stream {} status {}", url, response.status());
+            return Err(eyre!("MEV_SHARE_STREAM_STATUS_ERROR"));
+        }
+
+        Ok(Self { response, buffer: String::new() })
+    }
+
+    /// Returns the next hint, None if the stream is closed. Events that are not hints are skipped.
+    pub async fn next_hint(&mut self) -> Result<Option<MevShareHint>> {
+        loop {
+            if let Some(event) = self.pop_event() {
+                if let Some(data) = parse_sse_event(&event) {
+                    match serde_json::from_str::<MevShareHint>(&data) {
+                        Ok(hint) => return Ok(Some(hint)),
+                        Err(e) => error!("MEV-Share hint parse error : {} {}", e, data),
+                    }
+                }
+                continue;
+            }
+
+            match self.response.chunk().await? {
+                Some(chunk) => self.buffer.push_str(&String::from_utf8_lossy(&chunk).replace("\r\n", "\n")),
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn pop_event(&mut self) -> Option<String> {
+        let pos = self.buffer.find("\n\n")?;
+        let event = self.buffer[..pos].to_string();
+        self.buffer.drain(..pos + 2);
+        Some(event)
+    }
+}
+
+/// A bundle item of `mev_sendBundle`, either a hint referenced by hash or a signed transaction.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum MevBundleBody {
+    Hash {
+        hash: TxHash,
+    },
+    #[serde(rename_all = "camelCase")]
+    Tx {
+        tx: Bytes,
+        can_revert: bool,
+    },
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MevBundleInclusion {
+    block: U64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_block: Option<U64>,
+}
+
+/// Share of the bundle profit refunded to the sender of the body item.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MevBundleRefund {
+    pub body_idx: u64,
+    pub percent: u64,
+}
+
+/// Share of the refund paid to the address.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct MevBundleRefundConfig {
+    pub address: Address,
+    pub percent: u64,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MevBundleValidity {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub refund: Vec<MevBundleRefund>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub refund_config: Vec<MevBundleRefundConfig>,
+}
+
+/// Data of the bundle shared with searchers.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MevPrivacyHint {
+    Calldata,
+    ContractAddress,
+    Logs,
+    FunctionSelector,
+    Hash,
+    TxHash,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct MevBundlePrivacy {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hints: Option<Vec<MevPrivacyHint>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub builders: Option<Vec<String>>,
+}
+
+/// A bundle submitted with `mev_sendBundle`.
+///
+/// Unlike [`BundleRequest`](crate::client::BundleRequest), transactions can be referenced by the hash
+/// of an MEV-Share hint.
+#[derive(Clone, Debug, Serialize)]
+pub struct MevSendBundleRequest {
+    version: String,
+    inclusion: MevBundleInclusion,
+    body: Vec<MevBundleBody>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    validity: Option<MevBundleValidity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    privacy: Option<MevBundlePrivacy>,
+}
+
+impl Default for MevSendBundleRequest {
+    fn default() -> Self {
+        Self { version: "v0.1".to_string(), inclusion: Default::default(), body: Vec::new(), validity: None, privacy: None }
+    }
+}
+
+impl MevSendBundleRequest {
+    /// Creates an empty bundle request.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a transaction referenced by the hint hash.
+    pub fn push_hash(mut self, hash: TxHash) -> Self {
+        self.body.push(MevBundleBody::Hash { hash });
+        self
+    }
+
+    /// Adds a signed transaction.
+    pub fn push_transaction(mut self, tx: Bytes, can_revert: bool) -> Self {
+        self.body.push(MevBundleBody::Tx { tx, can_revert });
+        self
+    }
+
+    pub fn body(&self) -> &Vec<MevBundleBody> {
+        &self.body
+    }
+
+    /// Get the target block.
+    pub fn block(&self) -> U64 {
+        self.inclusion.block
+    }
+
+    /// Set the target block of the bundle.
+    pub fn set_block(mut self, block: U64) -> Self {
+        self.inclusion.block = block;
+        self
+    }
+
+    /// Set the last block the bundle is valid for.
+    pub fn set_max_block(mut self, max_block: U64) -> Self {
+        self.inclusion.max_block = Some(max_block);
+        self
+    }
+
+    pub fn set_validity(mut self, validity: MevBundleValidity) -> Self {
+        self.validity = Some(validity);
+        self
+    }
+
+    pub fn set_privacy(mut self, privacy: MevBundlePrivacy) -> Self {
+        self.privacy = Some(privacy);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::hex;
+
+    use super::*;
+
+    #[test]
+    fn hint_deserialize() {
+        let data = parse_sse_event(
+            r#"data: {"hash":"0xcc6c61428c6516a252768859d167dc8f5c8c8c682334a184710f898e422530f8","logs":[{"address":"0x0d4a11d5eeaac28ec3f61d100daf4d40471f1852","topics":["0x1c411e9a96e071241c2f21f7726b17ae89e3cab4c78be50e062b03a9fffbbad1"],"data":"0x01"}],"txs":[{"to":"0x7a250d5630b4cf539739df2c5dacb4c659f2488d","functionSelector":"0x7ff36ab5","callData":null}],"mevGasPrice":"0x2540be400","gasUsed":"0x30d40"}"#,
+        )
+        .unwrap();
+
+        let hint: MevShareHint = serde_json::from_str(&data).unwrap();
+
+        assert_eq!(hint.hash, TxHash::from(hex!("cc6c61428c6516a252768859d167dc8f5c8c8c682334a184710f898e422530f8")));
+        assert_eq!(hint.logs.unwrap()[0].data, Bytes::from(vec![0x1]));
+        assert_eq!(hint.txs.unwrap()[0].function_selector, Some(FixedBytes::from(hex!("7ff36ab5"))));
+        assert_eq!(hint.mev_gas_price, Some(U256::from(10_000_000_000u64)));
+        assert_eq!(hint.gas_used, Some(U256::from(200_000)));
+    }
+
+    #[test]
+    fn sse_event_parse() {
+        assert_eq!(parse_sse_event(": keep-alive"), None);
+        assert_eq!(parse_sse_event("event: message\ndata: {\"a\":1}"), Some(r#"{"a":1}"#.to_string()));
+        assert_eq!(parse_sse_event("data:1\ndata: 2"), Some("1\n2".to_string()));
+    }
+
+    #[test]
+    fn mev_bundle_serialize() {
+        let bundle = MevSendBundleRequest::new()
+            .push_hash(TxHash::repeat_byte(0x11))
+            .push_transaction(Bytes::from(vec![0x2]), false)
+            .set_block(U64::from(2))
+            .set_max_block(U64::from(3))
+            .set_validity(MevBundleValidity {
+                refund: vec![MevBundleRefund { body_idx: 0, percent: 90 }],
+                refund_config: vec![MevBundleRefundConfig { address: Address::repeat_byte(0x22), percent: 100 }],
+            })
+            .set_privacy(MevBundlePrivacy { hints: Some(vec![MevPrivacyHint::Calldata, MevPrivacyHint::TxHash]), builders: None });
+
+        assert_eq!(
+            &serde_json::to_string(&bundle).unwrap(),
+            r#"{"version":"v0.1","inclusion":{"block":"0x2","maxBlock":"0x3"},"body":[{"hash":"0x1111111111111111111111111111111111111111111111111111111111111111"},{"tx":"0x02","canRevert":false}],"validity":{"refund":[{"bodyIdx":0,"percent":90}],"refundConfig":[{"address":"0x2222222222222222222222222222222222222222","percent":100}]},"privacy":{"hints":["calldata","tx_hash"]}}"#
+        );
+    }
+}
@@ -10,7 +10,7 @@ use url::Url;
 
 use crate::client::SendBundleResponseType;
 use crate::{
-    client::bundle::{BundleRequest, SimulatedBundle},
+    client::bundle::{BundleHash, BundleRequest, SimulatedBundle},
     client::relay::{Relay, RelayError},
 };
 
@@ -114,7 +114,7 @@ where
     /// See [`eth_sendBundle`][fb_sendBundle] for more information.
     ///
     /// [fb_sendBundle]: https://docs.flashbots.net/flashbots-auction/searchers/advanced/rpc-endpoint#eth_sendbundle
-    pub async fn send_bundle(&self, bundle: &BundleRequest) -> Result<(), FlashbotsMiddlewareError> {
+    pub async fn send_bundle(&self, bundle: &BundleRequest) -> Result<Option<BundleHash>, FlashbotsMiddlewareError> {
         // The target block must be set
         bundle.block().ok_or(FlashbotsMiddlewareError::MissingParameters)?;
 
@@ -123,9 +123,9 @@ where
             return Err(FlashbotsMiddlewareError::MissingParameters);
         }
 
-        let _response: SendBundleResponseType =
+        let response: SendBundleResponseType =
             self.relay.request("eth_sendBundle", [bundle]).await.map_err(FlashbotsMiddlewareError::RelayError)?;
 
-        Ok(())
+        Ok(response.bundle_hash())
     }
 }
@@ -6,13 +6,20 @@
 //! In addition to leveraging the standard Ethers middleware API ([`send_transaction`][ethers::providers::Middleware::send_transaction]),
 //! custom bundles can be crafted, simulated and submitted.
 pub use body::make_signed_body;
-pub use bundle::{BundleHash, BundleRequest, BundleTransaction, SimulatedBundle, SimulatedTransaction};
+pub use bundle::{
+    BundleHash, BundleRequest, BundleTransaction, CancelBundleRequest, PrivateTransactionRequest, SimulatedBundle, SimulatedTransaction,
+};
 pub use jsonrpc::SendBundleResponseType;
+pub use mev_share::{
+    parse_sse_event, HintLog, HintTx, MevBundleBody, MevBundlePrivacy, MevBundleRefund, MevBundleRefundConfig, MevBundleValidity,
+    MevPrivacyHint, MevSendBundleRequest, MevShareHint, MevShareHintStream,
+};
 pub use middleware::{FlashbotsMiddleware, FlashbotsMiddlewareError};
 pub use relay::{Relay, RelayConfig, RelayError};
 
 mod bundle;
 
+mod mev_share;
 mod middleware;
 
 mod jsonrpc;
@@ -18,6 +18,14 @@ pub struct RelayConfig {
     pub name: String,
     pub url: String,
     pub no_sign: Option<bool>,
+    /// Relay accepts `replacementUuid` in `eth_sendBundle`
+    pub supports_replacement: Option<bool>,
+    /// Relay supports `eth_cancelBundle`
+    pub supports_cancel: Option<bool>,
+    /// Relay supports `mev_sendBundle`
+    pub supports_mev_share: Option<bool>,
+    /// Relay supports `eth_sendPrivateTransaction`
+    pub supports_private_tx: Option<bool>,
 }
 
 /// A Flashbots relay client.
@@ -59,6 +67,16 @@ pub enum RelayError {
     ResponseSerdeJson { err: serde_json::Error, text: String },
 }
 
+impl RelayError {
+    /// Transport errors and server errors (500-599) are transient, the request is worth retrying.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            RelayError::RequestError(err) => err.status().map_or(true, |status| status.is_server_error()),
+            _ => false,
+        }
+    }
+}
+
 impl Relay {
     /// Initializes a new relay client.
     pub fn new(url: impl Into<Url>, signer: Option<PrivateKeySigner>) -> Self {
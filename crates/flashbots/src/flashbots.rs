@@ -1,9 +1,10 @@
 use crate::client::{
-    make_signed_body, BundleRequest, BundleTransaction, FlashbotsMiddleware, FlashbotsMiddlewareError, RelayConfig, SendBundleResponseType,
-    SimulatedBundle,
+    make_signed_body, BundleHash, BundleRequest, BundleTransaction, CancelBundleRequest, FlashbotsMiddleware, FlashbotsMiddlewareError,
+    MevSendBundleRequest, PrivateTransactionRequest, RelayConfig, RelayError, SendBundleResponseType, SimulatedBundle,
 };
+use crate::submission::{RelayHealth, RelaySubmission, ReplacementRegistry, ReplacementTicket, RetryPolicy};
 use alloy_network::Ethereum;
-use alloy_primitives::{TxHash, U64};
+use alloy_primitives::{Bytes, TxHash, U64};
 use alloy_provider::Provider;
 use alloy_signer_local::PrivateKeySigner;
 use alloy_transport::Transport;
@@ -11,13 +12,20 @@ use eyre::{eyre, Result};
 use std::marker::PhantomData;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tracing::{debug, error, info};
+use std::time::Instant;
+use tokio::sync::broadcast;
+use tracing::{debug, error, info, warn};
 use url::Url;
 
 #[derive(Clone)]
 pub struct FlashbotsClient<P, T> {
     pub flashbots_middleware: FlashbotsMiddleware<P, T>,
     pub name: String,
+    pub supports_replacement: bool,
+    pub supports_cancel: bool,
+    pub supports_mev_share: bool,
+    pub supports_private_tx: bool,
+    pub health: Arc<RelayHealth>,
 }
 
 impl<P, T> FlashbotsClient<P, T>
@@ -30,7 +38,15 @@ where
 
         let name = url.to_string();
 
-        FlashbotsClient { flashbots_middleware, name }
+        FlashbotsClient {
+            flashbots_middleware,
+            name,
+            supports_replacement: false,
+            supports_cancel: false,
+            supports_mev_share: false,
+            supports_private_tx: false,
+            health: Default::default(),
+        }
     }
 
     pub fn new_no_sign(provider: P, url: &str) -> Self {
@@ -38,7 +54,31 @@ where
 
         let name = url.to_string();
 
-        FlashbotsClient { flashbots_middleware: flashbots_client, name }
+        FlashbotsClient {
+            flashbots_middleware: flashbots_client,
+            name,
+            supports_replacement: false,
+            supports_cancel: false,
+            supports_mev_share: false,
+            supports_private_tx: false,
+            health: Default::default(),
+        }
+    }
+
+    pub fn with_replacement(self, supports_replacement: bool) -> Self {
+        Self { supports_replacement, ..self }
+    }
+
+    pub fn with_cancel(self, supports_cancel: bool) -> Self {
+        Self { supports_cancel, ..self }
+    }
+
+    pub fn with_mev_share(self, supports_mev_share: bool) -> Self {
+        Self { supports_mev_share, ..self }
+    }
+
+    pub fn with_private_tx(self, supports_private_tx: bool) -> Self {
+        Self { supports_private_tx, ..self }
     }
 
     fn create_flashbots_middleware(provider: P, url: &str) -> FlashbotsMiddleware<P, T> {
@@ -63,11 +103,11 @@ where
     }
 
     #[allow(dead_code)]
-    pub async fn send_bundle(&self, request: &BundleRequest) -> Result<()> {
+    pub async fn send_bundle(&self, request: &BundleRequest) -> Result<Option<BundleHash>> {
         match self.flashbots_middleware.send_bundle(request).await {
-            Ok(_resp) => {
-                info!("Bundle sent to : {}", self.name);
-                Ok(())
+            Ok(bundle_hash) => {
+                info!("Bundle sent to : {} {:?}", self.name, bundle_hash);
+                Ok(bundle_hash)
             }
             Err(error) => match error {
                 FlashbotsMiddlewareError::MissingParameters => {
@@ -86,26 +126,106 @@ where
         }
     }
 
-    pub async fn send_signed_body(&self, body: String, signature: String) -> Result<()> {
+    pub async fn send_signed_body(&self, body: String, signature: String) -> Result<Option<BundleHash>, RelayError> {
         match self.flashbots_middleware.relay().serialized_request::<SendBundleResponseType>(body, Some(signature)).await {
-            Ok(_resp) => {
+            Ok(resp) => {
                 debug!("Bundle sent to : {}", self.name);
-                Ok(())
+                Ok(resp.bundle_hash())
             }
             Err(error) => {
                 error!("{} {}", self.name, error.to_string());
-                Err(eyre!("FLASHBOTS_RELAY_ERROR"))
+                Err(error)
             }
         }
     }
 }
 
+/// Sends the signed body to the relay retrying transport and server errors with backoff. Retry of a bundle is dropped once
+/// a newer one with the same replacement UUID was sent. Relay is muted after consecutive failed submissions.
+#[allow(clippy::too_many_arguments)]
+async fn send_with_retry<P, T>(
+    client: Arc<FlashbotsClient<P, T>>,
+    method: &'static str,
+    block: Option<u64>,
+    body: String,
+    signature: String,
+    replacement: Option<ReplacementTicket>,
+    retry_policy: RetryPolicy,
+    submissions_tx: broadcast::Sender<RelaySubmission>,
+) where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    debug!("Sending {} to {}", method, client.name);
+
+    let mut attempt = 0;
+    let submission = loop {
+        attempt += 1;
+        let start_time = Instant::now();
+        let result = client.send_signed_body(body.clone(), signature.clone()).await;
+        let latency = start_time.elapsed();
+
+        match result {
+            Ok(bundle_hash) => {
+                info!("Flashbots {} sent successfully {}", method, client.name);
+                client.health.record_success();
+                break RelaySubmission {
+                    relay: client.name.clone(),
+                    method: method.to_string(),
+                    block,
+                    bundle_hash,
+                    error: None,
+                    latency,
+                    attempts: attempt,
+                };
+            }
+            Err(e) if attempt < retry_policy.max_attempts && e.is_retryable() => {
+                debug!("Retrying {} to {} attempt {} : {}", method, client.name, attempt, e);
+                tokio::time::sleep(retry_policy.backoff(attempt)).await;
+                if replacement.as_ref().is_some_and(|replacement| replacement.is_superseded()) {
+                    debug!("Retry of {} to {} dropped, newer bundle with the replacement uuid was sent", method, client.name);
+                    break RelaySubmission {
+                        relay: client.name.clone(),
+                        method: method.to_string(),
+                        block,
+                        bundle_hash: None,
+                        error: Some("SUPERSEDED".to_string()),
+                        latency,
+                        attempts: attempt,
+                    };
+                }
+            }
+            Err(e) => {
+                error!("Broadcasting {} error to {} : {}", method, client.name, e);
+                if client.health.record_failure(&retry_policy) {
+                    warn!("Relay {} muted for {:?}", client.name, retry_policy.mute_duration);
+                }
+                break RelaySubmission {
+                    relay: client.name.clone(),
+                    method: method.to_string(),
+                    block,
+                    bundle_hash: None,
+                    error: Some(e.to_string()),
+                    latency,
+                    attempts: attempt,
+                };
+            }
+        }
+    };
+
+    // no receivers is not an error, telemetry is optional
+    let _ = submissions_tx.send(submission);
+}
+
 pub struct Flashbots<P, T> {
     req_id: AtomicU64,
     signer: PrivateKeySigner,
     provider: P,
     simulation_client: FlashbotsClient<P, T>,
     clients: Vec<Arc<FlashbotsClient<P, T>>>,
+    retry_policy: RetryPolicy,
+    replacements: Arc<ReplacementRegistry>,
+    submissions_tx: broadcast::Sender<RelaySubmission>,
     _t: PhantomData<T>,
 }
 
@@ -118,16 +238,73 @@ where
         let signer = signer.unwrap_or(PrivateKeySigner::random());
         let simulation_client = FlashbotsClient::new(provider.clone(), simulation_endpoint);
 
-        Flashbots { req_id: AtomicU64::new(0), signer, provider, clients: vec![], simulation_client, _t: PhantomData }
+        let (submissions_tx, _) = broadcast::channel(1000);
+
+        Flashbots {
+            req_id: AtomicU64::new(0),
+            signer,
+            provider,
+            clients: vec![],
+            simulation_client,
+            retry_policy: RetryPolicy::default(),
+            replacements: Default::default(),
+            submissions_tx,
+            _t: PhantomData,
+        }
+    }
+
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self { retry_policy, ..self }
+    }
+
+    /// Subscribe to relay responses of all submissions
+    pub fn subscribe_submissions(&self) -> broadcast::Receiver<RelaySubmission> {
+        self.submissions_tx.subscribe()
+    }
+
+    /// Relays that are not muted after consecutive failures
+    fn active_clients(&self) -> impl Iterator<Item = &Arc<FlashbotsClient<P, T>>> {
+        self.clients.iter().filter(|client| !client.health.is_muted())
+    }
+
+    fn spawn_send(
+        &self,
+        client: &Arc<FlashbotsClient<P, T>>,
+        method: &'static str,
+        block: Option<u64>,
+        body: String,
+        signature: String,
+        replacement: Option<ReplacementTicket>,
+    ) {
+        tokio::task::spawn(send_with_retry(
+            client.clone(),
+            method,
+            block,
+            body,
+            signature,
+            replacement,
+            self.retry_policy.clone(),
+            self.submissions_tx.clone(),
+        ));
     }
 
     pub fn with_default_relays(self) -> Self {
         let provider = self.provider.clone();
 
-        let flashbots = FlashbotsClient::new(provider.clone(), "https://relay.flashbots.net");
-        let beaverbuild = FlashbotsClient::new(provider.clone(), "https://rpc.beaverbuild.org/");
-        let titan = FlashbotsClient::new(provider.clone(), "https://rpc.titanbuilder.xyz");
-        let rsync = FlashbotsClient::new(provider.clone(), "https://rsync-builder.xyz");
+        let flashbots = FlashbotsClient::new(provider.clone(), "https://relay.flashbots.net")
+            .with_replacement(true)
+            .with_cancel(true)
+            .with_mev_share(true)
+            .with_private_tx(true);
+        let beaverbuild = FlashbotsClient::new(provider.clone(), "https://rpc.beaverbuild.org/")
+            .with_replacement(true)
+            .with_cancel(true)
+            .with_private_tx(true);
+        let titan = FlashbotsClient::new(provider.clone(), "https://rpc.titanbuilder.xyz")
+            .with_replacement(true)
+            .with_cancel(true)
+            .with_private_tx(true);
+        let rsync = FlashbotsClient::new(provider.clone(), "https://rsync-builder.xyz").with_private_tx(true);
         //let builder0x69 = FlashbotsClient::new_no_sign(provider.clone(), "https://builder0x69.io");
         let eden = FlashbotsClient::new(provider.clone(), "https://api.edennetwork.io/v1/bundle");
         let eth_builder = FlashbotsClient::new_no_sign(provider.clone(), "https://eth-builder.com");
@@ -175,11 +352,18 @@ where
         let clients: Vec<Arc<FlashbotsClient<P, T>>> = relays
             .into_iter()
             .map(|relay| {
-                if relay.no_sign.unwrap_or(false) {
-                    Arc::new(FlashbotsClient::new_no_sign(self.provider.clone(), relay.url.as_str()))
+                let client = if relay.no_sign.unwrap_or(false) {
+                    FlashbotsClient::new_no_sign(self.provider.clone(), relay.url.as_str())
                 } else {
-                    Arc::new(FlashbotsClient::new(self.provider.clone(), relay.url.as_str()))
-                }
+                    FlashbotsClient::new(self.provider.clone(), relay.url.as_str())
+                };
+                Arc::new(
+                    client
+                        .with_replacement(relay.supports_replacement.unwrap_or(false))
+                        .with_cancel(relay.supports_cancel.unwrap_or(false))
+                        .with_mev_share(relay.supports_mev_share.unwrap_or(false))
+                        .with_private_tx(relay.supports_private_tx.unwrap_or(false)),
+                )
             })
             .collect();
         Self { clients, ..self }
@@ -207,6 +391,15 @@ where
     }
 
     pub async fn broadcast_txes<TX>(&self, txs: Vec<TX>, block: u64) -> Result<()>
+    where
+        BundleTransaction: From<TX>,
+    {
+        self.broadcast_txes_with_replacement(txs, block, None).await
+    }
+
+    /// Broadcasts the bundle to all relays. Relays supporting replacement receive the bundle with the replacement UUID,
+    /// so it supersedes the bundle previously sent with the same UUID.
+    pub async fn broadcast_txes_with_replacement<TX>(&self, txs: Vec<TX>, block: u64, replacement_uuid: Option<String>) -> Result<()>
     where
         BundleTransaction: From<TX>,
     {
@@ -216,32 +409,87 @@ where
             bundle = bundle.push_transaction(t);
         }
 
-        let next_req_id = self.req_id.load(Ordering::SeqCst) + 1;
-        self.req_id.store(next_req_id, Ordering::SeqCst);
+        let next_req_id = self.next_req_id();
+
+        let replacement_signed_body = match replacement_uuid {
+            Some(replacement_uuid) => {
+                self.replacements.register(&replacement_uuid, next_req_id);
+                let ticket = ReplacementTicket {
+                    registry: self.replacements.clone(),
+                    replacement_uuid: replacement_uuid.clone(),
+                    req_id: next_req_id,
+                };
+                let (body, signature) =
+                    make_signed_body(next_req_id, "eth_sendBundle", bundle.clone().set_replacement_uuid(replacement_uuid), &self.signer)?;
+                Some((body, signature, ticket))
+            }
+            None => None,
+        };
 
         let (body, signature) = make_signed_body(next_req_id, "eth_sendBundle", bundle, &self.signer)?;
 
-        for client in self.clients.iter() {
-            let client_clone = client.clone();
-            let body_clone = body.clone();
-            let signature_clone = signature.clone();
-
-            tokio::task::spawn(async move {
-                debug!("Sending bundle to {}", client_clone.name);
-                let bundle_result = client_clone.send_signed_body(body_clone, signature_clone).await;
-                match bundle_result {
-                    Ok(_) => {
-                        info!("Flashbots bundle broadcast successfully {}", client_clone.name);
-                    }
-                    Err(x) => {
-                        error!("Broadcasting error to {} : {}", client_clone.name, x.to_string());
-                    }
+        for client in self.active_clients() {
+            let (body, signature, replacement) = match &replacement_signed_body {
+                Some((replacement_body, replacement_signature, ticket)) if client.supports_replacement => {
+                    (replacement_body.clone(), replacement_signature.clone(), Some(ticket.clone()))
                 }
-            });
+                _ => (body.clone(), signature.clone(), None),
+            };
+            self.spawn_send(client, "eth_sendBundle", Some(block), body, signature, replacement);
+        }
+
+        Ok(())
+    }
+
+    /// Cancels bundles sent with the replacement UUID on all relays supporting cancellation.
+    pub async fn cancel_bundle(&self, replacement_uuid: String) -> Result<()> {
+        let next_req_id = self.next_req_id();
+        // pending retries of the cancelled bundle are dropped
+        self.replacements.register(&replacement_uuid, next_req_id);
+
+        let request = CancelBundleRequest::new(replacement_uuid);
+
+        let (body, signature) = make_signed_body(next_req_id, "eth_cancelBundle", request, &self.signer)?;
+
+        for client in self.active_clients().filter(|client| client.supports_cancel) {
+            self.spawn_send(client, "eth_cancelBundle", None, body.clone(), signature.clone(), None);
         }
 
         Ok(())
     }
+
+    /// Sends the bundle with `mev_sendBundle` to all relays supporting MEV-Share.
+    pub async fn send_mev_bundle(&self, request: MevSendBundleRequest) -> Result<()> {
+        let block = request.block().to::<u64>();
+        let next_req_id = self.next_req_id();
+
+        let (body, signature) = make_signed_body(next_req_id, "mev_sendBundle", request, &self.signer)?;
+
+        for client in self.active_clients().filter(|client| client.supports_mev_share) {
+            self.spawn_send(client, "mev_sendBundle", Some(block), body.clone(), signature.clone(), None);
+        }
+
+        Ok(())
+    }
+
+    /// Sends the signed transaction with `eth_sendPrivateTransaction` to all relays supporting private transactions.
+    /// Transaction is valid until `max_block`.
+    pub async fn send_private_transaction(&self, tx: Bytes, max_block: u64) -> Result<()> {
+        let request = PrivateTransactionRequest::new(tx).set_max_block_number(U64::from(max_block));
+        let next_req_id = self.next_req_id();
+
+        let (body, signature) = make_signed_body(next_req_id, "eth_sendPrivateTransaction", request, &self.signer)?;
+
+        for client in self.active_clients().filter(|client| client.supports_private_tx) {
+            self.spawn_send(client, "eth_sendPrivateTransaction", Some(max_block), body.clone(), signature.clone(), None);
+        }
+
+        Ok(())
+    }
+
+    fn next_req_id(&self) -> u64 {
+        self.req_id.fetch_add(1, Ordering::SeqCst) + 1
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,6 @@
 pub use flashbots::{Flashbots, FlashbotsClient};
+pub use submission::{RelayHealth, RelaySubmission, ReplacementRegistry, ReplacementTicket, RetryPolicy};
 
 pub mod client;
 mod flashbots;
+mod submission;
@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::client::BundleHash;
+
+/// Relay response to a bundle submission.
+#[derive(Clone, Debug)]
+pub struct RelaySubmission {
+    pub relay: String,
+    pub method: String,
+    pub block: Option<u64>,
+    pub bundle_hash: Option<BundleHash>,
+    pub error: Option<String>,
+    /// Latency of the last attempt
+    pub latency: Duration,
+    pub attempts: u32,
+}
+
+impl RelaySubmission {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Retry and mute policy for relay submissions.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts of one submission
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for every next one
+    pub initial_backoff: Duration,
+    /// Relay is muted after this number of consecutive failed submissions
+    pub mute_after_failures: u32,
+    /// Time the relay stays muted
+    pub mute_duration: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(50),
+            mute_after_failures: 10,
+            mute_duration: Duration::from_secs(300),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_attempts(self, max_attempts: u32) -> Self {
+        Self { max_attempts, ..self }
+    }
+
+    pub fn with_initial_backoff(self, initial_backoff: Duration) -> Self {
+        Self { initial_backoff, ..self }
+    }
+
+    pub fn with_mute_after_failures(self, mute_after_failures: u32) -> Self {
+        Self { mute_after_failures, ..self }
+    }
+
+    pub fn with_mute_duration(self, mute_duration: Duration) -> Self {
+        Self { mute_duration, ..self }
+    }
+
+    /// Delay before the retry following the attempt, attempts are counted from 1.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff * 2u32.saturating_pow(attempt.saturating_sub(1))
+    }
+}
+
+/// Consecutive failures and mute state of a relay.
+#[derive(Debug, Default)]
+pub struct RelayHealth {
+    consecutive_failures: AtomicU32,
+    muted_until: RwLock<Option<Instant>>,
+}
+
+impl RelayHealth {
+    pub fn is_muted(&self) -> bool {
+        self.muted_until.read().map(|muted_until| muted_until.is_some_and(|t| Instant::now() < t)).unwrap_or(false)
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures.load(Ordering::SeqCst)
+    }
+
+    pub fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::SeqCst);
+    }
+
+    /// Records failed submission, returns true if the relay got muted.
+    pub fn record_failure(&self, policy: &RetryPolicy) -> bool {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1;
+        if failures >= policy.mute_after_failures {
+            self.consecutive_failures.store(0, Ordering::SeqCst);
+            if let Ok(mut muted_until) = self.muted_until.write() {
+                *muted_until = Some(Instant::now() + policy.mute_duration);
+            }
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Latest request id sent with each replacement UUID.
+#[derive(Debug, Default)]
+pub struct ReplacementRegistry {
+    latest: Mutex<HashMap<String, u64>>,
+}
+
+impl ReplacementRegistry {
+    /// UUIDs are tracked for this number of later requests
+    const TRACKED_REQUESTS: u64 = 10000;
+
+    /// Records the request sent or cancelled with the UUID, previous requests with it are superseded.
+    pub fn register(&self, replacement_uuid: &str, req_id: u64) {
+        if let Ok(mut latest) = self.latest.lock() {
+            latest.insert(replacement_uuid.to_string(), req_id);
+            if latest.len() as u64 > Self::TRACKED_REQUESTS {
+                latest.retain(|_, id| *id + Self::TRACKED_REQUESTS > req_id);
+            }
+        }
+    }
+
+    pub fn is_superseded(&self, replacement_uuid: &str, req_id: u64) -> bool {
+        self.latest.lock().map(|latest| latest.get(replacement_uuid).is_some_and(|id| *id > req_id)).unwrap_or(false)
+    }
+}
+
+/// Request sent with the replacement UUID. Its retries are dropped once a newer request with the UUID was sent.
+#[derive(Clone, Debug)]
+pub struct ReplacementTicket {
+    pub registry: Arc<ReplacementRegistry>,
+    pub replacement_uuid: String,
+    pub req_id: u64,
+}
+
+impl ReplacementTicket {
+    pub fn is_superseded(&self) -> bool {
+        self.registry.is_superseded(&self.replacement_uuid, self.req_id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default().with_initial_backoff(Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn test_relay_mute() {
+        let policy = RetryPolicy::default().with_mute_after_failures(2).with_mute_duration(Duration::from_secs(60));
+        let health = RelayHealth::default();
+
+        assert!(!health.record_failure(&policy));
+        health.record_success();
+        assert!(!health.record_failure(&policy));
+        assert!(!health.is_muted());
+        assert!(health.record_failure(&policy));
+        assert!(health.is_muted());
+        assert_eq!(health.consecutive_failures(), 0);
+
+        let policy = policy.with_mute_after_failures(1).with_mute_duration(Duration::ZERO);
+        health.record_failure(&policy);
+        assert!(!health.is_muted());
+    }
+
+    #[test]
+    fn test_replacement_superseded() {
+        let registry = Arc::new(ReplacementRegistry::default());
+        registry.register("a", 1);
+        let ticket = ReplacementTicket { registry: registry.clone(), replacement_uuid: "a".to_string(), req_id: 1 };
+        let other = ReplacementTicket { registry: registry.clone(), replacement_uuid: "b".to_string(), req_id: 2 };
+        registry.register("b", 2);
+
+        assert!(!ticket.is_superseded());
+        assert!(!other.is_superseded());
+
+        registry.register("a", 3);
+        assert!(ticket.is_superseded());
+        assert!(!other.is_superseded());
+    }
+}
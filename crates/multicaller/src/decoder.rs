@@ -0,0 +1,352 @@
+use std::fmt;
+
+use alloy_primitives::{Address, Bytes, U256};
+use alloy_sol_types::SolInterface;
+use eyre::{eyre, Result};
+
+use defi_abi::aave3::IAaveV3Pool::IAaveV3PoolCalls;
+use defi_abi::balancer::IVault::IVaultCalls;
+use defi_abi::curve::ICurveI128_2::ICurveI128_2Calls;
+use defi_abi::curve::ICurveI128_2_To::ICurveI128_2_ToCalls;
+use defi_abi::curve::ICurveI128_2_To_Meta::ICurveI128_2_To_MetaCalls;
+use defi_abi::curve::ICurveU256_2::ICurveU256_2Calls;
+use defi_abi::curve::ICurveU256_2_Eth_To::ICurveU256_2_Eth_ToCalls;
+use defi_abi::curve::ICurveU256_2_To::ICurveU256_2_ToCalls;
+use defi_abi::curve::ICurveU256_3_Eth::ICurveU256_3_EthCalls;
+use defi_abi::lido::IStEth::IStEthCalls;
+use defi_abi::lido::IWStEth::IWStEthCalls;
+use defi_abi::maker::IDssFlash::IDssFlashCalls;
+use defi_abi::maverick::IMaverickPool::IMaverickPoolCalls;
+use defi_abi::multicaller::IMultiCaller::IMultiCallerCalls;
+use defi_abi::uniswap2::IUniswapV2Pair::IUniswapV2PairCalls;
+use defi_abi::uniswap3::IUniswapV3Pool::IUniswapV3PoolCalls;
+use defi_abi::uniswap4::IUniswapV4PoolManager::IUniswapV4PoolManagerCalls;
+use defi_abi::IERC20::IERC20Calls;
+use defi_abi::IWETH::IWETHCalls;
+use defi_types::{CallType, MulticallerCall, MulticallerCalls};
+
+const HEADER_LEN: usize = 12;
+
+/// Stack reference of the call, the stack value is written to the call data or the return data is saved to the stack.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StackRef {
+    pub is_relative: bool,
+    pub stack_offset: u32,
+    pub data_offset: u32,
+    pub data_len: u32,
+}
+
+impl StackRef {
+    fn decode(value: u32) -> Option<Self> {
+        if value == 0 {
+            return None;
+        }
+        Some(Self {
+            is_relative: value & 0x800000 != 0,
+            stack_offset: (value >> 20) & 0x7,
+            data_len: (value >> 12) & 0xFF,
+            data_offset: value & 0xFFF,
+        })
+    }
+
+    fn data_range(&self, shift: u32) -> String {
+        let start = self.data_offset.saturating_sub(shift);
+        format!("[{:#x}..{:#x}]", start, start + self.data_len)
+    }
+}
+
+impl fmt::Display for StackRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_relative {
+            write!(f, "stack[rel {}]", self.stack_offset)
+        } else {
+            write!(f, "stack[{}]", self.stack_offset)
+        }
+    }
+}
+
+/// Multicaller call decoded from the call data.
+#[derive(Clone, Debug)]
+pub struct DecodedCall {
+    pub call: MulticallerCall,
+    /// Known ABI function of the call data
+    pub function: Option<String>,
+    /// Calls packed to the bytes argument of the call: doCalls, flash swap and flash loan callbacks
+    pub inner_calls: Vec<DecodedCall>,
+}
+
+impl DecodedCall {
+    /// Stack value written to the call data before the call.
+    pub fn call_stack(&self) -> Option<StackRef> {
+        StackRef::decode(self.call.call_stack)
+    }
+
+    /// Return data saved to the stack after the call.
+    pub fn return_stack(&self) -> Option<StackRef> {
+        StackRef::decode(self.call.return_stack)
+    }
+
+    fn write_tree(&self, f: &mut impl fmt::Write, idx: usize, depth: usize) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        write!(f, "{indent}{idx}: {:?}", self.call.call_type)?;
+        if !matches!(self.call.call_type, CallType::InternalCall | CallType::CalculationCall) {
+            write!(f, " {}", self.call.to)?;
+        }
+        if let Some(value) = self.call.value {
+            write!(f, " value={value}")?;
+        }
+        match &self.function {
+            Some(function) => writeln!(f, " {function}")?,
+            None => writeln!(f, " {}", self.call.call_data)?,
+        }
+
+        // call data offsets are stored with the selector header length added
+        let shift = match self.call.call_type {
+            CallType::InternalCall | CallType::CalculationCall => 0xC,
+            _ => 0x20,
+        };
+        if let Some(stack_ref) = self.call_stack() {
+            writeln!(f, "{indent}  call_data{} <- {stack_ref}", stack_ref.data_range(shift))?;
+        }
+        if let Some(stack_ref) = self.return_stack() {
+            writeln!(f, "{indent}  return_data{} -> {stack_ref}", stack_ref.data_range(0))?;
+        }
+
+        for (inner_idx, inner_call) in self.inner_calls.iter().enumerate() {
+            inner_call.write_tree(f, inner_idx, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DecodedCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_tree(f, 0, 0)
+    }
+}
+
+macro_rules! decode_function {
+    ($data:expr, $($name:literal => $calls:ty),+ $(,)?) => {
+        $(
+            if let Ok(call) = <$calls>::abi_decode($data, false) {
+                return Some(format!("{}::{:?}", $name, call));
+            }
+        )+
+    };
+}
+
+/// Decoder of the multicaller call data for debugging.
+pub struct MulticallerDecoder;
+
+impl MulticallerDecoder {
+    /// Inverse of the packed calls encoding, the data must contain whole calls only.
+    pub fn unpack_do_calls_data(data: &[u8]) -> Result<MulticallerCalls> {
+        let mut calls = MulticallerCalls::new();
+        let mut offset = 0;
+
+        while offset < data.len() {
+            let header_bytes = data.get(offset..offset + HEADER_LEN).ok_or_else(|| eyre!("CALL_HEADER_OUT_OF_BOUNDS"))?;
+            let mut buf = [0u8; 16];
+            buf[16 - HEADER_LEN..].copy_from_slice(header_bytes);
+            let header = u128::from_be_bytes(buf);
+            offset += HEADER_LEN;
+
+            let data_len = (header & 0xFFFF) as usize;
+            let call_stack = ((header >> 16) & 0xFFFFFF) as u32;
+            let return_stack = ((header >> 40) & 0xFFFFFF) as u32;
+
+            let (call_type, value, call_stack, return_stack) = if header & (1 << 95) != 0 {
+                (CallType::Call, Some(U256::from((header >> 16) & ((1 << 79) - 1))), 0, 0)
+            } else {
+                match header >> 80 {
+                    0x7FFA => (CallType::Call, Some(U256::ZERO), call_stack, return_stack),
+                    0x7FFB => (CallType::CalculationCall, None, call_stack, return_stack),
+                    0x7FFC => (CallType::Call, None, call_stack, return_stack),
+                    0x7FFD => (CallType::InternalCall, None, call_stack, return_stack),
+                    0x7FFE => (CallType::StaticCall, None, call_stack, return_stack),
+                    0x7FFF => (CallType::DelegateCall, None, call_stack, return_stack),
+                    selector => return Err(eyre!("UNKNOWN_CALL_SELECTOR {:#x}", selector)),
+                }
+            };
+
+            let to = match call_type {
+                CallType::InternalCall | CallType::CalculationCall => Address::ZERO,
+                _ => {
+                    let address_bytes = data.get(offset..offset + 20).ok_or_else(|| eyre!("CALL_ADDRESS_OUT_OF_BOUNDS"))?;
+                    offset += 20;
+                    Address::from_slice(address_bytes)
+                }
+            };
+
+            let call_data = data.get(offset..offset + data_len).ok_or_else(|| eyre!("CALL_DATA_OUT_OF_BOUNDS"))?;
+            offset += data_len;
+
+            calls.add(MulticallerCall { call_type, call_data: Bytes::copy_from_slice(call_data), to, value, call_stack, return_stack });
+        }
+
+        Ok(calls)
+    }
+
+    /// Decodes doCalls call data or the packed calls to the tree of calls.
+    pub fn decode(data: &[u8]) -> Result<Vec<DecodedCall>> {
+        let calls = match IMultiCallerCalls::abi_decode(data, false) {
+            Ok(IMultiCallerCalls::doCalls(do_calls)) => Self::unpack_do_calls_data(&do_calls.data)?,
+            _ => Self::unpack_do_calls_data(data)?,
+        };
+
+        Ok(calls.opcodes_vec.into_iter().map(Self::decode_call).collect())
+    }
+
+    /// Pretty printed tree of the calls, see [`MulticallerDecoder::decode`].
+    pub fn to_pretty_string(data: &[u8]) -> Result<String> {
+        let mut ret = String::new();
+        for (idx, call) in Self::decode(data)?.iter().enumerate() {
+            call.write_tree(&mut ret, idx, 0)?;
+        }
+        Ok(ret)
+    }
+
+    fn decode_call(call: MulticallerCall) -> DecodedCall {
+        let function = Self::decode_function(&call.call_data);
+        // bytes arguments are not always packed calls, they are left as is
+        let inner_calls = Self::inner_call_data(&call.call_data).and_then(|data| Self::decode(&data).ok()).unwrap_or_default();
+        DecodedCall { call, function, inner_calls }
+    }
+
+    fn decode_function(data: &[u8]) -> Option<String> {
+        decode_function!(data,
+            "IMultiCaller" => IMultiCallerCalls,
+            "IERC20" => IERC20Calls,
+            "IWETH" => IWETHCalls,
+            "IUniswapV2Pair" => IUniswapV2PairCalls,
+            "IUniswapV3Pool" => IUniswapV3PoolCalls,
+            "IUniswapV4PoolManager" => IUniswapV4PoolManagerCalls,
+            "IMaverickPool" => IMaverickPoolCalls,
+            "IVault" => IVaultCalls,
+            "IAaveV3Pool" => IAaveV3PoolCalls,
+            "IDssFlash" => IDssFlashCalls,
+            "IStEth" => IStEthCalls,
+            "IWStEth" => IWStEthCalls,
+            "ICurveI128_2" => ICurveI128_2Calls,
+            "ICurveI128_2_To" => ICurveI128_2_ToCalls,
+            "ICurveI128_2_To_Meta" => ICurveI128_2_To_MetaCalls,
+            "ICurveU256_2" => ICurveU256_2Calls,
+            "ICurveU256_2_To" => ICurveU256_2_ToCalls,
+            "ICurveU256_3_Eth" => ICurveU256_3_EthCalls,
+            "ICurveU256_2_Eth_To" => ICurveU256_2_Eth_ToCalls,
+        );
+        None
+    }
+
+    fn inner_call_data(data: &[u8]) -> Option<Bytes> {
+        if let Ok(IMultiCallerCalls::doCalls(call)) = IMultiCallerCalls::abi_decode(data, false) {
+            return Some(call.data);
+        }
+        if let Ok(IUniswapV2PairCalls::swap(call)) = IUniswapV2PairCalls::abi_decode(data, false) {
+            return Some(call.data);
+        }
+        if let Ok(IUniswapV3PoolCalls::swap(call)) = IUniswapV3PoolCalls::abi_decode(data, false) {
+            return Some(call.data);
+        }
+        if let Ok(IUniswapV3PoolCalls::flash(call)) = IUniswapV3PoolCalls::abi_decode(data, false) {
+            return Some(call.data);
+        }
+        if let Ok(IMaverickPoolCalls::swap(call)) = IMaverickPoolCalls::abi_decode(data, false) {
+            return Some(call.data);
+        }
+        if let Ok(IVaultCalls::flashLoan(call)) = IVaultCalls::abi_decode(data, false) {
+            return Some(call.userData);
+        }
+        if let Ok(IAaveV3PoolCalls::flashLoanSimple(call)) = IAaveV3PoolCalls::abi_decode(data, false) {
+            return Some(call.params);
+        }
+        if let Ok(IDssFlashCalls::flashLoan(call)) = IDssFlashCalls::abi_decode(data, false) {
+            return Some(call.data);
+        }
+        if let Ok(IUniswapV4PoolManagerCalls::unlock(call)) = IUniswapV4PoolManagerCalls::abi_decode(data, false) {
+            return Some(call.data);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloy_sol_types::SolCall;
+
+    use defi_abi::uniswap2::IUniswapV2Pair;
+    use defi_abi::IERC20;
+
+    use super::*;
+    use crate::opcodes_encoder::{OpcodesEncoder, OpcodesEncoderV2};
+
+    #[test]
+    fn test_unpack_round_trip() -> Result<()> {
+        let to = Address::repeat_byte(1);
+        let data = Bytes::from(vec![0x11, 0x22, 0x33, 0x44]);
+
+        let mut calls = MulticallerCalls::new();
+        calls.add(MulticallerCall::new_call_with_value(to, &data, U256::from(1_000_000_000u64)));
+        calls.add(MulticallerCall::new_call_with_value(to, &data, U256::ZERO));
+        let mut call = MulticallerCall::new_call(to, &data);
+        call.set_call_stack(true, 1, 0x4, 0x20).set_return_stack(false, 2, 0x20, 0x20);
+        calls.add(call);
+        let mut call = MulticallerCall::new_internal_call(&data);
+        call.set_call_stack(false, 0, 0x24, 0x20);
+        calls.add(call);
+        calls.add(MulticallerCall::new_static_call(to, &Bytes::new()));
+        calls.add(MulticallerCall::new_delegate_call(to, &data));
+        calls.add(MulticallerCall::new_calculation_call(&data));
+
+        let decoded = MulticallerDecoder::unpack_do_calls_data(&OpcodesEncoderV2::pack_do_calls_data(&calls)?)?;
+        assert_eq!(decoded, calls);
+
+        let decoded = MulticallerDecoder::decode(&OpcodesEncoderV2::pack_do_calls(&calls)?)?;
+        assert_eq!(decoded.len(), 7);
+        assert_eq!(decoded[2].call_stack(), Some(StackRef { is_relative: true, stack_offset: 1, data_offset: 0x24, data_len: 0x20 }));
+        assert_eq!(decoded[2].return_stack(), Some(StackRef { is_relative: false, stack_offset: 2, data_offset: 0x20, data_len: 0x20 }));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unpack_errors() {
+        assert!(MulticallerDecoder::unpack_do_calls_data(&[0u8; 12]).is_err());
+        assert!(MulticallerDecoder::unpack_do_calls_data(&[0u8; 5]).is_err());
+
+        let calls = MulticallerCalls { opcodes_vec: vec![MulticallerCall::new_call(Address::ZERO, &Bytes::from(vec![1u8; 8]))] };
+        let packed = OpcodesEncoderV2::pack_do_calls_data(&calls).unwrap();
+        assert!(MulticallerDecoder::unpack_do_calls_data(&packed[..packed.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_decode_nested_calls() -> Result<()> {
+        let token = Address::repeat_byte(1);
+        let pool = Address::repeat_byte(2);
+        let multicaller = Address::repeat_byte(3);
+
+        let mut inner_calls = MulticallerCalls::new();
+        inner_calls.add(MulticallerCall::new_call(token, &IERC20::transferCall { to: pool, amount: U256::from(100) }.abi_encode().into()));
+
+        let swap_call_data = IUniswapV2Pair::swapCall {
+            amount0Out: U256::ZERO,
+            amount1Out: U256::from(90),
+            to: multicaller,
+            data: OpcodesEncoderV2::pack_do_calls_data(&inner_calls)?,
+        }
+        .abi_encode();
+
+        let mut calls = MulticallerCalls::new();
+        calls.add(MulticallerCall::new_call(pool, &swap_call_data.into()));
+
+        let decoded = MulticallerDecoder::decode(&OpcodesEncoderV2::pack_do_calls(&calls)?)?;
+        assert_eq!(decoded.len(), 1);
+        assert!(decoded[0].function.as_ref().unwrap().starts_with("IUniswapV2Pair::swap"));
+        assert_eq!(decoded[0].inner_calls.len(), 1);
+        assert!(decoded[0].inner_calls[0].function.as_ref().unwrap().starts_with("IERC20::transfer"));
+        assert_eq!(decoded[0].inner_calls[0].call.to, token);
+
+        let pretty = MulticallerDecoder::to_pretty_string(&OpcodesEncoderV2::pack_do_calls(&calls)?)?;
+        assert_eq!(pretty.lines().count(), 2);
+        Ok(())
+    }
+}
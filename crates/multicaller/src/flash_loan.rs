@@ -0,0 +1,141 @@
+use alloy_primitives::{Address, U256};
+
+use defi_address_book::{AaveV3Address, BalancerAddress, MakerAddress, TokenAddress};
+use defi_entities::Token;
+
+const FEE_DENOMINATOR: u64 = 1_000_000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FlashLoanProvider {
+    Balancer,
+    AaveV3,
+    Maker,
+    /// Uniswap V3 pool flash, the pool must not be used by the swap
+    UniswapV3,
+}
+
+/// External flash loan source used when the first pool of the swap cannot flash swap.
+#[derive(Clone, Debug)]
+pub struct FlashLoanSource {
+    pub provider: FlashLoanProvider,
+    /// Lender contract or the pool
+    pub address: Address,
+    /// Tokens that can be borrowed, any token if empty. Uniswap V3 source keeps token0 and token1
+    pub tokens: Vec<Address>,
+    /// Fee in hundredths of a bip
+    pub fee: u32,
+    /// Gas used on top of the swap
+    pub gas: u64,
+}
+
+impl FlashLoanSource {
+    /// Balancer Vault flash loan without fee, limited to the tokens with deep Vault balances on mainnet by default.
+    pub fn balancer() -> Self {
+        Self {
+            provider: FlashLoanProvider::Balancer,
+            address: BalancerAddress::VAULT,
+            tokens: vec![TokenAddress::WETH, TokenAddress::USDC, TokenAddress::USDT, TokenAddress::DAI, TokenAddress::WBTC],
+            fee: 0,
+            gas: 60_000,
+        }
+    }
+
+    /// Aave V3 flashLoanSimple of the mainnet Pool with 0.05% premium, the reserves are limited to major tokens by default.
+    pub fn aave_v3() -> Self {
+        Self {
+            provider: FlashLoanProvider::AaveV3,
+            address: AaveV3Address::POOL,
+            tokens: vec![TokenAddress::WETH, TokenAddress::USDC, TokenAddress::USDT, TokenAddress::DAI, TokenAddress::WBTC],
+            fee: 500,
+            gas: 90_000,
+        }
+    }
+
+    /// Maker DSS flash mint of DAI.
+    pub fn maker() -> Self {
+        Self { provider: FlashLoanProvider::Maker, address: MakerAddress::DSS_FLASH, tokens: vec![TokenAddress::DAI], fee: 0, gas: 110_000 }
+    }
+
+    /// Default sources of the chain, only mainnet lenders are known. Sources of other chains are set with
+    /// `with_flash_loan_sources` of the encoder.
+    pub fn chain_defaults(chain_id: u64) -> Vec<Self> {
+        match chain_id {
+            1 => vec![Self::balancer(), Self::aave_v3(), Self::maker()],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn uniswap_v3(pool: Address, token0: Address, token1: Address, fee: u32) -> Self {
+        Self { provider: FlashLoanProvider::UniswapV3, address: pool, tokens: vec![token0, token1], fee, gas: 50_000 }
+    }
+
+    pub fn with_tokens(self, tokens: Vec<Address>) -> Self {
+        Self { tokens, ..self }
+    }
+
+    pub fn with_fee(self, fee: u32) -> Self {
+        Self { fee, ..self }
+    }
+
+    pub fn with_gas(self, gas: u64) -> Self {
+        Self { gas, ..self }
+    }
+
+    pub fn can_borrow(&self, token: Address) -> bool {
+        self.tokens.is_empty() || self.tokens.contains(&token)
+    }
+
+    /// Fee amount in the borrowed token, rounded the way the lender does.
+    pub fn fee_amount(&self, amount: U256) -> U256 {
+        let fee = U256::from(self.fee);
+        let denominator = U256::from(FEE_DENOMINATOR);
+        match self.provider {
+            FlashLoanProvider::AaveV3 => (amount * fee + denominator / U256::from(2)) / denominator,
+            _ => (amount * fee).div_ceil(denominator),
+        }
+    }
+
+    /// Fee and gas cost in wei, None if the fee cannot be priced in ETH.
+    pub fn cost(&self, token: &Token, amount: U256, gas_price: u128) -> Option<U256> {
+        let fee_amount = self.fee_amount(amount);
+        let fee_eth = if fee_amount.is_zero() { U256::ZERO } else { token.calc_eth_value(fee_amount)? };
+        Some(fee_eth + U256::from(self.gas) * U256::from(gas_price))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fee_amount() {
+        let amount = U256::from(1_000_001u64);
+        assert_eq!(FlashLoanSource::aave_v3().fee_amount(amount), U256::from(500));
+        assert_eq!(FlashLoanSource::aave_v3().fee_amount(U256::from(1_000_999u64)), U256::from(500));
+        assert_eq!(FlashLoanSource::aave_v3().fee_amount(U256::from(1_001_000u64)), U256::from(501));
+        assert_eq!(FlashLoanSource::uniswap_v3(Address::ZERO, Address::ZERO, Address::ZERO, 3000).fee_amount(amount), U256::from(3001));
+        assert_eq!(FlashLoanSource::balancer().fee_amount(amount), U256::ZERO);
+    }
+
+    #[test]
+    fn test_cost() {
+        let token = Token::new(TokenAddress::DAI);
+        let amount = U256::from(1_000_000u64);
+
+        assert_eq!(FlashLoanSource::maker().cost(&token, amount, 10), Some(U256::from(1_100_000)));
+        assert!(FlashLoanSource::maker().can_borrow(TokenAddress::DAI));
+        assert!(!FlashLoanSource::maker().can_borrow(TokenAddress::WETH));
+
+        // the fee cannot be priced without the token price
+        assert_eq!(FlashLoanSource::aave_v3().cost(&token, amount, 10), None);
+        token.set_eth_price(Some(U256::from(10).pow(U256::from(18))));
+        assert_eq!(FlashLoanSource::aave_v3().cost(&token, amount, 10), Some(U256::from(500 + 900_000)));
+    }
+
+    #[test]
+    fn test_chain_defaults() {
+        assert!(!FlashLoanSource::balancer().can_borrow(Address::repeat_byte(2)));
+        assert_eq!(FlashLoanSource::chain_defaults(1).len(), 3);
+        assert!(FlashLoanSource::chain_defaults(8453).is_empty());
+    }
+}
@@ -1,18 +1,16 @@
 use alloy_primitives::{Address, Bytes, U256};
 use alloy_sol_types::SolInterface;
 
+use defi_abi::aave3::IAaveV3Pool;
 use defi_abi::balancer::IVault;
 use defi_abi::lido::{IStEth, IWStEth};
+use defi_abi::maker::IDssFlash;
+use defi_abi::uniswap3::IUniswapV3Pool;
 use defi_abi::{IMultiCaller, IERC20, IWETH};
-use defi_address_book::TokenAddress;
 
 pub struct EncoderHelper;
 
 impl EncoderHelper {
-    pub fn is_weth(address: Address) -> bool {
-        address == TokenAddress::WETH
-    }
-
     pub fn encode_weth_deposit() -> Bytes {
         IWETH::IWETHCalls::deposit(IWETH::depositCall {}).abi_encode().into()
     }
@@ -96,6 +94,42 @@ impl EncoderHelper {
         Bytes::from(call.abi_encode())
     }
 
+    pub fn encode_aave3_flash_loan_simple(asset: Address, amount: U256, params: Bytes, receiver: Address) -> Bytes {
+        let call = IAaveV3Pool::IAaveV3PoolCalls::flashLoanSimple(IAaveV3Pool::flashLoanSimpleCall {
+            receiverAddress: receiver,
+            asset,
+            amount,
+            params,
+            referralCode: 0,
+        });
+
+        Bytes::from(call.abi_encode())
+    }
+
+    pub fn encode_maker_flash_loan(token: Address, amount: U256, data: Bytes, receiver: Address) -> Bytes {
+        let call = IDssFlash::IDssFlashCalls::flashLoan(IDssFlash::flashLoanCall { receiver, token, amount, data });
+
+        Bytes::from(call.abi_encode())
+    }
+
+    pub fn encode_uniswap3_flash(amount0: U256, amount1: U256, data: Bytes, recipient: Address) -> Bytes {
+        let call = IUniswapV3Pool::IUniswapV3PoolCalls::flash(IUniswapV3Pool::flashCall { recipient, amount0, amount1, data });
+
+        Bytes::from(call.abi_encode())
+    }
+
+    pub fn encode_aave3_liquidation_call(collateral_asset: Address, debt_asset: Address, user: Address, debt_to_cover: U256) -> Bytes {
+        let call = IAaveV3Pool::IAaveV3PoolCalls::liquidationCall(IAaveV3Pool::liquidationCallCall {
+            collateralAsset: collateral_asset,
+            debtAsset: debt_asset,
+            user,
+            debtToCover: debt_to_cover,
+            receiveAToken: false,
+        });
+
+        Bytes::from(call.abi_encode())
+    }
+
     pub fn encode_wsteth_wrap(st_eth_amount: U256) -> Bytes {
         let call = IWStEth::IWStEthCalls::wrap(IWStEth::wrapCall { stETHAmount: st_eth_amount });
 
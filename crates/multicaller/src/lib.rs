@@ -1,15 +1,21 @@
 #![allow(dead_code)]
+pub use decoder::{DecodedCall, MulticallerDecoder, StackRef};
 pub use deploy::{MulticallerDeployer, DEFAULT_VIRTUAL_ADDRESS};
+pub use flash_loan::{FlashLoanProvider, FlashLoanSource};
 pub use helpers::EncoderHelper;
 pub use multicaller_encoder::MulticallerEncoder;
 pub use multicaller_encoder::MulticallerSwapEncoder;
 pub use swapline_encoder::SwapLineEncoder;
 pub use swapstep_encoder::SwapStepEncoder;
 
+mod decoder;
 mod deploy;
+mod flash_loan;
 mod helpers;
 mod multicaller_encoder;
 mod opcodes_encoder;
+#[cfg(test)]
+mod pool_encoders_test;
 pub mod poolencoders;
 mod swap_encoder;
 mod swapline_encoder;
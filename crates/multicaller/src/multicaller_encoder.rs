@@ -5,7 +5,7 @@ use tracing::error;
 use defi_entities::Swap;
 use defi_types::MulticallerCalls;
 
-use crate::SwapStepEncoder;
+use crate::{FlashLoanSource, SwapStepEncoder};
 
 pub trait MulticallerEncoder {
     fn encode_calls(&self, calls: MulticallerCalls) -> Result<(Address, Bytes)>;
@@ -24,6 +24,14 @@ impl MulticallerSwapEncoder {
         Self { multicaller_address, swap_step_encoder: SwapStepEncoder::new(multicaller_address) }
     }
 
+    pub fn with_flash_loan_sources(self, flash_loan_sources: Vec<FlashLoanSource>) -> Self {
+        Self { swap_step_encoder: self.swap_step_encoder.with_flash_loan_sources(flash_loan_sources), ..self }
+    }
+
+    pub fn with_wrapped_native_token(self, wrapped_native_token: Address) -> Self {
+        Self { swap_step_encoder: self.swap_step_encoder.with_wrapped_native_token(wrapped_native_token), ..self }
+    }
+
     pub fn get_contract_address(&self) -> Address {
         self.multicaller_address
     }
@@ -42,9 +50,9 @@ impl MulticallerEncoder for MulticallerSwapEncoder {
         match swap {
             Swap::BackrunSwapLine(swap_line) => {
                 let (swap_step_0, swap_step_1) = swap_line.to_swap_steps(self.multicaller_address).ok_or_eyre("SWAP_TYPE_NOT_COVERED")?;
-                self.swap_step_encoder.encode_swap_steps(&swap_step_0, &swap_step_1)
+                self.swap_step_encoder.encode_swap_steps(&swap_step_0, &swap_step_1, None)
             }
-            Swap::BackrunSwapSteps((swap_step_0, swap_step_1)) => self.swap_step_encoder.encode_swap_steps(swap_step_0, swap_step_1),
+            Swap::BackrunSwapSteps((swap_step_0, swap_step_1)) => self.swap_step_encoder.encode_swap_steps(swap_step_0, swap_step_1, None),
             Swap::Multiple(swap_vec) => {
                 if swap_vec.len() == 1 {
                     self.make_calls(&swap_vec[0])
@@ -56,6 +64,7 @@ impl MulticallerEncoder for MulticallerSwapEncoder {
                     Ok(multicaller_calls)
                 }
             }
+            Swap::Liquidation(liquidation) => self.swap_step_encoder.encode_liquidation(liquidation, None),
             _ => {
                 error!("Swap type not supported");
                 Err(eyre!("SWAP_TYPE_NOT_SUPPORTED"))
@@ -0,0 +1,690 @@
+//! Executes a swap encoded through every pool encoder in revm and checks the received amount against the pool model.
+//! Flash loans of every provider are executed the same way and checked to be repaid with the fee.
+//! Pool state is replayed from fixtures, `record_pool_encoder_fixtures` records them from a forked node.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use alloy_json_rpc::{ErrorPayload, RequestPacket, Response, ResponsePacket, ResponsePayload, SerializedRequest};
+use alloy_network::Ethereum;
+use alloy_primitives::{address, Address, Bytes, B256, U256};
+use alloy_provider::{Provider, ProviderBuilder, RootProvider};
+use alloy_rpc_client::RpcClient;
+use alloy_rpc_types::state::{AccountOverride, StateOverride};
+use alloy_rpc_types::{BlockNumberOrTag, TransactionInput, TransactionRequest};
+use alloy_rpc_types_trace::geth::{AccountState, GethDebugTracingCallOptions};
+use alloy_sol_types::SolCall;
+use alloy_transport::{TransportError, TransportErrorKind, TransportFut};
+use debug_provider::{AnvilDebugProviderFactory, AnvilDebugProviderType};
+use defi_abi::lido::{IStEth, IWStEth};
+use defi_address_book::{CurvePoolAddress, TokenAddress, UniswapV2PoolAddress, UniswapV3PoolAddress};
+use defi_entities::required_state::{RequiredState, RequiredStateReader};
+use defi_entities::{
+    AbiSwapEncoder, MarketState, Pool, PoolClass, PoolProtocol, PoolWrapper, SwapAmountType, SwapLine, SwapPath, SwapStep, Token,
+};
+use defi_pools::protocols::CurveProtocol;
+use defi_pools::{CurvePool, MaverickPool, UniswapV2Pool, UniswapV3Pool};
+use defi_types::{debug_trace_call_pre_state, GethStateUpdate, MulticallerCall, MulticallerCalls};
+use eyre::{eyre, ErrReport, OptionExt, Result};
+use loom_revm_db::LoomInMemoryDB;
+use loom_utils::evm::{env_for_block, evm_call};
+use revm::primitives::{Env, TransactTo, SHANGHAI};
+use revm::{DatabaseCommit, DatabaseRef, Evm};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use serde_json::Value;
+use tokio::sync::RwLock;
+use tower::Service;
+
+use crate::opcodes_encoder::{OpcodesEncoder, OpcodesEncoderV2};
+use crate::{EncoderHelper, FlashLoanSource, MulticallerDeployer, SwapLineEncoder, SwapStepEncoder, DEFAULT_VIRTUAL_ADDRESS};
+
+const MULTICALLER: Address = DEFAULT_VIRTUAL_ADDRESS;
+const TX_CALLER: Address = Address::repeat_byte(0x11);
+const SWAP_GAS_LIMIT: u64 = 3_000_000;
+const FIXTURE_BLOCK: u64 = 20045799;
+const MAVERICK_POOL: Address = address!("352b186090068eb35d532428676ce510e17ab581");
+
+#[derive(Serialize, Deserialize)]
+struct PoolEncoderFixture {
+    block_number: u64,
+    block_timestamp: u64,
+    state: GethStateUpdate,
+}
+
+impl PoolEncoderFixture {
+    fn path(name: &str) -> String {
+        format!("{}/fixtures/pool_encoders/{}.json", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    /// Fixtures are committed, a missing one fails the test.
+    fn load(name: &str) -> Result<Self> {
+        let path = Self::path(name);
+        let data = fs::read_to_string(&path).map_err(|e| eyre!("FIXTURE_NOT_FOUND {path} : {e}, run record_pool_encoder_fixtures"))?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    fn save(&self, name: &str) -> Result<()> {
+        let path = Self::path(name);
+        if let Some(dir) = std::path::Path::new(&path).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+fn merge_state(target: &mut GethStateUpdate, update: GethStateUpdate) {
+    for (address, account) in update {
+        let entry = target.entry(address).or_default();
+        if account.balance.is_some() {
+            entry.balance = account.balance;
+        }
+        if account.nonce.is_some() {
+            entry.nonce = account.nonce;
+        }
+        if account.code.is_some() {
+            entry.code = account.code;
+        }
+        entry.storage.extend(account.storage);
+    }
+}
+
+/// JSON-RPC transport answering pool loader requests from the fixture state. With an upstream node the state touched
+/// by every request is traced and recorded first.
+#[derive(Clone)]
+struct FixtureTransport {
+    market_state: Arc<RwLock<MarketState>>,
+    recorded: Arc<RwLock<GethStateUpdate>>,
+    env: Env,
+    upstream: Option<AnvilDebugProviderType>,
+}
+
+type FixtureProvider = RootProvider<FixtureTransport, Ethereum>;
+
+impl FixtureTransport {
+    fn replay(fixture: PoolEncoderFixture) -> Self {
+        let mut market_state = MarketState::new(LoomInMemoryDB::default());
+        market_state.add_state(&fixture.state);
+        Self {
+            market_state: Arc::new(RwLock::new(market_state)),
+            recorded: Arc::new(RwLock::new(fixture.state)),
+            env: env_for_block(fixture.block_number, fixture.block_timestamp),
+            upstream: None,
+        }
+    }
+
+    fn record(upstream: AnvilDebugProviderType, block_number: u64, block_timestamp: u64) -> Self {
+        Self {
+            market_state: Arc::new(RwLock::new(MarketState::new(LoomInMemoryDB::default()))),
+            recorded: Arc::new(RwLock::new(GethStateUpdate::new())),
+            env: env_for_block(block_number, block_timestamp),
+            upstream: Some(upstream),
+        }
+    }
+
+    fn provider(&self) -> FixtureProvider {
+        ProviderBuilder::new().on_client(RpcClient::new(self.clone(), true))
+    }
+
+    async fn fixture(&self) -> PoolEncoderFixture {
+        PoolEncoderFixture {
+            block_number: self.env.block.number.to(),
+            block_timestamp: self.env.block.timestamp.to(),
+            state: self.recorded.read().await.clone(),
+        }
+    }
+
+    async fn apply(&self, update: GethStateUpdate) {
+        let mut recorded = self.recorded.write().await;
+        merge_state(&mut recorded, update);
+
+        let mut market_state = self.market_state.write().await;
+        *market_state = MarketState::new(LoomInMemoryDB::default());
+        market_state.add_state(&recorded);
+    }
+
+    async fn record_required_state(&self, required_state: RequiredState) -> Result<()> {
+        if let Some(upstream) = &self.upstream {
+            let update = RequiredStateReader::fetch_calls_and_slots(upstream.clone(), required_state, None).await?;
+            self.apply(update).await;
+        }
+        Ok(())
+    }
+
+    async fn record_swap(&self, call_data: Bytes, amount: U256) -> Result<()> {
+        if let Some(upstream) = &self.upstream {
+            let tx = TransactionRequest::default()
+                .from(TX_CALLER)
+                .to(MULTICALLER)
+                .input(TransactionInput::new(call_data))
+                .gas_limit(SWAP_GAS_LIMIT);
+
+            let multicaller_override =
+                AccountOverride { code: MulticallerDeployer::new().account_info().code, balance: Some(amount), ..Default::default() };
+            let mut state_override = StateOverride::default();
+            state_override.insert(MULTICALLER, multicaller_override);
+
+            let opts = GethDebugTracingCallOptions { state_overrides: Some(state_override), ..Default::default() };
+            let update = debug_trace_call_pre_state(upstream.clone(), tx, BlockNumberOrTag::Latest.into(), Some(opts)).await?;
+            self.apply(update).await;
+        }
+        Ok(())
+    }
+
+    async fn calculate_out_amount(&self, pool: &PoolWrapper, token_from: Address, token_to: Address, amount: U256) -> Result<U256> {
+        let market_state = self.market_state.read().await;
+        Ok(pool.calculate_out_amount(&market_state.state_db, self.env.clone(), &token_from, &token_to, amount)?.0)
+    }
+
+    async fn balance_of(&self, token: Address, account: Address) -> Result<U256> {
+        let market_state = self.market_state.read().await;
+        let (value, _) =
+            evm_call(&market_state.state_db, self.env.clone(), token, EncoderHelper::encode_erc20_balance_of(account).to_vec())?;
+        Ok(U256::from_be_slice(value.get(0..32).ok_or_eyre("BAD_BALANCE_RESPONSE")?))
+    }
+
+    /// Deploys the multicaller funded with `amount` of ETH and executes the calls from it
+    async fn execute_swap(&self, call_data: Bytes, amount: U256) -> Result<()> {
+        let mut market_state = self.market_state.write().await;
+
+        let multicaller_state = AccountState { balance: Some(amount), ..MulticallerDeployer::new().account_info() };
+        market_state.add_state(&BTreeMap::from([(MULTICALLER, multicaller_state)]));
+
+        let mut env = self.env.clone();
+        env.tx.caller = TX_CALLER;
+        env.tx.transact_to = TransactTo::Call(MULTICALLER);
+        env.tx.data = call_data;
+        env.tx.gas_limit = SWAP_GAS_LIMIT;
+
+        let mut evm = Evm::builder().with_spec_id(SHANGHAI).with_ref_db(&market_state.state_db).with_env(Box::new(env)).build();
+        let result_and_state = evm.transact().map_err(|e| eyre!("TRANSACT_ERROR {:?}", e))?;
+        drop(evm);
+
+        if !result_and_state.result.is_success() {
+            return Err(eyre!("SWAP_FAILED {:?}", result_and_state.result));
+        }
+        market_state.state_db.commit(result_and_state.state);
+        Ok(())
+    }
+
+    async fn eth_call(&self, tx: TransactionRequest) -> Result<ResponsePayload> {
+        let to = tx.to.and_then(|to| to.to().copied()).ok_or_eyre("NO_CALL_TARGET")?;
+        let call_data = tx.input.input().cloned().unwrap_or_default();
+
+        if let Some(upstream) = &self.upstream {
+            let update = debug_trace_call_pre_state(upstream.clone(), tx, BlockNumberOrTag::Latest.into(), None).await?;
+            self.apply(update).await;
+        }
+
+        let market_state = self.market_state.read().await;
+        match evm_call(&market_state.state_db, self.env.clone(), to, call_data.to_vec()) {
+            Ok((value, _)) => success_payload(Bytes::from(value)),
+            Err(_) => Ok(ResponsePayload::Failure(ErrorPayload { code: 3, message: "execution reverted".into(), data: None })),
+        }
+    }
+
+    async fn eth_get_code(&self, address: Address) -> Result<ResponsePayload> {
+        if let Some(upstream) = &self.upstream {
+            let code = upstream.get_code_at(address).await?;
+            self.apply(BTreeMap::from([(address, AccountState { code: Some(code), ..Default::default() })])).await;
+        }
+
+        let market_state = self.market_state.read().await;
+        let Ok(account) = market_state.state_db.basic_ref(address);
+        let code = match account {
+            Some(account) => match account.code {
+                Some(code) => code.original_bytes(),
+                None => {
+                    let Ok(code) = market_state.state_db.code_by_hash_ref(account.code_hash);
+                    code.original_bytes()
+                }
+            },
+            None => Bytes::new(),
+        };
+        success_payload(code)
+    }
+
+    async fn eth_get_storage_at(&self, address: Address, slot: U256) -> Result<ResponsePayload> {
+        if let Some(upstream) = &self.upstream {
+            let value = upstream.get_storage_at(address, slot).await?;
+            let storage = BTreeMap::from([(B256::from(slot), B256::from(value))]);
+            self.apply(BTreeMap::from([(address, AccountState { storage, ..Default::default() })])).await;
+        }
+
+        let market_state = self.market_state.read().await;
+        let Ok(value) = market_state.state_db.storage_ref(address, slot);
+        success_payload(value)
+    }
+
+    async fn handle(self, req: SerializedRequest) -> Result<ResponsePacket, TransportError> {
+        let params: Vec<Value> = match req.params() {
+            Some(params) => serde_json::from_str(params.get()).map_err(TransportErrorKind::custom)?,
+            None => Vec::new(),
+        };
+
+        let payload = match req.method() {
+            "eth_call" => self.eth_call(param(&params, 0)?).await,
+            "eth_getCode" => self.eth_get_code(param(&params, 0)?).await,
+            "eth_getStorageAt" => self.eth_get_storage_at(param(&params, 0)?, param(&params, 1)?).await,
+            method => Err(eyre!("METHOD_NOT_SUPPORTED {}", method)),
+        }
+        .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?;
+
+        Ok(ResponsePacket::Single(Response { id: req.id().clone(), payload }))
+    }
+}
+
+fn param<T: for<'de> Deserialize<'de>>(params: &[Value], idx: usize) -> Result<T, TransportError> {
+    let value = params.get(idx).cloned().ok_or_else(|| TransportErrorKind::custom_str("MISSING_PARAM"))?;
+    serde_json::from_value(value).map_err(TransportErrorKind::custom)
+}
+
+fn success_payload<T: Serialize>(value: T) -> Result<ResponsePayload> {
+    Ok(ResponsePayload::Success(RawValue::from_string(serde_json::to_string(&value)?)?))
+}
+
+impl Service<RequestPacket> for FixtureTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        let transport = self.clone();
+        Box::pin(async move {
+            match req {
+                RequestPacket::Single(req) => transport.handle(req).await,
+                RequestPacket::Batch(_) => Err(TransportErrorKind::custom_str("BATCH_NOT_SUPPORTED")),
+            }
+        })
+    }
+}
+
+/// ETH is submitted to Lido with an empty call, stETH mints on fallback and wstETH wraps on receive
+#[derive(Clone)]
+struct LidoAbiSwapEncoder {}
+
+impl AbiSwapEncoder for LidoAbiSwapEncoder {
+    fn encode_swap_in_amount_provided(
+        &self,
+        _token_from_address: Address,
+        _token_to_address: Address,
+        _amount: U256,
+        _recipient: Address,
+        _payload: Bytes,
+    ) -> Result<Bytes> {
+        Ok(Bytes::new())
+    }
+}
+
+#[derive(Clone)]
+struct LidoPool {
+    class: PoolClass,
+    address: Address,
+    encoder: LidoAbiSwapEncoder,
+}
+
+impl LidoPool {
+    fn new(class: PoolClass, address: Address) -> Self {
+        Self { class, address, encoder: LidoAbiSwapEncoder {} }
+    }
+
+    fn out_amount_calls(&self, in_amount: U256) -> Vec<(Address, Bytes)> {
+        match self.class {
+            PoolClass::LidoStEth => vec![
+                (TokenAddress::STETH, IStEth::getSharesByPooledEthCall { _ethAmount: in_amount }.abi_encode().into()),
+                (TokenAddress::STETH, IStEth::getPooledEthBySharesCall { _sharesAmount: in_amount }.abi_encode().into()),
+            ],
+            _ => vec![(TokenAddress::WSTETH, IWStEth::getWstETHByStETHCall { stETHAmount: in_amount }.abi_encode().into())],
+        }
+    }
+}
+
+impl Pool for LidoPool {
+    fn get_class(&self) -> PoolClass {
+        self.class
+    }
+
+    fn get_protocol(&self) -> PoolProtocol {
+        if self.class == PoolClass::LidoStEth {
+            PoolProtocol::LidoStEth
+        } else {
+            PoolProtocol::LidoWstEth
+        }
+    }
+
+    fn get_address(&self) -> Address {
+        self.address
+    }
+
+    fn get_tokens(&self) -> Vec<Address> {
+        vec![TokenAddress::WETH, self.address]
+    }
+
+    fn calculate_out_amount(
+        &self,
+        state: &LoomInMemoryDB,
+        env: Env,
+        _token_address_from: &Address,
+        _token_address_to: &Address,
+        in_amount: U256,
+    ) -> Result<(U256, u64), ErrReport> {
+        if self.class == PoolClass::LidoStEth {
+            let call_data = IStEth::getSharesByPooledEthCall { _ethAmount: in_amount }.abi_encode();
+            let (value, gas_used) = evm_call(state, env.clone(), TokenAddress::STETH, call_data)?;
+            let shares = IStEth::getSharesByPooledEthCall::abi_decode_returns(&value, false)?._0;
+
+            let call_data = IStEth::getPooledEthBySharesCall { _sharesAmount: shares }.abi_encode();
+            let (value, _) = evm_call(state, env, TokenAddress::STETH, call_data)?;
+            Ok((IStEth::getPooledEthBySharesCall::abi_decode_returns(&value, false)?._0, gas_used))
+        } else {
+            let call_data = IWStEth::getWstETHByStETHCall { stETHAmount: in_amount }.abi_encode();
+            let (value, gas_used) = evm_call(state, env, TokenAddress::WSTETH, call_data)?;
+            Ok((IWStEth::getWstETHByStETHCall::abi_decode_returns(&value, false)?._0, gas_used))
+        }
+    }
+
+    fn calculate_in_amount(
+        &self,
+        _state: &LoomInMemoryDB,
+        _env: Env,
+        _token_address_from: &Address,
+        _token_address_to: &Address,
+        _out_amount: U256,
+    ) -> Result<(U256, u64), ErrReport> {
+        Err(eyre!("NOT_IMPLEMENTED"))
+    }
+
+    fn can_flash_swap(&self) -> bool {
+        false
+    }
+
+    fn get_encoder(&self) -> &dyn AbiSwapEncoder {
+        &self.encoder
+    }
+
+    fn get_state_required(&self) -> Result<RequiredState> {
+        let mut state_required = RequiredState::new();
+        for (to, call_data) in self.out_amount_calls(U256::from(10).pow(U256::from(18))) {
+            state_required.add_call(to, call_data);
+        }
+        Ok(state_required)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum PoolLoader {
+    UniswapV2,
+    UniswapV3,
+    Maverick,
+    Curve,
+    LidoStEth,
+    LidoWstEth,
+}
+
+struct PoolEncoderCase {
+    name: &'static str,
+    loader: PoolLoader,
+    pool_address: Address,
+    // output token, the first pool token other than WETH if not set
+    token_to: Option<Address>,
+    // rounding of the pool model against the executed swap
+    tolerance: U256,
+}
+
+impl PoolEncoderCase {
+    fn new(name: &'static str, loader: PoolLoader, pool_address: Address, token_to: Option<Address>, tolerance: u64) -> Self {
+        Self { name, loader, pool_address, token_to, tolerance: U256::from(tolerance) }
+    }
+}
+
+async fn load_pool(loader: PoolLoader, pool_address: Address, provider: FixtureProvider) -> Result<PoolWrapper> {
+    let pool: PoolWrapper = match loader {
+        PoolLoader::UniswapV2 => UniswapV2Pool::fetch_pool_data(provider, pool_address).await?.into(),
+        PoolLoader::UniswapV3 => UniswapV3Pool::fetch_pool_data(provider, pool_address).await?.into(),
+        PoolLoader::Maverick => MaverickPool::fetch_pool_data(provider, pool_address).await?.into(),
+        PoolLoader::Curve => {
+            let contract =
+                CurveProtocol::<FixtureProvider, Ethereum, FixtureTransport>::get_contract_from_code(provider.clone(), pool_address)
+                    .await?;
+            CurvePool::fetch_pool_data(provider, contract).await?.into()
+        }
+        PoolLoader::LidoStEth => LidoPool::new(PoolClass::LidoStEth, pool_address).into(),
+        PoolLoader::LidoWstEth => LidoPool::new(PoolClass::LidoWstEth, pool_address).into(),
+    };
+    Ok(pool)
+}
+
+fn pool_encoder_cases() -> Vec<PoolEncoderCase> {
+    vec![
+        PoolEncoderCase::new("uniswap2_weth_usdt", PoolLoader::UniswapV2, UniswapV2PoolAddress::WETH_USDT, None, 0),
+        PoolEncoderCase::new("uniswap3_usdc_weth_500", PoolLoader::UniswapV3, UniswapV3PoolAddress::USDC_WETH_500, None, 0),
+        // the model subtracts one from the quoted amount
+        PoolEncoderCase::new("maverick_weth", PoolLoader::Maverick, MAVERICK_POOL, None, 1),
+        PoolEncoderCase::new("curve_usdt_btc_eth", PoolLoader::Curve, CurvePoolAddress::USDT_BTC_ETH, Some(TokenAddress::USDT), 1),
+        // stETH balance is recalculated from shares after the submit
+        PoolEncoderCase::new("lido_steth", PoolLoader::LidoStEth, TokenAddress::STETH, Some(TokenAddress::STETH), 2),
+        PoolEncoderCase::new("lido_wsteth", PoolLoader::LidoWstEth, TokenAddress::WSTETH, Some(TokenAddress::WSTETH), 0),
+    ]
+}
+
+/// Loads the pool, encodes WETH to `token_to` swap after a WETH deposit and executes it from the multicaller
+async fn run_case(case: &PoolEncoderCase, transport: &FixtureTransport) -> Result<()> {
+    let amount = U256::from(10).pow(U256::from(18));
+
+    let pool = load_pool(case.loader, case.pool_address, transport.provider()).await?;
+    transport.record_required_state(pool.get_state_required()?).await?;
+
+    let token_from = TokenAddress::WETH;
+    let token_to = match case.token_to {
+        Some(token_to) => token_to,
+        None => pool.get_tokens().into_iter().find(|token| *token != token_from).ok_or_eyre("TOKEN_TO_NOT_FOUND")?,
+    };
+
+    let expected = transport.calculate_out_amount(&pool, token_from, token_to, amount).await?;
+
+    let mut swap_line = SwapLine::from(SwapPath::new(vec![Token::new(token_from), Token::new(token_to)], vec![pool.clone()]));
+    swap_line.amount_in = SwapAmountType::Set(amount);
+
+    let mut calls = MulticallerCalls::new();
+    calls.add(MulticallerCall::new_call_with_value(token_from, &EncoderHelper::encode_weth_deposit(), amount));
+    calls.merge(SwapLineEncoder::new(MULTICALLER).encode_swap_line_in_amount(&swap_line, MULTICALLER, MULTICALLER)?);
+    let call_data = OpcodesEncoderV2::pack_do_calls(&calls)?;
+
+    transport.record_swap(call_data.clone(), amount).await?;
+
+    let balance_before = transport.balance_of(token_to, MULTICALLER).await?;
+    transport.execute_swap(call_data, amount).await?;
+    let received = transport.balance_of(token_to, MULTICALLER).await? - balance_before;
+
+    let diff = if received > expected { received - expected } else { expected - received };
+    if diff > case.tolerance {
+        return Err(eyre!("{} received {} expected {}", case.name, received, expected));
+    }
+    Ok(())
+}
+
+/// Swap of a pool to `token_to`
+#[derive(Clone, Copy)]
+struct PoolHop {
+    loader: PoolLoader,
+    pool_address: Address,
+    token_to: Address,
+}
+
+impl PoolHop {
+    fn new(loader: PoolLoader, pool_address: Address, token_to: Address) -> Self {
+        Self { loader, pool_address, token_to }
+    }
+}
+
+struct FlashLoanCase {
+    name: &'static str,
+    source: FlashLoanSource,
+    // the borrowed token is bought with WETH before the loan to pay the fee, WETH is borrowed if empty
+    funding: Vec<PoolHop>,
+    // swap of the borrowed amount inside of the loan
+    swap: PoolHop,
+    amount: U256,
+    tolerance: U256,
+}
+
+impl FlashLoanCase {
+    fn new(name: &'static str, source: FlashLoanSource, funding: Vec<PoolHop>, swap: PoolHop, amount: U256, tolerance: u64) -> Self {
+        Self { name, source, funding, swap, amount, tolerance: U256::from(tolerance) }
+    }
+
+    fn token(&self) -> Address {
+        self.funding.last().map_or(TokenAddress::WETH, |hop| hop.token_to)
+    }
+}
+
+fn flash_loan_cases() -> Vec<FlashLoanCase> {
+    let eth = U256::from(10).pow(U256::from(18));
+    vec![
+        FlashLoanCase::new(
+            "flash_loan_aave_v3",
+            FlashLoanSource::aave_v3(),
+            vec![],
+            PoolHop::new(PoolLoader::UniswapV3, UniswapV3PoolAddress::WETH_USDT_3000, TokenAddress::USDT),
+            eth,
+            0,
+        ),
+        FlashLoanCase::new(
+            "flash_loan_maker",
+            FlashLoanSource::maker(),
+            vec![
+                PoolHop::new(PoolLoader::UniswapV2, UniswapV2PoolAddress::WETH_USDT, TokenAddress::USDT),
+                PoolHop::new(PoolLoader::Curve, CurvePoolAddress::DAI_USDC_USDT, TokenAddress::DAI),
+            ],
+            PoolHop::new(PoolLoader::Curve, CurvePoolAddress::DAI_USDC_USDT, TokenAddress::USDC),
+            U256::from(1000) * eth,
+            1,
+        ),
+        // the pool of the source cannot be used by the swap
+        FlashLoanCase::new(
+            "flash_loan_uniswap3",
+            FlashLoanSource::uniswap_v3(UniswapV3PoolAddress::USDC_WETH_500, TokenAddress::USDC, TokenAddress::WETH, 500),
+            vec![],
+            PoolHop::new(PoolLoader::UniswapV2, UniswapV2PoolAddress::WETH_USDT, TokenAddress::USDT),
+            eth,
+            0,
+        ),
+    ]
+}
+
+/// Buys the borrowed token for the fee in the first transaction, borrows the amount and swaps it in the second one. The swap
+/// must receive the pool model amount and the loan must cost the amount with the fee of the source.
+async fn run_flash_loan_case(case: &FlashLoanCase, transport: &FixtureTransport) -> Result<()> {
+    let funding_amount = U256::from(2) * U256::from(10).pow(U256::from(18));
+    let token = case.token();
+
+    let mut funding_calls = MulticallerCalls::new();
+    funding_calls.add(MulticallerCall::new_call_with_value(TokenAddress::WETH, &EncoderHelper::encode_weth_deposit(), funding_amount));
+    if !case.funding.is_empty() {
+        let mut tokens = vec![Token::new(TokenAddress::WETH)];
+        let mut pools = Vec::new();
+        for hop in case.funding.iter() {
+            let pool = load_pool(hop.loader, hop.pool_address, transport.provider()).await?;
+            transport.record_required_state(pool.get_state_required()?).await?;
+            tokens.push(Token::new(hop.token_to));
+            pools.push(pool);
+        }
+        let mut funding_line = SwapLine::from(SwapPath::new(tokens, pools));
+        funding_line.amount_in = SwapAmountType::Set(funding_amount);
+        funding_calls.merge(SwapLineEncoder::new(MULTICALLER).encode_swap_line_in_amount(&funding_line, MULTICALLER, MULTICALLER)?);
+    }
+
+    let pool = load_pool(case.swap.loader, case.swap.pool_address, transport.provider()).await?;
+    transport.record_required_state(pool.get_state_required()?).await?;
+
+    let mut swap_line = SwapLine::from(SwapPath::new(vec![Token::new(token), Token::new(case.swap.token_to)], vec![pool.clone()]));
+    swap_line.amount_in = SwapAmountType::Set(case.amount);
+    let mut swap_step = SwapStep::new(MULTICALLER);
+    swap_step.add(swap_line);
+    let flash_calls = SwapStepEncoder::new(MULTICALLER).encode_flash_loan(vec![swap_step], &case.source)?;
+
+    let funding_call_data = OpcodesEncoderV2::pack_do_calls(&funding_calls)?;
+    let flash_call_data = OpcodesEncoderV2::pack_do_calls(&flash_calls)?;
+
+    // the node does not keep the funding transaction, both are traced as one call
+    let mut recorded_calls = funding_calls;
+    recorded_calls.merge(flash_calls);
+    transport.record_swap(OpcodesEncoderV2::pack_do_calls(&recorded_calls)?, funding_amount).await?;
+
+    transport.execute_swap(funding_call_data, funding_amount).await?;
+
+    let expected = transport.calculate_out_amount(&pool, token, case.swap.token_to, case.amount).await?;
+    let token_balance_before = transport.balance_of(token, MULTICALLER).await?;
+    let balance_before = transport.balance_of(case.swap.token_to, MULTICALLER).await?;
+
+    transport.execute_swap(flash_call_data, U256::ZERO).await?;
+
+    let received = transport.balance_of(case.swap.token_to, MULTICALLER).await? - balance_before;
+    let diff = if received > expected { received - expected } else { expected - received };
+    if diff > case.tolerance {
+        return Err(eyre!("{} received {} expected {}", case.name, received, expected));
+    }
+
+    let token_balance = transport.balance_of(token, MULTICALLER).await?;
+    let spent = token_balance_before.checked_sub(token_balance).ok_or_eyre("TOKEN_BALANCE_INCREASED")?;
+    let expected_spent = case.amount + case.source.fee_amount(case.amount);
+    if spent != expected_spent {
+        return Err(eyre!("{} spent {} expected {}", case.name, spent, expected_spent));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pool_encoders_execution() -> Result<()> {
+    for case in pool_encoder_cases() {
+        run_case(&case, &FixtureTransport::replay(PoolEncoderFixture::load(case.name)?)).await?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_flash_loan_execution() -> Result<()> {
+    for case in flash_loan_cases() {
+        run_flash_loan_case(&case, &FixtureTransport::replay(PoolEncoderFixture::load(case.name)?)).await?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore]
+async fn record_pool_encoder_fixtures() -> Result<()> {
+    let node_url = std::env::var("MAINNET_WS")?;
+
+    for case in pool_encoder_cases() {
+        let client = AnvilDebugProviderFactory::from_node_on_block(node_url.clone(), FIXTURE_BLOCK).await?;
+        let block = client.get_block_by_number(BlockNumberOrTag::Latest, false).await?.ok_or_eyre("BLOCK_NOT_FOUND")?;
+
+        let transport = FixtureTransport::record(client, block.header.number, block.header.timestamp);
+        run_case(&case, &transport).await?;
+
+        // the recorded state must be enough to pass offline
+        let fixture = transport.fixture().await;
+        fixture.save(case.name)?;
+        run_case(&case, &FixtureTransport::replay(PoolEncoderFixture::load(case.name)?)).await?;
+    }
+
+    for case in flash_loan_cases() {
+        let client = AnvilDebugProviderFactory::from_node_on_block(node_url.clone(), FIXTURE_BLOCK).await?;
+        let block = client.get_block_by_number(BlockNumberOrTag::Latest, false).await?.ok_or_eyre("BLOCK_NOT_FOUND")?;
+
+        let transport = FixtureTransport::record(client, block.header.number, block.header.timestamp);
+        run_flash_loan_case(&case, &transport).await?;
+
+        let fixture = transport.fixture().await;
+        fixture.save(case.name)?;
+        run_flash_loan_case(&case, &FixtureTransport::replay(PoolEncoderFixture::load(case.name)?)).await?;
+    }
+    Ok(())
+}
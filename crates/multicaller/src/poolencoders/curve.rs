@@ -28,6 +28,7 @@ impl CurveSwapEncoder {
         *NEED_BALANCE_MAP.get(&address).unwrap_or(&false)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn encode_swap_in_amount_provided(
         token_from_address: Address,
         token_to_address: Address,
@@ -36,12 +37,13 @@ impl CurveSwapEncoder {
         cur_pool: &PoolWrapper,
         next_pool: Option<&PoolWrapper>,
         multicaller: Address,
+        wrapped_native_token: Address,
     ) -> Result<()> {
         let pool_encoder = cur_pool.get_encoder();
         let pool_address = cur_pool.get_address();
 
-        let in_native = if pool_encoder.is_native() { EncoderHelper::is_weth(token_from_address) } else { false };
-        let out_native = if pool_encoder.is_native() { EncoderHelper::is_weth(token_to_address) } else { false };
+        let in_native = if pool_encoder.is_native() { token_from_address == wrapped_native_token } else { false };
+        let out_native = if pool_encoder.is_native() { token_to_address == wrapped_native_token } else { false };
 
         match amount_in {
             SwapAmountType::Set(amount) => {
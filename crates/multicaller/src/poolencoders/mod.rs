@@ -1,7 +1,9 @@
 pub use curve::CurveSwapEncoder;
+pub use singleton::SingletonSwapEncoder;
 pub use steth::StEthSwapEncoder;
 pub use wsteth::WstEthSwapEncoder;
 
 mod curve;
+mod singleton;
 mod steth;
 mod wsteth;
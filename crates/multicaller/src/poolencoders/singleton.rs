@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use alloy_primitives::{Address, Bytes, U256};
+use defi_entities::{PoolWrapper, PreswapRequirement, SwapAmountType, Token};
+use defi_types::{MulticallerCall, MulticallerCalls};
+use eyre::{eyre, OptionExt, Result};
+use lazy_static::lazy_static;
+
+use crate::helpers::EncoderHelper;
+use crate::opcodes_encoder::{OpcodesEncoder, OpcodesEncoderV2};
+
+/// unlock(bytes) payload starts after the selector, offset and length words
+const UNLOCK_PAYLOAD_OFFSET: u32 = 0x44;
+/// Packed call header and the target address
+const PACKED_CALL_PREFIX_LEN: u32 = 0xC + 0x14;
+
+lazy_static! {
+    static ref NEGATE_CALCULATION: Bytes = Bytes::from(vec![0x8, 0x2A, 0x00]);
+    /// Zero on the stack top is returned by the callback, the singleton decodes it as empty bytes
+    static ref PUSH_ZERO_CALCULATION: Bytes = Bytes::from([vec![0x20], vec![0u8; 0x20], vec![0x0]].concat());
+}
+
+pub struct SingletonSwapEncoder {}
+
+impl SingletonSwapEncoder {
+    /// Encodes consecutive swaps of one singleton inside of a single unlock callback. The input is paid upfront with
+    /// sync, transfer and settle, the swaps are chained over their balance deltas and the output is taken at the end.
+    /// The amount taken is known only inside of the callback, `inside_opcodes` of the following hops are executed
+    /// there after the take. If the singleton is already unlocked by an outer swap the calls are added without unlock.
+    #[allow(clippy::too_many_arguments)]
+    pub fn encode_swap_in_amount_provided(
+        pools: &[PoolWrapper],
+        tokens: &[Arc<Token>],
+        amount_in: SwapAmountType,
+        swap_opcodes: &mut MulticallerCalls,
+        next_pool: Option<&PoolWrapper>,
+        inside_opcodes: MulticallerCalls,
+        unlocked: bool,
+        multicaller: Address,
+        funds_to: Address,
+    ) -> Result<()> {
+        let singleton_encoder = pools.first().ok_or_eyre("NO_POOLS")?.get_encoder();
+        let singleton = singleton_encoder.singleton_address().ok_or_eyre("NOT_SINGLETON_POOL")?;
+        if tokens.len() != pools.len() + 1 {
+            return Err(eyre!("TOKENS_POOLS_MISMATCH"));
+        }
+
+        let token_in_address = tokens[0].get_address();
+        let token_out_address = tokens[pools.len()].get_address();
+
+        let take_to = match next_pool {
+            Some(next_pool) => match next_pool.get_encoder().preswap_requirement() {
+                PreswapRequirement::Transfer(next_funds_to) => next_funds_to,
+                _ => multicaller,
+            },
+            None => funds_to,
+        };
+
+        let mut unlock_opcodes = MulticallerCalls::new();
+
+        let transfer_amount = if let SwapAmountType::Set(amount) = amount_in { amount } else { U256::ZERO };
+        let mut transfer_opcode =
+            MulticallerCall::new_call(token_in_address, &EncoderHelper::encode_erc20_transfer(singleton, transfer_amount));
+        if unlocked {
+            // the input is on the stack of the outer callback
+            Self::set_amount_in_stack(&mut transfer_opcode, amount_in, token_in_address, &mut unlock_opcodes, 0x24);
+        }
+
+        unlock_opcodes.add(MulticallerCall::new_call(singleton, &singleton_encoder.encode_singleton_sync(token_in_address)?));
+
+        let transfer_amount_offset =
+            UNLOCK_PAYLOAD_OFFSET + OpcodesEncoderV2::pack_do_calls_data(&unlock_opcodes)?.len() as u32 + PACKED_CALL_PREFIX_LEN + 0x24;
+        unlock_opcodes.add(transfer_opcode);
+
+        // settle returns the amount paid, negated it is the exact input of the first swap
+        let mut settle_opcode = MulticallerCall::new_call(singleton, &singleton_encoder.encode_singleton_settle()?);
+        settle_opcode.set_return_stack(true, 0, 0x0, 0x20);
+        unlock_opcodes.add(settle_opcode);
+        unlock_opcodes.add(MulticallerCall::new_calculation_call(&NEGATE_CALCULATION));
+
+        for (i, pool) in pools.iter().enumerate() {
+            let token_from_address = tokens[i].get_address();
+            let token_to_address = tokens[i + 1].get_address();
+            let pool_encoder = pool.get_encoder();
+
+            if pool_encoder.singleton_address() != Some(singleton) {
+                return Err(eyre!("SINGLETON_MISMATCH"));
+            }
+
+            let mut swap_opcode = MulticallerCall::new_call(
+                singleton,
+                &pool_encoder.encode_swap_in_amount_provided(token_from_address, token_to_address, U256::ZERO, take_to, Bytes::new())?,
+            );
+            swap_opcode.set_call_stack(
+                true,
+                0,
+                pool_encoder.swap_in_amount_offset(token_from_address, token_to_address).ok_or_eyre("SWAP_IN_AMOUNT_OFFSET_NOT_SET")?,
+                0x20,
+            );
+            swap_opcode.set_return_stack(
+                true,
+                0,
+                pool_encoder
+                    .swap_in_amount_return_offset(token_from_address, token_to_address)
+                    .ok_or_eyre("SWAP_IN_AMOUNT_RETURN_OFFSET_NOT_SET")?,
+                0x20,
+            );
+            unlock_opcodes.add(swap_opcode);
+
+            if let Some(script) = pool_encoder.swap_in_amount_return_script(token_from_address, token_to_address) {
+                unlock_opcodes.add(MulticallerCall::new_calculation_call(&script));
+            }
+            if i < pools.len() - 1 {
+                unlock_opcodes.add(MulticallerCall::new_calculation_call(&NEGATE_CALCULATION));
+            }
+        }
+
+        // the amount out stays on the stack for the following hops
+        let mut take_opcode =
+            MulticallerCall::new_call(singleton, &singleton_encoder.encode_singleton_take(token_out_address, take_to, U256::ZERO)?);
+        take_opcode.set_call_stack(
+            true,
+            0,
+            singleton_encoder.singleton_take_amount_offset().ok_or_eyre("TAKE_AMOUNT_OFFSET_NOT_SET")?,
+            0x20,
+        );
+        unlock_opcodes.add(take_opcode);
+        unlock_opcodes.merge(inside_opcodes);
+
+        if unlocked {
+            swap_opcodes.merge(unlock_opcodes);
+            return Ok(());
+        }
+
+        unlock_opcodes.add(MulticallerCall::new_calculation_call(&PUSH_ZERO_CALCULATION));
+
+        let mut unlock_opcode = MulticallerCall::new_call(
+            singleton,
+            &singleton_encoder.encode_singleton_unlock(OpcodesEncoderV2::pack_do_calls_data(&unlock_opcodes)?)?,
+        );
+        Self::set_amount_in_stack(&mut unlock_opcode, amount_in, token_in_address, swap_opcodes, transfer_amount_offset);
+        swap_opcodes.add(unlock_opcode);
+
+        Ok(())
+    }
+
+    /// Input amount of the call is read from the stack, the balance is pushed to the stack by a call added to `opcodes`.
+    fn set_amount_in_stack(
+        call: &mut MulticallerCall,
+        amount_in: SwapAmountType,
+        token_in_address: Address,
+        opcodes: &mut MulticallerCalls,
+        data_offset: u32,
+    ) {
+        match amount_in {
+            SwapAmountType::Set(_) => {}
+            SwapAmountType::Balance(addr) => {
+                let mut balance_opcode = MulticallerCall::new_static_call(token_in_address, &EncoderHelper::encode_erc20_balance_of(addr));
+                balance_opcode.set_return_stack(true, 0, 0x0, 0x20);
+                opcodes.add(balance_opcode);
+
+                call.set_call_stack(true, 0, data_offset, 0x20);
+            }
+            SwapAmountType::RelativeStack(stack_offset) => {
+                call.set_call_stack(true, stack_offset, data_offset, 0x20);
+            }
+            _ => {
+                call.set_call_stack(false, 0, data_offset, 0x20);
+            }
+        }
+    }
+}
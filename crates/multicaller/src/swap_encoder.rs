@@ -7,11 +7,13 @@ use eyre::{eyre, OptionExt, Result};
 use tracing::{debug, error};
 
 impl SwapEncoder for MulticallerSwapEncoder {
+    #[allow(clippy::too_many_arguments)]
     fn encode(
         &self,
         swap: Swap,
         tips_pct: Option<u32>,
         _next_block_number: Option<BlockNumber>,
+        next_block_gas_price: Option<u128>,
         gas_cost: Option<U256>,
         sender_address: Option<Address>,
         sender_eth_balance: Option<U256>,
@@ -27,7 +29,7 @@ impl SwapEncoder for MulticallerSwapEncoder {
                 }
                 ret
             }
-            Swap::ExchangeSwapLine(_) => vec![],
+            Swap::ExchangeSwapLine(_) | Swap::Liquidation(_) => vec![],
             Swap::None => {
                 vec![]
             }
@@ -49,16 +51,22 @@ impl SwapEncoder for MulticallerSwapEncoder {
                         }
                     }
                 }
+                Swap::Liquidation(liquidation) => {
+                    debug!("Swap::Liquidation encoding started");
+                    self.swap_step_encoder.encode_liquidation(liquidation, next_block_gas_price)?
+                }
                 _ => return Err(eyre!("NO_SWAP_STEPS")),
             }
         } else if swap_vec.len() == 1 {
             let sp0 = &swap_vec[0].0;
             let sp1 = &swap_vec[0].1;
-            self.swap_step_encoder.encode_swap_steps(sp0, sp1)?
+            self.swap_step_encoder.encode_swap_steps(sp0, sp1, next_block_gas_price)?
         } else {
             let mut ret = MulticallerCalls::new();
             for (sp0, sp1) in swap_vec.iter() {
-                ret = self.swap_step_encoder.encode_do_calls(ret, self.swap_step_encoder.encode_swap_steps(sp0, sp1)?)?;
+                ret = self
+                    .swap_step_encoder
+                    .encode_do_calls(ret, self.swap_step_encoder.encode_swap_steps(sp0, sp1, next_block_gas_price)?)?;
             }
             ret
         };
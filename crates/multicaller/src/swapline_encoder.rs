@@ -10,16 +10,22 @@ use defi_types::{MulticallerCall, MulticallerCalls};
 
 use crate::helpers::EncoderHelper;
 use crate::opcodes_encoder::{OpcodesEncoder, OpcodesEncoderV2};
-use crate::poolencoders::{CurveSwapEncoder, StEthSwapEncoder, WstEthSwapEncoder};
+use crate::poolencoders::{CurveSwapEncoder, SingletonSwapEncoder, StEthSwapEncoder, WstEthSwapEncoder};
 
 #[derive(Clone)]
 pub struct SwapLineEncoder {
     multicaller: Address,
+    /// Wrapped native token of the chain, it is unwrapped for native pools and tips
+    wrapped_native_token: Address,
 }
 
 impl SwapLineEncoder {
     pub fn new(multicaller: Address) -> SwapLineEncoder {
-        SwapLineEncoder { multicaller }
+        SwapLineEncoder { multicaller, wrapped_native_token: TokenAddress::WETH }
+    }
+
+    pub fn with_wrapped_native_token(self, wrapped_native_token: Address) -> Self {
+        Self { wrapped_native_token, ..self }
     }
 
     pub fn encode_flash_swap_line_in_amount(
@@ -432,13 +438,58 @@ impl SwapLineEncoder {
     }
 
     pub fn encode_swap_line_in_amount(&self, swap_path: &SwapLine, funds_from: Address, funds_to: Address) -> Result<MulticallerCalls> {
+        self.encode_swap_line_pools_in_amount(swap_path, 0, funds_from, funds_to, &[])
+    }
+
+    /// Encodes the pools of the swap line from `start`, the input of a pool after the first one is on the stack.
+    /// `unlocked` singletons are unlocked by the outer calls.
+    fn encode_swap_line_pools_in_amount(
+        &self,
+        swap_path: &SwapLine,
+        start: usize,
+        funds_from: Address,
+        funds_to: Address,
+        unlocked: &[Address],
+    ) -> Result<MulticallerCalls> {
         let mut swap_opcodes = MulticallerCalls::new();
 
-        for i in 0..swap_path.pools().len() {
+        for i in start..swap_path.pools().len() {
             let token_from_address = swap_path.tokens()[i].get_address();
             let token_to_address = swap_path.tokens()[i + 1].get_address();
 
             let cur_pool = &swap_path.pools()[i].clone();
+
+            // consecutive pools of the same singleton are swapped inside of one unlock callback
+            if let Some(singleton) = cur_pool.get_encoder().singleton_address() {
+                let mut last = i;
+                while last < swap_path.pools().len() - 1 && swap_path.pools()[last + 1].get_encoder().singleton_address() == Some(singleton)
+                {
+                    last += 1;
+                }
+
+                // the following pools are swapped inside of the callback where the amount taken is on the stack
+                let inside_opcodes = if last < swap_path.pools().len() - 1 {
+                    let mut inside_unlocked = unlocked.to_vec();
+                    inside_unlocked.push(singleton);
+                    self.encode_swap_line_pools_in_amount(swap_path, last + 1, funds_from, funds_to, &inside_unlocked)?
+                } else {
+                    MulticallerCalls::new()
+                };
+
+                SingletonSwapEncoder::encode_swap_in_amount_provided(
+                    &swap_path.pools()[i..=last],
+                    &swap_path.tokens()[i..=last + 1],
+                    if i == 0 { swap_path.amount_in } else { SwapAmountType::RelativeStack(0) },
+                    &mut swap_opcodes,
+                    swap_path.pools().get(last + 1),
+                    inside_opcodes,
+                    unlocked.contains(&singleton),
+                    self.multicaller,
+                    funds_to,
+                )?;
+                break;
+            }
+
             let next_pool: Option<&PoolWrapper> = if i < swap_path.pools().len() - 1 { Some(&swap_path.pools()[i + 1]) } else { None };
 
             let swap_to: Address = if let Some(next_pool) = next_pool {
@@ -666,6 +717,7 @@ impl SwapLineEncoder {
                         cur_pool,
                         next_pool,
                         self.multicaller,
+                        self.wrapped_native_token,
                     )?;
                 }
                 PoolClass::LidoWstEth => {
@@ -709,7 +761,7 @@ impl SwapLineEncoder {
     ) -> Result<MulticallerCalls> {
         let mut tips_opcodes = swap_opcodes.clone();
 
-        let call_data = if token_address == TokenAddress::WETH {
+        let call_data = if token_address == self.wrapped_native_token {
             EncoderHelper::encode_multicaller_transfer_tips_weth(min_balance, tips, to)
         } else {
             EncoderHelper::encode_multicaller_transfer_tips(token_address, min_balance, tips, to)
@@ -718,3 +770,177 @@ impl SwapLineEncoder {
         Ok(tips_opcodes)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MulticallerDecoder;
+    use alloy_primitives::I256;
+    use defi_address_book::UniswapV4Address;
+    use defi_entities::required_state::RequiredState;
+    use defi_entities::{AbiSwapEncoder, Pool, SwapPath};
+    use defi_pools::{UniswapV2Pool, UniswapV4AbiSwapEncoder};
+    use eyre::ErrReport;
+    use loom_revm_db::LoomInMemoryDB;
+    use revm::primitives::Env;
+
+    #[derive(Clone)]
+    struct MockUniswapV4Pool {
+        address: Address,
+        encoder: UniswapV4AbiSwapEncoder,
+    }
+
+    impl MockUniswapV4Pool {
+        fn new(address: Address, token0: Address, token1: Address) -> Self {
+            Self { address, encoder: UniswapV4AbiSwapEncoder::new(UniswapV4Address::POOL_MANAGER, token0, token1, 500, 10, Address::ZERO) }
+        }
+    }
+
+    impl Pool for MockUniswapV4Pool {
+        fn get_class(&self) -> PoolClass {
+            PoolClass::UniswapV4
+        }
+
+        fn get_address(&self) -> Address {
+            self.address
+        }
+
+        fn calculate_out_amount(
+            &self,
+            _state: &LoomInMemoryDB,
+            _env: Env,
+            _token_address_from: &Address,
+            _token_address_to: &Address,
+            _in_amount: U256,
+        ) -> Result<(U256, u64), ErrReport> {
+            panic!("Not implemented")
+        }
+
+        fn calculate_in_amount(
+            &self,
+            _state: &LoomInMemoryDB,
+            _env: Env,
+            _token_address_from: &Address,
+            _token_address_to: &Address,
+            _out_amount: U256,
+        ) -> Result<(U256, u64), ErrReport> {
+            panic!("Not implemented")
+        }
+
+        fn can_flash_swap(&self) -> bool {
+            false
+        }
+
+        fn get_encoder(&self) -> &dyn AbiSwapEncoder {
+            &self.encoder
+        }
+
+        fn get_state_required(&self) -> Result<RequiredState> {
+            Ok(RequiredState::new())
+        }
+    }
+
+    #[test]
+    fn test_encode_singleton_swap_line() -> Result<()> {
+        let multicaller = Address::repeat_byte(3);
+        let funds_to = Address::repeat_byte(4);
+        let amount = U256::from(1_000_000);
+
+        let swap_path = SwapPath::new(
+            vec![Token::new(TokenAddress::WETH), Token::new(TokenAddress::USDC), Token::new(TokenAddress::DAI)],
+            vec![
+                MockUniswapV4Pool::new(Address::repeat_byte(1), TokenAddress::USDC, TokenAddress::WETH),
+                MockUniswapV4Pool::new(Address::repeat_byte(2), TokenAddress::DAI, TokenAddress::USDC),
+            ],
+        );
+        let mut swap_line = SwapLine::from(swap_path);
+        swap_line.amount_in = SwapAmountType::Set(amount);
+
+        let encoder = SwapLineEncoder::new(multicaller);
+        let calls = encoder.encode_swap_line_in_amount(&swap_line, multicaller, funds_to)?;
+
+        // both hops are inside of one unlock
+        let decoded = MulticallerDecoder::decode(&OpcodesEncoderV2::pack_do_calls(&calls)?)?;
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].call.to, UniswapV4Address::POOL_MANAGER);
+        assert!(decoded[0].function.as_ref().unwrap().starts_with("IUniswapV4PoolManager::unlock"));
+
+        let functions: Vec<&str> = decoded[0]
+            .inner_calls
+            .iter()
+            .map(|call| call.function.as_deref().unwrap_or_default().split('(').next().unwrap_or_default())
+            .collect();
+        assert_eq!(
+            functions,
+            vec![
+                "IUniswapV4PoolManager::sync",
+                "IERC20::transfer",
+                "IUniswapV4PoolManager::settle",
+                "",
+                "IUniswapV4PoolManager::swap",
+                "",
+                "",
+                "IUniswapV4PoolManager::swap",
+                "",
+                "IUniswapV4PoolManager::take",
+                "",
+            ]
+        );
+
+        // the input amount from the stack is written to the transfer inside of the unlock payload
+        swap_line.amount_in = SwapAmountType::Stack0;
+        let stack_calls = encoder.encode_swap_line_in_amount(&swap_line, multicaller, funds_to)?;
+        let unlock_call = stack_calls.get(0).unwrap();
+        let data_offset = ((unlock_call.call_stack & 0xFFF) - 0x20) as usize;
+        assert_eq!(U256::from_be_slice(&calls.get(0).unwrap().call_data[data_offset..data_offset + 0x20]), amount);
+
+        // the last hop output is taken to funds_to
+        let take_data = &decoded[0].inner_calls[9].call.call_data;
+        assert_eq!(Address::from_slice(&take_data[0x24 + 12..0x44]), funds_to);
+
+        // the first swap is exact input of the amount settled
+        let swap_data = &decoded[0].inner_calls[4].call.call_data;
+        assert_eq!(I256::try_from_be_slice(&swap_data[0xC4..0xE4]), Some(I256::ZERO));
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_hops_after_singleton_inside_unlock() -> Result<()> {
+        let multicaller = Address::repeat_byte(3);
+        let funds_to = Address::repeat_byte(4);
+        let pair = Address::repeat_byte(2);
+
+        let swap_path = SwapPath::new(
+            vec![Token::new(TokenAddress::WETH), Token::new(TokenAddress::USDC), Token::new(TokenAddress::DAI)],
+            vec![
+                PoolWrapper::from(MockUniswapV4Pool::new(Address::repeat_byte(1), TokenAddress::USDC, TokenAddress::WETH)),
+                PoolWrapper::from(UniswapV2Pool::new_with_data(
+                    pair,
+                    TokenAddress::DAI,
+                    TokenAddress::USDC,
+                    Address::ZERO,
+                    U256::ZERO,
+                    U256::ZERO,
+                )),
+            ],
+        );
+        let mut swap_line = SwapLine::from(swap_path);
+        swap_line.amount_in = SwapAmountType::Set(U256::from(1_000_000));
+
+        let calls = SwapLineEncoder::new(multicaller).encode_swap_line_in_amount(&swap_line, multicaller, funds_to)?;
+
+        // the amount taken is not read from the multicaller balance, the next hop is swapped inside of the unlock
+        let decoded = MulticallerDecoder::decode(&OpcodesEncoderV2::pack_do_calls(&calls)?)?;
+        assert_eq!(decoded.len(), 1);
+
+        let functions: Vec<&str> = decoded[0].inner_calls.iter().map(|call| call.function.as_deref().unwrap_or_default()).collect();
+        let take_idx = functions.iter().position(|f| f.starts_with("IUniswapV4PoolManager::take")).unwrap();
+        assert!(functions[take_idx..].iter().any(|f| f.starts_with("IUniswapV2Pair::swap")));
+        assert!(!functions.iter().any(|f| f.starts_with("IERC20::balanceOf")));
+
+        // the output of the singleton is taken to the pair
+        let take_data = &decoded[0].inner_calls[take_idx].call.call_data;
+        assert_eq!(Address::from_slice(&take_data[0x24 + 12..0x44]), pair);
+        Ok(())
+    }
+}
@@ -1,28 +1,41 @@
-use alloy_primitives::{Address, Bytes, U256};
-use eyre::Result;
+use alloy_primitives::{keccak256, Address, Bytes, U256};
+use eyre::{OptionExt, Result};
 use lazy_static::lazy_static;
 use tracing::{debug, trace};
 
-use defi_entities::{SwapAmountType, SwapStep};
+use defi_entities::{Liquidation, SwapAmountType, SwapStep, Token};
 use defi_types::{MulticallerCall, MulticallerCalls};
 
+use crate::flash_loan::{FlashLoanProvider, FlashLoanSource};
 use crate::helpers::EncoderHelper;
 use crate::opcodes_encoder::{OpcodesEncoder, OpcodesEncoderV2};
 use crate::SwapLineEncoder;
 
 lazy_static! {
-    static ref BALANCER_VAULT_ADDRESS: Address = "0xBA12222222228d8Ba445958a75a0704d566BF2C8".parse().unwrap();
+    /// Calculation call pushing keccak256("ERC3156FlashBorrower.onFlashLoan") to the stack, it is returned by the callback
+    static ref ERC3156_CALLBACK_SUCCESS_CALCULATION: Bytes =
+        Bytes::from([vec![0x20], keccak256("ERC3156FlashBorrower.onFlashLoan").to_vec(), vec![0x0]].concat());
 }
 
 #[derive(Clone)]
 pub struct SwapStepEncoder {
     pub multicaller: Address,
     pub swap_line_encoder: SwapLineEncoder,
+    /// Flash loan sources for swaps that cannot be started with a flash swap
+    pub flash_loan_sources: Vec<FlashLoanSource>,
 }
 
 impl SwapStepEncoder {
     pub fn new(multicaller: Address) -> Self {
-        Self { multicaller, swap_line_encoder: SwapLineEncoder::new(multicaller) }
+        Self { multicaller, swap_line_encoder: SwapLineEncoder::new(multicaller), flash_loan_sources: FlashLoanSource::chain_defaults(1) }
+    }
+
+    pub fn with_flash_loan_sources(self, flash_loan_sources: Vec<FlashLoanSource>) -> Self {
+        Self { flash_loan_sources, ..self }
+    }
+
+    pub fn with_wrapped_native_token(self, wrapped_native_token: Address) -> Self {
+        Self { swap_line_encoder: self.swap_line_encoder.with_wrapped_native_token(wrapped_native_token), ..self }
     }
 
     pub fn get_contract_address(&self) -> Address {
@@ -48,16 +61,34 @@ impl SwapStepEncoder {
     }
 
     pub fn encode_balancer_flash_loan(&self, steps: Vec<SwapStep>) -> Result<MulticallerCalls> {
+        self.encode_flash_loan(steps, &FlashLoanSource::balancer())
+    }
+
+    /// Cheapest source of the token by fee and gas at the gas price of the transaction, pools of the swap cannot be used.
+    pub fn select_flash_loan_source(
+        &self,
+        token: &Token,
+        amount: U256,
+        swap_pools: &[Address],
+        gas_price: u128,
+    ) -> Option<&FlashLoanSource> {
+        self.flash_loan_sources
+            .iter()
+            .filter(|source| source.can_borrow(token.get_address()) && !swap_pools.contains(&source.address))
+            .min_by_key(|source| source.cost(token, amount, gas_price).unwrap_or(U256::MAX))
+    }
+
+    pub fn encode_flash_loan(&self, steps: Vec<SwapStep>, source: &FlashLoanSource) -> Result<MulticallerCalls> {
         let flash_funds_to = self.multicaller;
 
         let mut swap_opcodes = MulticallerCalls::new();
 
-        let first_swap = &steps[0];
+        let first_swap = steps.first().ok_or_eyre("NO_SWAP_STEPS")?;
 
         let mut steps = steps.clone();
 
-        let token = first_swap.first_token().unwrap();
-        let in_amount = first_swap.get_in_amount().unwrap();
+        let token = first_swap.first_token().ok_or_eyre("NO_FLASH_LOAN_TOKEN")?.get_address();
+        let in_amount = first_swap.get_in_amount()?;
 
         for (swap_idx, swap) in steps.iter_mut().enumerate() {
             if swap_idx > 0 {
@@ -79,17 +110,97 @@ impl SwapStepEncoder {
             }
         }
 
-        let inside_call_bytes = OpcodesEncoderV2::pack_do_calls_data(&swap_opcodes)?;
+        self.encode_flash_loan_calls(token, in_amount, swap_opcodes, source)
+    }
 
-        let mut flash_opcodes = MulticallerCalls::new();
+    /// Borrows the amount of the token from the source and executes the calls inside of the flash loan callback.
+    fn encode_flash_loan_calls(
+        &self,
+        token: Address,
+        in_amount: U256,
+        swap_opcodes: MulticallerCalls,
+        source: &FlashLoanSource,
+    ) -> Result<MulticallerCalls> {
+        let mut swap_opcodes = swap_opcodes;
+        let repay_amount = in_amount + source.fee_amount(in_amount);
+
+        let flash_call_data = match source.provider {
+            // repaid by receiveFlashLoan callback of the multicaller
+            FlashLoanProvider::Balancer => {
+                let inside_call_bytes = OpcodesEncoderV2::pack_do_calls_data(&swap_opcodes)?;
+                EncoderHelper::encode_balancer_flashloan(token, in_amount, inside_call_bytes, self.multicaller)
+            }
+            // the pool pulls the debt after the callback, result of approve is left on the stack as executeOperation return value
+            FlashLoanProvider::AaveV3 => {
+                let mut approve_opcode =
+                    MulticallerCall::new_call(token, &EncoderHelper::encode_erc20_approve(source.address, repay_amount));
+                approve_opcode.set_return_stack(true, 0, 0x0, 0x20);
+                swap_opcodes.add(approve_opcode);
+
+                let inside_call_bytes = OpcodesEncoderV2::pack_do_calls_data(&swap_opcodes)?;
+                EncoderHelper::encode_aave3_flash_loan_simple(token, in_amount, inside_call_bytes, self.multicaller)
+            }
+            // DssFlash burns the debt after the callback, onFlashLoan must return the ERC3156 success value
+            FlashLoanProvider::Maker => {
+                swap_opcodes.add(MulticallerCall::new_call(token, &EncoderHelper::encode_erc20_approve(source.address, repay_amount)));
+                swap_opcodes.add(MulticallerCall::new_calculation_call(&ERC3156_CALLBACK_SUCCESS_CALCULATION));
+
+                let inside_call_bytes = OpcodesEncoderV2::pack_do_calls_data(&swap_opcodes)?;
+                EncoderHelper::encode_maker_flash_loan(token, in_amount, inside_call_bytes, self.multicaller)
+            }
+            // the pool checks its balance after the callback
+            FlashLoanProvider::UniswapV3 => {
+                swap_opcodes.add(MulticallerCall::new_call(token, &EncoderHelper::encode_erc20_transfer(source.address, repay_amount)));
+
+                let inside_call_bytes = OpcodesEncoderV2::pack_do_calls_data(&swap_opcodes)?;
+                let (amount0, amount1) =
+                    if source.tokens.first() == Some(&token) { (in_amount, U256::ZERO) } else { (U256::ZERO, in_amount) };
+                EncoderHelper::encode_uniswap3_flash(amount0, amount1, inside_call_bytes, self.multicaller)
+            }
+        };
 
-        let flash_call_data = EncoderHelper::encode_balancer_flashloan(token.get_address(), in_amount, inside_call_bytes, self.multicaller);
+        let mut flash_opcodes = MulticallerCalls::new();
 
-        flash_opcodes.add(MulticallerCall::new_call(*BALANCER_VAULT_ADDRESS, &flash_call_data));
+        flash_opcodes.add(MulticallerCall::new_call(source.address, &flash_call_data));
 
         Ok(flash_opcodes)
     }
 
+    /// Flash loan debt token from the cheapest source, repay user's debt with liquidationCall and swap seized collateral back
+    /// to debt token. Only the seized amount is swapped, other collateral token balance of the multicaller is kept.
+    pub fn encode_liquidation(&self, liquidation: &Liquidation, gas_price: Option<u128>) -> Result<MulticallerCalls> {
+        let debt_token_address = liquidation.debt_token.get_address();
+
+        let mut swap_line = liquidation.swap_line.clone();
+        swap_line.amount_in = SwapAmountType::Set(liquidation.collateral_amount);
+
+        let mut inside_opcodes = MulticallerCalls::new();
+
+        inside_opcodes.add(MulticallerCall::new_call(
+            debt_token_address,
+            &EncoderHelper::encode_erc20_approve(liquidation.lending_pool, liquidation.debt_to_cover),
+        ));
+        inside_opcodes.add(MulticallerCall::new_call(
+            liquidation.lending_pool,
+            &EncoderHelper::encode_aave3_liquidation_call(
+                liquidation.collateral_token.get_address(),
+                debt_token_address,
+                liquidation.user,
+                liquidation.debt_to_cover,
+            ),
+        ));
+        inside_opcodes.merge(self.swap_line_encoder.encode_swap_line_in_amount(&swap_line, self.multicaller, self.multicaller)?);
+
+        // lending pool is in the middle of the liquidation and cannot lend
+        let swap_pools: Vec<Address> =
+            swap_line.pools().iter().map(|pool| pool.get_address()).chain(std::iter::once(liquidation.lending_pool)).collect();
+        let source = self
+            .select_flash_loan_source(&liquidation.debt_token, liquidation.debt_to_cover, &swap_pools, gas_price.unwrap_or_default())
+            .ok_or_eyre("NO_FLASH_LOAN_SOURCE")?;
+
+        self.encode_flash_loan_calls(debt_token_address, liquidation.debt_to_cover, inside_opcodes, source)
+    }
+
     pub fn encode_in_amount(&self, step0: SwapStep, step1: SwapStep) -> Result<MulticallerCalls> {
         let flash = step0.clone();
         let mut swap = step1.clone();
@@ -175,13 +286,54 @@ impl SwapStepEncoder {
         Ok((self.multicaller, call_data))
     }
 
-    pub fn encode_swap_steps(&self, sp0: &SwapStep, sp1: &SwapStep) -> Result<MulticallerCalls> {
+    /// Flash loan source is selected by fee only if the gas price is unknown.
+    pub fn encode_swap_steps(&self, sp0: &SwapStep, sp1: &SwapStep, gas_price: Option<u128>) -> Result<MulticallerCalls> {
         if sp0.can_flash_swap() {
             self.encode_in_amount(sp0.clone(), sp1.clone())
         } else if sp1.can_flash_swap() {
             self.encode_out_amount(sp0.clone(), sp1.clone())
         } else {
-            self.encode_balancer_flash_loan(vec![sp0.clone(), sp1.clone()])
+            let token = sp0.first_token().ok_or_eyre("NO_FLASH_LOAN_TOKEN")?;
+            let swap_pools: Vec<Address> = sp0.get_pools().iter().chain(sp1.get_pools().iter()).map(|pool| pool.get_address()).collect();
+            let source = self
+                .select_flash_loan_source(token, sp0.get_in_amount()?, &swap_pools, gas_price.unwrap_or_default())
+                .ok_or_eyre("NO_FLASH_LOAN_SOURCE")?;
+            self.encode_flash_loan(vec![sp0.clone(), sp1.clone()], source)
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use defi_address_book::TokenAddress;
+
+    use super::*;
+
+    #[test]
+    fn test_select_flash_loan_source() {
+        let pool = Address::repeat_byte(1);
+        let encoder = SwapStepEncoder::new(Address::ZERO).with_flash_loan_sources(vec![
+            FlashLoanSource::balancer().with_tokens(vec![TokenAddress::WETH]),
+            FlashLoanSource::aave_v3(),
+            FlashLoanSource::maker(),
+            FlashLoanSource::uniswap_v3(pool, TokenAddress::DAI, TokenAddress::USDC, 100),
+        ]);
+
+        let token = Token::new(TokenAddress::DAI);
+        token.set_eth_price(Some(U256::from(10).pow(U256::from(18))));
+
+        // gas dominates for small amounts
+        let source = encoder.select_flash_loan_source(&token, U256::from(1_000_000), &[], 10).unwrap();
+        assert_eq!(source.provider, FlashLoanProvider::UniswapV3);
+        let source = encoder.select_flash_loan_source(&token, U256::from(1_000_000), &[pool], 10).unwrap();
+        assert_eq!(source.provider, FlashLoanProvider::AaveV3);
+
+        // fee dominates for large amounts
+        let source = encoder.select_flash_loan_source(&token, U256::from(10_000_000_000u64), &[], 10).unwrap();
+        assert_eq!(source.provider, FlashLoanProvider::Maker);
+
+        let weth = Token::new(TokenAddress::WETH);
+        assert_eq!(encoder.select_flash_loan_source(&weth, U256::from(1_000_000), &[], 10).unwrap().provider, FlashLoanProvider::Balancer);
+        assert!(encoder.select_flash_loan_source(&Token::new(Address::repeat_byte(2)), U256::from(1_000_000), &[], 10).is_none());
+    }
+}
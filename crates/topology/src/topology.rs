@@ -14,23 +14,26 @@ use tracing::{error, info, warn};
 use crate::topology_config::TransportType;
 use crate::topology_config::{BroadcasterConfig, ClientConfigParams, EncoderConfig, EstimatorConfig, SignersConfig, TopologyConfig};
 use defi_actors::{
-    BlockHistoryActor, CurvePoolLoaderOneShotActor, EvmEstimatorActor, FlashbotsBroadcastActor, GethEstimatorActor,
+    BlockHistoryActor, BundlePreflightActor, CurvePoolLoaderOneShotActor, EvmEstimatorActor, FlashbotsBroadcastActor, GethEstimatorActor,
     HistoryPoolLoaderOneShotActor, InitializeSignersOneShotBlockingActor, MarketStatePreloadedOneShotActor, MempoolActor,
-    NewPoolLoaderActor, NodeBlockActor, NodeBlockActorConfig, NodeExExGrpcActor, NodeMempoolActor, NonceAndBalanceMonitorActor,
-    PoolHealthMonitorActor, PoolLoaderActor, PriceActor, TxSignersActor,
+    MevShareHintActor, NewPoolLoaderActor, NodeBlockActor, NodeBlockActorConfig, NodeExExGrpcActor, NodeMempoolActor,
+    NonceAndBalanceMonitorActor, PaperTradingBroadcastActor, PoolHealthMonitorActor, PoolLoaderActor, PriceActor, PrivateTxBroadcastActor,
+    PublicTxBroadcastActor, StateChangeArbSearcherConfig, TxSignersActor,
 };
 use defi_blockchain::Blockchain;
 use defi_entities::TxSigners;
 use defi_pools::PoolsConfig;
+use defi_types::ChainParameters;
 use flashbots::Flashbots;
 use loom_actors::{Accessor, Actor, Consumer, Producer, SharedState, WorkerResult};
-use loom_multicaller::MulticallerSwapEncoder;
+use loom_multicaller::{FlashLoanSource, MulticallerSwapEncoder};
 
 pub struct Topology {
     clients: HashMap<String, ClientConfigParams>,
     blockchains: HashMap<String, Blockchain>,
     signers: HashMap<String, SharedState<TxSigners>>,
     multicaller_encoders: HashMap<String, MulticallerSwapEncoder>,
+    searchers: HashMap<String, StateChangeArbSearcherConfig>,
     default_blockchain_name: Option<String>,
     default_multicaller_encoder_name: Option<String>,
     default_signer_name: Option<String>,
@@ -43,6 +46,7 @@ impl Topology {
             blockchains: HashMap::new(),
             signers: HashMap::new(),
             multicaller_encoders: HashMap::new(),
+            searchers: HashMap::new(),
             default_blockchain_name: None,
             default_multicaller_encoder_name: None,
             default_signer_name: None,
@@ -99,8 +103,12 @@ impl Topology {
             }
         }
 
+        for (k, params) in config.searchers.clone().unwrap_or_default() {
+            topology.searchers.insert(k, params.try_into()?);
+        }
+
         for (k, params) in config.blockchains.iter() {
-            let blockchain = Blockchain::new(params.chain_id.unwrap_or(1) as u64);
+            let blockchain = Blockchain::new_with_chain_parameters(ChainParameters::from(params));
 
             info!("Starting block history actor {k}");
             let mut block_history_actor = BlockHistoryActor::new(topology.get_client(None)?);
@@ -127,6 +135,7 @@ impl Topology {
             info!("Starting mempool actor {k}");
             let mut mempool_actor = MempoolActor::new();
             match mempool_actor
+                .access(blockchain.market())
                 .access(blockchain.mempool())
                 .consume(blockchain.new_mempool_tx_channel())
                 .consume(blockchain.new_block_headers_channel())
@@ -176,7 +185,11 @@ impl Topology {
                         }
                     }
 
-                    let mut signers_actor = TxSignersActor::new();
+                    // signed bundles are sent to pre-flight if any pre-flight actor is configured
+                    let mut signers_actor = match config.actors.preflight.is_some() {
+                        true => TxSignersActor::new().with_preflight(),
+                        false => TxSignersActor::new(),
+                    };
                     match signers_actor.consume(blockchain.compose_channel()).produce(blockchain.compose_channel()).start() {
                         Ok(r) => {
                             tasks.extend(r);
@@ -293,6 +306,23 @@ impl Topology {
             }
         }
 
+        if let Some(mev_share_actors) = config.actors.mev_share {
+            for (name, params) in mev_share_actors {
+                let blockchain = topology.get_blockchain(params.blockchain.as_ref())?;
+                info!("Starting MEV-Share hint actor {name}");
+                let mut mev_share_actor = MevShareHintActor::new(params.url.clone()).with_name(name.clone());
+                match mev_share_actor.produce(blockchain.new_mempool_tx_channel()).start() {
+                    Ok(r) => {
+                        tasks.extend(r);
+                        info!("MEV-Share hint actor started successfully {name}")
+                    }
+                    Err(e) => {
+                        panic!("{}", e)
+                    }
+                }
+            }
+        }
+
         if let Some(price_actors) = config.actors.price {
             for (name, c) in price_actors {
                 let client = topology.get_client(c.client.as_ref())?;
@@ -346,9 +376,25 @@ impl Topology {
                         let client = topology.get_client(params.client.as_ref())?;
                         let blockchain = topology.get_blockchain(params.blockchain.as_ref())?;
 
-                        let flashbots_client = Flashbots::new(client, "https://relay.flashbots.net", None).with_default_relays();
+                        let flashbots_client = Flashbots::new(client, "https://relay.flashbots.net", None)
+                            .with_default_relays()
+                            .with_retry_policy(params.retry_policy());
                         let mut flashbots_actor = FlashbotsBroadcastActor::new(flashbots_client, params.smart.unwrap_or(false), true);
-                        match flashbots_actor.consume(blockchain.compose_channel()).start() {
+                        if let Some(smart_pct) = params.smart_pct {
+                            flashbots_actor = flashbots_actor.with_smart_pct(smart_pct);
+                        }
+                        flashbots_actor = flashbots_actor
+                            .with_targeting_policy(params.targeting_policy())
+                            .with_chain_parameters(blockchain.chain_parameters());
+                        match flashbots_actor
+                            .access(blockchain.block_history())
+                            .access(blockchain.nonce_and_balance())
+                            .consume(blockchain.compose_channel())
+                            .consume(blockchain.market_events_channel())
+                            .produce(blockchain.compose_channel())
+                            .produce(blockchain.influxdb_write_channel())
+                            .start()
+                        {
                             Ok(r) => {
                                 tasks.extend(r);
                                 info!("Flashbots broadcaster actor {name} started successfully for {}", blockchain.chain_id())
@@ -358,6 +404,72 @@ impl Topology {
                             }
                         }
                     }
+                    BroadcasterConfig::PaperTrading(params) => {
+                        let blockchain = topology.get_blockchain(params.blockchain.as_ref())?;
+
+                        let mut paper_trading_actor = match params.journal {
+                            Some(journal) => PaperTradingBroadcastActor::new().with_journal(journal),
+                            None => PaperTradingBroadcastActor::new(),
+                        };
+                        match paper_trading_actor
+                            .access(blockchain.block_history())
+                            .consume(blockchain.compose_channel())
+                            .consume(blockchain.market_events_channel())
+                            .start()
+                        {
+                            Ok(r) => {
+                                tasks.extend(r);
+                                info!("Paper trading broadcaster actor {name} started successfully for {}", blockchain.chain_id())
+                            }
+                            Err(e) => {
+                                panic!("Error starting paper trading broadcaster actor {name} for {} : {}", blockchain.chain_id(), e)
+                            }
+                        }
+                    }
+                    BroadcasterConfig::PrivateTx(params) => {
+                        let client = topology.get_client(params.client.as_ref())?;
+                        let blockchain = topology.get_blockchain(params.blockchain.as_ref())?;
+
+                        let flashbots_client = Flashbots::new(client, "https://relay.flashbots.net", None);
+                        let flashbots_client = match params.relays() {
+                            Some(relays) => flashbots_client.with_relays(relays),
+                            None => flashbots_client.with_default_relays(),
+                        };
+                        let mut private_tx_actor = PrivateTxBroadcastActor::new(flashbots_client);
+                        if let Some(target_blocks) = params.target_blocks {
+                            private_tx_actor = private_tx_actor.with_target_blocks(target_blocks);
+                        }
+                        if let Some(fee_bump_pct) = params.fee_bump_pct {
+                            private_tx_actor = private_tx_actor.with_fee_bump_pct(fee_bump_pct);
+                        }
+                        match private_tx_actor.consume(blockchain.compose_channel()).start() {
+                            Ok(r) => {
+                                tasks.extend(r);
+                                info!("Private tx broadcaster actor {name} started successfully for {}", blockchain.chain_id())
+                            }
+                            Err(e) => {
+                                panic!("Error starting private tx broadcaster actor {name} for {} : {}", blockchain.chain_id(), e)
+                            }
+                        }
+                    }
+                    BroadcasterConfig::PublicTx(params) => {
+                        let client = topology.get_client(params.client.as_ref())?;
+                        let blockchain = topology.get_blockchain(params.blockchain.as_ref())?;
+
+                        let mut public_tx_actor = PublicTxBroadcastActor::new(client);
+                        if let Some(fee_bump_pct) = params.fee_bump_pct {
+                            public_tx_actor = public_tx_actor.with_fee_bump_pct(fee_bump_pct);
+                        }
+                        match public_tx_actor.consume(blockchain.compose_channel()).start() {
+                            Ok(r) => {
+                                tasks.extend(r);
+                                info!("Public tx broadcaster actor {name} started successfully for {}", blockchain.chain_id())
+                            }
+                            Err(e) => {
+                                panic!("Error starting public tx broadcaster actor {name} for {} : {}", blockchain.chain_id(), e)
+                            }
+                        }
+                    }
                 }
             }
         } else {
@@ -387,7 +499,8 @@ impl Topology {
                 if params.protocol {
                     info!("Starting curve pools loader {name}");
 
-                    let mut curve_pools_loader_actor = CurvePoolLoaderOneShotActor::new(client.clone());
+                    let mut curve_pools_loader_actor =
+                        CurvePoolLoaderOneShotActor::new(client.clone()).with_state_reader_config(params.state_reader_config());
                     match curve_pools_loader_actor.access(blockchain.market()).access(blockchain.market_state()).start() {
                         Err(e) => {
                             panic!("CurvePoolLoaderOneShotActor : {}", e)
@@ -414,8 +527,13 @@ impl Topology {
                 }
 
                 info!("Starting pool loader actor {name}");
-                let mut pool_loader_actor = PoolLoaderActor::new(client.clone());
-                match pool_loader_actor.access(blockchain.market()).consume(blockchain.tasks_channel()).start() {
+                let mut pool_loader_actor = PoolLoaderActor::new(client.clone()).with_state_reader_config(params.state_reader_config());
+                match pool_loader_actor
+                    .access(blockchain.market())
+                    .access(blockchain.market_state())
+                    .consume(blockchain.tasks_channel())
+                    .start()
+                {
                     Ok(r) => {
                         tasks.extend(r);
                         info!("Pool loader actor started successfully")
@@ -434,9 +552,9 @@ impl Topology {
                 match params {
                     EstimatorConfig::Evm(params) => {
                         let blockchain = topology.get_blockchain(params.blockchain.as_ref())?;
-                        let encoder = topology.get_multicaller_encoder(params.encoder.as_ref())?;
-                        let mut evm_estimator_actor = EvmEstimatorActor::new(encoder);
-                        match evm_estimator_actor.consume(blockchain.compose_channel()).produce(blockchain.compose_channel()).start() {
+                        let encoder = topology.get_multicaller_encoder_for_chain(params.encoder.as_ref(), blockchain)?;
+                        let evm_estimator_actor = EvmEstimatorActor::new(encoder).on_bc(blockchain);
+                        match evm_estimator_actor.start() {
                             Ok(r) => {
                                 tasks.extend(r);
                                 info!("EVM estimator actor started successfully {name} @ {}", blockchain.chain_id())
@@ -449,12 +567,12 @@ impl Topology {
                     EstimatorConfig::Geth(params) => {
                         let client = topology.get_client(params.client.as_ref())?;
                         let blockchain = topology.get_blockchain(params.blockchain.as_ref())?;
-                        let encoder = topology.get_multicaller_encoder(params.encoder.as_ref())?;
+                        let encoder = topology.get_multicaller_encoder_for_chain(params.encoder.as_ref(), blockchain)?;
 
                         let flashbots_client = Arc::new(Flashbots::new(client, "https://relay.flashbots.net", None).with_default_relays());
 
-                        let mut geth_estimator_actor = GethEstimatorActor::new(flashbots_client, encoder);
-                        match geth_estimator_actor.consume(blockchain.compose_channel()).produce(blockchain.compose_channel()).start() {
+                        let geth_estimator_actor = GethEstimatorActor::new(flashbots_client, encoder).on_bc(blockchain);
+                        match geth_estimator_actor.start() {
                             Ok(r) => {
                                 tasks.extend(r);
                                 info!("Geth estimator actor started successfully {name} @ {}", blockchain.chain_id())
@@ -470,6 +588,34 @@ impl Topology {
             warn!("No estimator actors in config")
         }
 
+        if let Some(preflight_actors) = config.actors.preflight {
+            for (name, params) in preflight_actors {
+                let client = topology.get_client(params.client.as_ref())?;
+                let blockchain = topology.get_blockchain(params.blockchain.as_ref())?;
+
+                let flashbots_client = Arc::new(Flashbots::new(client, "https://relay.flashbots.net", None).with_default_relays());
+
+                let mut preflight_actor = BundlePreflightActor::new(flashbots_client);
+                if let Some(max_deviation_pct) = params.max_deviation_pct {
+                    preflight_actor = preflight_actor.with_max_deviation_pct(max_deviation_pct);
+                }
+                match preflight_actor
+                    .consume(blockchain.compose_channel())
+                    .produce(blockchain.compose_channel())
+                    .produce(blockchain.pool_health_monitor_channel())
+                    .start()
+                {
+                    Ok(r) => {
+                        tasks.extend(r);
+                        info!("Bundle pre-flight actor started successfully {name} @ {}", blockchain.chain_id())
+                    }
+                    Err(e) => {
+                        panic!("Error starting bundle pre-flight actor {name} @ {} : {}", blockchain.chain_id(), e)
+                    }
+                }
+            }
+        }
+
         Ok((topology, tasks))
     }
 
@@ -494,6 +640,14 @@ impl Topology {
         }
     }
 
+    /// Encoder with the wrapped native token and the flash loan sources of the chain.
+    pub fn get_multicaller_encoder_for_chain(&self, name: Option<&String>, blockchain: &Blockchain) -> Result<MulticallerSwapEncoder> {
+        Ok(self
+            .get_multicaller_encoder(name)?
+            .with_wrapped_native_token(blockchain.wrapped_native_token())
+            .with_flash_loan_sources(FlashLoanSource::chain_defaults(blockchain.chain_id())))
+    }
+
     pub fn get_multicaller_encoder(&self, name: Option<&String>) -> Result<MulticallerSwapEncoder> {
         match self.multicaller_encoders.get(name.unwrap_or(&self.default_multicaller_encoder_name.clone().unwrap())) {
             Some(encoder) => Ok(encoder.clone()),
@@ -507,6 +661,11 @@ impl Topology {
             None => Err(eyre!("SIGNERS_NOT_FOUND")),
         }
     }
+    /// Searcher config of the blockchain, default config is returned if not set
+    pub fn get_searcher_config(&self, name: Option<&String>) -> StateChangeArbSearcherConfig {
+        self.searchers.get(name.unwrap_or(&self.default_blockchain_name.clone().unwrap())).cloned().unwrap_or_default()
+    }
+
     pub fn get_mut_blockchain(&mut self, name: Option<&String>) -> Result<&mut Blockchain> {
         match self.blockchains.get_mut(name.unwrap_or(&self.default_blockchain_name.clone().unwrap())) {
             Some(a) => Ok(a),
@@ -1,16 +1,90 @@
 use std::collections::HashMap;
 use std::fs;
+use std::time::Duration;
 
+use alloy_eips::eip1559::BaseFeeParams;
+use alloy_primitives::utils::parse_units;
+use alloy_primitives::{Address, U256};
 use alloy_provider::RootProvider;
 use alloy_transport::BoxTransport;
-use eyre::Result;
+use defi_actors::{BlockTargetingPolicy, PendingTxSimulation, StateChangeArbSearcherConfig};
+use defi_entities::required_state::RequiredStateReaderConfig;
+use defi_types::{ChainParameters, ChainToken};
+use eyre::{ErrReport, Result};
 use flashbots::client::RelayConfig;
+use flashbots::RetryPolicy;
 use serde::Deserialize;
 use strum_macros::Display;
 
 #[derive(Debug, Deserialize)]
 pub struct BlockchainConfig {
     pub chain_id: Option<i64>,
+    pub base_fee_max_change_denominator: Option<u128>,
+    pub base_fee_elasticity_multiplier: Option<u128>,
+    pub wrapped_native_token: Option<Address>,
+    pub multicaller: Option<Address>,
+    /// Replaces the default tokens of the chain
+    pub tokens: Option<Vec<TokenConfig>>,
+    /// L1 data fee is added to the transaction cost
+    pub op_stack: Option<bool>,
+    /// Seconds between blocks
+    pub block_time: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TokenConfig {
+    pub address: Address,
+    pub symbol: String,
+    pub decimals: u8,
+    #[serde(default = "default_true")]
+    pub basic: bool,
+    #[serde(default)]
+    pub middle: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl From<&BlockchainConfig> for ChainParameters {
+    fn from(config: &BlockchainConfig) -> Self {
+        let mut chain_parameters = ChainParameters::from(config.chain_id.unwrap_or(1) as u64);
+        if let Some(max_change_denominator) = config.base_fee_max_change_denominator {
+            let elasticity_multiplier = chain_parameters.base_fee_params.elasticity_multiplier;
+            chain_parameters = chain_parameters.with_base_fee_params(BaseFeeParams::new(max_change_denominator, elasticity_multiplier));
+        }
+        if let Some(elasticity_multiplier) = config.base_fee_elasticity_multiplier {
+            let max_change_denominator = chain_parameters.base_fee_params.max_change_denominator;
+            chain_parameters = chain_parameters.with_base_fee_params(BaseFeeParams::new(max_change_denominator, elasticity_multiplier));
+        }
+        if let Some(wrapped_native_token) = config.wrapped_native_token {
+            chain_parameters = chain_parameters.with_wrapped_native_token(wrapped_native_token);
+        }
+        if let Some(multicaller) = config.multicaller {
+            chain_parameters = chain_parameters.with_multicaller_address(multicaller);
+        }
+        if let Some(tokens) = &config.tokens {
+            chain_parameters = chain_parameters.with_tokens(
+                tokens
+                    .iter()
+                    .map(|token| ChainToken {
+                        address: token.address,
+                        symbol: token.symbol.clone(),
+                        decimals: token.decimals,
+                        basic: token.basic,
+                        middle: token.middle,
+                    })
+                    .collect(),
+            );
+        }
+        if let Some(op_stack) = config.op_stack {
+            chain_parameters = chain_parameters.with_op_stack(op_stack);
+        }
+        if let Some(block_time) = config.block_time {
+            chain_parameters = chain_parameters.with_block_time(block_time);
+        }
+        chain_parameters
+    }
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Display)]
@@ -35,6 +109,24 @@ pub enum TransportType {
     Ipc,
 }
 
+#[derive(Clone, Copy, Debug, Default, Deserialize, Display)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum SimulationType {
+    #[default]
+    Tracer,
+    Local,
+}
+
+impl From<SimulationType> for PendingTxSimulation {
+    fn from(simulation: SimulationType) -> Self {
+        match simulation {
+            SimulationType::Tracer => PendingTxSimulation::Tracer,
+            SimulationType::Local => PendingTxSimulation::Local,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct InfluxDbConfig {
     pub url: String,
@@ -49,6 +141,8 @@ pub struct ClientConfigParams {
     pub transport: TransportType,
     pub db_path: Option<String>,
     pub exex: Option<String>,
+    #[serde(default)]
+    pub simulation: SimulationType,
     #[serde(skip)]
     pub provider: Option<RootProvider<BoxTransport>>,
 }
@@ -129,17 +223,37 @@ pub struct ExExClientConfig {
     pub url: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct MevShareConfig {
+    #[serde(rename = "bc")]
+    pub blockchain: Option<String>,
+    pub url: String,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct FlashbotsRelayConfig {
     id: u16,
     name: String,
     url: String,
     no_sign: Option<bool>,
+    supports_replacement: Option<bool>,
+    supports_cancel: Option<bool>,
+    supports_mev_share: Option<bool>,
+    supports_private_tx: Option<bool>,
 }
 
 impl From<FlashbotsRelayConfig> for RelayConfig {
     fn from(config: FlashbotsRelayConfig) -> Self {
-        RelayConfig { id: config.id, name: config.name, url: config.url, no_sign: config.no_sign }
+        RelayConfig {
+            id: config.id,
+            name: config.name,
+            url: config.url,
+            no_sign: config.no_sign,
+            supports_replacement: config.supports_replacement,
+            supports_cancel: config.supports_cancel,
+            supports_mev_share: config.supports_mev_share,
+            supports_private_tx: config.supports_private_tx,
+        }
     }
 }
 
@@ -149,20 +263,96 @@ pub struct FlashbotsBroadcasterConfig {
     pub blockchain: Option<String>,
     pub client: Option<String>,
     pub smart: Option<bool>,
+    pub smart_pct: Option<u32>,
     pub relays: Option<Vec<FlashbotsRelayConfig>>,
+    pub retry_attempts: Option<u32>,
+    pub retry_backoff_ms: Option<u64>,
+    pub mute_after_failures: Option<u32>,
+    pub mute_secs: Option<u64>,
+    pub target_blocks: Option<u64>,
+    pub resubmit_delay_ms: Option<u64>,
+    pub re_estimate: Option<bool>,
 }
 
 impl FlashbotsBroadcasterConfig {
+    pub fn targeting_policy(&self) -> BlockTargetingPolicy {
+        let mut targeting_policy = BlockTargetingPolicy::default();
+        if let Some(target_blocks) = self.target_blocks {
+            targeting_policy = targeting_policy.with_target_blocks(target_blocks);
+        }
+        if let Some(resubmit_delay_ms) = self.resubmit_delay_ms {
+            targeting_policy = targeting_policy.with_resubmit_delay(Duration::from_millis(resubmit_delay_ms));
+        }
+        if let Some(re_estimate) = self.re_estimate {
+            targeting_policy = targeting_policy.with_re_estimate(re_estimate);
+        }
+        targeting_policy
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        let mut retry_policy = RetryPolicy::default();
+        if let Some(retry_attempts) = self.retry_attempts {
+            retry_policy = retry_policy.with_max_attempts(retry_attempts);
+        }
+        if let Some(retry_backoff_ms) = self.retry_backoff_ms {
+            retry_policy = retry_policy.with_initial_backoff(Duration::from_millis(retry_backoff_ms));
+        }
+        if let Some(mute_after_failures) = self.mute_after_failures {
+            retry_policy = retry_policy.with_mute_after_failures(mute_after_failures);
+        }
+        if let Some(mute_secs) = self.mute_secs {
+            retry_policy = retry_policy.with_mute_duration(Duration::from_secs(mute_secs));
+        }
+        retry_policy
+    }
+
     pub fn relays(&self) -> Vec<RelayConfig> {
         self.relays.as_ref().map(|relays| relays.iter().map(|r| r.clone().into()).collect()).unwrap_or_default()
     }
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct PaperTradingBroadcasterConfig {
+    #[serde(rename = "bc")]
+    pub blockchain: Option<String>,
+    pub journal: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PrivateTxBroadcasterConfig {
+    #[serde(rename = "bc")]
+    pub blockchain: Option<String>,
+    pub client: Option<String>,
+    pub relays: Option<Vec<FlashbotsRelayConfig>>,
+    pub target_blocks: Option<u64>,
+    pub fee_bump_pct: Option<u32>,
+}
+
+impl PrivateTxBroadcasterConfig {
+    pub fn relays(&self) -> Option<Vec<RelayConfig>> {
+        self.relays.as_ref().map(|relays| relays.iter().map(|r| r.clone().into()).collect())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PublicTxBroadcasterConfig {
+    #[serde(rename = "bc")]
+    pub blockchain: Option<String>,
+    pub client: Option<String>,
+    pub fee_bump_pct: Option<u32>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type")]
 pub enum BroadcasterConfig {
     #[serde(rename = "flashbots")]
     Flashbots(FlashbotsBroadcasterConfig),
+    #[serde(rename = "paper")]
+    PaperTrading(PaperTradingBroadcasterConfig),
+    #[serde(rename = "private")]
+    PrivateTx(PrivateTxBroadcasterConfig),
+    #[serde(rename = "public")]
+    PublicTx(PublicTxBroadcasterConfig),
 }
 
 #[derive(Debug, Deserialize)]
@@ -189,6 +379,14 @@ pub enum EstimatorConfig {
     Geth(GethEstimatorConfig),
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PreflightConfig {
+    pub client: Option<String>,
+    #[serde(rename = "bc")]
+    pub blockchain: Option<String>,
+    pub max_deviation_pct: Option<u32>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PoolsConfig {
     #[serde(rename = "bc")]
@@ -197,6 +395,66 @@ pub struct PoolsConfig {
     pub history: bool,
     pub new: bool,
     pub protocol: bool,
+    pub state_batch_size: Option<usize>,
+    pub state_concurrency: Option<usize>,
+    pub slot_reader: Option<bool>,
+}
+
+impl PoolsConfig {
+    pub fn state_reader_config(&self) -> RequiredStateReaderConfig {
+        let mut state_reader_config = RequiredStateReaderConfig::default();
+        if let Some(state_batch_size) = self.state_batch_size {
+            state_reader_config = state_reader_config.with_batch_size(state_batch_size);
+        }
+        if let Some(state_concurrency) = self.state_concurrency {
+            state_reader_config = state_reader_config.with_concurrency(state_concurrency);
+        }
+        if let Some(slot_reader) = self.slot_reader {
+            state_reader_config = state_reader_config.with_slot_reader(slot_reader);
+        }
+        state_reader_config
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SearcherConfig {
+    pub min_profit_eth: Option<f64>,
+    pub gas_units_multiplier: Option<u64>,
+    pub start_input_eth: Option<f64>,
+    pub token_start_input_eth: Option<HashMap<Address, f64>>,
+    pub max_paths_per_event: Option<usize>,
+    pub best_answer_pct: Option<u32>,
+}
+
+fn eth_to_wei(value: f64) -> Result<U256> {
+    Ok(parse_units(&value.to_string(), "ether")?.get_absolute())
+}
+
+impl TryFrom<SearcherConfig> for StateChangeArbSearcherConfig {
+    type Error = ErrReport;
+
+    fn try_from(config: SearcherConfig) -> Result<Self> {
+        let mut searcher_config = StateChangeArbSearcherConfig::default();
+        if let Some(min_profit_eth) = config.min_profit_eth {
+            searcher_config = searcher_config.with_min_profit_eth(eth_to_wei(min_profit_eth)?);
+        }
+        if let Some(gas_units_multiplier) = config.gas_units_multiplier {
+            searcher_config = searcher_config.with_gas_units_multiplier(gas_units_multiplier);
+        }
+        if let Some(start_input_eth) = config.start_input_eth {
+            searcher_config = searcher_config.with_start_input_eth(eth_to_wei(start_input_eth)?);
+        }
+        for (token, start_input_eth) in config.token_start_input_eth.unwrap_or_default() {
+            searcher_config = searcher_config.with_token_start_input_eth(token, eth_to_wei(start_input_eth)?);
+        }
+        if let Some(max_paths_per_event) = config.max_paths_per_event {
+            searcher_config = searcher_config.with_max_paths_per_event(max_paths_per_event);
+        }
+        if let Some(best_answer_pct) = config.best_answer_pct {
+            searcher_config = searcher_config.with_best_answer_pct(best_answer_pct);
+        }
+        Ok(searcher_config)
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -205,10 +463,12 @@ pub struct ActorConfig {
     pub node: Option<HashMap<String, BlockchainClientConfig>>,
     pub node_exex: Option<HashMap<String, ExExClientConfig>>,
     pub mempool: Option<HashMap<String, BlockchainClientConfig>>,
+    pub mev_share: Option<HashMap<String, MevShareConfig>>,
     pub price: Option<HashMap<String, BlockchainClientConfig>>,
     pub pools: Option<HashMap<String, PoolsConfig>>,
     pub noncebalance: Option<HashMap<String, BlockchainClientConfig>>,
     pub estimator: Option<HashMap<String, EstimatorConfig>>,
+    pub preflight: Option<HashMap<String, PreflightConfig>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -220,6 +480,7 @@ pub struct TopologyConfig {
     pub signers: HashMap<String, SignersConfig>,
     pub encoders: HashMap<String, EncoderConfig>,
     pub preloaders: Option<HashMap<String, PreloaderConfig>>,
+    pub searchers: Option<HashMap<String, SearcherConfig>>,
 }
 
 impl TopologyConfig {
@@ -245,4 +506,46 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_searcher_config() -> Result<()> {
+        let config: SearcherConfig = toml::from_str(
+            r#"
+            min_profit_eth = 0.001
+            max_paths_per_event = 100
+            token_start_input_eth = { "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2" = 0.1 }
+            "#,
+        )?;
+        let searcher_config: StateChangeArbSearcherConfig = config.try_into()?;
+
+        assert_eq!(searcher_config.min_profit_eth, U256::from(1_000_000_000_000_000u64));
+        assert_eq!(searcher_config.max_paths_per_event, Some(100));
+        assert_eq!(searcher_config.gas_units_multiplier, 200_000);
+        assert_eq!(searcher_config.best_answer_pct, 9000);
+        assert_eq!(
+            searcher_config.token_start_input_eth.get(&"0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".parse::<Address>()?),
+            Some(&U256::from(100_000_000_000_000_000u64))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_blockchain_config() -> Result<()> {
+        let config: BlockchainConfig = toml::from_str(
+            r#"
+            chain_id = 8453
+            multicaller = "0x0000000000000000000000000000000000000001"
+            base_fee_elasticity_multiplier = 2
+            tokens = [{ address = "0x4200000000000000000000000000000000000006", symbol = "WETH", decimals = 18 }]
+            "#,
+        )?;
+        let chain_parameters = ChainParameters::from(&config);
+
+        assert_eq!(chain_parameters.chain_id, 8453);
+        assert_eq!(chain_parameters.multicaller_address, Some(Address::with_last_byte(1)));
+        assert_eq!(chain_parameters.base_fee_params, BaseFeeParams::new(250, 2));
+        assert_eq!(chain_parameters.tokens.len(), 1);
+        assert!(chain_parameters.tokens[0].basic && !chain_parameters.tokens[0].middle);
+        Ok(())
+    }
 }
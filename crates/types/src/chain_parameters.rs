@@ -1,15 +1,160 @@
 use alloy_eips::eip1559::BaseFeeParams;
+use alloy_primitives::{address, Address};
 use alloy_rpc_types_eth::Header;
+use defi_address_book::TokenAddress;
+
+/// Token added to the market at start.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainToken {
+    pub address: Address,
+    pub symbol: String,
+    pub decimals: u8,
+    pub basic: bool,
+    pub middle: bool,
+}
+
+impl ChainToken {
+    pub fn new(address: Address, symbol: &str, decimals: u8) -> Self {
+        Self { address, symbol: symbol.to_string(), decimals, basic: true, middle: false }
+    }
+
+    pub fn middle(address: Address, symbol: &str, decimals: u8) -> Self {
+        Self { address, symbol: symbol.to_string(), decimals, basic: false, middle: true }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct ChainParameters {
     pub chain_id: u64,
     pub base_fee_params: BaseFeeParams,
+    /// Wrapped native token, profits are calculated in it
+    pub wrapped_native_token: Address,
+    pub tokens: Vec<ChainToken>,
+    pub multicaller_address: Option<Address>,
+    /// OP stack chains charge L1 data fee on top of the execution gas
+    pub op_stack: bool,
+    /// Seconds between blocks
+    pub block_time: u64,
 }
 
+const OP_WETH: Address = address!("4200000000000000000000000000000000000006");
+
 impl ChainParameters {
+    /// Parameters of an unknown chain with ethereum base fee rules and no tokens.
+    pub fn new(chain_id: u64) -> ChainParameters {
+        ChainParameters {
+            chain_id,
+            base_fee_params: BaseFeeParams::ethereum(),
+            wrapped_native_token: Address::ZERO,
+            tokens: Vec::new(),
+            multicaller_address: None,
+            op_stack: false,
+            block_time: 12,
+        }
+    }
+
     pub fn ethereum() -> ChainParameters {
-        ChainParameters { chain_id: 1, base_fee_params: BaseFeeParams::ethereum() }
+        ChainParameters {
+            chain_id: 1,
+            base_fee_params: BaseFeeParams::ethereum(),
+            wrapped_native_token: TokenAddress::WETH,
+            tokens: vec![
+                ChainToken::new(TokenAddress::WETH, "WETH", 18),
+                ChainToken::new(TokenAddress::USDC, "USDC", 6),
+                ChainToken::new(TokenAddress::USDT, "USDT", 6),
+                ChainToken::new(TokenAddress::DAI, "DAI", 18),
+                ChainToken::new(TokenAddress::WBTC, "WBTC", 8),
+                ChainToken::middle(TokenAddress::THREECRV, "3Crv", 18),
+            ],
+            multicaller_address: None,
+            op_stack: false,
+            block_time: 12,
+        }
+    }
+
+    pub fn base() -> ChainParameters {
+        ChainParameters {
+            chain_id: 8453,
+            base_fee_params: BaseFeeParams::optimism_canyon(),
+            wrapped_native_token: OP_WETH,
+            tokens: vec![
+                ChainToken::new(OP_WETH, "WETH", 18),
+                ChainToken::new(address!("833589fcd6edb6e08f4c7c32d4f71b54bda02913"), "USDC", 6),
+                ChainToken::new(address!("d9aaec86b65d86f6a7b5b1b0c42ffa531710b6ca"), "USDbC", 6),
+                ChainToken::new(address!("50c5725949a6f0c72e6c4a641f24049a917db0cb"), "DAI", 18),
+            ],
+            multicaller_address: None,
+            op_stack: true,
+            block_time: 2,
+        }
+    }
+
+    pub fn optimism() -> ChainParameters {
+        ChainParameters {
+            chain_id: 10,
+            base_fee_params: BaseFeeParams::optimism_canyon(),
+            wrapped_native_token: OP_WETH,
+            tokens: vec![
+                ChainToken::new(OP_WETH, "WETH", 18),
+                ChainToken::new(address!("0b2c639c533813f4aa9d7837caf62653d097ff85"), "USDC", 6),
+                ChainToken::new(address!("94b008aa00579c1307b0ef2c499ad98a8ce58e58"), "USDT", 6),
+                ChainToken::new(address!("da10009cbd5d07dd0cecc66161fc93d7c9000da1"), "DAI", 18),
+                ChainToken::new(address!("68f180fcce6836688e9084f035309e29bf0a2095"), "WBTC", 8),
+            ],
+            multicaller_address: None,
+            op_stack: true,
+            block_time: 2,
+        }
+    }
+
+    /// Arbitrum base fee is not EIP-1559, ethereum params are used as an approximation. Blocks are produced several
+    /// times a second, block time is rounded up to one second.
+    pub fn arbitrum() -> ChainParameters {
+        let weth = address!("82af49447d8a07e3bd95bd0d56f35241523fbab1");
+        ChainParameters {
+            chain_id: 42161,
+            base_fee_params: BaseFeeParams::ethereum(),
+            wrapped_native_token: weth,
+            tokens: vec![
+                ChainToken::new(weth, "WETH", 18),
+                ChainToken::new(address!("af88d065e77c8cc2239327c5edb3a432268e5831"), "USDC", 6),
+                ChainToken::new(address!("fd086bc7cd5c481dcc9c85ebe478a1c0b69fcbb9"), "USDT", 6),
+                ChainToken::new(address!("da10009cbd5d07dd0cecc66161fc93d7c9000da1"), "DAI", 18),
+                ChainToken::new(address!("2f2a2543b76a4166549f7aab2e75bef0aefc5b0f"), "WBTC", 8),
+            ],
+            multicaller_address: None,
+            op_stack: false,
+            block_time: 1,
+        }
+    }
+
+    pub fn with_base_fee_params(self, base_fee_params: BaseFeeParams) -> Self {
+        Self { base_fee_params, ..self }
+    }
+
+    pub fn with_wrapped_native_token(self, wrapped_native_token: Address) -> Self {
+        Self { wrapped_native_token, ..self }
+    }
+
+    /// Replaces the default tokens of the chain.
+    pub fn with_tokens(self, tokens: Vec<ChainToken>) -> Self {
+        Self { tokens, ..self }
+    }
+
+    pub fn with_multicaller_address(self, multicaller_address: Address) -> Self {
+        Self { multicaller_address: Some(multicaller_address), ..self }
+    }
+
+    pub fn with_op_stack(self, op_stack: bool) -> Self {
+        Self { op_stack, ..self }
+    }
+
+    pub fn with_block_time(self, block_time: u64) -> Self {
+        Self { block_time, ..self }
+    }
+
+    pub fn next_block_timestamp(&self, timestamp: u64) -> u64 {
+        timestamp + self.block_time
     }
 
     pub fn calc_next_block_base_fee(&self, gas_used: u64, gas_limit: u64, base_fee: u64) -> u64 {
@@ -30,7 +175,38 @@ impl From<u64> for ChainParameters {
     fn from(chain_id: u64) -> Self {
         match chain_id {
             1 => ChainParameters::ethereum(),
-            _ => unimplemented!(),
+            10 => ChainParameters::optimism(),
+            8453 => ChainParameters::base(),
+            42161 => ChainParameters::arbitrum(),
+            _ => ChainParameters::new(chain_id),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_chain_id() {
+        let base = ChainParameters::from(8453);
+        assert_eq!(base.chain_id, 8453);
+        assert_eq!(base.wrapped_native_token, OP_WETH);
+        assert!(base.op_stack);
+        assert_eq!(base.next_block_timestamp(100), 102);
+        assert_eq!(base.calc_next_block_base_fee(30_000_000, 30_000_000, 1_000_000), 1_020_000);
+
+        let unknown = ChainParameters::from(31337);
+        assert_eq!(unknown.chain_id, 31337);
+        assert!(unknown.tokens.is_empty());
+        assert_eq!(unknown.calc_next_block_base_fee(30_000_000, 30_000_000, 1_000_000), 1_125_000);
+    }
+
+    #[test]
+    fn test_overrides() {
+        let multicaller = Address::repeat_byte(1);
+        let params = ChainParameters::ethereum().with_multicaller_address(multicaller);
+        assert_eq!(params.multicaller_address, Some(multicaller));
+        assert!(params.tokens.iter().any(|token| token.address == TokenAddress::WETH && token.basic));
+    }
+}
@@ -1,8 +1,8 @@
 pub use accountnoncetx::AccountNonceAndTransactions;
-pub use chain_parameters::ChainParameters;
+pub use chain_parameters::{ChainParameters, ChainToken};
 pub use fetchstate::FetchState;
 pub use mempool::Mempool;
-pub use mempool_tx::MempoolTx;
+pub use mempool_tx::{MempoolTx, MempoolTxHint, MempoolTxImpact};
 pub use opcodes::*;
 pub use state_update::{
     debug_log_geth_state_update, debug_trace_block, debug_trace_call_diff, debug_trace_call_post_state, debug_trace_call_pre_state,
@@ -1,4 +1,4 @@
-use alloy_primitives::{BlockNumber, TxHash};
+use alloy_primitives::{Address, BlockNumber, Bytes, FixedBytes, TxHash};
 use alloy_rpc_types::{Log, Transaction};
 use chrono::{DateTime, Utc};
 
@@ -15,6 +15,46 @@ pub struct MempoolTx {
     pub failed: Option<bool>,
     pub state_update: Option<GethStateUpdate>,
     pub pre_state: Option<FetchState<GethStateUpdate>>,
+    pub hint: Option<MempoolTxHint>,
+    pub impact: MempoolTxImpact,
+}
+
+/// Market pools impact of the transaction predicted from its calldata when it is received.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MempoolTxImpact {
+    /// Calldata is not a known router swap, impact can only be found by tracing
+    #[default]
+    Unknown,
+    /// Router swap that does not go through any market pool
+    NoPools,
+    /// Router swap through market pools
+    Pools,
+}
+
+impl MempoolTxImpact {
+    pub fn is_affecting(&self) -> bool {
+        *self != MempoolTxImpact::NoPools
+    }
+
+    /// Lower is processed first
+    pub fn priority(&self) -> u8 {
+        match self {
+            MempoolTxImpact::Pools => 0,
+            MempoolTxImpact::Unknown => 1,
+            MempoolTxImpact::NoPools => 2,
+        }
+    }
+}
+
+/// Partial transaction disclosed by an order flow hint. The signed transaction is not available,
+/// so it can only be referenced by hash in a bundle.
+#[derive(Clone, Debug, Default)]
+pub struct MempoolTxHint {
+    pub to: Option<Address>,
+    pub function_selector: Option<FixedBytes<4>>,
+    pub call_data: Option<Bytes>,
+    pub mev_gas_price: Option<u128>,
+    pub gas_used: Option<u64>,
 }
 
 impl MempoolTx {
@@ -24,6 +64,11 @@ impl MempoolTx {
     pub fn new_with_hash(tx_hash: TxHash) -> MempoolTx {
         MempoolTx { tx_hash, ..MempoolTx::default() }
     }
+
+    /// Transaction is known from a hint only and has no signed transaction.
+    pub fn is_partial(&self) -> bool {
+        self.tx.is_none() && self.hint.is_some()
+    }
 }
 
 impl Default for MempoolTx {
@@ -38,6 +83,8 @@ impl Default for MempoolTx {
             mined: None,
             failed: None,
             pre_state: None,
+            hint: None,
+            impact: MempoolTxImpact::Unknown,
         }
     }
 }
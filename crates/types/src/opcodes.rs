@@ -11,7 +11,7 @@ pub enum CallType {
     CalculationCall,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MulticallerCall {
     pub call_type: CallType,
     pub call_data: Bytes,
@@ -96,7 +96,7 @@ impl MulticallerCall {
      */
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct MulticallerCalls {
     pub opcodes_vec: Vec<MulticallerCall>,
 }
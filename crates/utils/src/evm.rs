@@ -20,7 +20,7 @@ use revm::inspector_handle_register;
 use revm::interpreter::Host;
 #[cfg(feature = "trace-calls")]
 use revm::primitives::HashSet;
-use revm::primitives::{Account, BlockEnv, Env, ExecutionResult, Output, ResultAndState, TransactTo, TxEnv, SHANGHAI};
+use revm::primitives::{Account, BlockEnv, Env, ExecutionResult, Output, ResultAndState, TransactTo, TxEnv, TxKind, SHANGHAI};
 use revm::{Database, DatabaseCommit, DatabaseRef, Evm};
 #[cfg(feature = "trace-calls")]
 use revm_inspectors::tracing::{TracingInspector, TracingInspectorConfig};
@@ -34,6 +34,22 @@ pub fn env_for_block(block_id: u64, block_timestamp: u64) -> Env {
     env
 }
 
+/// Transaction environment of the request. State db may not have actual signer nonces, so nonce and chain id checks are skipped.
+pub fn tx_env_from_request(tx: &TransactionRequest) -> TxEnv {
+    TxEnv {
+        caller: tx.from.unwrap_or_default(),
+        gas_limit: tx.gas.unwrap_or_default(),
+        gas_price: U256::from(tx.max_fee_per_gas.unwrap_or(tx.gas_price.unwrap_or_default())),
+        gas_priority_fee: tx.max_priority_fee_per_gas.map(U256::from),
+        transact_to: tx.to.unwrap_or(TxKind::Create),
+        value: tx.value.unwrap_or_default(),
+        data: tx.input.input().cloned().unwrap_or_default(),
+        nonce: None,
+        chain_id: None,
+        ..Default::default()
+    }
+}
+
 pub fn evm_call<DB>(state_db: DB, env: Env, transact_to: Address, call_data_vec: Vec<u8>) -> Result<(Vec<u8>, u64)>
 where
     DB: DatabaseRef<Error = Infallible>,
@@ -130,6 +146,9 @@ where
     let txto = tx.to.unwrap_or_default().to().map_or(Address::ZERO, |x| *x);
 
     env.tx.chain_id = tx.chain_id;
+    if let Some(chain_id) = tx.chain_id {
+        env.cfg.chain_id = chain_id;
+    }
     env.tx.transact_to = TransactTo::Call(txto);
     env.tx.nonce = tx.nonce;
     env.tx.data = tx.input.clone().input.unwrap();